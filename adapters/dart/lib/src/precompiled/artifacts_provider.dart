@@ -13,12 +13,18 @@ class ManifestPlatform {
     required this.name,
     required this.triples,
     required this.artifacts,
+    this.variants = const {},
   });
 
   final String name;
   final List<String> triples;
+
+  /// Artifacts of the primary variant.
   final List<String> artifacts;
 
+  /// Artifacts of every other variant, keyed by variant name.
+  final Map<String, List<String>> variants;
+
   bool matchesTarget(String target) => name == target || triples.contains(target);
 }
 
@@ -29,10 +35,17 @@ class XforgeManifest {
 }
 
 class ArtifactSelection {
-  ArtifactSelection({required this.platform, required this.artifactName});
+  ArtifactSelection({
+    required this.platform,
+    required this.artifactName,
+    this.variant,
+  });
 
   final ManifestPlatform platform;
   final String artifactName;
+
+  /// The variant the artifact belongs to; `null` for the primary one.
+  final String? variant;
 }
 
 class ArtifactResolution {
@@ -133,9 +146,13 @@ class PrecompiledArtifactsProvider {
     return _parseManifest(decoded);
   }
 
+  /// Picks the first artifact of [variant] on the platform matching
+  /// [target], or of the primary variant when [variant] is `null` or not
+  /// published for that platform.
   ArtifactSelection selectArtifact({
     required XforgeManifest manifest,
     required String target,
+    String? variant,
   }) {
     final platform = manifest.platforms.firstWhere(
       (entry) => entry.matchesTarget(target),
@@ -144,6 +161,15 @@ class PrecompiledArtifactsProvider {
       ),
     );
 
+    final variantArtifacts = variant == null ? null : platform.variants[variant];
+    if (variantArtifacts != null && variantArtifacts.isNotEmpty) {
+      return ArtifactSelection(
+        platform: platform,
+        artifactName: variantArtifacts.first,
+        variant: variant,
+      );
+    }
+
     if (platform.artifacts.isEmpty) {
       throw ArtifactNotFoundException(
         'Manifest platform "${platform.name}" has no artifacts.',
@@ -193,12 +219,17 @@ class PrecompiledArtifactsProvider {
     required String target,
     required PrecompiledBinaryMode mode,
     required bool rustAvailable,
+    String? variant,
   }) async {
     final manifest = await fetchVerifiedManifest(buildId: buildId);
 
     ArtifactSelection selection;
     try {
-      selection = selectArtifact(manifest: manifest, target: target);
+      selection = selectArtifact(
+        manifest: manifest,
+        target: target,
+        variant: variant,
+      );
     } on PlatformNotFoundException catch (err) {
       return _fallbackOrThrow(err.toString(), mode, rustAvailable);
     } on ArtifactNotFoundException catch (err) {
@@ -263,13 +294,40 @@ XforgeManifest _parseManifest(String jsonString) {
     final triples = _stringListOrEmpty(triplesRaw);
     final artifactsRaw = entry['artifacts'];
     final artifacts = _stringListOrEmpty(artifactsRaw);
+    final variants = _parseVariants(entry['variants']);
     platforms.add(
-      ManifestPlatform(name: name, triples: triples, artifacts: artifacts),
+      ManifestPlatform(
+        name: name,
+        triples: triples,
+        artifacts: artifacts,
+        variants: variants,
+      ),
     );
   }
   return XforgeManifest(platforms: platforms);
 }
 
+Map<String, List<String>> _parseVariants(Object? raw) {
+  if (raw == null) {
+    return const {};
+  }
+  if (raw is! List) {
+    throw FormatException('Manifest platform.variants must be a list.');
+  }
+  final variants = <String, List<String>>{};
+  for (final entry in raw) {
+    if (entry is! Map) {
+      throw FormatException('Manifest platform variant must be a map.');
+    }
+    final name = entry['name'];
+    if (name is! String || name.isEmpty) {
+      throw FormatException('Manifest variant.name must be a string.');
+    }
+    variants[name] = _stringListOrEmpty(entry['artifacts']);
+  }
+  return variants;
+}
+
 List<String> _stringListOrEmpty(Object? raw) {
   if (raw == null) {
     return const [];
@@ -122,11 +122,15 @@ class XforgeOptions {
 }
 
 class AppPrecompiledOverrides {
-  const AppPrecompiledOverrides({this.mode, this.logLevel});
+  const AppPrecompiledOverrides({this.mode, this.logLevel, this.variant});
 
   final PrecompiledBinaryMode? mode;
   final Level? logLevel;
 
+  /// Manifest variant to download instead of the platform's primary
+  /// artifacts, e.g. `release-with-asserts`.
+  final String? variant;
+
   static AppPrecompiledOverrides? parse(Object? raw) {
     if (raw == null) {
       return null;
@@ -187,10 +191,19 @@ class AppPrecompiledOverrides {
       }
     }
 
-    if (mode == null && logLevel == null) {
+    String? variant;
+    final variantNode = precompiledNode['variant'];
+    if (variantNode != null) {
+      if (variantNode is! String || variantNode.trim().isEmpty) {
+        throw FormatException('precompiled_binaries.variant must be a non-empty string');
+      }
+      variant = variantNode.trim();
+    }
+
+    if (mode == null && logLevel == null && variant == null) {
       return null;
     }
-    return AppPrecompiledOverrides(mode: mode, logLevel: logLevel);
+    return AppPrecompiledOverrides(mode: mode, logLevel: logLevel, variant: variant);
   }
 }
 
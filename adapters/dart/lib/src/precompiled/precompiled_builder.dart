@@ -104,6 +104,7 @@ final class PrecompiledBuilder implements Builder {
         target: targetTriple,
         mode: config.mode,
         rustAvailable: rustAvailable,
+        variant: appOverrides?.variant,
       );
     } catch (err, stack) {
       logger.severe('Failed to resolve precompiled artifact: $err', err, stack);
@@ -226,6 +227,14 @@ Archive _decodeArchive(String filePath, List<int> bytes) {
     final decompressed = GZipDecoder().decodeBytes(bytes);
     return TarDecoder().decodeBytes(decompressed);
   }
+  if (filePath.endsWith('.tar.zst')) {
+    final decompressed = ZstdDecoder().decodeBytes(bytes);
+    return TarDecoder().decodeBytes(decompressed);
+  }
+  if (filePath.endsWith('.tar.xz')) {
+    final decompressed = XZDecoder().decodeBytes(bytes);
+    return TarDecoder().decodeBytes(decompressed);
+  }
   throw StateError('Unsupported archive type: $filePath');
 }
 
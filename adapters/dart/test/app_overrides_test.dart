@@ -17,6 +17,15 @@ void main() {
     expect(overrides?.logLevel, Level.INFO);
   });
 
+  test('parses a variant override', () {
+    final overrides = AppPrecompiledOverrides.parse({
+      'precompiled_binaries': {'variant': 'release-with-asserts'},
+    });
+
+    expect(overrides?.variant, 'release-with-asserts');
+    expect(overrides?.mode, isNull);
+  });
+
   test('boolean false disables precompiled binaries', () {
     final overrides = AppPrecompiledOverrides.parse({
       'precompiled_binaries': false,
@@ -0,0 +1,441 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use xforge_core::build_plan::{BuildPlan, BuildTargetPlan, BuiltArtifact};
+use xforge_core::config::CommandSettings;
+use xforge_core::platform::PlatformKey;
+
+use crate::builder::{BuildError, BuildExecutor, BuildResult};
+use crate::cargo::CargoExecutor;
+use crate::command::CommandExecutor;
+use crate::cross::CrossExecutor;
+use crate::events::{BuildObserver, NoopObserver};
+use crate::ndk::{detect_ndk_home, NdkExecutor};
+use crate::zigbuild::ZigbuildExecutor;
+
+/// Concrete executors that automatic selection can choose between.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ExecutorKind {
+    Cargo,
+    Cross,
+    Zigbuild,
+    Ndk,
+    /// The `build.command` from `xforge.yaml`; only chosen by override.
+    Command,
+}
+
+impl ExecutorKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExecutorKind::Cargo => "cargo",
+            ExecutorKind::Cross => "cross",
+            ExecutorKind::Zigbuild => "zigbuild",
+            ExecutorKind::Ndk => "ndk",
+            ExecutorKind::Command => "command",
+        }
+    }
+}
+
+impl std::fmt::Display for ExecutorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ExecutorKind {
+    type Err = BuildError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "cargo" => Ok(ExecutorKind::Cargo),
+            "cross" => Ok(ExecutorKind::Cross),
+            "zigbuild" => Ok(ExecutorKind::Zigbuild),
+            "ndk" => Ok(ExecutorKind::Ndk),
+            "command" => Ok(ExecutorKind::Command),
+            other => Err(BuildError::new(format!(
+                "unknown executor '{}'; expected cargo, cross, zigbuild, ndk, or command",
+                other
+            ))),
+        }
+    }
+}
+
+/// Host triple and cross-compilation tools available to the build.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HostEnvironment {
+    pub host_triple: String,
+    pub zigbuild: bool,
+    pub cross: bool,
+    pub container_engine: bool,
+    pub ndk_home: Option<PathBuf>,
+}
+
+impl HostEnvironment {
+    pub fn detect() -> BuildResult<Self> {
+        Ok(Self {
+            host_triple: rustc_host_triple()?,
+            zigbuild: probe("zig", &["version"]) && probe("cargo-zigbuild", &["--version"]),
+            cross: probe("cross", &["--version"]),
+            container_engine: probe("docker", &["--version"]) || probe("podman", &["--version"]),
+            ndk_home: detect_ndk_home(),
+        })
+    }
+
+    fn is_apple(&self) -> bool {
+        self.host_triple.contains("-apple-darwin")
+    }
+
+    fn is_windows(&self) -> bool {
+        self.host_triple.contains("-windows-")
+    }
+
+    fn can_run_cross(&self) -> bool {
+        self.cross && self.container_engine
+    }
+}
+
+/// The executor chosen for a single target and why it was chosen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutorSelection {
+    pub rust_target_triple: String,
+    pub kind: ExecutorKind,
+    pub reason: String,
+}
+
+impl std::fmt::Display for ExecutorSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> {} ({})",
+            self.rust_target_triple, self.kind, self.reason
+        )
+    }
+}
+
+/// Picks an executor per target from the host triple, detected tools and
+/// per-target overrides, then delegates each target to that executor.
+#[derive(Clone, Debug, Default)]
+pub struct AutoExecutor {
+    host: HostEnvironment,
+    overrides: BTreeMap<String, String>,
+    command: Option<CommandSettings>,
+}
+
+impl AutoExecutor {
+    pub fn new(host: HostEnvironment, overrides: BTreeMap<String, String>) -> Self {
+        Self {
+            host,
+            overrides,
+            command: None,
+        }
+    }
+
+    /// Settings for targets overridden to the `command` executor.
+    pub fn with_command(mut self, command: Option<CommandSettings>) -> Self {
+        self.command = command;
+        self
+    }
+
+    pub fn selections(&self, plan: &BuildPlan) -> BuildResult<Vec<ExecutorSelection>> {
+        plan.targets
+            .iter()
+            .map(|target| select_executor(&self.host, target, &self.overrides))
+            .collect()
+    }
+}
+
+impl BuildExecutor for AutoExecutor {
+    fn execute(&self, plan: &BuildPlan) -> BuildResult<Vec<BuiltArtifact>> {
+        self.execute_observed(plan, &NoopObserver)
+    }
+
+    fn execute_observed(
+        &self,
+        plan: &BuildPlan,
+        observer: &dyn BuildObserver,
+    ) -> BuildResult<Vec<BuiltArtifact>> {
+        let selections = self.selections(plan)?;
+        let mut artifacts = Vec::with_capacity(plan.targets.len());
+        for (target, selection) in plan.targets.iter().zip(selections) {
+            let single = BuildPlan {
+                targets: vec![target.clone()],
+                ..plan.clone()
+            };
+            let built = match selection.kind {
+                ExecutorKind::Cargo => CargoExecutor::new().execute_observed(&single, observer)?,
+                ExecutorKind::Cross => CrossExecutor::new().execute_observed(&single, observer)?,
+                ExecutorKind::Zigbuild => {
+                    ZigbuildExecutor::new().execute_observed(&single, observer)?
+                }
+                ExecutorKind::Ndk => {
+                    let mut executor = NdkExecutor::new();
+                    if let Some(ndk_home) = &self.host.ndk_home {
+                        executor = executor.with_ndk_home(ndk_home.clone());
+                    }
+                    executor.execute_observed(&single, observer)?
+                }
+                ExecutorKind::Command => match &self.command {
+                    Some(settings) => {
+                        CommandExecutor::new(settings.clone()).execute_observed(&single, observer)?
+                    }
+                    None => {
+                        return Err(BuildError::new(format!(
+                            "{} uses the command executor but xforge.yaml has no build.command",
+                            target.rust_target_triple
+                        )))
+                    }
+                },
+            };
+            artifacts.extend(built);
+        }
+        Ok(artifacts)
+    }
+}
+
+pub fn select_executor(
+    host: &HostEnvironment,
+    target: &BuildTargetPlan,
+    overrides: &BTreeMap<String, String>,
+) -> BuildResult<ExecutorSelection> {
+    let triple = target.rust_target_triple.as_str();
+    let select = |kind: ExecutorKind, reason: String| ExecutorSelection {
+        rust_target_triple: triple.to_string(),
+        kind,
+        reason,
+    };
+
+    if let Some(name) = overrides.get(triple) {
+        let kind = name.parse::<ExecutorKind>()?;
+        return Ok(select(
+            kind,
+            "configured in xforge.yaml build.executors".to_string(),
+        ));
+    }
+    if triple == host.host_triple {
+        return Ok(select(
+            ExecutorKind::Cargo,
+            "target matches the host triple".to_string(),
+        ));
+    }
+
+    match target_family(target.platform) {
+        TargetFamily::Android => {
+            if let Some(ndk_home) = &host.ndk_home {
+                return Ok(select(
+                    ExecutorKind::Ndk,
+                    format!("android ndk found at {}", ndk_home.display()),
+                ));
+            }
+            if host.can_run_cross() {
+                return Ok(select(
+                    ExecutorKind::Cross,
+                    "no android ndk found; cross provides an ndk image".to_string(),
+                ));
+            }
+            Err(no_executor(
+                triple,
+                "install the android ndk or cross with docker",
+            ))
+        }
+        TargetFamily::Macos | TargetFamily::Ios if host.is_apple() => Ok(select(
+            ExecutorKind::Cargo,
+            "apple host links apple targets with the xcode toolchain".to_string(),
+        )),
+        TargetFamily::Macos => {
+            if host.zigbuild {
+                return Ok(select(
+                    ExecutorKind::Zigbuild,
+                    "zig links macos targets from a non-apple host".to_string(),
+                ));
+            }
+            Err(no_executor(
+                triple,
+                "install zig and cargo-zigbuild or build on macos",
+            ))
+        }
+        TargetFamily::Ios => Err(no_executor(triple, "ios targets require a macos host")),
+        TargetFamily::Linux => {
+            if host.zigbuild {
+                return Ok(select(
+                    ExecutorKind::Zigbuild,
+                    "zig provides a cross linker and pinned glibc".to_string(),
+                ));
+            }
+            if host.can_run_cross() {
+                return Ok(select(
+                    ExecutorKind::Cross,
+                    "zig not found; cross provides a linux toolchain image".to_string(),
+                ));
+            }
+            Ok(select(
+                ExecutorKind::Cargo,
+                "no cross toolchain found; relying on the host linker configuration".to_string(),
+            ))
+        }
+        TargetFamily::Windows if host.is_windows() => Ok(select(
+            ExecutorKind::Cargo,
+            "windows host links msvc targets natively".to_string(),
+        )),
+        TargetFamily::Windows => {
+            if host.can_run_cross() {
+                return Ok(select(
+                    ExecutorKind::Cross,
+                    "cross provides a windows toolchain image".to_string(),
+                ));
+            }
+            Err(no_executor(
+                triple,
+                "install cross with docker or build on windows",
+            ))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TargetFamily {
+    Linux,
+    Android,
+    Macos,
+    Ios,
+    Windows,
+}
+
+fn target_family(platform: PlatformKey) -> TargetFamily {
+    match platform {
+        PlatformKey::LinuxX86_64
+        | PlatformKey::LinuxAarch64
+        | PlatformKey::LinuxX86_64Musl
+        | PlatformKey::LinuxAarch64Musl => TargetFamily::Linux,
+        PlatformKey::AndroidArm64 | PlatformKey::AndroidArmv7 | PlatformKey::AndroidX86_64 => {
+            TargetFamily::Android
+        }
+        PlatformKey::MacosArm64 | PlatformKey::MacosX86_64 | PlatformKey::MacosUniversal => {
+            TargetFamily::Macos
+        }
+        PlatformKey::IosArm64
+        | PlatformKey::IosSimulatorArm64
+        | PlatformKey::IosSimulatorX86_64 => TargetFamily::Ios,
+        PlatformKey::WindowsX86_64Msvc | PlatformKey::WindowsArm64Msvc => TargetFamily::Windows,
+    }
+}
+
+fn no_executor(triple: &str, hint: &str) -> BuildError {
+    BuildError::new(format!(
+        "no executor available for target {}; {}",
+        triple, hint
+    ))
+}
+
+fn probe(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+fn rustc_host_triple() -> BuildResult<String> {
+    let output = Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .map_err(|error| BuildError::new(format!("failed to run rustc -vV: {}", error)))?;
+    if !output.status.success() {
+        return Err(BuildError::new(format!(
+            "rustc -vV exited with status {}",
+            output.status
+        )));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(|triple| triple.trim().to_string())
+        .ok_or_else(|| BuildError::new("host triple missing from rustc -vV output"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::target;
+
+    fn linux_host() -> HostEnvironment {
+        HostEnvironment {
+            host_triple: "x86_64-unknown-linux-gnu".to_string(),
+            zigbuild: true,
+            cross: true,
+            container_engine: true,
+            ndk_home: Some(PathBuf::from("/opt/android-ndk")),
+        }
+    }
+
+    fn kind_for(host: &HostEnvironment, triple: &str) -> ExecutorKind {
+        select_executor(host, &target(triple), &BTreeMap::new())
+            .expect("selection")
+            .kind
+    }
+
+    #[test]
+    fn linux_host_prefers_native_zig_ndk_and_cross() {
+        let host = linux_host();
+        assert_eq!(
+            kind_for(&host, "x86_64-unknown-linux-gnu"),
+            ExecutorKind::Cargo
+        );
+        assert_eq!(
+            kind_for(&host, "aarch64-unknown-linux-gnu"),
+            ExecutorKind::Zigbuild
+        );
+        assert_eq!(
+            kind_for(&host, "aarch64-apple-darwin"),
+            ExecutorKind::Zigbuild
+        );
+        assert_eq!(kind_for(&host, "aarch64-linux-android"), ExecutorKind::Ndk);
+        assert_eq!(
+            kind_for(&host, "x86_64-pc-windows-msvc"),
+            ExecutorKind::Cross
+        );
+    }
+
+    #[test]
+    fn android_falls_back_to_cross_without_ndk() {
+        let host = HostEnvironment {
+            ndk_home: None,
+            ..linux_host()
+        };
+        assert_eq!(
+            kind_for(&host, "armv7-linux-androideabi"),
+            ExecutorKind::Cross
+        );
+    }
+
+    #[test]
+    fn ios_requires_apple_host() {
+        let host = linux_host();
+        let error = select_executor(&host, &target("aarch64-apple-ios"), &BTreeMap::new())
+            .expect_err("ios on linux");
+        assert!(error.message.contains("macos host"));
+
+        let host = HostEnvironment {
+            host_triple: "aarch64-apple-darwin".to_string(),
+            ..linux_host()
+        };
+        assert_eq!(kind_for(&host, "aarch64-apple-ios"), ExecutorKind::Cargo);
+    }
+
+    #[test]
+    fn config_override_wins_and_is_explained() {
+        let host = linux_host();
+        let mut overrides = BTreeMap::new();
+        overrides.insert("aarch64-linux-android".to_string(), "cross".to_string());
+        let selection = select_executor(&host, &target("aarch64-linux-android"), &overrides)
+            .expect("selection");
+        assert_eq!(selection.kind, ExecutorKind::Cross);
+        assert!(selection.reason.contains("xforge.yaml"));
+
+        overrides.insert("aarch64-linux-android".to_string(), "bazel".to_string());
+        assert!(select_executor(&host, &target("aarch64-linux-android"), &overrides).is_err());
+    }
+}
@@ -1,18 +1,44 @@
+use serde::Serialize;
 use xforge_core::build_plan::{BuildPlan, BuiltArtifact};
 
+use crate::events::{observe_target, BuildObserver};
+
 pub type BuildResult<T> = Result<T, BuildError>;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct BuildError {
     pub message: String,
+    pub kind: FailureKind,
+}
+
+/// Why a build attempt failed, which decides whether it is worth retrying.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailureKind {
+    #[default]
+    Failed,
+    /// A network or container registry hiccup; see `process::classify_failure`.
+    Transient,
+    TimedOut,
+    Cancelled,
 }
 
 impl BuildError {
     pub fn new(message: impl Into<String>) -> Self {
         Self {
             message: message.into(),
+            kind: FailureKind::Failed,
         }
     }
+
+    pub fn with_kind(mut self, kind: FailureKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn is_transient(&self) -> bool {
+        self.kind == FailureKind::Transient
+    }
 }
 
 impl std::fmt::Display for BuildError {
@@ -25,4 +51,40 @@ impl std::error::Error for BuildError {}
 
 pub trait BuildExecutor {
     fn execute(&self, plan: &BuildPlan) -> BuildResult<Vec<BuiltArtifact>>;
+
+    /// Build `plan` while reporting progress to `observer`. The default runs
+    /// [`BuildExecutor::execute`] one target at a time and reports each as
+    /// started and finished; executors that drive cargo also report compiler
+    /// diagnostics.
+    fn execute_observed(
+        &self,
+        plan: &BuildPlan,
+        observer: &dyn BuildObserver,
+    ) -> BuildResult<Vec<BuiltArtifact>> {
+        let mut artifacts = Vec::with_capacity(plan.targets.len());
+        for target in &plan.targets {
+            let single = BuildPlan {
+                targets: vec![target.clone()],
+                ..plan.clone()
+            };
+            artifacts.extend(observe_target(observer, target, false, || {
+                self.execute(&single)
+            })?);
+        }
+        Ok(artifacts)
+    }
+}
+
+impl<T: BuildExecutor + ?Sized> BuildExecutor for Box<T> {
+    fn execute(&self, plan: &BuildPlan) -> BuildResult<Vec<BuiltArtifact>> {
+        (**self).execute(plan)
+    }
+
+    fn execute_observed(
+        &self,
+        plan: &BuildPlan,
+        observer: &dyn BuildObserver,
+    ) -> BuildResult<Vec<BuiltArtifact>> {
+        (**self).execute_observed(plan, observer)
+    }
 }
@@ -0,0 +1,866 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use xforge_core::build_id::{hash_build_inputs, AbiInput, BuildInputs};
+use xforge_core::build_plan::{BuildPlan, BuildTargetPlan, BuiltArtifact};
+
+use crate::builder::{BuildError, BuildExecutor, BuildResult};
+use crate::events::{observe_target, BuildObserver, NoopObserver};
+
+const ENTRY_FILE_NAME: &str = "entry.json";
+const CACHE_DIR_ENV: &str = "XFORGE_CACHE_DIR";
+
+/// Content-addressed store of built libraries under
+/// `<root>/<build_id>/<target>/<profile>/`.
+///
+/// Entries also record a fingerprint of the cargo flags and crate sources, and
+/// the digest of every source file cargo's dep-info listed for the library,
+/// so that edits that do not change the build id are never served from cache.
+#[derive(Clone, Debug)]
+pub struct BuildCache {
+    root: PathBuf,
+    max_size_bytes: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub build_id: String,
+    pub target: String,
+    pub profile: String,
+    pub fingerprint: String,
+    pub last_used: u64,
+    pub files: Vec<CachedFile>,
+    /// Source files the library was built from, by absolute path. Any of
+    /// them changing or disappearing makes the entry stale.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<CachedFile>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedFile {
+    pub name: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+impl CacheEntry {
+    pub fn size_bytes(&self) -> u64 {
+        self.files.iter().map(|file| file.size).sum()
+    }
+}
+
+impl BuildCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            max_size_bytes: None,
+        }
+    }
+
+    pub fn with_max_size(mut self, max_size_bytes: Option<u64>) -> Self {
+        self.max_size_bytes = max_size_bytes;
+        self
+    }
+
+    /// `XFORGE_CACHE_DIR`, else the user cache directory (`~/.cache/xforge`).
+    pub fn default_root() -> Option<PathBuf> {
+        if let Some(dir) = std::env::var_os(CACHE_DIR_ENV) {
+            return Some(PathBuf::from(dir));
+        }
+        if let Some(dir) = std::env::var_os("XDG_CACHE_HOME") {
+            return Some(PathBuf::from(dir).join("xforge"));
+        }
+        if cfg!(target_os = "windows") {
+            if let Some(dir) = std::env::var_os("LOCALAPPDATA") {
+                return Some(PathBuf::from(dir).join("xforge").join("cache"));
+            }
+        }
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache").join("xforge"))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn entry_dir(&self, build_id: &str, target: &str, profile: &str) -> PathBuf {
+        self.root.join(build_id).join(target).join(profile)
+    }
+
+    /// Copy verified cached libraries to `destinations`. Corrupt or stale
+    /// entries are removed and reported as a miss.
+    pub fn restore(
+        &self,
+        build_id: &str,
+        target: &str,
+        profile: &str,
+        fingerprint: &str,
+        destinations: &[PathBuf],
+    ) -> BuildResult<bool> {
+        let dir = self.entry_dir(build_id, target, profile);
+        let mut entry = match read_entry(&dir)? {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+        if entry.fingerprint != fingerprint
+            || !verify_files(&dir, &entry)?
+            || !verify_sources(&entry)?
+        {
+            remove_dir(&dir)?;
+            return Ok(false);
+        }
+        let mut copies = Vec::with_capacity(destinations.len());
+        for destination in destinations {
+            let file_name = file_name(destination)?;
+            if !entry.files.iter().any(|file| file.name == file_name) {
+                return Ok(false);
+            }
+            copies.push((dir.join(&file_name), destination));
+        }
+        for (source, destination) in copies {
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).map_err(|error| cache_io("create", parent, error))?;
+            }
+            fs::copy(&source, destination)
+                .map_err(|error| cache_io("restore", &source, error))?;
+        }
+        entry.last_used = now_secs();
+        write_entry(&dir, &entry)?;
+        Ok(true)
+    }
+
+    pub fn store(
+        &self,
+        build_id: &str,
+        target: &str,
+        profile: &str,
+        fingerprint: &str,
+        libraries: &[PathBuf],
+    ) -> BuildResult<CacheEntry> {
+        self.store_with_sources(build_id, target, profile, fingerprint, libraries, &[])
+    }
+
+    /// Like [`BuildCache::store`], also recording the digest of each of
+    /// `sources` that exists so [`BuildCache::restore`] can tell when one of
+    /// them changed.
+    pub fn store_with_sources(
+        &self,
+        build_id: &str,
+        target: &str,
+        profile: &str,
+        fingerprint: &str,
+        libraries: &[PathBuf],
+        sources: &[PathBuf],
+    ) -> BuildResult<CacheEntry> {
+        let mut recorded = Vec::with_capacity(sources.len());
+        for source in sources {
+            // Paths inside a build container do not exist on the host.
+            if source.is_file() {
+                let (sha256, size) = file_digest(source)?;
+                recorded.push(CachedFile {
+                    name: source.to_string_lossy().into_owned(),
+                    sha256,
+                    size,
+                });
+            }
+        }
+        let dir = self.entry_dir(build_id, target, profile);
+        remove_dir(&dir)?;
+        fs::create_dir_all(&dir).map_err(|error| cache_io("create", &dir, error))?;
+        let mut files = Vec::with_capacity(libraries.len());
+        for library in libraries {
+            let name = file_name(library)?;
+            let destination = dir.join(&name);
+            fs::copy(library, &destination).map_err(|error| cache_io("store", library, error))?;
+            let (sha256, size) = file_digest(&destination)?;
+            files.push(CachedFile { name, sha256, size });
+        }
+        let entry = CacheEntry {
+            build_id: build_id.to_string(),
+            target: target.to_string(),
+            profile: profile.to_string(),
+            fingerprint: fingerprint.to_string(),
+            last_used: now_secs(),
+            files,
+            sources: recorded,
+        };
+        write_entry(&dir, &entry)?;
+        if let Some(max_size) = self.max_size_bytes {
+            self.prune(max_size)?;
+        }
+        Ok(entry)
+    }
+
+    /// Every readable entry, most recently used first.
+    pub fn entries(&self) -> BuildResult<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
+        for build_dir in child_dirs(&self.root)? {
+            for target_dir in child_dirs(&build_dir)? {
+                for profile_dir in child_dirs(&target_dir)? {
+                    // Skip entries whose recorded key does not match where
+                    // they sit, so removing one never leaves the cache root.
+                    if let Some(entry) = read_entry(&profile_dir)? {
+                        if self.entry_dir(&entry.build_id, &entry.target, &entry.profile)
+                            == profile_dir
+                        {
+                            entries.push(entry);
+                        }
+                    }
+                }
+            }
+        }
+        entries.sort_by(|left, right| {
+            right
+                .last_used
+                .cmp(&left.last_used)
+                .then_with(|| left.build_id.cmp(&right.build_id))
+                .then_with(|| left.target.cmp(&right.target))
+        });
+        Ok(entries)
+    }
+
+    /// Evict least recently used entries until the cache fits `max_size_bytes`.
+    pub fn prune(&self, max_size_bytes: u64) -> BuildResult<Vec<CacheEntry>> {
+        let entries = self.entries()?;
+        let mut total: u64 = entries.iter().map(CacheEntry::size_bytes).sum();
+        let mut evicted = Vec::new();
+        for entry in entries.into_iter().rev() {
+            if total <= max_size_bytes {
+                break;
+            }
+            total -= entry.size_bytes();
+            self.remove(&entry)?;
+            evicted.push(entry);
+        }
+        Ok(evicted)
+    }
+
+    /// Remove every entry. Files that are not cache entries, and the root
+    /// itself, are left in place: the root may be a directory the user
+    /// shares with other things.
+    pub fn clear(&self) -> BuildResult<usize> {
+        let entries = self.entries()?;
+        for entry in &entries {
+            self.remove(entry)?;
+        }
+        Ok(entries.len())
+    }
+
+    fn remove(&self, entry: &CacheEntry) -> BuildResult<()> {
+        remove_dir(&self.entry_dir(&entry.build_id, &entry.target, &entry.profile))?;
+        let target_dir = self.root.join(&entry.build_id).join(&entry.target);
+        remove_if_empty(&target_dir);
+        remove_if_empty(&self.root.join(&entry.build_id));
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// The library was restored from the cache; the executor did not run.
+    Hit,
+    /// The executor ran and its library was stored.
+    Stored,
+    /// The executor ran but produced no library to store.
+    Uncached,
+}
+
+impl CacheStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CacheStatus::Hit => "hit",
+            CacheStatus::Stored => "stored",
+            CacheStatus::Uncached => "uncached",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheOutcome {
+    pub rust_target_triple: String,
+    pub build_id: String,
+    pub status: CacheStatus,
+}
+
+/// Wraps an executor so targets whose per-target build id and fingerprint are
+/// already cached are restored instead of rebuilt.
+pub struct CachedExecutor<E> {
+    inner: E,
+    cache: BuildCache,
+}
+
+impl<E: BuildExecutor> CachedExecutor<E> {
+    pub fn new(inner: E, cache: BuildCache) -> Self {
+        Self { inner, cache }
+    }
+
+    pub fn execute_with_outcomes(
+        &self,
+        plan: &BuildPlan,
+    ) -> BuildResult<(Vec<BuiltArtifact>, Vec<CacheOutcome>)> {
+        self.execute_with_outcomes_observed(plan, &NoopObserver)
+    }
+
+    /// Like [`CachedExecutor::execute_with_outcomes`], reporting restored
+    /// targets to `observer` as finished from cache and handing rebuilt ones
+    /// to the inner executor's observer support.
+    pub fn execute_with_outcomes_observed(
+        &self,
+        plan: &BuildPlan,
+        observer: &dyn BuildObserver,
+    ) -> BuildResult<(Vec<BuiltArtifact>, Vec<CacheOutcome>)> {
+        let mut artifacts = Vec::with_capacity(plan.targets.len());
+        let mut outcomes = Vec::with_capacity(plan.targets.len());
+        for target in &plan.targets {
+            let build_id = target_build_id(target)?;
+            let fingerprint = target_fingerprint(plan, target)?;
+            let triple = &target.rust_target_triple;
+            let libraries: Vec<PathBuf> = target
+                .artifact
+                .library_paths()
+                .into_iter()
+                .map(PathBuf::from)
+                .collect();
+            let restored = self.cache.restore(
+                &build_id,
+                triple,
+                &plan.profile.name,
+                &fingerprint,
+                &libraries,
+            )?;
+            let status = if restored {
+                artifacts.extend(observe_target(observer, target, true, || {
+                    Ok(vec![target.artifact.clone()])
+                })?);
+                CacheStatus::Hit
+            } else {
+                let single = BuildPlan {
+                    targets: vec![target.clone()],
+                    ..plan.clone()
+                };
+                artifacts.extend(self.inner.execute_observed(&single, observer)?);
+                if libraries.iter().all(|library| library.is_file()) {
+                    let sources = dep_info_sources(&manifest_dir(target), &libraries)?;
+                    self.cache.store_with_sources(
+                        &build_id,
+                        triple,
+                        &plan.profile.name,
+                        &fingerprint,
+                        &libraries,
+                        &sources,
+                    )?;
+                    CacheStatus::Stored
+                } else {
+                    CacheStatus::Uncached
+                }
+            };
+            outcomes.push(CacheOutcome {
+                rust_target_triple: triple.clone(),
+                build_id,
+                status,
+            });
+        }
+        Ok((artifacts, outcomes))
+    }
+}
+
+impl<E: BuildExecutor> BuildExecutor for CachedExecutor<E> {
+    fn execute(&self, plan: &BuildPlan) -> BuildResult<Vec<BuiltArtifact>> {
+        self.execute_with_outcomes(plan)
+            .map(|(artifacts, _outcomes)| artifacts)
+    }
+
+    fn execute_observed(
+        &self,
+        plan: &BuildPlan,
+        observer: &dyn BuildObserver,
+    ) -> BuildResult<Vec<BuiltArtifact>> {
+        self.execute_with_outcomes_observed(plan, observer)
+            .map(|(artifacts, _outcomes)| artifacts)
+    }
+}
+
+fn manifest_dir(target: &BuildTargetPlan) -> PathBuf {
+    let manifest_path = Path::new(&target.working_dir).join(&target.cargo_manifest_path);
+    manifest_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from(&target.working_dir))
+}
+
+fn target_build_id(target: &BuildTargetPlan) -> BuildResult<String> {
+    let inputs = BuildInputs::from_manifest_dir(
+        &manifest_dir(target),
+        AbiInput::new(target.rust_target_triple.clone()),
+        None,
+    )
+    .map_err(|error| BuildError::new(format!("failed to read build inputs: {}", error)))?;
+    hash_build_inputs(&inputs)
+        .map_err(|error| BuildError::new(format!("failed to hash build inputs: {}", error)))
+}
+
+fn target_fingerprint(plan: &BuildPlan, target: &BuildTargetPlan) -> BuildResult<String> {
+    let mut hasher = Sha256::new();
+    let mut push = |label: &str, value: &str| {
+        hasher.update(label.as_bytes());
+        hasher.update([0]);
+        hasher.update(value.as_bytes());
+        hasher.update([0]);
+    };
+    for arg in &plan.profile.cargo_args {
+        push("profile.cargo_arg", arg);
+    }
+    for arg in &target.cargo_args {
+        push("target.cargo_arg", arg);
+    }
+    for flag in &plan.profile.rustflags {
+        push("profile.rustflag", flag);
+    }
+    for feature in &target.cargo_features {
+        push("target.feature", feature);
+    }
+    for entry in plan.profile.env.iter().chain(&target.env) {
+        push("env", &format!("{}={}", entry.key, entry.value));
+    }
+    // Rustflags cargo picks up from the environment and `.cargo/config.toml`
+    // rather than from the plan.
+    let env_triple = target.rust_target_triple.replace(['-', '.'], "_");
+    for key in [
+        "RUSTFLAGS".to_string(),
+        "CARGO_ENCODED_RUSTFLAGS".to_string(),
+        "CARGO_BUILD_RUSTFLAGS".to_string(),
+        format!("CARGO_TARGET_{}_RUSTFLAGS", env_triple.to_uppercase()),
+    ] {
+        if let Some(value) = std::env::var_os(&key) {
+            push("env", &format!("{}={}", key, value.to_string_lossy()));
+        }
+    }
+    let root = manifest_dir(target);
+    for dir in root.ancestors() {
+        for name in ["config.toml", "config"] {
+            let config = dir.join(".cargo").join(name);
+            if config.is_file() {
+                let (digest, _size) = file_digest(&config)?;
+                push(
+                    "cargo_config",
+                    &format!("{}={}", config.to_string_lossy(), digest),
+                );
+            }
+        }
+    }
+    let mut sources = Vec::new();
+    collect_sources(&root.join("src"), &mut sources)?;
+    let build_script = root.join("build.rs");
+    if build_script.is_file() {
+        sources.push(build_script);
+    }
+    sources.sort();
+    for source in sources {
+        let relative = source.strip_prefix(&root).unwrap_or(&source);
+        let (digest, _size) = file_digest(&source)?;
+        push(
+            "source",
+            &format!("{}={}", relative.to_string_lossy(), digest),
+        );
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Every source file listed in the dep-info cargo writes next to each of
+/// `libraries` (`libdemo.so` -> `libdemo.d`): the crate's own files,
+/// `include!`d files and the sources of path dependencies. Relative entries
+/// are resolved against `root`.
+fn dep_info_sources(root: &Path, libraries: &[PathBuf]) -> BuildResult<Vec<PathBuf>> {
+    let mut sources = Vec::new();
+    for library in libraries {
+        let path = library.with_extension("d");
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(cache_io("read", &path, error)),
+        };
+        for source in parse_dep_info(&contents) {
+            sources.push(root.join(source));
+        }
+    }
+    sources.sort();
+    sources.dedup();
+    Ok(sources)
+}
+
+/// Prerequisites of every `output: input input ...` rule in a makefile-style
+/// dep-info file, with `\ ` unescaped to a space.
+fn parse_dep_info(contents: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for line in contents.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let Some((_output, inputs)) = line.split_once(": ") else {
+            continue;
+        };
+        let mut current = String::new();
+        let mut chars = inputs.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' if chars.peek() == Some(&' ') => {
+                    current.push(' ');
+                    chars.next();
+                }
+                ' ' => {
+                    if !current.is_empty() {
+                        paths.push(PathBuf::from(std::mem::take(&mut current)));
+                    }
+                }
+                _ => current.push(ch),
+            }
+        }
+        if !current.is_empty() {
+            paths.push(PathBuf::from(current));
+        }
+    }
+    paths
+}
+
+fn collect_sources(dir: &Path, out: &mut Vec<PathBuf>) -> BuildResult<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for path in child_entries(dir)? {
+        if path.is_dir() {
+            collect_sources(&path, out)?;
+        } else {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> BuildResult<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| BuildError::new(format!("cached path '{}' has no file name", path.display())))
+}
+
+fn verify_files(dir: &Path, entry: &CacheEntry) -> BuildResult<bool> {
+    for file in &entry.files {
+        let path = dir.join(&file.name);
+        if !path.is_file() {
+            return Ok(false);
+        }
+        let (sha256, size) = file_digest(&path)?;
+        if sha256 != file.sha256 || size != file.size {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn verify_sources(entry: &CacheEntry) -> BuildResult<bool> {
+    for source in &entry.sources {
+        let path = Path::new(&source.name);
+        if !path.is_file() {
+            return Ok(false);
+        }
+        let (sha256, size) = file_digest(path)?;
+        if sha256 != source.sha256 || size != source.size {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn file_digest(path: &Path) -> BuildResult<(String, u64)> {
+    let mut file = fs::File::open(path).map_err(|error| cache_io("read", path, error))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|error| cache_io("read", path, error))?;
+        if read == 0 {
+            break;
+        }
+        size += read as u64;
+        hasher.update(&buffer[..read]);
+    }
+    Ok((hex::encode(hasher.finalize()), size))
+}
+
+fn read_entry(dir: &Path) -> BuildResult<Option<CacheEntry>> {
+    let path = dir.join(ENTRY_FILE_NAME);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(cache_io("read", &path, error)),
+    };
+    Ok(serde_json::from_str(&contents).ok())
+}
+
+fn write_entry(dir: &Path, entry: &CacheEntry) -> BuildResult<()> {
+    let path = dir.join(ENTRY_FILE_NAME);
+    let contents = serde_json::to_string_pretty(entry)
+        .map_err(|error| BuildError::new(format!("failed to encode cache entry: {}", error)))?;
+    fs::write(&path, contents).map_err(|error| cache_io("write", &path, error))
+}
+
+fn child_entries(dir: &Path) -> BuildResult<Vec<PathBuf>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(cache_io("read", dir, error)),
+    };
+    let mut paths = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|error| cache_io("read", dir, error))?;
+        paths.push(entry.path());
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn child_dirs(dir: &Path) -> BuildResult<Vec<PathBuf>> {
+    Ok(child_entries(dir)?
+        .into_iter()
+        .filter(|path| path.is_dir())
+        .collect())
+}
+
+fn remove_dir(dir: &Path) -> BuildResult<()> {
+    match fs::remove_dir_all(dir) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(cache_io("remove", dir, error)),
+    }
+}
+
+fn remove_if_empty(dir: &Path) {
+    let _ = fs::remove_dir(dir);
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+fn cache_io(action: &str, path: &Path, error: std::io::Error) -> BuildError {
+    BuildError::new(format!(
+        "build cache failed to {} '{}': {}",
+        action,
+        path.display(),
+        error
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let mut path = std::env::temp_dir();
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        path.push(format!("xforge-build-cache-{}-{}", name, stamp));
+        fs::create_dir_all(&path).expect("create temp dir");
+        path
+    }
+
+    #[test]
+    fn store_then_restore_round_trips() {
+        let dir = temp_dir("round-trip");
+        let cache = BuildCache::new(dir.join("cache"));
+        let libraries = vec![dir.join("libdemo.so"), dir.join("libdemo.a")];
+        fs::write(&libraries[0], b"library").expect("write library");
+        fs::write(&libraries[1], b"archive").expect("write archive");
+        cache
+            .store(
+                "b1-abc",
+                "x86_64-unknown-linux-gnu",
+                "release",
+                "fp",
+                &libraries,
+            )
+            .expect("store");
+
+        let restored = vec![
+            dir.join("out").join("libdemo.so"),
+            dir.join("out").join("libdemo.a"),
+        ];
+        let hit = cache
+            .restore(
+                "b1-abc",
+                "x86_64-unknown-linux-gnu",
+                "release",
+                "fp",
+                &restored,
+            )
+            .expect("restore");
+        assert!(hit);
+        assert_eq!(fs::read(&restored[0]).expect("read restored"), b"library");
+        assert_eq!(fs::read(&restored[1]).expect("read restored"), b"archive");
+
+        let stale = cache
+            .restore(
+                "b1-abc",
+                "x86_64-unknown-linux-gnu",
+                "release",
+                "other",
+                &restored,
+            )
+            .expect("restore");
+        assert!(!stale);
+        assert!(cache.entries().expect("entries").is_empty());
+    }
+
+    #[test]
+    fn corrupt_entry_is_discarded() {
+        let dir = temp_dir("corrupt");
+        let cache = BuildCache::new(dir.join("cache"));
+        let library = dir.join("libdemo.so");
+        fs::write(&library, b"library").expect("write library");
+        cache
+            .store("b1-abc", "aarch64-linux-android", "release", "fp", &[library])
+            .expect("store");
+        let cached = cache
+            .entry_dir("b1-abc", "aarch64-linux-android", "release")
+            .join("libdemo.so");
+        fs::write(&cached, b"tampered").expect("tamper");
+
+        let hit = cache
+            .restore(
+                "b1-abc",
+                "aarch64-linux-android",
+                "release",
+                "fp",
+                &[dir.join("libdemo.so")],
+            )
+            .expect("restore");
+        assert!(!hit);
+        assert!(cache.entries().expect("entries").is_empty());
+    }
+
+    #[test]
+    fn prune_evicts_least_recently_used() {
+        let dir = temp_dir("prune");
+        let cache = BuildCache::new(dir.join("cache"));
+        let library = dir.join("libdemo.so");
+        fs::write(&library, vec![0u8; 100]).expect("write library");
+        for build_id in ["b1-old", "b1-new"] {
+            cache
+                .store(
+                    build_id,
+                    "x86_64-unknown-linux-gnu",
+                    "release",
+                    "fp",
+                    &[library.clone()],
+                )
+                .expect("store");
+        }
+        let old_dir = cache.entry_dir("b1-old", "x86_64-unknown-linux-gnu", "release");
+        let mut entry = read_entry(&old_dir).expect("read").expect("entry");
+        entry.last_used = 1;
+        write_entry(&old_dir, &entry).expect("write");
+
+        let evicted = cache.prune(150).expect("prune");
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].build_id, "b1-old");
+        let remaining = cache.entries().expect("entries");
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].build_id, "b1-new");
+        assert!(!dir.join("cache").join("b1-old").exists());
+
+        fs::write(dir.join("cache").join("notes.txt"), b"keep").expect("write notes");
+        assert_eq!(cache.clear().expect("clear"), 1);
+        assert!(cache.entries().expect("entries").is_empty());
+        assert!(!dir.join("cache").join("b1-new").exists());
+        assert!(dir.join("cache").join("notes.txt").is_file());
+    }
+
+    #[test]
+    fn changed_dep_info_source_makes_entry_stale() {
+        let dir = temp_dir("sources");
+        let cache = BuildCache::new(dir.join("cache"));
+        let library = dir.join("libdemo.so");
+        fs::write(&library, b"library").expect("write library");
+        let shared = dir.join("shared dir").join("lib.rs");
+        fs::create_dir_all(shared.parent().expect("parent")).expect("create shared");
+        fs::write(&shared, "pub fn a() {}").expect("write shared");
+        fs::write(
+            dir.join("libdemo.d"),
+            format!(
+                "{}: src/lib.rs {}\n",
+                library.display(),
+                shared.display().to_string().replace(' ', "\\ ")
+            ),
+        )
+        .expect("write dep-info");
+        fs::create_dir_all(dir.join("src")).expect("create src");
+        fs::write(dir.join("src").join("lib.rs"), "").expect("write lib.rs");
+
+        let sources = dep_info_sources(&dir, &[library.clone()]).expect("sources");
+        assert_eq!(
+            sources,
+            vec![dir.join("shared dir/lib.rs"), dir.join("src/lib.rs")]
+        );
+        cache
+            .store_with_sources(
+                "b1-abc",
+                "x86_64-unknown-linux-gnu",
+                "release",
+                "fp",
+                &[library.clone()],
+                &sources,
+            )
+            .expect("store");
+        let restore = || {
+            cache
+                .restore(
+                    "b1-abc",
+                    "x86_64-unknown-linux-gnu",
+                    "release",
+                    "fp",
+                    &[dir.join("out").join("libdemo.so")],
+                )
+                .expect("restore")
+        };
+        assert!(restore());
+
+        fs::write(&shared, "pub fn b() {}").expect("edit shared");
+        assert!(!restore());
+    }
+
+    #[test]
+    fn entries_outside_their_key_are_ignored() {
+        let dir = temp_dir("misplaced");
+        let cache = BuildCache::new(dir.join("cache"));
+        let library = dir.join("libdemo.so");
+        fs::write(&library, b"library").expect("write library");
+        let mut entry = cache
+            .store(
+                "b1-abc",
+                "x86_64-unknown-linux-gnu",
+                "release",
+                "fp",
+                &[library],
+            )
+            .expect("store");
+        entry.build_id = "..".to_string();
+        write_entry(
+            &cache.entry_dir("b1-abc", "x86_64-unknown-linux-gnu", "release"),
+            &entry,
+        )
+        .expect("write");
+
+        assert!(cache.entries().expect("entries").is_empty());
+        assert_eq!(cache.clear().expect("clear"), 0);
+        assert!(dir.join("libdemo.so").is_file());
+    }
+}
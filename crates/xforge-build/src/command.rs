@@ -0,0 +1,461 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use xforge_core::artifact::LibraryKind;
+use xforge_core::build_plan::{BuildPlan, BuildTargetPlan, BuiltArtifact};
+use xforge_core::config::CommandSettings;
+
+use crate::builder::{BuildError, BuildExecutor, BuildResult};
+use crate::cargo::{apply_env, apply_rustflags, apply_toolchain};
+use crate::deterministic::apply_env_allowlist;
+use crate::process::run_supervised;
+
+/// Runs the command line from `build.command` once per target, then copies
+/// the files its output glob matches to the library paths the plan expects,
+/// so build ids, the cache, stripping and bundling work as for cargo.
+///
+/// `{target}`, `{profile}`, `{target_dir}` and `{features}` are substituted
+/// in both the command and the glob; in the command each value is quoted
+/// for the shell, and is also exported as `XFORGE_TARGET`, `XFORGE_PROFILE`,
+/// `XFORGE_TARGET_DIR` and `XFORGE_FEATURES`. The command runs through `sh
+/// -c` (`cmd /C` on Windows) from the target's working directory; a relative
+/// glob is resolved against that directory.
+#[derive(Clone, Debug)]
+pub struct CommandExecutor {
+    settings: CommandSettings,
+}
+
+impl CommandExecutor {
+    pub fn new(settings: CommandSettings) -> Self {
+        Self { settings }
+    }
+}
+
+impl BuildExecutor for CommandExecutor {
+    fn execute(&self, plan: &BuildPlan) -> BuildResult<Vec<BuiltArtifact>> {
+        let mut artifacts = Vec::with_capacity(plan.targets.len());
+        for target in &plan.targets {
+            let run = render(&self.settings.run, plan, target, shell_quote);
+            let mut command = shell(&run);
+            command.current_dir(&target.working_dir);
+            apply_env_allowlist(&plan.profile.env_allowlist, &mut command);
+            apply_rustflags(&plan.profile.rustflags, &mut command);
+            apply_env(&plan.profile.env, &mut command);
+            apply_env(&target.env, &mut command);
+            apply_toolchain(&plan.profile.toolchain.channel, &mut command);
+            for (name, value) in placeholders(plan, target) {
+                command.env(format!("XFORGE_{}", name.to_uppercase()), value);
+            }
+            run_supervised(&mut command, target.timeout_secs, None)
+                .map_err(|error| {
+                    BuildError::new(format!("failed to run build command '{}': {}", run, error))
+                })?
+                .check(&format!("build command '{}'", run))?;
+
+            let pattern = render(&self.settings.outputs, plan, target, str::to_string);
+            let pattern = Path::new(&target.working_dir).join(&pattern);
+            let outputs = glob(&pattern);
+            if outputs.is_empty() {
+                return Err(BuildError::new(format!(
+                    "build command for {} produced nothing matching '{}'",
+                    target.rust_target_triple,
+                    pattern.display()
+                )));
+            }
+            collect_outputs(&target.artifact, &outputs, &target.rust_target_triple)?;
+            artifacts.push(target.artifact.clone());
+        }
+        Ok(artifacts)
+    }
+}
+
+/// The `{name}` placeholders of a target and their values.
+fn placeholders(plan: &BuildPlan, target: &BuildTargetPlan) -> [(&'static str, String); 4] {
+    [
+        ("target", target.rust_target_triple.clone()),
+        ("profile", plan.profile.name.clone()),
+        (
+            "target_dir",
+            target_dir(target).to_string_lossy().into_owned(),
+        ),
+        ("features", target.cargo_features.join(",")),
+    ]
+}
+
+/// `template` with each placeholder replaced by `escape(value)`. Values are
+/// substituted in one pass, so a value holding `{profile}` stays literal.
+fn render(
+    template: &str,
+    plan: &BuildPlan,
+    target: &BuildTargetPlan,
+    escape: fn(&str) -> String,
+) -> String {
+    let placeholders = placeholders(plan, target);
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let found = placeholders.iter().find(|(name, _)| {
+            rest[1..]
+                .strip_prefix(name)
+                .is_some_and(|after| after.starts_with('}'))
+        });
+        match found {
+            Some((name, value)) => {
+                rendered.push_str(&escape(value));
+                rest = &rest[name.len() + 2..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// `value` as one word for the shell `shell` runs: single-quoted for `sh`,
+/// double-quoted with `"` doubled for `cmd`.
+fn shell_quote(value: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// Cargo's target directory: the plan expects libraries at
+/// `<target_dir>/<triple>/<profile>/<file>`.
+fn target_dir(target: &BuildTargetPlan) -> PathBuf {
+    Path::new(&target.artifact.library_path)
+        .ancestors()
+        .nth(3)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| Path::new(&target.working_dir).join("target"))
+}
+
+fn shell(command_line: &str) -> Command {
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    }
+}
+
+/// Copy each expected library from the matched outputs: an exact file name
+/// wins, otherwise the single output with the same extension.
+fn collect_outputs(
+    artifact: &BuiltArtifact,
+    outputs: &[PathBuf],
+    triple: &str,
+) -> BuildResult<()> {
+    let expected: Vec<(LibraryKind, &str)> = if artifact.libraries.is_empty() {
+        vec![(LibraryKind::Dynamic, artifact.library_path.as_str())]
+    } else {
+        artifact
+            .libraries
+            .iter()
+            .map(|entry| (entry.kind, entry.path.as_str()))
+            .collect()
+    };
+    for (kind, path) in expected {
+        let destination = Path::new(path);
+        let file_name = file_name(destination);
+        let source = match outputs.iter().find(|output| file_name_of(output) == file_name) {
+            Some(output) => output,
+            None => {
+                let suffix = file_name.find('.').map(|dot| &file_name[dot..]).unwrap_or("");
+                let candidates: Vec<&PathBuf> = outputs
+                    .iter()
+                    .filter(|output| !suffix.is_empty() && file_name_of(output).ends_with(suffix))
+                    .collect();
+                match candidates.as_slice() {
+                    [single] => *single,
+                    [] => {
+                        return Err(BuildError::new(format!(
+                            "build command for {} produced no {} library (expected {})",
+                            triple, kind, file_name
+                        )))
+                    }
+                    _ => {
+                        return Err(BuildError::new(format!(
+                            "build command for {} produced several {} libraries ({}); \
+                             narrow the output glob",
+                            triple,
+                            kind,
+                            candidates
+                                .iter()
+                                .map(|candidate| file_name_of(candidate))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )))
+                    }
+                }
+            }
+        };
+        if source == destination {
+            continue;
+        }
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).map_err(|error| io_error("create", parent, error))?;
+        }
+        fs::copy(source, destination).map_err(|error| io_error("copy", source, error))?;
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+}
+
+fn file_name_of(path: &Path) -> String {
+    file_name(path).to_string()
+}
+
+/// Files matching `pattern`, sorted. `*` and `?` match within one path
+/// component and `**` matches any number of directories.
+fn glob(pattern: &Path) -> Vec<PathBuf> {
+    let parts: Vec<String> = pattern
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let literal = parts
+        .iter()
+        .position(|part| part.contains(['*', '?']))
+        .unwrap_or(parts.len());
+    let base: PathBuf = pattern.components().take(literal).collect();
+    let mut matches = Vec::new();
+    glob_from(&base, &parts[literal..], &mut matches);
+    matches.sort();
+    matches.dedup();
+    matches
+}
+
+fn glob_from(dir: &Path, parts: &[String], matches: &mut Vec<PathBuf>) {
+    let Some((part, rest)) = parts.split_first() else {
+        if dir.is_file() {
+            matches.push(dir.to_path_buf());
+        }
+        return;
+    };
+    if part == "**" {
+        glob_from(dir, rest, matches);
+        for entry in sorted_entries(dir) {
+            if entry.is_dir() {
+                glob_from(&entry, parts, matches);
+            }
+        }
+        return;
+    }
+    for entry in sorted_entries(dir) {
+        if wildcard_match(part.as_bytes(), file_name(&entry).as_bytes()) {
+            glob_from(&entry, rest, matches);
+        }
+    }
+}
+
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            wildcard_match(rest, name) || (!name.is_empty() && wildcard_match(pattern, &name[1..]))
+        }
+        (Some((b'?', rest)), Some((_, name_rest))) => wildcard_match(rest, name_rest),
+        (Some((expected, rest)), Some((actual, name_rest))) if expected == actual => {
+            wildcard_match(rest, name_rest)
+        }
+        _ => false,
+    }
+}
+
+fn io_error(action: &str, path: &Path, error: std::io::Error) -> BuildError {
+    BuildError::new(format!(
+        "failed to {} '{}': {}",
+        action,
+        path.display(),
+        error
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use std::os::unix::fs::PermissionsExt;
+    use xforge_core::artifact::LibraryEntry;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("xforge-command-{}-{}", name, stamp));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    /// A stand-in build tool: logs its arguments and writes `libdemo.so` and
+    /// `libdemo.a` into the directory given as its last argument.
+    fn stub_build(dir: &Path) -> PathBuf {
+        let script = format!(
+            "#!/bin/sh\n\
+             echo \"$@\" >> '{log}'\n\
+             for arg; do out=\"$arg\"; done\n\
+             mkdir -p \"$out\"\n\
+             echo dynamic > \"$out/libdemo.so\"\n\
+             echo static > \"$out/libdemo.a\"\n",
+            log = dir.join("build.log").display(),
+        );
+        let path = dir.join("build.sh");
+        fs::write(&path, script).expect("write stub");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod stub");
+        path
+    }
+
+    fn plan(dir: &Path) -> BuildPlan {
+        let profile_dir = dir.join("target/x86_64-unknown-linux-gnu/release");
+        let library = |name: &str| profile_dir.join(name).to_string_lossy().into_owned();
+        let mut plan = test_support::plan(&["x86_64-unknown-linux-gnu"]);
+        let target = &mut plan.targets[0];
+        target.working_dir = dir.to_string_lossy().into_owned();
+        target.cargo_manifest_path = dir.join("Cargo.toml").to_string_lossy().into_owned();
+        target.cargo_features = vec!["ffi".to_string(), "simd".to_string()];
+        target.artifact.library_path = library("libdemo.so");
+        target.artifact.libraries = vec![
+            LibraryEntry {
+                kind: LibraryKind::Dynamic,
+                path: library("libdemo.so"),
+            },
+            LibraryEntry {
+                kind: LibraryKind::Static,
+                path: library("libdemo.a"),
+            },
+        ];
+        plan
+    }
+
+    #[test]
+    fn runs_templated_command_and_collects_outputs() {
+        let dir = temp_dir("run");
+        let script = stub_build(&dir);
+        let executor = CommandExecutor::new(CommandSettings {
+            run: format!(
+                "{} --target {{target}} --profile {{profile}} --features {{features}} out/{{target}}",
+                script.display()
+            ),
+            outputs: "out/{target}/libdemo.*".to_string(),
+        });
+
+        let built = executor.execute(&plan(&dir)).expect("build");
+
+        let log = fs::read_to_string(dir.join("build.log")).expect("log");
+        assert_eq!(
+            log.trim(),
+            "--target x86_64-unknown-linux-gnu --profile release --features ffi,simd \
+             out/x86_64-unknown-linux-gnu"
+        );
+        assert_eq!(
+            fs::read_to_string(&built[0].library_path).expect("dynamic").trim(),
+            "dynamic"
+        );
+        let static_lib = built[0].library(LibraryKind::Static).expect("static");
+        assert_eq!(fs::read_to_string(static_lib).expect("static").trim(), "static");
+    }
+
+    #[test]
+    fn target_dir_placeholder_points_at_cargo_target_dir() {
+        let dir = temp_dir("target-dir");
+        let script = stub_build(&dir);
+        let executor = CommandExecutor::new(CommandSettings {
+            run: format!("{} {{target_dir}}/{{target}}/{{profile}}", script.display()),
+            outputs: "{target_dir}/**/libdemo.*".to_string(),
+        });
+
+        executor.execute(&plan(&dir)).expect("build");
+
+        assert!(dir
+            .join("target/x86_64-unknown-linux-gnu/release/libdemo.a")
+            .is_file());
+    }
+
+    #[test]
+    fn placeholders_are_quoted_and_exported() {
+        let dir = temp_dir("spaced $HOME");
+        let script = stub_build(&dir);
+        let executor = CommandExecutor::new(CommandSettings {
+            run: format!(
+                "printf '%s\\n' \"$XFORGE_TARGET\" \"$XFORGE_FEATURES\" > env.txt && \
+                 '{}' {{target_dir}}/{{target}}/{{profile}}",
+                script.display()
+            ),
+            outputs: "{target_dir}/**/libdemo.*".to_string(),
+        });
+
+        executor.execute(&plan(&dir)).expect("build");
+
+        assert!(dir
+            .join("target/x86_64-unknown-linux-gnu/release/libdemo.so")
+            .is_file());
+        assert_eq!(
+            fs::read_to_string(dir.join("env.txt")).expect("env"),
+            "x86_64-unknown-linux-gnu\nffi,simd\n"
+        );
+    }
+
+    #[test]
+    fn render_substitutes_each_placeholder_once() {
+        let dir = temp_dir("render");
+        let mut plan = plan(&dir);
+        plan.profile.name = "{target}".to_string();
+        let rendered = render(
+            "{profile} {target} {unknown}",
+            &plan,
+            &plan.targets[0],
+            shell_quote,
+        );
+        assert_eq!(rendered, "'{target}' 'x86_64-unknown-linux-gnu' {unknown}");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn missing_outputs_fail_the_build() {
+        let dir = temp_dir("missing");
+        let executor = CommandExecutor::new(CommandSettings {
+            run: "true".to_string(),
+            outputs: "out/*.so".to_string(),
+        });
+        let error = executor.execute(&plan(&dir)).expect_err("no outputs");
+        assert!(error.to_string().contains("produced nothing matching"));
+
+        let executor = CommandExecutor::new(CommandSettings {
+            run: "false".to_string(),
+            outputs: "out/*.so".to_string(),
+        });
+        let error = executor.execute(&plan(&dir)).expect_err("failing command");
+        assert!(error.to_string().contains("exited with status"));
+    }
+
+    #[test]
+    fn wildcards_match_within_a_component() {
+        assert!(wildcard_match(b"lib*.so", b"libdemo.so"));
+        assert!(wildcard_match(b"lib?emo.*", b"libdemo.a"));
+        assert!(!wildcard_match(b"*.so", b"libdemo.so.debug"));
+    }
+}
@@ -0,0 +1,373 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use xforge_core::build_plan::{BuildEnvVar, BuildPlan, BuiltArtifact};
+use xforge_core::config::DeterministicSettings;
+use xforge_core::manifest::DeterministicBuild;
+
+use crate::builder::{BuildExecutor, BuildResult};
+use crate::events::BuildObserver;
+
+/// 1980-01-01T00:00:00Z, the earliest timestamp zip archives can carry and the
+/// one the packers already stamp on every entry.
+pub const DEFAULT_SOURCE_DATE_EPOCH: u64 = 315_532_800;
+
+const WORKSPACE_PREFIX: &str = "/xforge/workspace";
+const CARGO_HOME_PREFIX: &str = "/xforge/cargo";
+const RUSTUP_HOME_PREFIX: &str = "/xforge/rustup";
+
+/// Variables a build may inherit from the host. Per-target compiler, linker,
+/// `pkg-config`, `cross` and wrapper settings are kept so existing cross
+/// setups still work; everything else (`RUSTFLAGS`, `CC`, `CFLAGS`, locale,
+/// time zone, ...) is dropped so two machines run cargo with the same inputs.
+/// Entries ending in `*` match a prefix.
+pub const DEFAULT_ENV_ALLOWLIST: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "TMPDIR",
+    "TMP",
+    "TEMP",
+    "TERM",
+    "CARGO_HOME",
+    "RUSTUP_HOME",
+    "CARGO_HTTP_*",
+    "CARGO_NET_*",
+    "CARGO_REGISTRIES_*",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "NO_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "no_proxy",
+    "SSL_CERT_FILE",
+    "SSL_CERT_DIR",
+    "ANDROID_HOME",
+    "ANDROID_SDK_ROOT",
+    "ANDROID_NDK_HOME",
+    "ANDROID_NDK_ROOT",
+    "ANDROID_NDK",
+    "NDK_HOME",
+    "DEVELOPER_DIR",
+    "SDKROOT",
+    "MACOSX_DEPLOYMENT_TARGET",
+    "IPHONEOS_DEPLOYMENT_TARGET",
+    "TVOS_DEPLOYMENT_TARGET",
+    "WATCHOS_DEPLOYMENT_TARGET",
+    "XROS_DEPLOYMENT_TARGET",
+    "CC_*",
+    "CXX_*",
+    "AR_*",
+    "CARGO_TARGET_*",
+    "PKG_CONFIG*",
+    "RUSTC_WRAPPER",
+    "SCCACHE_*",
+    "DOCKER_HOST",
+    "CROSS_*",
+    "SystemRoot",
+    "SYSTEMROOT",
+    "windir",
+    "ComSpec",
+    "PATHEXT",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "ProgramData",
+    "ProgramFiles",
+    "ProgramFiles(x86)",
+    "NUMBER_OF_PROCESSORS",
+    "PROCESSOR_ARCHITECTURE",
+    "INCLUDE",
+    "LIB",
+    "LIBPATH",
+];
+
+/// Normalised environment applied to every build unless `build.deterministic`
+/// is false: a fixed `SOURCE_DATE_EPOCH`, `--remap-path-prefix` for the
+/// workspace, cargo home and rustup home, `CARGO_INCREMENTAL=0`, and an
+/// environment allowlist.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeterministicEnv {
+    source_date_epoch: u64,
+    cargo_home: Option<PathBuf>,
+    rustup_home: Option<PathBuf>,
+    env_allowlist: Vec<String>,
+}
+
+impl DeterministicEnv {
+    /// `None` when deterministic builds are disabled. An explicit
+    /// `SOURCE_DATE_EPOCH` in the environment wins over the default.
+    pub fn new(settings: &DeterministicSettings) -> Option<Self> {
+        if !settings.enabled {
+            return None;
+        }
+        let home = std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(PathBuf::from);
+        let tool_home = |var: &str, dir: &str| {
+            std::env::var_os(var)
+                .map(PathBuf::from)
+                .or_else(|| home.as_ref().map(|home| home.join(dir)))
+        };
+        let mut env_allowlist: Vec<String> = DEFAULT_ENV_ALLOWLIST
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        for name in &settings.env_passthrough {
+            if !env_allowlist.contains(name) {
+                env_allowlist.push(name.clone());
+            }
+        }
+        Some(Self {
+            source_date_epoch: std::env::var("SOURCE_DATE_EPOCH")
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(DEFAULT_SOURCE_DATE_EPOCH),
+            cargo_home: tool_home("CARGO_HOME", ".cargo"),
+            rustup_home: tool_home("RUSTUP_HOME", ".rustup"),
+            env_allowlist,
+        })
+    }
+
+    /// Names of the host variables the allowlist drops from `plan`'s build,
+    /// sorted.
+    pub fn dropped_env(&self, plan: &BuildPlan) -> Vec<String> {
+        let allowlist = self.allowlist_for(plan);
+        let mut dropped: Vec<String> = std::env::vars_os()
+            .map(|(key, _value)| key.to_string_lossy().into_owned())
+            .filter(|key| !is_allowed(&allowlist, key))
+            .collect();
+        dropped.sort();
+        dropped
+    }
+
+    /// The allowlist, plus `RUSTUP_TOOLCHAIN` when the plan pins no channel:
+    /// then the host's toolchain override is what selects rustc, and dropping
+    /// it would quietly switch to rustup's default.
+    fn allowlist_for(&self, plan: &BuildPlan) -> Vec<String> {
+        let mut allowlist = self.env_allowlist.clone();
+        let toolchain = "RUSTUP_TOOLCHAIN".to_string();
+        if plan.profile.toolchain.channel.is_none() && !allowlist.contains(&toolchain) {
+            allowlist.push(toolchain);
+        }
+        allowlist
+    }
+
+    /// What to record in the manifest's `BuildIdentity`.
+    pub fn identity(&self) -> DeterministicBuild {
+        DeterministicBuild {
+            source_date_epoch: self.source_date_epoch,
+            remapped_path_prefixes: vec![
+                WORKSPACE_PREFIX.to_string(),
+                CARGO_HOME_PREFIX.to_string(),
+                RUSTUP_HOME_PREFIX.to_string(),
+            ],
+            cargo_incremental: false,
+            env_allowlist: self.env_allowlist.clone(),
+        }
+    }
+
+    /// Copy of `plan` carrying the deterministic env, rustflags and allowlist.
+    pub fn apply(&self, plan: &BuildPlan) -> BuildPlan {
+        let allowlist = self.allowlist_for(plan);
+        let mut plan = plan.clone();
+        let mut remaps = Vec::new();
+        for target in &plan.targets {
+            let workspace = workspace_root(Path::new(&target.working_dir));
+            if !remaps.iter().any(|(from, _)| from == &workspace) {
+                remaps.push((workspace, WORKSPACE_PREFIX));
+            }
+        }
+        // rustc applies the last matching remap, so the tool homes go after
+        // the workspace in case they live inside it.
+        if let Some(cargo_home) = &self.cargo_home {
+            remaps.push((cargo_home.clone(), CARGO_HOME_PREFIX));
+        }
+        if let Some(rustup_home) = &self.rustup_home {
+            remaps.push((rustup_home.clone(), RUSTUP_HOME_PREFIX));
+        }
+        let profile = &mut plan.profile;
+        for (from, to) in remaps {
+            profile
+                .rustflags
+                .push(format!("--remap-path-prefix={}={}", from.display(), to));
+        }
+        for (key, value) in [
+            ("SOURCE_DATE_EPOCH", self.source_date_epoch.to_string()),
+            ("CARGO_INCREMENTAL", "0".to_string()),
+        ] {
+            if !profile.env.iter().any(|var| var.key == key) {
+                profile.env.push(BuildEnvVar {
+                    key: key.to_string(),
+                    value,
+                });
+            }
+        }
+        if profile.env_allowlist.is_none() {
+            profile.env_allowlist = Some(allowlist);
+        }
+        plan
+    }
+}
+
+/// Runs every build through [`DeterministicEnv::apply`].
+pub struct DeterministicExecutor<E> {
+    inner: E,
+    env: DeterministicEnv,
+}
+
+impl<E: BuildExecutor> DeterministicExecutor<E> {
+    pub fn new(inner: E, env: DeterministicEnv) -> Self {
+        Self { inner, env }
+    }
+}
+
+impl<E: BuildExecutor> BuildExecutor for DeterministicExecutor<E> {
+    fn execute(&self, plan: &BuildPlan) -> BuildResult<Vec<BuiltArtifact>> {
+        self.inner.execute(&self.env.apply(plan))
+    }
+
+    fn execute_observed(
+        &self,
+        plan: &BuildPlan,
+        observer: &dyn BuildObserver,
+    ) -> BuildResult<Vec<BuiltArtifact>> {
+        self.inner.execute_observed(&self.env.apply(plan), observer)
+    }
+}
+
+/// Clear `command`'s inherited environment down to `allowlist`; a `None`
+/// allowlist leaves it untouched.
+pub(crate) fn apply_env_allowlist(allowlist: &Option<Vec<String>>, command: &mut Command) {
+    let Some(allowlist) = allowlist else {
+        return;
+    };
+    command.env_clear();
+    for (key, value) in std::env::vars_os() {
+        let key_str = key.to_string_lossy();
+        if is_allowed(allowlist, &key_str) {
+            command.env(&key, value);
+        }
+    }
+}
+
+fn is_allowed(allowlist: &[String], key: &str) -> bool {
+    allowlist.iter().any(|entry| match entry.strip_suffix('*') {
+        Some(prefix) => key.starts_with(prefix),
+        None => entry == key,
+    })
+}
+
+/// The directory holding `Cargo.lock`, which is the root cargo reports source
+/// paths against; the working directory itself when there is none.
+fn workspace_root(working_dir: &Path) -> PathBuf {
+    let mut current = Some(working_dir);
+    while let Some(dir) = current {
+        if dir.join("Cargo.lock").exists() {
+            return dir.to_path_buf();
+        }
+        current = dir.parent();
+    }
+    working_dir.to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn env() -> DeterministicEnv {
+        DeterministicEnv {
+            source_date_epoch: DEFAULT_SOURCE_DATE_EPOCH,
+            cargo_home: Some(PathBuf::from("/home/dev/.cargo")),
+            rustup_home: None,
+            env_allowlist: vec!["PATH".to_string(), "CARGO_NET_*".to_string()],
+        }
+    }
+
+    fn plan(env: Vec<BuildEnvVar>) -> BuildPlan {
+        let mut plan = test_support::plan(&[]);
+        plan.profile.rustflags = vec!["-Copt-level=3".to_string()];
+        plan.profile.env = env;
+        plan
+    }
+
+    #[test]
+    fn apply_adds_epoch_remaps_and_allowlist() {
+        let applied = env().apply(&plan(vec![]));
+        let profile = &applied.profile;
+        assert_eq!(
+            profile.rustflags,
+            vec![
+                "-Copt-level=3".to_string(),
+                "--remap-path-prefix=/home/dev/.cargo=/xforge/cargo".to_string(),
+            ]
+        );
+        assert!(profile
+            .env
+            .iter()
+            .any(|var| var.key == "SOURCE_DATE_EPOCH" && var.value == "315532800"));
+        assert!(profile
+            .env
+            .iter()
+            .any(|var| var.key == "CARGO_INCREMENTAL" && var.value == "0"));
+        assert_eq!(profile.env_allowlist.as_ref().map(Vec::len), Some(3));
+    }
+
+    #[test]
+    fn rustup_toolchain_is_kept_unless_the_plan_pins_a_channel() {
+        let allowlist = |channel: Option<&str>| {
+            let mut plan = plan(vec![]);
+            plan.profile.toolchain.channel = channel.map(str::to_string);
+            env().apply(&plan).profile.env_allowlist.unwrap_or_default()
+        };
+        assert!(is_allowed(&allowlist(None), "RUSTUP_TOOLCHAIN"));
+        assert!(!is_allowed(&allowlist(Some("1.80.0")), "RUSTUP_TOOLCHAIN"));
+    }
+
+    #[test]
+    fn explicit_plan_env_is_kept() {
+        let applied = env().apply(&plan(vec![BuildEnvVar {
+            key: "SOURCE_DATE_EPOCH".to_string(),
+            value: "1700000000".to_string(),
+        }]));
+        let epochs: Vec<&str> = applied
+            .profile
+            .env
+            .iter()
+            .filter(|var| var.key == "SOURCE_DATE_EPOCH")
+            .map(|var| var.value.as_str())
+            .collect();
+        assert_eq!(epochs, vec!["1700000000"]);
+    }
+
+    #[test]
+    fn allowlist_matches_names_and_prefixes() {
+        let allowlist = vec!["PATH".to_string(), "CARGO_NET_*".to_string()];
+        assert!(is_allowed(&allowlist, "PATH"));
+        assert!(is_allowed(&allowlist, "CARGO_NET_OFFLINE"));
+        assert!(!is_allowed(&allowlist, "RUSTFLAGS"));
+        assert!(!is_allowed(&allowlist, "PATHEXT"));
+    }
+
+    #[test]
+    fn default_allowlist_keeps_cross_toolchain_settings() {
+        let allowlist: Vec<String> = DEFAULT_ENV_ALLOWLIST
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        for key in [
+            "CC_aarch64_unknown_linux_gnu",
+            "CXX_aarch64_unknown_linux_gnu",
+            "AR_aarch64_unknown_linux_gnu",
+            "CARGO_TARGET_AARCH64_UNKNOWN_LINUX_GNU_LINKER",
+            "PKG_CONFIG_SYSROOT_DIR",
+            "CROSS_REMOTE",
+            "RUSTC_WRAPPER",
+        ] {
+            assert!(is_allowed(&allowlist, key), "{}", key);
+        }
+        assert!(!is_allowed(&allowlist, "RUSTFLAGS"));
+        assert!(!is_allowed(&allowlist, "CFLAGS"));
+    }
+}
@@ -0,0 +1,312 @@
+use std::path::Path;
+use std::process::Command;
+use std::time::Instant;
+
+use serde::Serialize;
+use xforge_core::artifact::LibraryKind;
+use xforge_core::build_plan::{BuildPlan, BuildTargetPlan, BuiltArtifact};
+use xforge_core::platform::PlatformKey;
+
+use crate::builder::{BuildResult, FailureKind};
+use crate::process::{run_supervised, ProcessOutcome};
+
+/// Progress reported while a plan builds. Serialized as one JSON object per
+/// event with an `event` tag, which is what `xforge build --events json`
+/// prints.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum BuildEvent {
+    #[serde(rename_all = "camelCase")]
+    PlanResolved {
+        build_id: String,
+        profile: String,
+        targets: Vec<String>,
+        /// Host variables the deterministic environment keeps from cargo.
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        dropped_env: Vec<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    TargetStarted {
+        rust_target_triple: String,
+        platform: PlatformKey,
+    },
+    /// A warning or error from rustc, as reported by cargo's JSON messages.
+    #[serde(rename_all = "camelCase")]
+    Diagnostic {
+        rust_target_triple: String,
+        level: String,
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rendered: Option<String>,
+    },
+    /// One build attempt ended; a retried target finishes more than once.
+    #[serde(rename_all = "camelCase")]
+    TargetFinished {
+        rust_target_triple: String,
+        success: bool,
+        /// Restored from the build cache instead of built.
+        cached: bool,
+        duration_ms: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        failure: Option<FailureKind>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// A transient failure is about to be retried after `delay_ms`.
+    #[serde(rename_all = "camelCase")]
+    TargetRetrying {
+        rust_target_triple: String,
+        /// The attempt that is about to start, counting from 1.
+        attempt: u32,
+        delay_ms: u64,
+        reason: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    ArtifactLocated {
+        rust_target_triple: String,
+        kind: LibraryKind,
+        path: String,
+    },
+}
+
+impl BuildEvent {
+    /// `dropped_env` lists the host variables the build will not see; see
+    /// [`DeterministicEnv::dropped_env`](crate::DeterministicEnv::dropped_env).
+    pub fn plan_resolved(plan: &BuildPlan, dropped_env: Vec<String>) -> Self {
+        BuildEvent::PlanResolved {
+            build_id: plan.build_id.clone(),
+            profile: plan.profile.name.clone(),
+            targets: plan
+                .targets
+                .iter()
+                .map(|target| target.rust_target_triple.clone())
+                .collect(),
+            dropped_env,
+        }
+    }
+}
+
+/// Receives [`BuildEvent`]s from [`BuildExecutor::execute_observed`].
+///
+/// [`BuildExecutor::execute_observed`]: crate::BuildExecutor::execute_observed
+pub trait BuildObserver {
+    fn on_event(&self, event: &BuildEvent);
+
+    /// Whether cargo should report compiler diagnostics as events. When false
+    /// cargo prints them to the terminal as usual.
+    fn wants_diagnostics(&self) -> bool {
+        true
+    }
+}
+
+/// Ignores every event and leaves cargo's output on the terminal.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopObserver;
+
+impl BuildObserver for NoopObserver {
+    fn on_event(&self, _event: &BuildEvent) {}
+
+    fn wants_diagnostics(&self) -> bool {
+        false
+    }
+}
+
+/// Report `target` as started, run `build`, then report it as finished and
+/// announce every library it left on disk.
+pub(crate) fn observe_target<F>(
+    observer: &dyn BuildObserver,
+    target: &BuildTargetPlan,
+    cached: bool,
+    build: F,
+) -> BuildResult<Vec<BuiltArtifact>>
+where
+    F: FnOnce() -> BuildResult<Vec<BuiltArtifact>>,
+{
+    let triple = &target.rust_target_triple;
+    observer.on_event(&BuildEvent::TargetStarted {
+        rust_target_triple: triple.clone(),
+        platform: target.platform,
+    });
+    let started = Instant::now();
+    let result = build();
+    let error = result.as_ref().err();
+    observer.on_event(&BuildEvent::TargetFinished {
+        rust_target_triple: triple.clone(),
+        success: error.is_none(),
+        cached,
+        duration_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+        failure: error.map(|error| error.kind),
+        error: error.map(|error| error.message.clone()),
+    });
+    if let Ok(artifacts) = &result {
+        for library in artifacts.iter().flat_map(|artifact| &artifact.libraries) {
+            if Path::new(&library.path).is_file() {
+                observer.on_event(&BuildEvent::ArtifactLocated {
+                    rust_target_triple: triple.clone(),
+                    kind: library.kind,
+                    path: library.path.clone(),
+                });
+            }
+        }
+    }
+    result
+}
+
+/// Extra cargo arguments needed to report diagnostics to `observer`.
+pub(crate) fn message_format_args(observer: &dyn BuildObserver) -> Vec<String> {
+    if observer.wants_diagnostics() {
+        vec!["--message-format=json".to_string()]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Run a cargo-compatible build under the target's timeout. With an observer
+/// that wants diagnostics the command must have been given
+/// [`message_format_args`]; its JSON messages become
+/// [`BuildEvent::Diagnostic`]s and any other stdout goes to stderr.
+pub(crate) fn run_cargo(
+    command: &mut Command,
+    target: &BuildTargetPlan,
+    observer: &dyn BuildObserver,
+) -> std::io::Result<ProcessOutcome> {
+    if !observer.wants_diagnostics() {
+        return run_supervised(command, target.timeout_secs, None);
+    }
+    let mut on_line = |line: &str| match cargo_message(line, &target.rust_target_triple) {
+        Some(Some(event)) => observer.on_event(&event),
+        Some(None) => {}
+        None => eprintln!("{}", line),
+    };
+    run_supervised(command, target.timeout_secs, Some(&mut on_line))
+}
+
+/// `None` for lines that are not cargo JSON messages, `Some(None)` for
+/// messages other than compiler diagnostics.
+fn cargo_message(line: &str, rust_target_triple: &str) -> Option<Option<BuildEvent>> {
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    let reason = value.get("reason")?.as_str()?;
+    if reason != "compiler-message" {
+        return Some(None);
+    }
+    let message = value.get("message")?;
+    let text = |key: &str| {
+        message
+            .get(key)
+            .and_then(|value| value.as_str())
+            .map(str::to_string)
+    };
+    Some(Some(BuildEvent::Diagnostic {
+        rust_target_triple: rust_target_triple.to_string(),
+        level: text("level").unwrap_or_else(|| "unknown".to_string()),
+        message: text("message").unwrap_or_default(),
+        rendered: text("rendered"),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    use crate::builder::BuildExecutor;
+    use crate::test_support;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: RefCell<Vec<BuildEvent>>,
+    }
+
+    impl BuildObserver for Recorder {
+        fn on_event(&self, event: &BuildEvent) {
+            self.events.borrow_mut().push(event.clone());
+        }
+    }
+
+    struct Succeeds;
+
+    impl BuildExecutor for Succeeds {
+        fn execute(&self, plan: &BuildPlan) -> BuildResult<Vec<BuiltArtifact>> {
+            Ok(plan
+                .targets
+                .iter()
+                .map(|target| target.artifact.clone())
+                .collect())
+        }
+    }
+
+    fn plan(library: &Path) -> BuildPlan {
+        let mut plan = test_support::plan(&["x86_64-unknown-linux-gnu"]);
+        plan.targets[0].artifact =
+            test_support::dynamic_artifact(PlatformKey::LinuxX86_64, library);
+        plan
+    }
+
+    #[test]
+    fn default_execute_observed_reports_each_target() {
+        let dir = std::env::temp_dir().join(format!("xforge-events-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let library = dir.join("libdemo.so");
+        std::fs::write(&library, b"lib").expect("write library");
+
+        let recorder = Recorder::default();
+        Succeeds
+            .execute_observed(&plan(&library), &recorder)
+            .expect("build");
+        let events = recorder.events.into_inner();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0], BuildEvent::TargetStarted { .. }));
+        assert!(matches!(
+            events[1],
+            BuildEvent::TargetFinished {
+                success: true,
+                cached: false,
+                ..
+            }
+        ));
+        assert_eq!(
+            events[2],
+            BuildEvent::ArtifactLocated {
+                rust_target_triple: "x86_64-unknown-linux-gnu".to_string(),
+                kind: LibraryKind::Dynamic,
+                path: library.to_string_lossy().into_owned(),
+            }
+        );
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn parses_compiler_messages_only() {
+        let line = r#"{"reason":"compiler-message","package_id":"demo","message":{"level":"warning","message":"unused variable: `x`","rendered":"warning: unused variable: `x`\n"}}"#;
+        assert_eq!(
+            cargo_message(line, "aarch64-apple-darwin"),
+            Some(Some(BuildEvent::Diagnostic {
+                rust_target_triple: "aarch64-apple-darwin".to_string(),
+                level: "warning".to_string(),
+                message: "unused variable: `x`".to_string(),
+                rendered: Some("warning: unused variable: `x`\n".to_string()),
+            }))
+        );
+        assert_eq!(
+            cargo_message(r#"{"reason":"build-finished","success":true}"#, "t"),
+            Some(None)
+        );
+        assert_eq!(cargo_message("   Compiling demo v0.1.0", "t"), None);
+    }
+
+    #[test]
+    fn events_serialize_with_a_tag() {
+        let event = BuildEvent::TargetFinished {
+            rust_target_triple: "x86_64-pc-windows-msvc".to_string(),
+            success: false,
+            cached: false,
+            duration_ms: 1200,
+            failure: Some(FailureKind::TimedOut),
+            error: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).expect("json"),
+            r#"{"event":"target-finished","rustTargetTriple":"x86_64-pc-windows-msvc","success":false,"cached":false,"durationMs":1200,"failure":"timed-out"}"#
+        );
+    }
+}
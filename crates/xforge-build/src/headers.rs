@@ -0,0 +1,219 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use xforge_core::build_plan::BuiltArtifact;
+use xforge_core::config::{HeaderLanguage, HeaderSettings};
+use xforge_core::platform::PlatformKey;
+
+use crate::builder::{BuildError, BuildResult};
+
+/// The header cbindgen wrote for one platform.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderOutcome {
+    pub platform: PlatformKey,
+    pub header: PathBuf,
+}
+
+/// Post-build stage that runs cbindgen once per built artifact and writes the
+/// header into the artifact's `include_dir`, which bundling packs as the
+/// archive's `include/` directory. `TARGET` is set to the artifact's rust
+/// target so `[defines]` in the cbindgen config can follow `cfg(target_*)`.
+#[derive(Clone, Debug)]
+pub struct HeaderGenerator {
+    cbindgen: PathBuf,
+    config: Option<PathBuf>,
+    language: HeaderLanguage,
+    file_name: String,
+}
+
+impl HeaderGenerator {
+    pub fn new(settings: &HeaderSettings, package_name: &str) -> Self {
+        Self {
+            cbindgen: settings
+                .cbindgen
+                .clone()
+                .unwrap_or_else(|| PathBuf::from("cbindgen")),
+            config: settings.config.clone(),
+            language: settings.language,
+            file_name: settings.file_name_for(package_name),
+        }
+    }
+
+    /// Generate the header of every artifact that has an `include_dir`.
+    /// The directory is recreated so stale headers never reach an archive.
+    pub fn generate(
+        &self,
+        crate_dir: &Path,
+        artifacts: &[BuiltArtifact],
+    ) -> BuildResult<Vec<HeaderOutcome>> {
+        let mut outcomes = Vec::new();
+        for artifact in artifacts {
+            let Some(include_dir) = &artifact.include_dir else {
+                continue;
+            };
+            let include_dir = Path::new(include_dir);
+            if include_dir.exists() {
+                fs::remove_dir_all(include_dir)
+                    .map_err(|error| header_io("remove", include_dir, error))?;
+            }
+            fs::create_dir_all(include_dir)
+                .map_err(|error| header_io("create", include_dir, error))?;
+            let header = include_dir.join(&self.file_name);
+            self.run_cbindgen(crate_dir, artifact.platform, &header)?;
+            if !header.is_file() {
+                return Err(BuildError::new(format!(
+                    "{} did not write '{}'",
+                    self.cbindgen.display(),
+                    header.display()
+                )));
+            }
+            outcomes.push(HeaderOutcome {
+                platform: artifact.platform,
+                header,
+            });
+        }
+        Ok(outcomes)
+    }
+
+    fn run_cbindgen(
+        &self,
+        crate_dir: &Path,
+        platform: PlatformKey,
+        header: &Path,
+    ) -> BuildResult<()> {
+        let mut command = Command::new(&self.cbindgen);
+        command
+            .arg("--quiet")
+            .arg("--lang")
+            .arg(self.language.as_str())
+            .arg("--output")
+            .arg(header);
+        if let Some(config) = &self.config {
+            command.arg("--config").arg(config);
+        }
+        command.arg(crate_dir).stdin(Stdio::null());
+        if let Some(target) = platform.rust_targets().first() {
+            command.env("TARGET", target);
+        }
+        let output = command.output().map_err(|error| {
+            BuildError::new(format!(
+                "failed to run {} for {}: {}",
+                self.cbindgen.display(),
+                platform,
+                error
+            ))
+        })?;
+        if !output.status.success() {
+            return Err(BuildError::new(format!(
+                "{} exited with status {} for {}: {}",
+                self.cbindgen.display(),
+                output.status,
+                platform,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn header_io(action: &str, path: &Path, error: std::io::Error) -> BuildError {
+    BuildError::new(format!(
+        "failed to {} '{}' while generating headers: {}",
+        action,
+        path.display(),
+        error
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("xforge-headers-{}-{}", name, stamp));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    /// A fake cbindgen that logs its arguments and `TARGET`, and writes the
+    /// file passed to `--output`.
+    fn stub_cbindgen(dir: &Path) -> PathBuf {
+        let script = format!(
+            "#!/bin/sh\n\
+             echo \"$TARGET $@\" >> '{log}'\n\
+             while [ $# -gt 0 ]; do\n\
+             if [ \"$1\" = --output ]; then out=\"$2\"; fi\n\
+             shift\n\
+             done\n\
+             echo '#pragma once' > \"$out\"\n",
+            log = dir.join("cbindgen.log").display(),
+        );
+        let path = dir.join("cbindgen");
+        fs::write(&path, script).expect("write stub");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod stub");
+        path
+    }
+
+    fn artifact(platform: PlatformKey, include_dir: Option<&Path>) -> BuiltArtifact {
+        BuiltArtifact {
+            include_dir: include_dir.map(|dir| dir.to_string_lossy().into_owned()),
+            ..test_support::artifact(platform)
+        }
+    }
+
+    #[test]
+    fn writes_one_header_per_artifact_with_an_include_dir() {
+        let dir = temp_dir("generate");
+        let include_dir = dir.join("aarch64-linux-android").join("include");
+        fs::create_dir_all(&include_dir).expect("create include dir");
+        fs::write(include_dir.join("stale.h"), "stale").expect("write stale header");
+        let generator = HeaderGenerator::new(
+            &HeaderSettings {
+                cbindgen: Some(stub_cbindgen(&dir)),
+                config: Some(dir.join("cbindgen.toml")),
+                language: HeaderLanguage::C,
+                file_name: None,
+                per_target: false,
+            },
+            "demo-ffi",
+        );
+
+        let outcomes = generator
+            .generate(
+                &dir,
+                &[
+                    artifact(PlatformKey::AndroidArm64, Some(&include_dir)),
+                    artifact(PlatformKey::LinuxX86_64, None),
+                ],
+            )
+            .expect("generate");
+
+        let header = include_dir.join("demo_ffi.h");
+        assert_eq!(
+            outcomes,
+            vec![HeaderOutcome {
+                platform: PlatformKey::AndroidArm64,
+                header: header.clone(),
+            }]
+        );
+        assert!(header.is_file());
+        assert!(!include_dir.join("stale.h").exists());
+        let log = fs::read_to_string(dir.join("cbindgen.log")).expect("read log");
+        assert_eq!(
+            log.trim(),
+            format!(
+                "aarch64-linux-android --quiet --lang c --output {} --config {} {}",
+                header.display(),
+                dir.join("cbindgen.toml").display(),
+                dir.display()
+            )
+        );
+    }
+}
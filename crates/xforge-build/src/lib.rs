@@ -1,6 +1,39 @@
+pub mod auto;
 pub mod builder;
+pub mod cache;
 pub mod cargo;
+pub mod command;
 pub mod cross;
+pub mod deterministic;
+pub mod events;
+pub mod headers;
+pub mod matrix;
+pub mod ndk;
+pub mod process;
+pub mod provision;
+pub mod report;
+pub mod reproducible;
+pub mod retry;
+pub mod strip;
+#[cfg(test)]
+mod test_support;
 pub mod zigbuild;
 
-pub use builder::{BuildError, BuildExecutor, BuildResult};
+pub use auto::{AutoExecutor, ExecutorKind, ExecutorSelection, HostEnvironment};
+pub use builder::{BuildError, BuildExecutor, BuildResult, FailureKind};
+pub use cache::{BuildCache, CacheOutcome, CacheStatus, CachedExecutor};
+pub use command::CommandExecutor;
+pub use deterministic::{DeterministicEnv, DeterministicExecutor};
+pub use events::{BuildEvent, BuildObserver, NoopObserver};
+pub use headers::{HeaderGenerator, HeaderOutcome};
+pub use matrix::{github_matrix, GithubMatrix, GithubMatrixEntry, RunnerOs};
+pub use provision::{ProvisionMode, ProvisionReport, Rustup};
+pub use report::{
+    report_file_name, AttemptLog, BuildAttempt, BuildReport, LibraryDigest, TargetReport,
+    REPORT_FILE_NAME,
+};
+pub use reproducible::{
+    verify_reproducible, LibraryComparison, ReproducibilityOptions, ReproducibilityResult,
+};
+pub use retry::RetryExecutor;
+pub use strip::{StripOutcome, Stripper, STRIPPED_DIR_NAME};
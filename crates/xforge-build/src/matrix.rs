@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use xforge_core::build_plan::BuildPlan;
+use xforge_core::platform::PlatformKey;
+
+use crate::auto::{select_executor, ExecutorKind, HostEnvironment};
+use crate::builder::BuildResult;
+
+/// Hosted GitHub Actions runner images that can build a platform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RunnerOs {
+    Ubuntu,
+    Macos,
+    Windows,
+}
+
+impl RunnerOs {
+    pub fn for_platform(platform: PlatformKey) -> Self {
+        match platform {
+            PlatformKey::MacosArm64
+            | PlatformKey::MacosX86_64
+            | PlatformKey::MacosUniversal
+            | PlatformKey::IosArm64
+            | PlatformKey::IosSimulatorArm64
+            | PlatformKey::IosSimulatorX86_64 => RunnerOs::Macos,
+            PlatformKey::WindowsX86_64Msvc | PlatformKey::WindowsArm64Msvc => RunnerOs::Windows,
+            PlatformKey::LinuxX86_64
+            | PlatformKey::LinuxAarch64
+            | PlatformKey::LinuxX86_64Musl
+            | PlatformKey::LinuxAarch64Musl
+            | PlatformKey::AndroidArm64
+            | PlatformKey::AndroidArmv7
+            | PlatformKey::AndroidX86_64 => RunnerOs::Ubuntu,
+        }
+    }
+
+    pub fn github_label(self) -> &'static str {
+        match self {
+            RunnerOs::Ubuntu => "ubuntu-latest",
+            RunnerOs::Macos => "macos-latest",
+            RunnerOs::Windows => "windows-latest",
+        }
+    }
+
+    /// The toolset a stock hosted runner offers once the recommended
+    /// executor has been installed.
+    fn host(self) -> HostEnvironment {
+        match self {
+            RunnerOs::Ubuntu => HostEnvironment {
+                host_triple: "x86_64-unknown-linux-gnu".to_string(),
+                zigbuild: true,
+                cross: true,
+                container_engine: true,
+                ndk_home: Some(PathBuf::from("$ANDROID_NDK_HOME")),
+            },
+            RunnerOs::Macos => HostEnvironment {
+                host_triple: "aarch64-apple-darwin".to_string(),
+                ..HostEnvironment::default()
+            },
+            RunnerOs::Windows => HostEnvironment {
+                host_triple: "x86_64-pc-windows-msvc".to_string(),
+                ..HostEnvironment::default()
+            },
+        }
+    }
+}
+
+/// `strategy.matrix` for a GitHub Actions workflow; one job per runner and
+/// executor pair.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct GithubMatrix {
+    pub include: Vec<GithubMatrixEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct GithubMatrixEntry {
+    pub os: String,
+    pub executor: String,
+    pub targets: Vec<String>,
+}
+
+/// Group the plan's targets by the runner that should build them and the
+/// executor that runner would pick, honouring `build.executors` overrides.
+pub fn github_matrix(
+    plan: &BuildPlan,
+    overrides: &BTreeMap<String, String>,
+) -> BuildResult<GithubMatrix> {
+    let mut groups: BTreeMap<(RunnerOs, ExecutorKind), Vec<String>> = BTreeMap::new();
+    for target in &plan.targets {
+        let runner = RunnerOs::for_platform(target.platform);
+        let selection = select_executor(&runner.host(), target, overrides)?;
+        groups
+            .entry((runner, selection.kind))
+            .or_default()
+            .push(target.rust_target_triple.clone());
+    }
+    Ok(GithubMatrix {
+        include: groups
+            .into_iter()
+            .map(|((runner, kind), targets)| GithubMatrixEntry {
+                os: runner.github_label().to_string(),
+                executor: kind.as_str().to_string(),
+                targets,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::plan;
+
+    #[test]
+    fn groups_targets_by_runner_and_executor() {
+        let plan = plan(&[
+            "x86_64-unknown-linux-gnu",
+            "aarch64-linux-android",
+            "armv7-linux-androideabi",
+            "aarch64-apple-ios",
+            "aarch64-apple-darwin",
+            "x86_64-pc-windows-msvc",
+        ]);
+        let matrix = github_matrix(&plan, &BTreeMap::new()).expect("matrix");
+        let entries: Vec<(&str, &str, Vec<&str>)> = matrix
+            .include
+            .iter()
+            .map(|entry| {
+                (
+                    entry.os.as_str(),
+                    entry.executor.as_str(),
+                    entry.targets.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("ubuntu-latest", "cargo", vec!["x86_64-unknown-linux-gnu"]),
+                (
+                    "ubuntu-latest",
+                    "ndk",
+                    vec!["aarch64-linux-android", "armv7-linux-androideabi"]
+                ),
+                (
+                    "macos-latest",
+                    "cargo",
+                    vec!["aarch64-apple-ios", "aarch64-apple-darwin"]
+                ),
+                ("windows-latest", "cargo", vec!["x86_64-pc-windows-msvc"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn overrides_split_groups() {
+        let plan = plan(&["aarch64-linux-android", "x86_64-linux-android"]);
+        let overrides = BTreeMap::from([(
+            "x86_64-linux-android".to_string(),
+            "cross".to_string(),
+        )]);
+        let matrix = github_matrix(&plan, &overrides).expect("matrix");
+        assert_eq!(matrix.include.len(), 2);
+        assert_eq!(matrix.include[0].executor, "cross");
+        assert_eq!(matrix.include[0].targets, vec!["x86_64-linux-android"]);
+        assert_eq!(matrix.include[1].executor, "ndk");
+    }
+}
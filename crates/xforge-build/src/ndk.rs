@@ -0,0 +1,185 @@
+use std::path::{Path, PathBuf};
+
+use xforge_core::build_plan::{BuildEnvVar, BuildPlan, BuiltArtifact};
+use xforge_core::platform::PlatformKey;
+
+use crate::builder::{BuildError, BuildExecutor, BuildResult};
+use crate::cargo::CargoExecutor;
+use crate::events::{BuildObserver, NoopObserver};
+
+pub const DEFAULT_ANDROID_API_LEVEL: u32 = 21;
+
+const NDK_HOME_VARS: &[&str] = &["ANDROID_NDK_HOME", "ANDROID_NDK_ROOT", "NDK_HOME"];
+const SDK_HOME_VARS: &[&str] = &["ANDROID_HOME", "ANDROID_SDK_ROOT"];
+
+/// Builds Android targets with plain cargo, pointing the linker, C compiler and
+/// archiver at the NDK's LLVM toolchain.
+#[derive(Clone, Debug)]
+pub struct NdkExecutor {
+    ndk_home: Option<PathBuf>,
+    api_level: u32,
+}
+
+impl Default for NdkExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NdkExecutor {
+    pub fn new() -> Self {
+        Self {
+            ndk_home: None,
+            api_level: DEFAULT_ANDROID_API_LEVEL,
+        }
+    }
+
+    pub fn with_ndk_home(mut self, ndk_home: PathBuf) -> Self {
+        self.ndk_home = Some(ndk_home);
+        self
+    }
+
+    pub fn with_api_level(mut self, api_level: u32) -> Self {
+        self.api_level = api_level;
+        self
+    }
+}
+
+impl BuildExecutor for NdkExecutor {
+    fn execute(&self, plan: &BuildPlan) -> BuildResult<Vec<BuiltArtifact>> {
+        self.execute_observed(plan, &NoopObserver)
+    }
+
+    fn execute_observed(
+        &self,
+        plan: &BuildPlan,
+        observer: &dyn BuildObserver,
+    ) -> BuildResult<Vec<BuiltArtifact>> {
+        let ndk_home = match &self.ndk_home {
+            Some(path) => path.clone(),
+            None => detect_ndk_home().ok_or_else(|| {
+                BuildError::new("android ndk not found; set ANDROID_NDK_HOME".to_string())
+            })?,
+        };
+        let bin_dir = ndk_bin_dir(&ndk_home)?;
+        let mut plan = plan.clone();
+        for target in &mut plan.targets {
+            let env = ndk_env(
+                &bin_dir,
+                target.platform,
+                &target.rust_target_triple,
+                self.api_level,
+            )?;
+            target.env.extend(env);
+        }
+        CargoExecutor::new().execute_observed(&plan, observer)
+    }
+}
+
+/// Locate an NDK from the conventional environment variables, falling back to
+/// the newest side-by-side install under the Android SDK.
+pub fn detect_ndk_home() -> Option<PathBuf> {
+    for key in NDK_HOME_VARS {
+        if let Some(value) = std::env::var_os(key) {
+            let path = PathBuf::from(value);
+            if path.is_dir() {
+                return Some(path);
+            }
+        }
+    }
+    for key in SDK_HOME_VARS {
+        if let Some(value) = std::env::var_os(key) {
+            if let Some(path) = latest_side_by_side_ndk(&PathBuf::from(value).join("ndk")) {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+fn latest_side_by_side_ndk(root: &Path) -> Option<PathBuf> {
+    let entries = std::fs::read_dir(root).ok()?;
+    let mut versions: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    versions.sort();
+    versions.pop()
+}
+
+fn ndk_bin_dir(ndk_home: &Path) -> BuildResult<PathBuf> {
+    let bin_dir = ndk_home
+        .join("toolchains")
+        .join("llvm")
+        .join("prebuilt")
+        .join(ndk_host_tag())
+        .join("bin");
+    if !bin_dir.is_dir() {
+        return Err(BuildError::new(format!(
+            "android ndk toolchain missing at '{}'",
+            bin_dir.display()
+        )));
+    }
+    Ok(bin_dir)
+}
+
+fn ndk_env(
+    bin_dir: &Path,
+    platform: PlatformKey,
+    rust_target_triple: &str,
+    api_level: u32,
+) -> BuildResult<Vec<BuildEnvVar>> {
+    let clang_target = match platform {
+        PlatformKey::AndroidArm64 => "aarch64-linux-android",
+        PlatformKey::AndroidArmv7 => "armv7a-linux-androideabi",
+        PlatformKey::AndroidX86_64 => "x86_64-linux-android",
+        _ => {
+            return Err(BuildError::new(format!(
+                "ndk executor cannot build non-android target {}",
+                rust_target_triple
+            )))
+        }
+    };
+    let clang = bin_dir.join(format!(
+        "{}{}-clang{}",
+        clang_target,
+        api_level,
+        script_suffix()
+    ));
+    let ar = bin_dir.join(format!("llvm-ar{}", std::env::consts::EXE_SUFFIX));
+    let clang = clang.to_string_lossy().into_owned();
+    let env_triple = rust_target_triple.replace('-', "_");
+    Ok(vec![
+        BuildEnvVar {
+            key: format!("CARGO_TARGET_{}_LINKER", env_triple.to_uppercase()),
+            value: clang.clone(),
+        },
+        BuildEnvVar {
+            key: format!("CC_{}", env_triple),
+            value: clang,
+        },
+        BuildEnvVar {
+            key: format!("AR_{}", env_triple),
+            value: ar.to_string_lossy().into_owned(),
+        },
+    ])
+}
+
+fn ndk_host_tag() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "darwin-x86_64"
+    } else if cfg!(target_os = "windows") {
+        "windows-x86_64"
+    } else {
+        "linux-x86_64"
+    }
+}
+
+fn script_suffix() -> &'static str {
+    if cfg!(target_os = "windows") {
+        ".cmd"
+    } else {
+        ""
+    }
+}
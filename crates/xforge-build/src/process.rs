@@ -0,0 +1,432 @@
+use std::io::{BufRead, BufReader, IsTerminal, Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::builder::{BuildError, BuildResult, FailureKind};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Time a process tree gets to exit after SIGTERM before it is killed.
+const KILL_GRACE: Duration = Duration::from_secs(3);
+/// How long to wait for output still buffered in the pipes once a child has
+/// exited; a daemon it spawned (sccache, a container) may hold them open.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+/// Bytes of stderr kept for classifying a failure.
+const STDERR_TAIL_BYTES: usize = 16 * 1024;
+
+/// Fragments of a cargo `error:` line that mark a network failure worth
+/// retrying, with the reason reported for the retry. Only cargo's own error
+/// lines are searched, so a build script or test that prints one of these
+/// does not make a real failure look transient.
+const NETWORK_PATTERNS: &[(&str, &str)] = &[
+    ("failed to download", "dependency download failed"),
+    ("failed to fetch", "dependency download failed"),
+    ("could not resolve host", "network error"),
+    ("temporary failure in name resolution", "network error"),
+    ("connection reset", "network error"),
+    ("connection refused", "network error"),
+    ("connection timed out", "network error"),
+    ("operation timed out", "network error"),
+    ("tls handshake timeout", "network error"),
+    ("i/o timeout", "network error"),
+    ("502 bad gateway", "registry unavailable"),
+    ("503 service unavailable", "registry unavailable"),
+    ("504 gateway timeout", "registry unavailable"),
+];
+
+/// Fragments that mark a line as a container engine error; the engine's
+/// messages have no common prefix, so any line may carry one.
+const CONTAINER_ERRORS: &[&str] = &[
+    "error response from daemon",
+    "error pulling image",
+    "failed to pull",
+];
+
+/// Causes that make a container engine error worth retrying. Permanent
+/// failures such as "no such image", "manifest unknown" or "pull access
+/// denied" name none of these, so they fail straight away.
+const CONTAINER_CAUSES: &[(&str, &str)] = &[
+    ("toomanyrequests", "container registry rate limit"),
+    ("i/o timeout", "container network error"),
+    ("tls handshake timeout", "container network error"),
+    ("context deadline exceeded", "container network error"),
+    ("while waiting for connection", "container network error"),
+    ("dial tcp", "container network error"),
+    ("no such host", "container network error"),
+    ("connection reset", "container network error"),
+    ("connection refused", "container network error"),
+    ("502 bad gateway", "container registry unavailable"),
+    ("503 service unavailable", "container registry unavailable"),
+    ("504 gateway timeout", "container registry unavailable"),
+];
+
+/// The warning cargo prints before retrying a request itself.
+const SPURIOUS_NETWORK_WARNING: &str = "warning: spurious network error";
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Stop every supervised build: running process trees are killed and no new
+/// ones start. Library callers wire this to their own cancellation.
+pub fn cancel() {
+    CANCELLED.store(true, Ordering::SeqCst);
+}
+
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+/// Turn the first Ctrl-C (or SIGTERM) into [`cancel`]; a second one exits
+/// immediately. On Windows the console already delivers Ctrl-C to every
+/// process in the tree, so the default handling is kept.
+pub fn install_ctrl_c_handler() {
+    #[cfg(unix)]
+    {
+        extern "C" fn on_signal(_signal: libc::c_int) {
+            if CANCELLED.swap(true, Ordering::SeqCst) {
+                // SAFETY: `_exit` is async-signal-safe.
+                unsafe { libc::_exit(130) };
+            }
+        }
+        let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        // SAFETY: the handler only touches an atomic and calls `_exit`.
+        unsafe {
+            libc::signal(libc::SIGINT, handler);
+            libc::signal(libc::SIGTERM, handler);
+        }
+    }
+}
+
+/// Why a supervised process did not run to completion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Stopped {
+    TimedOut(u64),
+    Cancelled,
+}
+
+#[derive(Debug)]
+pub(crate) struct ProcessOutcome {
+    /// `None` when the process was never started because the build was
+    /// already cancelled.
+    pub status: Option<ExitStatus>,
+    pub stopped: Option<Stopped>,
+    /// The end of what the process wrote to stderr.
+    pub stderr_tail: String,
+}
+
+impl ProcessOutcome {
+    /// `Ok` when the process exited successfully; otherwise an error naming
+    /// `tool` and classified as timed out, cancelled, transient or failed.
+    pub fn check(&self, tool: &str) -> BuildResult<()> {
+        match (self.stopped, self.status) {
+            (Some(Stopped::TimedOut(secs)), _) => Err(BuildError::new(format!(
+                "{} timed out after {}s",
+                tool, secs
+            ))
+            .with_kind(FailureKind::TimedOut)),
+            (Some(Stopped::Cancelled), _) | (None, None) => {
+                Err(BuildError::new(format!("{} was cancelled", tool))
+                    .with_kind(FailureKind::Cancelled))
+            }
+            (None, Some(status)) if status.success() => Ok(()),
+            (None, Some(status)) => {
+                let message = format!("{} exited with status {}", tool, status);
+                Err(match classify_failure(&self.stderr_tail) {
+                    Some(reason) => BuildError::new(format!("{} ({})", message, reason))
+                        .with_kind(FailureKind::Transient),
+                    None => BuildError::new(message),
+                })
+            }
+        }
+    }
+}
+
+/// The reason a failed build looks transient, judged from its output.
+pub fn classify_failure(output: &str) -> Option<&'static str> {
+    let find = |patterns: &[(&str, &'static str)], text: &str| {
+        patterns
+            .iter()
+            .find(|(pattern, _)| text.contains(pattern))
+            .map(|(_, reason)| *reason)
+    };
+    let mut container = None;
+    for line in output.lines() {
+        let line = strip_ansi(line).to_lowercase();
+        if line.starts_with(SPURIOUS_NETWORK_WARNING) {
+            return Some("network error");
+        }
+        if let Some(message) = line.strip_prefix("error:") {
+            if let Some(reason) = find(NETWORK_PATTERNS, message) {
+                return Some(reason);
+            }
+        }
+        if CONTAINER_ERRORS.iter().any(|error| line.contains(error)) {
+            container = container.or_else(|| find(CONTAINER_CAUSES, &line));
+        }
+    }
+    container
+}
+
+/// `line` without the ANSI color sequences cargo adds on a terminal.
+fn strip_ansi(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            // CSI sequences end at the first byte in `@`..=`~`.
+            if chars.next() == Some('[') {
+                for ch in chars.by_ref() {
+                    if ('@'..='~').contains(&ch) {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        plain.push(ch);
+    }
+    plain
+}
+
+/// Run `command` in its own process group, copying its stderr to ours. With
+/// `on_stdout_line` its stdout is read line by line, otherwise inherited.
+/// The whole process tree is killed when `timeout_secs` elapses or the build
+/// is cancelled.
+pub(crate) fn run_supervised(
+    command: &mut Command,
+    timeout_secs: Option<u64>,
+    mut on_stdout_line: Option<&mut dyn FnMut(&str)>,
+) -> std::io::Result<ProcessOutcome> {
+    if is_cancelled() {
+        return Ok(ProcessOutcome {
+            status: None,
+            stopped: Some(Stopped::Cancelled),
+            stderr_tail: String::new(),
+        });
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let color_set = command
+        .get_envs()
+        .any(|(key, _)| key == "CARGO_TERM_COLOR");
+    if !color_set && std::io::stderr().is_terminal() {
+        command.env("CARGO_TERM_COLOR", "always");
+    }
+    command.stderr(Stdio::piped());
+    if on_stdout_line.is_some() {
+        command.stdout(Stdio::piped());
+    } else {
+        command.stdout(Stdio::inherit());
+    }
+    let mut child = command.spawn()?;
+
+    let stderr_tail = Arc::new(Mutex::new(Vec::new()));
+    let (stderr_done, stderr_finished) = mpsc::channel::<()>();
+    if let Some(mut stderr) = child.stderr.take() {
+        let tail = Arc::clone(&stderr_tail);
+        thread::spawn(move || {
+            let mut buffer = [0u8; 8192];
+            while let Ok(read) = stderr.read(&mut buffer) {
+                if read == 0 {
+                    break;
+                }
+                let chunk = &buffer[..read];
+                let _ = std::io::stderr().write_all(chunk);
+                if let Ok(mut tail) = tail.lock() {
+                    tail.extend_from_slice(chunk);
+                    let excess = tail.len().saturating_sub(STDERR_TAIL_BYTES);
+                    tail.drain(..excess);
+                }
+            }
+            let _ = stderr_done.send(());
+        });
+    }
+    let stdout_lines = child.stdout.take().map(|stdout| {
+        let (sender, receiver) = mpsc::channel::<String>();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        receiver
+    });
+
+    let deadline = timeout_secs.map(|secs| Instant::now() + Duration::from_secs(secs));
+    let (status, stopped) = loop {
+        match (&stdout_lines, on_stdout_line.as_deref_mut()) {
+            (Some(lines), Some(on_line)) => match lines.recv_timeout(POLL_INTERVAL) {
+                Ok(line) => on_line(&line),
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => {
+                    thread::sleep(POLL_INTERVAL)
+                }
+            },
+            _ => thread::sleep(POLL_INTERVAL),
+        }
+        if let Some(status) = child.try_wait()? {
+            break (status, None);
+        }
+        if is_cancelled() {
+            break (kill_tree(&mut child)?, Some(Stopped::Cancelled));
+        }
+        if let (Some(deadline), Some(secs)) = (deadline, timeout_secs) {
+            if Instant::now() >= deadline {
+                break (kill_tree(&mut child)?, Some(Stopped::TimedOut(secs)));
+            }
+        }
+    };
+    if let (Some(lines), Some(on_line)) = (&stdout_lines, on_stdout_line) {
+        while let Ok(line) = lines.recv_timeout(DRAIN_TIMEOUT) {
+            on_line(&line);
+        }
+    }
+    let _ = stderr_finished.recv_timeout(DRAIN_TIMEOUT);
+    let stderr_tail = stderr_tail
+        .lock()
+        .map(|tail| String::from_utf8_lossy(&tail).into_owned())
+        .unwrap_or_default();
+    Ok(ProcessOutcome {
+        status: Some(status),
+        stopped,
+        stderr_tail,
+    })
+}
+
+/// Ask the child's process group to terminate, then kill whatever is left.
+#[cfg(unix)]
+fn kill_tree(child: &mut Child) -> std::io::Result<ExitStatus> {
+    let group = -(child.id() as libc::pid_t);
+    // SAFETY: signalling a process group has no memory-safety requirements.
+    unsafe { libc::kill(group, libc::SIGTERM) };
+    let grace = Instant::now() + KILL_GRACE;
+    while Instant::now() < grace && child.try_wait()?.is_none() {
+        thread::sleep(POLL_INTERVAL);
+    }
+    // Grandchildren may outlive the leader; the group id stays valid while
+    // any of them is alive.
+    // SAFETY: as above.
+    unsafe { libc::kill(group, libc::SIGKILL) };
+    child.wait()
+}
+
+#[cfg(not(unix))]
+fn kill_tree(child: &mut Child) -> std::io::Result<ExitStatus> {
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &child.id().to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    let _ = child.kill();
+    child.wait()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_network_and_registry_failures() {
+        assert_eq!(
+            classify_failure("warning: spurious network error (2 tries remaining)"),
+            Some("network error")
+        );
+        assert_eq!(
+            classify_failure("Error response from daemon: toomanyrequests: rate limit"),
+            Some("container registry rate limit")
+        );
+        assert_eq!(
+            classify_failure("error[E0308]: mismatched types"),
+            None
+        );
+        assert_eq!(
+            classify_failure(
+                "Error response from daemon: Get \"https://ghcr.io/v2/\": net/http: TLS handshake timeout"
+            ),
+            Some("container network error")
+        );
+    }
+
+    #[test]
+    fn permanent_container_failures_are_not_retried() {
+        for output in [
+            "Error response from daemon: No such image: ghcr.io/cross-rs/demo:edge",
+            "Error response from daemon: manifest for ghcr.io/cross-rs/demo:edge not found: manifest unknown",
+            "Error response from daemon: pull access denied for demo, repository does not exist or may require 'docker login'",
+            "Error: initializing source docker://demo:latest: reading manifest latest: manifest unknown",
+            "Error pulling image: pull access denied",
+        ] {
+            assert_eq!(classify_failure(output), None, "{}", output);
+        }
+    }
+
+    #[test]
+    fn network_patterns_only_count_on_cargo_error_lines() {
+        assert_eq!(
+            classify_failure(
+                "\x1b[1m\x1b[31merror\x1b[0m\x1b[1m:\x1b[0m failed to download from `https://static.crates.io/crates/serde/1.0.0/download`"
+            ),
+            Some("dependency download failed")
+        );
+        assert_eq!(
+            classify_failure(
+                "error: failed to run custom build command for `demo-sys`\n\
+                 --- stderr\n\
+                 thread 'main' panicked: connection refused\n"
+            ),
+            None
+        );
+        assert_eq!(
+            classify_failure("test client::tests::retries ... connection reset by peer"),
+            None
+        );
+        assert_eq!(
+            classify_failure(
+                "error: failed to get `serde` as a dependency of package `demo`\n\
+                 Caused by:\n  no matching package named `serde` found"
+            ),
+            None
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timeout_kills_the_process_tree() {
+        let marker = std::env::temp_dir().join(format!("xforge-timeout-{}", std::process::id()));
+        let _ = std::fs::remove_file(&marker);
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(format!(
+            "(sleep 2; touch '{}') & sleep 30",
+            marker.display()
+        ));
+        let started = Instant::now();
+        let outcome = run_supervised(&mut command, Some(1), None).expect("run");
+        assert_eq!(outcome.stopped, Some(Stopped::TimedOut(1)));
+        assert!(started.elapsed() < Duration::from_secs(10));
+        let error = outcome.check("sleep").expect_err("timed out");
+        assert_eq!(error.kind, FailureKind::TimedOut);
+        thread::sleep(Duration::from_secs(2));
+        assert!(!marker.exists(), "background child outlived the timeout");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failed_exit_is_classified_from_stderr() {
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg("echo 'error: failed to download `serde`' >&2; exit 101");
+        let mut lines = Vec::new();
+        let mut on_line = |line: &str| lines.push(line.to_string());
+        let outcome = run_supervised(&mut command, None, Some(&mut on_line)).expect("run");
+        assert!(outcome.stderr_tail.contains("failed to download"));
+        let error = outcome.check("cargo build").expect_err("failed");
+        assert!(error.is_transient());
+        assert!(error.message.starts_with("cargo build exited with status"));
+    }
+}
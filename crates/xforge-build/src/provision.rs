@@ -0,0 +1,322 @@
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use xforge_core::toolchain::Toolchain;
+
+use crate::builder::{BuildError, BuildResult};
+
+/// Architectures that prefix the host triple rustup appends to toolchain and
+/// component names (`stable-x86_64-unknown-linux-gnu`, `rustfmt-aarch64-...`).
+const TRIPLE_ARCHES: &[&str] = &[
+    "x86_64", "aarch64", "arm", "armv7", "i586", "i686", "loongarch64", "powerpc", "powerpc64",
+    "powerpc64le", "riscv64gc", "s390x", "thumbv7neon", "wasm32",
+];
+
+/// Whether missing toolchain pieces are installed or only reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProvisionMode {
+    Install,
+    ReportOnly,
+}
+
+/// What rustup was missing for a build, and whether it was installed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProvisionReport {
+    /// Requested channel; `None` means the active rustup toolchain.
+    pub toolchain: Option<String>,
+    pub missing_toolchain: bool,
+    pub missing_targets: Vec<String>,
+    pub missing_components: Vec<String>,
+    pub installed: bool,
+}
+
+impl ProvisionReport {
+    pub fn is_complete(&self) -> bool {
+        !self.missing_toolchain
+            && self.missing_targets.is_empty()
+            && self.missing_components.is_empty()
+    }
+}
+
+/// Installs the toolchain, targets and components a build needs through rustup.
+#[derive(Clone, Debug)]
+pub struct Rustup {
+    program: PathBuf,
+}
+
+impl Default for Rustup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Rustup {
+    pub fn new() -> Self {
+        Self {
+            program: PathBuf::from("rustup"),
+        }
+    }
+
+    pub fn with_program(program: PathBuf) -> Self {
+        Self { program }
+    }
+
+    pub fn is_available(&self) -> bool {
+        Command::new(&self.program)
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+
+    /// Compare `toolchain` plus the rust targets about to be built with what
+    /// rustup has installed, installing the difference unless `mode` is
+    /// [`ProvisionMode::ReportOnly`].
+    pub fn provision(
+        &self,
+        toolchain: &Toolchain,
+        targets: &[String],
+        mode: ProvisionMode,
+    ) -> BuildResult<ProvisionReport> {
+        let channel = toolchain.channel.clone();
+        let mut report = ProvisionReport {
+            toolchain: channel.clone(),
+            ..ProvisionReport::default()
+        };
+        if let Some(channel) = &channel {
+            let installed = self.run(&["toolchain", "list"])?;
+            report.missing_toolchain = !installed
+                .lines()
+                .filter_map(|line| line.split_whitespace().next())
+                .any(|name| matches_host_qualified(name, channel));
+        }
+
+        let mut targets = targets.to_vec();
+        targets.sort();
+        targets.dedup();
+        if report.missing_toolchain {
+            report.missing_targets = targets;
+            report.missing_components = toolchain.components.clone();
+        } else {
+            let installed = self.run(&with_toolchain(
+                &["target", "list", "--installed"],
+                channel.as_deref(),
+            ))?;
+            let installed: Vec<&str> = installed.lines().map(str::trim).collect();
+            report.missing_targets = targets
+                .into_iter()
+                .filter(|target| !installed.contains(&target.as_str()))
+                .collect();
+
+            let installed = self.run(&with_toolchain(
+                &["component", "list", "--installed"],
+                channel.as_deref(),
+            ))?;
+            report.missing_components = toolchain
+                .components
+                .iter()
+                .filter(|component| {
+                    !installed
+                        .lines()
+                        .any(|line| matches_host_qualified(line.trim(), component))
+                })
+                .cloned()
+                .collect();
+        }
+
+        if mode == ProvisionMode::ReportOnly || report.is_complete() {
+            return Ok(report);
+        }
+        if report.missing_toolchain {
+            if let Some(channel) = &channel {
+                self.run(&["toolchain", "install", channel, "--profile", "minimal"])?;
+            }
+        }
+        if !report.missing_targets.is_empty() {
+            let mut args = with_toolchain(&["target", "add"], channel.as_deref());
+            args.extend(report.missing_targets.iter().map(String::as_str));
+            self.run(&args)?;
+        }
+        if !report.missing_components.is_empty() {
+            let mut args = with_toolchain(&["component", "add"], channel.as_deref());
+            args.extend(report.missing_components.iter().map(String::as_str));
+            self.run(&args)?;
+        }
+        report.installed = true;
+        Ok(report)
+    }
+
+    fn run(&self, args: &[&str]) -> BuildResult<String> {
+        let output = Command::new(&self.program)
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|error| {
+                BuildError::new(format!("failed to run rustup {}: {}", args.join(" "), error))
+            })?;
+        if !output.status.success() {
+            return Err(BuildError::new(format!(
+                "rustup {} exited with status {}: {}",
+                args.join(" "),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+fn with_toolchain<'a>(args: &[&'a str], channel: Option<&'a str>) -> Vec<&'a str> {
+    let mut args = args.to_vec();
+    if let Some(channel) = channel {
+        args.push("--toolchain");
+        args.push(channel);
+    }
+    args
+}
+
+/// rustup lists `stable` as `stable-<host>` and `rustfmt` as `rustfmt-<host>`;
+/// a bare match or a host-triple suffix both count.
+fn matches_host_qualified(listed: &str, name: &str) -> bool {
+    if listed == name {
+        return true;
+    }
+    let Some(rest) = listed
+        .strip_prefix(name)
+        .and_then(|rest| rest.strip_prefix('-'))
+    else {
+        return false;
+    };
+    let arch = rest.split('-').next().unwrap_or_default();
+    TRIPLE_ARCHES.contains(&arch)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("xforge-rustup-{}-{}", name, stamp));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    /// A fake rustup that answers list queries from fixtures and logs every
+    /// invocation.
+    fn stub_rustup(dir: &Path, toolchains: &str, targets: &str, components: &str) -> PathBuf {
+        let log = dir.join("rustup.log");
+        let script = format!(
+            "#!/bin/sh\n\
+             echo \"$@\" >> '{log}'\n\
+             case \"$1 $2\" in\n\
+             \"toolchain list\") printf '{toolchains}' ;;\n\
+             \"target list\") printf '{targets}' ;;\n\
+             \"component list\") printf '{components}' ;;\n\
+             esac\n",
+            log = log.display(),
+        );
+        let path = dir.join("rustup");
+        fs::write(&path, script).expect("write stub");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod stub");
+        path
+    }
+
+    fn logged(dir: &Path) -> Vec<String> {
+        fs::read_to_string(dir.join("rustup.log"))
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn toolchain(channel: &str, components: &[&str]) -> Toolchain {
+        Toolchain {
+            channel: Some(channel.to_string()),
+            targets: vec![],
+            components: components.iter().map(|value| value.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn installs_missing_targets_and_components() {
+        let dir = temp_dir("install");
+        let rustup = Rustup::with_program(stub_rustup(
+            &dir,
+            "stable-x86_64-unknown-linux-gnu (default)\\n",
+            "x86_64-unknown-linux-gnu\\n",
+            "rustfmt-x86_64-unknown-linux-gnu\\nrust-std-x86_64-unknown-linux-gnu\\n",
+        ));
+        let report = rustup
+            .provision(
+                &toolchain("stable", &["rustfmt", "clippy", "rust"]),
+                &[
+                    "x86_64-unknown-linux-gnu".to_string(),
+                    "aarch64-linux-android".to_string(),
+                ],
+                ProvisionMode::Install,
+            )
+            .expect("provision");
+        assert!(!report.missing_toolchain);
+        assert_eq!(report.missing_targets, vec!["aarch64-linux-android"]);
+        assert_eq!(report.missing_components, vec!["clippy", "rust"]);
+        assert!(report.installed);
+        let log = logged(&dir);
+        assert!(log.contains(&"target add --toolchain stable aarch64-linux-android".to_string()));
+        assert!(log.contains(&"component add --toolchain stable clippy rust".to_string()));
+        assert!(!log.iter().any(|line| line.starts_with("toolchain install")));
+    }
+
+    #[test]
+    fn report_only_mode_never_installs() {
+        let dir = temp_dir("report");
+        let rustup = Rustup::with_program(stub_rustup(
+            &dir,
+            "stable-x86_64-unknown-linux-gnu (default)\\nnightly-2024-01-01-x86_64-unknown-linux-gnu\\n",
+            "",
+            "",
+        ));
+        let report = rustup
+            .provision(
+                &toolchain("nightly", &["rust-src"]),
+                &["wasm32-unknown-unknown".to_string()],
+                ProvisionMode::ReportOnly,
+            )
+            .expect("provision");
+        assert!(report.missing_toolchain);
+        assert_eq!(report.missing_targets, vec!["wasm32-unknown-unknown"]);
+        assert_eq!(report.missing_components, vec!["rust-src"]);
+        assert!(!report.installed);
+        assert_eq!(logged(&dir), vec!["toolchain list".to_string()]);
+    }
+
+    #[test]
+    fn missing_toolchain_is_installed_before_targets() {
+        let dir = temp_dir("toolchain");
+        let rustup = Rustup::with_program(stub_rustup(&dir, "", "", ""));
+        let report = rustup
+            .provision(
+                &toolchain("1.80.0", &[]),
+                &["x86_64-unknown-linux-gnu".to_string()],
+                ProvisionMode::Install,
+            )
+            .expect("provision");
+        assert!(report.installed);
+        assert_eq!(
+            logged(&dir),
+            vec![
+                "toolchain list".to_string(),
+                "toolchain install 1.80.0 --profile minimal".to_string(),
+                "target add --toolchain 1.80.0 x86_64-unknown-linux-gnu".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use xforge_core::artifact::LibraryKind;
+use xforge_core::build_plan::BuildPlan;
+use xforge_core::platform::PlatformKey;
+
+use crate::builder::{BuildError, BuildResult, FailureKind};
+use crate::events::{BuildEvent, BuildObserver};
+use crate::reproducible::ReproducibilityResult;
+
+/// Written next to cargo's output directory by `xforge build`.
+pub const REPORT_FILE_NAME: &str = "xforge-build-report.json";
+
+/// Report file for `profile`: [`REPORT_FILE_NAME`] for `release`, otherwise
+/// suffixed with the profile so each profile of a release keeps its own.
+pub fn report_file_name(profile: &str) -> String {
+    if profile == "release" {
+        REPORT_FILE_NAME.to_string()
+    } else {
+        format!("xforge-build-report-{}.json", profile)
+    }
+}
+
+/// Machine-readable summary of one `xforge build` run.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildReport {
+    pub build_id: String,
+    pub profile: String,
+    pub targets: Vec<TargetReport>,
+    /// Present when the build ran with `--verify-reproducible`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reproducibility: Option<Vec<ReproducibilityResult>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetReport {
+    pub rust_target_triple: String,
+    pub platform: PlatformKey,
+    pub libraries: Vec<LibraryDigest>,
+    /// Every build attempt, including retries and cache restores.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<BuildAttempt>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildAttempt {
+    /// Counting from 1.
+    pub attempt: u32,
+    pub success: bool,
+    pub cached: bool,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure: Option<FailureKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryDigest {
+    pub kind: LibraryKind,
+    pub path: String,
+    /// `None` when the executor did not leave the file where the plan expects.
+    pub sha256: Option<String>,
+}
+
+impl BuildReport {
+    /// Digest every library the plan's targets produced.
+    pub fn new(plan: &BuildPlan) -> BuildResult<Self> {
+        let mut targets = Vec::with_capacity(plan.targets.len());
+        for target in &plan.targets {
+            let mut libraries = Vec::with_capacity(target.artifact.libraries.len());
+            for library in &target.artifact.libraries {
+                let path = Path::new(&library.path);
+                libraries.push(LibraryDigest {
+                    kind: library.kind,
+                    path: library.path.clone(),
+                    sha256: if path.is_file() {
+                        Some(sha256_file(path)?)
+                    } else {
+                        None
+                    },
+                });
+            }
+            targets.push(TargetReport {
+                rust_target_triple: target.rust_target_triple.clone(),
+                platform: target.platform,
+                libraries,
+                attempts: Vec::new(),
+            });
+        }
+        Ok(Self {
+            build_id: plan.build_id.clone(),
+            profile: plan.profile.name.clone(),
+            targets,
+            reproducibility: None,
+        })
+    }
+
+    /// Attach the attempts an [`AttemptLog`] collected to their targets.
+    pub fn record_attempts(&mut self, attempts: &BTreeMap<String, Vec<BuildAttempt>>) {
+        for target in &mut self.targets {
+            if let Some(recorded) = attempts.get(&target.rust_target_triple) {
+                target.attempts = recorded.clone();
+            }
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> BuildResult<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| report_io("create", parent, error))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|error| BuildError::new(format!("failed to encode build report: {}", error)))?;
+        fs::write(path, contents).map_err(|error| report_io("write", path, error))
+    }
+}
+
+/// Passes events through to another observer while collecting every
+/// finished attempt per rust target triple for the build report.
+pub struct AttemptLog<'a> {
+    inner: &'a dyn BuildObserver,
+    attempts: RefCell<BTreeMap<String, Vec<BuildAttempt>>>,
+}
+
+impl<'a> AttemptLog<'a> {
+    pub fn new(inner: &'a dyn BuildObserver) -> Self {
+        Self {
+            inner,
+            attempts: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn attempts(&self) -> BTreeMap<String, Vec<BuildAttempt>> {
+        self.attempts.borrow().clone()
+    }
+}
+
+impl BuildObserver for AttemptLog<'_> {
+    fn on_event(&self, event: &BuildEvent) {
+        if let BuildEvent::TargetFinished {
+            rust_target_triple,
+            success,
+            cached,
+            duration_ms,
+            failure,
+            error,
+        } = event
+        {
+            let mut attempts = self.attempts.borrow_mut();
+            let recorded = attempts.entry(rust_target_triple.clone()).or_default();
+            recorded.push(BuildAttempt {
+                attempt: recorded.len() as u32 + 1,
+                success: *success,
+                cached: *cached,
+                duration_ms: *duration_ms,
+                failure: *failure,
+                error: error.clone(),
+            });
+        }
+        self.inner.on_event(event);
+    }
+
+    fn wants_diagnostics(&self) -> bool {
+        self.inner.wants_diagnostics()
+    }
+}
+
+pub(crate) fn sha256_file(path: &Path) -> BuildResult<String> {
+    let bytes = fs::read(path).map_err(|error| report_io("read", path, error))?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
+
+fn report_io(action: &str, path: &Path, error: std::io::Error) -> BuildError {
+    BuildError::new(format!(
+        "failed to {} '{}': {}",
+        action,
+        path.display(),
+        error
+    ))
+}
@@ -0,0 +1,362 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use xforge_core::artifact::LibraryKind;
+use xforge_core::binary::diff_sections;
+use xforge_core::build_plan::{BuildEnvVar, BuildPlan, BuildTargetPlan};
+
+use crate::builder::{BuildError, BuildExecutor, BuildResult};
+use crate::report::sha256_file;
+
+/// Directories that are never copied when the second build runs from a
+/// different source path.
+const SKIPPED_SOURCE_DIRS: &[&str] = &["target", ".git"];
+
+/// Environment the second build runs under with `vary_environment`; none of it
+/// should reach a deterministic build's output.
+const VARIED_ENV: &[(&str, &str)] = &[
+    ("TZ", "Etc/GMT-14"),
+    ("LANG", "fr_CH.UTF-8"),
+    ("LC_ALL", "fr_CH.UTF-8"),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReproducibilityOptions {
+    /// Each build gets a fresh target directory under here.
+    pub scratch_dir: PathBuf,
+    /// Build the second time from a copy of the crate at another path.
+    pub vary_path: bool,
+    /// Build the second time with a different time zone and locale.
+    pub vary_environment: bool,
+}
+
+/// Outcome of building one target twice.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReproducibilityResult {
+    pub rust_target_triple: String,
+    pub reproducible: bool,
+    pub libraries: Vec<LibraryComparison>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryComparison {
+    pub kind: LibraryKind,
+    pub first_sha256: String,
+    pub second_sha256: String,
+    /// Sections whose bytes differ; empty when the digests match.
+    pub differing_sections: Vec<String>,
+    /// Why sections could not be compared, e.g. for static library archives.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Build every target of `plan` twice in isolated target directories and
+/// compare the library digests, diffing sections where they disagree. The
+/// caller's own target directory is left alone.
+pub fn verify_reproducible<E: BuildExecutor + ?Sized>(
+    executor: &E,
+    plan: &BuildPlan,
+    options: &ReproducibilityOptions,
+) -> BuildResult<Vec<ReproducibilityResult>> {
+    if plan.targets.is_empty() {
+        return Err(BuildError::new(
+            "reproducibility check has nothing to compare: the plan has no targets",
+        ));
+    }
+    let first = isolated_plan(plan, options, 1)?;
+    executor.execute(&first)?;
+    let second = isolated_plan(plan, options, 2)?;
+    executor.execute(&second)?;
+
+    let mut results = Vec::with_capacity(plan.targets.len());
+    for (left, right) in first.targets.iter().zip(&second.targets) {
+        // A plan without `libraries`, such as one written before they were
+        // recorded, still ships `library_path` as its dynamic library.
+        let outputs: Vec<(LibraryKind, &str)> = if left.artifact.libraries.is_empty() {
+            vec![(LibraryKind::Dynamic, left.artifact.library_path.as_str())]
+        } else {
+            left.artifact
+                .libraries
+                .iter()
+                .map(|library| (library.kind, library.path.as_str()))
+                .collect()
+        };
+        let mut libraries = Vec::with_capacity(outputs.len());
+        for (kind, path) in outputs {
+            let other = right.artifact.library(kind).ok_or_else(|| {
+                BuildError::new(format!(
+                    "second build of {} has no {} library",
+                    right.rust_target_triple, kind
+                ))
+            })?;
+            libraries.push(compare(kind, Path::new(path), Path::new(other))?);
+        }
+        results.push(ReproducibilityResult {
+            rust_target_triple: left.rust_target_triple.clone(),
+            reproducible: libraries
+                .iter()
+                .all(|library| library.first_sha256 == library.second_sha256),
+            libraries,
+        });
+    }
+    Ok(results)
+}
+
+fn compare(kind: LibraryKind, first: &Path, second: &Path) -> BuildResult<LibraryComparison> {
+    let first_sha256 = sha256_file(first)?;
+    let second_sha256 = sha256_file(second)?;
+    let mut comparison = LibraryComparison {
+        kind,
+        first_sha256,
+        second_sha256,
+        differing_sections: Vec::new(),
+        note: None,
+    };
+    if comparison.first_sha256 == comparison.second_sha256 {
+        return Ok(comparison);
+    }
+    let left = fs::read(first).map_err(|error| repro_io("read", first, error))?;
+    let right = fs::read(second).map_err(|error| repro_io("read", second, error))?;
+    match diff_sections(&left, &right) {
+        Ok(diffs) => {
+            comparison.differing_sections = diffs.into_iter().map(|diff| diff.name).collect();
+        }
+        Err(error) => comparison.note = Some(error.to_string()),
+    }
+    Ok(comparison)
+}
+
+/// The plan for build `run`: target directory moved to `<scratch>/run-<n>`,
+/// and for the second run optionally a relocated source tree and a varied
+/// environment.
+fn isolated_plan(
+    plan: &BuildPlan,
+    options: &ReproducibilityOptions,
+    run: usize,
+) -> BuildResult<BuildPlan> {
+    let mut isolated = plan.clone();
+    let mut targets = Vec::with_capacity(plan.targets.len());
+    let mut copied: Option<PathBuf> = None;
+    for target in &plan.targets {
+        let mut slice = plan.for_target(&target.rust_target_triple).ok_or_else(|| {
+            BuildError::new(format!("plan has no target '{}'", target.rust_target_triple))
+        })?;
+        let mut target_dir = target_dir(target);
+        if run == 2 && options.vary_path {
+            let working_dir = PathBuf::from(&target.working_dir);
+            let copy = options.scratch_dir.join("source-2");
+            if copied.as_ref() != Some(&working_dir) {
+                remove_dir(&copy)?;
+                copy_dir(&working_dir, &copy, &options.scratch_dir)?;
+                copied = Some(working_dir.clone());
+            }
+            slice = slice.relative_to(&working_dir).rooted_at(&copy);
+            if let Ok(relative) = target_dir.strip_prefix(&working_dir) {
+                target_dir = copy.join(relative);
+            }
+        }
+        let run_dir = options.scratch_dir.join(format!("run-{}", run));
+        slice = slice.relative_to(&target_dir).rooted_at(&run_dir);
+        let mut target = slice.targets.remove(0);
+        if run == 2 && options.vary_environment {
+            for (key, value) in VARIED_ENV {
+                target.env.push(BuildEnvVar {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                });
+            }
+        }
+        targets.push(target);
+    }
+    let run_dir = options.scratch_dir.join(format!("run-{}", run));
+    remove_dir(&run_dir)?;
+    fs::create_dir_all(&run_dir).map_err(|error| repro_io("create", &run_dir, error))?;
+    isolated.targets = targets;
+    Ok(isolated)
+}
+
+/// Where the plan points cargo's output: `CARGO_TARGET_DIR`, then
+/// `--target-dir`, then `<working_dir>/target`.
+fn target_dir(target: &BuildTargetPlan) -> PathBuf {
+    if let Some(var) = target.env.iter().find(|var| var.key == "CARGO_TARGET_DIR") {
+        return PathBuf::from(&var.value);
+    }
+    if let Some(index) = target.cargo_args.iter().position(|arg| arg == "--target-dir") {
+        if let Some(dir) = target.cargo_args.get(index + 1) {
+            return PathBuf::from(dir);
+        }
+    }
+    Path::new(&target.working_dir).join("target")
+}
+
+/// Copy a source tree, leaving out build output, VCS metadata and the scratch
+/// directory the copy itself lives in.
+fn copy_dir(from: &Path, to: &Path, scratch_dir: &Path) -> BuildResult<()> {
+    fs::create_dir_all(to).map_err(|error| repro_io("create", to, error))?;
+    let entries = fs::read_dir(from).map_err(|error| repro_io("read", from, error))?;
+    for entry in entries {
+        let entry = entry.map_err(|error| repro_io("read", from, error))?;
+        let path = entry.path();
+        let name = entry.file_name();
+        let file_type = entry
+            .file_type()
+            .map_err(|error| repro_io("read", &path, error))?;
+        if file_type.is_dir() {
+            if path == scratch_dir
+                || SKIPPED_SOURCE_DIRS
+                    .iter()
+                    .any(|skipped| name.as_os_str() == *skipped)
+            {
+                continue;
+            }
+            copy_dir(&path, &to.join(&name), scratch_dir)?;
+        } else if file_type.is_file() {
+            fs::copy(&path, to.join(&name)).map_err(|error| repro_io("copy", &path, error))?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_dir(dir: &Path) -> BuildResult<()> {
+    if dir.exists() {
+        fs::remove_dir_all(dir).map_err(|error| repro_io("remove", dir, error))?;
+    }
+    Ok(())
+}
+
+fn repro_io(action: &str, path: &Path, error: std::io::Error) -> BuildError {
+    BuildError::new(format!(
+        "reproducibility check failed to {} '{}': {}",
+        action,
+        path.display(),
+        error
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use std::cell::RefCell;
+    use xforge_core::build_plan::BuiltArtifact;
+    use xforge_core::platform::PlatformKey;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("xforge-repro-{}-{}", name, stamp));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    /// Writes each library with `contents(working_dir, env)` and records the
+    /// plans it ran.
+    struct WritingExecutor<F> {
+        contents: F,
+        runs: RefCell<Vec<BuildTargetPlan>>,
+    }
+
+    impl<F: Fn(&BuildTargetPlan) -> Vec<u8>> BuildExecutor for WritingExecutor<F> {
+        fn execute(&self, plan: &BuildPlan) -> BuildResult<Vec<BuiltArtifact>> {
+            for target in &plan.targets {
+                for library in target.artifact.library_paths() {
+                    let path = Path::new(library);
+                    fs::create_dir_all(path.parent().expect("parent")).expect("create dir");
+                    fs::write(path, (self.contents)(target)).expect("write library");
+                }
+                self.runs.borrow_mut().push(target.clone());
+            }
+            Ok(plan.targets.iter().map(|target| target.artifact.clone()).collect())
+        }
+    }
+
+    fn plan(root: &Path) -> BuildPlan {
+        let target_dir = root.join("target");
+        let library = target_dir
+            .join("x86_64-unknown-linux-gnu")
+            .join("release")
+            .join("libdemo.so");
+        let mut plan = test_support::plan(&["x86_64-unknown-linux-gnu"]);
+        let target = &mut plan.targets[0];
+        target.working_dir = root.to_string_lossy().into_owned();
+        target.env = vec![BuildEnvVar {
+            key: "CARGO_TARGET_DIR".to_string(),
+            value: target_dir.to_string_lossy().into_owned(),
+        }];
+        target.artifact = test_support::dynamic_artifact(PlatformKey::LinuxX86_64, &library);
+        plan
+    }
+
+    #[test]
+    fn identical_outputs_from_isolated_dirs_are_reproducible() {
+        let root = temp_dir("same");
+        fs::write(root.join("Cargo.toml"), "[package]").expect("write manifest");
+        let executor = WritingExecutor {
+            contents: |_: &BuildTargetPlan| b"same".to_vec(),
+            runs: RefCell::new(Vec::new()),
+        };
+        let options = ReproducibilityOptions {
+            scratch_dir: root.join("target").join("xforge-repro"),
+            vary_path: true,
+            vary_environment: true,
+        };
+        let results = verify_reproducible(&executor, &plan(&root), &options).expect("verify");
+        assert!(results[0].reproducible);
+        assert!(!root.join("target").join("x86_64-unknown-linux-gnu").exists());
+
+        let runs = executor.runs.borrow();
+        assert!(runs[0].artifact.library_path.contains("run-1"));
+        assert!(runs[1].artifact.library_path.contains("run-2"));
+        assert!(runs[1].working_dir.ends_with("source-2"));
+        assert!(Path::new(&runs[1].working_dir).join("Cargo.toml").is_file());
+        assert!(runs[1].env.iter().any(|var| var.key == "TZ"));
+        assert!(!runs[0].env.iter().any(|var| var.key == "TZ"));
+    }
+
+    #[test]
+    fn path_leaks_are_reported() {
+        let root = temp_dir("leak");
+        let executor = WritingExecutor {
+            contents: |target: &BuildTargetPlan| target.working_dir.clone().into_bytes(),
+            runs: RefCell::new(Vec::new()),
+        };
+        let options = ReproducibilityOptions {
+            scratch_dir: root.join("scratch"),
+            vary_path: true,
+            vary_environment: false,
+        };
+        let results = verify_reproducible(&executor, &plan(&root), &options).expect("verify");
+        assert!(!results[0].reproducible);
+        let library = &results[0].libraries[0];
+        assert_ne!(library.first_sha256, library.second_sha256);
+        assert!(library.note.is_some());
+    }
+
+    #[test]
+    fn plans_without_libraries_compare_the_library_path() {
+        let root = temp_dir("legacy");
+        let executor = WritingExecutor {
+            contents: |_: &BuildTargetPlan| b"same".to_vec(),
+            runs: RefCell::new(Vec::new()),
+        };
+        let options = ReproducibilityOptions {
+            scratch_dir: root.join("scratch"),
+            vary_path: false,
+            vary_environment: false,
+        };
+        let mut plan = plan(&root);
+        plan.targets[0].artifact.libraries.clear();
+        let results = verify_reproducible(&executor, &plan, &options).expect("verify");
+        assert_eq!(results[0].libraries.len(), 1);
+        assert_eq!(results[0].libraries[0].kind, LibraryKind::Dynamic);
+
+        plan.targets.clear();
+        let error = verify_reproducible(&executor, &plan, &options).expect_err("no targets");
+        assert!(error.message.contains("nothing to compare"));
+    }
+}
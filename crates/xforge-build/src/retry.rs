@@ -0,0 +1,171 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use xforge_core::build_plan::{BuildPlan, BuiltArtifact};
+use xforge_core::config::RetrySettings;
+
+use crate::builder::{BuildError, BuildExecutor, BuildResult, FailureKind};
+use crate::events::{BuildEvent, BuildObserver, NoopObserver};
+use crate::process::is_cancelled;
+
+/// Rebuilds a target whose attempt failed with a [`FailureKind::Transient`]
+/// error, up to `build.retry.attempts` attempts with `delay_secs` between
+/// them. Every attempt is reported to the observer, so callers can record
+/// them.
+pub struct RetryExecutor<E> {
+    inner: E,
+    settings: RetrySettings,
+}
+
+impl<E: BuildExecutor> RetryExecutor<E> {
+    pub fn new(inner: E, settings: RetrySettings) -> Self {
+        Self { inner, settings }
+    }
+}
+
+impl<E: BuildExecutor> BuildExecutor for RetryExecutor<E> {
+    fn execute(&self, plan: &BuildPlan) -> BuildResult<Vec<BuiltArtifact>> {
+        self.execute_observed(plan, &NoopObserver)
+    }
+
+    fn execute_observed(
+        &self,
+        plan: &BuildPlan,
+        observer: &dyn BuildObserver,
+    ) -> BuildResult<Vec<BuiltArtifact>> {
+        let delay = Duration::from_secs(self.settings.delay_secs);
+        let mut artifacts = Vec::with_capacity(plan.targets.len());
+        for target in &plan.targets {
+            let single = BuildPlan {
+                targets: vec![target.clone()],
+                ..plan.clone()
+            };
+            let mut attempt = 1;
+            loop {
+                match self.inner.execute_observed(&single, observer) {
+                    Ok(built) => {
+                        artifacts.extend(built);
+                        break;
+                    }
+                    Err(error) if error.is_transient() && attempt < self.settings.attempts => {
+                        attempt += 1;
+                        observer.on_event(&BuildEvent::TargetRetrying {
+                            rust_target_triple: target.rust_target_triple.clone(),
+                            attempt,
+                            delay_ms: u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+                            reason: error.message,
+                        });
+                        wait(delay)?;
+                    }
+                    Err(error) => return Err(error),
+                }
+            }
+        }
+        Ok(artifacts)
+    }
+}
+
+/// Sleep for `delay`, giving up early when the build is cancelled.
+fn wait(delay: Duration) -> BuildResult<()> {
+    let until = Instant::now() + delay;
+    loop {
+        if is_cancelled() {
+            return Err(BuildError::new("build was cancelled").with_kind(FailureKind::Cancelled));
+        }
+        let now = Instant::now();
+        if now >= until {
+            return Ok(());
+        }
+        thread::sleep((until - now).min(Duration::from_millis(100)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    use crate::test_support;
+
+    /// Fails with `kind` until `failures` attempts have been made.
+    struct Flaky {
+        failures: u32,
+        kind: FailureKind,
+        calls: Cell<u32>,
+    }
+
+    impl BuildExecutor for Flaky {
+        fn execute(&self, _plan: &BuildPlan) -> BuildResult<Vec<BuiltArtifact>> {
+            self.calls.set(self.calls.get() + 1);
+            if self.calls.get() <= self.failures {
+                Err(BuildError::new("cargo build exited with status 101 (network error)")
+                    .with_kind(self.kind))
+            } else {
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: RefCell<Vec<BuildEvent>>,
+    }
+
+    impl BuildObserver for Recorder {
+        fn on_event(&self, event: &BuildEvent) {
+            self.events.borrow_mut().push(event.clone());
+        }
+    }
+
+    fn plan() -> BuildPlan {
+        test_support::plan(&["x86_64-unknown-linux-gnu"])
+    }
+
+    fn executor(failures: u32, kind: FailureKind) -> RetryExecutor<Flaky> {
+        RetryExecutor::new(
+            Flaky {
+                failures,
+                kind,
+                calls: Cell::new(0),
+            },
+            RetrySettings {
+                attempts: 3,
+                delay_secs: 0,
+            },
+        )
+    }
+
+    #[test]
+    fn retries_transient_failures_up_to_the_limit() {
+        let recorder = Recorder::default();
+        let retry = executor(2, FailureKind::Transient);
+        retry
+            .execute_observed(&plan(), &recorder)
+            .expect("third attempt succeeds");
+        assert_eq!(retry.inner.calls.get(), 3);
+        let retries: Vec<u32> = recorder
+            .events
+            .borrow()
+            .iter()
+            .filter_map(|event| match event {
+                BuildEvent::TargetRetrying { attempt, .. } => Some(*attempt),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(retries, vec![2, 3]);
+
+        let exhausted = executor(3, FailureKind::Transient);
+        assert!(exhausted.execute(&plan()).is_err());
+        assert_eq!(exhausted.inner.calls.get(), 3);
+    }
+
+    #[test]
+    fn does_not_retry_other_failures() {
+        for kind in [FailureKind::Failed, FailureKind::TimedOut] {
+            let retry = executor(1, kind);
+            let error = retry.execute(&plan()).expect_err("fails");
+            assert_eq!(error.kind, kind);
+            assert_eq!(retry.inner.calls.get(), 1);
+        }
+    }
+}
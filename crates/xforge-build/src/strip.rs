@@ -0,0 +1,475 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use xforge_core::artifact::layout::debug_info_filename;
+use xforge_core::artifact::LibraryKind;
+use xforge_core::build_plan::BuiltArtifact;
+use xforge_core::config::{StripMode, StripSettings};
+use xforge_core::platform::PlatformKey;
+
+use crate::builder::{BuildError, BuildResult};
+
+/// Directory next to cargo's output that receives stripped libraries and their
+/// debug-info side files. Cargo's own outputs are never rewritten, so cargo's
+/// freshness checks and the build cache keep seeing the original files.
+pub const STRIPPED_DIR_NAME: &str = "xforge-stripped";
+
+/// Where a stripped library and its split debug info were written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StripOutcome {
+    pub platform: PlatformKey,
+    pub library: PathBuf,
+    /// `None` when debug info was discarded rather than split out.
+    pub debug_file: Option<PathBuf>,
+}
+
+/// Post-build stage that strips each artifact's dynamic library with
+/// `objcopy` (`dsymutil` for Apple debug info) and keeps the removed debug
+/// info as a side file linked back to the library: a GNU debuglink for ELF,
+/// the shared UUID for Mach-O, and the PDB path the MSVC linker embeds.
+#[derive(Clone, Debug)]
+pub struct Stripper {
+    mode: StripMode,
+    split_debuginfo: bool,
+    objcopy: PathBuf,
+    /// GNU `objcopy` cannot write Mach-O, so Apple libraries need LLVM's
+    /// tools; `None` when neither was found.
+    macho_strip: Option<MachoStrip>,
+    /// `None` when neither `dsymutil` nor `llvm-dsymutil` was found.
+    dsymutil: Option<PathBuf>,
+}
+
+/// The program that strips Mach-O libraries; the two take their output path
+/// differently.
+#[derive(Clone, Debug)]
+enum MachoStrip {
+    Objcopy(PathBuf),
+    Strip(PathBuf),
+}
+
+impl Stripper {
+    pub fn new(settings: &StripSettings) -> Self {
+        let (objcopy, macho_strip) = match &settings.objcopy {
+            Some(objcopy) => (objcopy.clone(), Some(MachoStrip::Objcopy(objcopy.clone()))),
+            None => {
+                let llvm_objcopy = first_available(&["llvm-objcopy"]);
+                let macho_strip = match &llvm_objcopy {
+                    Some(objcopy) => Some(MachoStrip::Objcopy(objcopy.clone())),
+                    None => first_available(&["llvm-strip"]).map(MachoStrip::Strip),
+                };
+                let objcopy = llvm_objcopy.unwrap_or_else(|| PathBuf::from("objcopy"));
+                (objcopy, macho_strip)
+            }
+        };
+        Self {
+            mode: settings.mode,
+            split_debuginfo: settings.split_debuginfo,
+            objcopy,
+            macho_strip,
+            dsymutil: first_available(&["dsymutil", "llvm-dsymutil"]),
+        }
+    }
+
+    pub fn with_dsymutil(mut self, dsymutil: PathBuf) -> Self {
+        self.dsymutil = Some(dsymutil);
+        self
+    }
+
+    /// Strip the dynamic library of every artifact into
+    /// [`STRIPPED_DIR_NAME`]. Artifacts without a dynamic library are skipped.
+    pub fn strip(&self, artifacts: &[BuiltArtifact]) -> BuildResult<Vec<StripOutcome>> {
+        if self.mode == StripMode::None {
+            return Ok(Vec::new());
+        }
+        let mut outcomes = Vec::new();
+        for artifact in artifacts {
+            let Some(library) = artifact.library(LibraryKind::Dynamic) else {
+                continue;
+            };
+            outcomes.push(self.strip_library(artifact.platform, Path::new(library))?);
+        }
+        Ok(outcomes)
+    }
+
+    fn strip_library(&self, platform: PlatformKey, library: &Path) -> BuildResult<StripOutcome> {
+        let source_dir = library.parent().unwrap_or_else(|| Path::new("."));
+        let file_name = library
+            .file_name()
+            .ok_or_else(|| {
+                BuildError::new(format!("invalid library path '{}'", library.display()))
+            })?
+            .to_string_lossy()
+            .into_owned();
+        let out_dir = source_dir.join(STRIPPED_DIR_NAME);
+        fs::create_dir_all(&out_dir).map_err(|error| strip_io("create", &out_dir, error))?;
+        let stripped = out_dir.join(&file_name);
+        let lib_name = lib_name_from_file(&file_name, platform);
+        let debug_file = out_dir.join(debug_info_filename(&lib_name, &platform));
+        remove_path(&debug_file)?;
+
+        let debug_file = match platform {
+            PlatformKey::WindowsX86_64Msvc | PlatformKey::WindowsArm64Msvc => {
+                // MSVC already keeps debug info out of the DLL; ship the PDB
+                // the linker wrote beside it.
+                copy_file(library, &stripped)?;
+                let pdb = source_dir.join(debug_info_filename(&lib_name, &platform));
+                if self.split_debuginfo && pdb.is_file() {
+                    copy_file(&pdb, &debug_file)?;
+                    Some(debug_file)
+                } else {
+                    None
+                }
+            }
+            PlatformKey::MacosArm64
+            | PlatformKey::MacosX86_64
+            | PlatformKey::MacosUniversal
+            | PlatformKey::IosArm64
+            | PlatformKey::IosSimulatorArm64
+            | PlatformKey::IosSimulatorX86_64 => {
+                let macho_strip = self.macho_strip.as_ref().ok_or_else(|| {
+                    BuildError::new(format!(
+                        "stripping {} needs llvm-objcopy or llvm-strip; install LLVM or set build.strip.objcopy",
+                        library.display()
+                    ))
+                })?;
+                let split = self.split_debuginfo.then_some(debug_file);
+                if let Some(dsym) = &split {
+                    let dsymutil = self.dsymutil.as_ref().ok_or_else(|| {
+                        BuildError::new(format!(
+                            "splitting debug info from {} needs dsymutil or llvm-dsymutil; install one or set build.strip.split_debuginfo: false",
+                            library.display()
+                        ))
+                    })?;
+                    run_tool(
+                        dsymutil,
+                        &[library.as_os_str(), "-o".as_ref(), dsym.as_os_str()],
+                    )?;
+                }
+                let flag: &std::ffi::OsStr = self.strip_flag().as_ref();
+                match macho_strip {
+                    MachoStrip::Objcopy(program) => {
+                        run_tool(program, &[flag, library.as_os_str(), stripped.as_os_str()])?
+                    }
+                    MachoStrip::Strip(program) => run_tool(
+                        program,
+                        &[
+                            flag,
+                            library.as_os_str(),
+                            "-o".as_ref(),
+                            stripped.as_os_str(),
+                        ],
+                    )?,
+                }
+                split
+            }
+            PlatformKey::LinuxX86_64
+            | PlatformKey::LinuxAarch64
+            | PlatformKey::LinuxX86_64Musl
+            | PlatformKey::LinuxAarch64Musl
+            | PlatformKey::AndroidArm64
+            | PlatformKey::AndroidArmv7
+            | PlatformKey::AndroidX86_64 => {
+                let split = self.split_debuginfo.then_some(debug_file);
+                let mut args = vec![self.strip_flag().to_string()];
+                if let Some(debug) = &split {
+                    run_tool(
+                        &self.objcopy,
+                        &[
+                            "--only-keep-debug".as_ref(),
+                            library.as_os_str(),
+                            debug.as_os_str(),
+                        ],
+                    )?;
+                    args.push(format!("--add-gnu-debuglink={}", debug.display()));
+                }
+                let mut args: Vec<&std::ffi::OsStr> = args.iter().map(|arg| arg.as_ref()).collect();
+                args.push(library.as_os_str());
+                args.push(stripped.as_os_str());
+                run_tool(&self.objcopy, &args)?;
+                split
+            }
+        };
+        Ok(StripOutcome {
+            platform,
+            library: stripped,
+            debug_file,
+        })
+    }
+
+    fn strip_flag(&self) -> &'static str {
+        match self.mode {
+            StripMode::Symbols => "--strip-unneeded",
+            StripMode::Debuginfo | StripMode::None => "--strip-debug",
+        }
+    }
+}
+
+/// Recover the crate's library name from a dynamic library file name so the
+/// side file is named the way bundling expects.
+fn lib_name_from_file(file_name: &str, platform: PlatformKey) -> String {
+    let stem = file_name.split('.').next().unwrap_or(file_name);
+    match platform {
+        PlatformKey::WindowsX86_64Msvc | PlatformKey::WindowsArm64Msvc => stem.to_string(),
+        _ => stem.strip_prefix("lib").unwrap_or(stem).to_string(),
+    }
+}
+
+/// The first of `programs` that runs.
+fn first_available(programs: &[&str]) -> Option<PathBuf> {
+    programs
+        .iter()
+        .map(PathBuf::from)
+        .find(|program| is_available(program))
+}
+
+fn is_available(program: &Path) -> bool {
+    Command::new(program)
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+fn run_tool(program: &Path, args: &[&std::ffi::OsStr]) -> BuildResult<()> {
+    let rendered = args
+        .iter()
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|error| {
+            BuildError::new(format!(
+                "failed to run {} {}: {}",
+                program.display(),
+                rendered,
+                error
+            ))
+        })?;
+    if !output.status.success() {
+        return Err(BuildError::new(format!(
+            "{} {} exited with status {}: {}",
+            program.display(),
+            rendered,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+fn copy_file(from: &Path, to: &Path) -> BuildResult<()> {
+    fs::copy(from, to)
+        .map(|_| ())
+        .map_err(|error| strip_io("copy", from, error))
+}
+
+fn remove_path(path: &Path) -> BuildResult<()> {
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else if path.exists() {
+        fs::remove_file(path)
+    } else {
+        return Ok(());
+    };
+    result.map_err(|error| strip_io("remove", path, error))
+}
+
+fn strip_io(action: &str, path: &Path, error: std::io::Error) -> BuildError {
+    BuildError::new(format!(
+        "failed to {} '{}' while stripping: {}",
+        action,
+        path.display(),
+        error
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::dynamic_artifact;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("xforge-strip-{}-{}", name, stamp));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    /// A fake tool that logs its arguments and copies its input to its output
+    /// (the last argument, or the one after `-o`).
+    fn stub_tool(dir: &Path, name: &str) -> PathBuf {
+        let log = dir.join(format!("{}.log", name));
+        let script = format!(
+            "#!/bin/sh\n\
+             echo \"$@\" >> '{log}'\n\
+             for arg; do last=\"$arg\"; done\n\
+             mkdir -p \"$(dirname \"$last\")\"\n\
+             echo stub > \"$last\"\n",
+            log = log.display(),
+        );
+        let path = dir.join(name);
+        fs::write(&path, script).expect("write stub");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod stub");
+        path
+    }
+
+    fn logged(dir: &Path, name: &str) -> Vec<String> {
+        fs::read_to_string(dir.join(format!("{}.log", name)))
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn stripper(dir: &Path, mode: StripMode, split_debuginfo: bool) -> Stripper {
+        Stripper::new(&StripSettings {
+            mode,
+            split_debuginfo,
+            objcopy: Some(stub_tool(dir, "objcopy")),
+        })
+        .with_dsymutil(stub_tool(dir, "dsymutil"))
+    }
+
+    #[test]
+    fn elf_debug_info_is_split_and_linked() {
+        let dir = temp_dir("elf");
+        let library = dir.join("libdemo_lib.so");
+        fs::write(&library, "elf").expect("write library");
+        let outcomes = stripper(&dir, StripMode::Debuginfo, true)
+            .strip(&[dynamic_artifact(PlatformKey::AndroidArm64, &library)])
+            .expect("strip");
+        let out_dir = dir.join(STRIPPED_DIR_NAME);
+        let debug = out_dir.join("libdemo_lib.so.debug");
+        assert_eq!(outcomes[0].library, out_dir.join("libdemo_lib.so"));
+        assert_eq!(outcomes[0].debug_file.as_deref(), Some(debug.as_path()));
+        assert_eq!(
+            logged(&dir, "objcopy"),
+            vec![
+                format!("--only-keep-debug {} {}", library.display(), debug.display()),
+                format!(
+                    "--strip-debug --add-gnu-debuglink={} {} {}",
+                    debug.display(),
+                    library.display(),
+                    out_dir.join("libdemo_lib.so").display()
+                ),
+            ]
+        );
+        assert_eq!(fs::read_to_string(&library).expect("original"), "elf");
+    }
+
+    #[test]
+    fn apple_debug_info_goes_to_dsym_bundle() {
+        let dir = temp_dir("apple");
+        let library = dir.join("libdemo.dylib");
+        fs::write(&library, "macho").expect("write library");
+        let outcomes = stripper(&dir, StripMode::Symbols, true)
+            .strip(&[dynamic_artifact(PlatformKey::IosArm64, &library)])
+            .expect("strip");
+        let dsym = dir.join(STRIPPED_DIR_NAME).join("libdemo.dylib.dSYM");
+        assert_eq!(outcomes[0].debug_file.as_deref(), Some(dsym.as_path()));
+        assert_eq!(
+            logged(&dir, "dsymutil"),
+            vec![format!("{} -o {}", library.display(), dsym.display())]
+        );
+        assert!(logged(&dir, "objcopy")[0].starts_with("--strip-unneeded "));
+    }
+
+    #[test]
+    fn apple_llvm_strip_writes_to_its_output_flag() {
+        let dir = temp_dir("apple-strip");
+        let library = dir.join("libdemo.dylib");
+        fs::write(&library, "macho").expect("write library");
+        let mut llvm_strip = stripper(&dir, StripMode::Debuginfo, false);
+        llvm_strip.macho_strip = Some(MachoStrip::Strip(stub_tool(&dir, "llvm-strip")));
+        llvm_strip
+            .strip(&[dynamic_artifact(PlatformKey::MacosArm64, &library)])
+            .expect("strip");
+        assert_eq!(
+            logged(&dir, "llvm-strip"),
+            vec![format!(
+                "--strip-debug {} -o {}",
+                library.display(),
+                dir.join(STRIPPED_DIR_NAME).join("libdemo.dylib").display()
+            )]
+        );
+        assert!(logged(&dir, "objcopy").is_empty());
+    }
+
+    #[test]
+    fn apple_without_llvm_tools_is_a_clear_error() {
+        let dir = temp_dir("apple-missing");
+        let library = dir.join("libdemo.dylib");
+        fs::write(&library, "macho").expect("write library");
+        let mut without_llvm = stripper(&dir, StripMode::Debuginfo, true);
+        without_llvm.macho_strip = None;
+        let error = without_llvm
+            .strip(&[dynamic_artifact(PlatformKey::MacosArm64, &library)])
+            .expect_err("no Mach-O strip tool");
+        assert!(error.message.contains("llvm-objcopy or llvm-strip"));
+
+        let mut without_dsymutil = stripper(&dir, StripMode::Debuginfo, true);
+        without_dsymutil.dsymutil = None;
+        let error = without_dsymutil
+            .strip(&[dynamic_artifact(PlatformKey::MacosArm64, &library)])
+            .expect_err("no dsymutil");
+        assert!(error.message.contains("dsymutil or llvm-dsymutil"));
+    }
+
+    #[test]
+    fn probing_skips_missing_tools() {
+        let dir = temp_dir("probe");
+        let tool = dir.join("llvm-dsymutil");
+        fs::write(&tool, "#!/bin/sh\nexit 0\n").expect("write tool");
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).expect("chmod tool");
+        let missing = dir.join("dsymutil");
+        assert_eq!(
+            first_available(&[
+                missing.to_str().expect("utf8"),
+                tool.to_str().expect("utf8")
+            ]),
+            Some(tool)
+        );
+        assert_eq!(first_available(&[missing.to_str().expect("utf8")]), None);
+    }
+
+    #[test]
+    fn windows_ships_linker_pdb_without_rewriting() {
+        let dir = temp_dir("windows");
+        let library = dir.join("demo.dll");
+        fs::write(&library, "pe").expect("write library");
+        fs::write(dir.join("demo.pdb"), "pdb").expect("write pdb");
+        let outcomes = stripper(&dir, StripMode::Debuginfo, true)
+            .strip(&[dynamic_artifact(PlatformKey::WindowsX86_64Msvc, &library)])
+            .expect("strip");
+        let pdb = dir.join(STRIPPED_DIR_NAME).join("demo.pdb");
+        assert_eq!(outcomes[0].debug_file.as_deref(), Some(pdb.as_path()));
+        assert_eq!(fs::read_to_string(pdb).expect("pdb"), "pdb");
+        assert!(logged(&dir, "objcopy").is_empty());
+    }
+
+    #[test]
+    fn discarding_debug_info_writes_no_side_file() {
+        let dir = temp_dir("discard");
+        let library = dir.join("libdemo.so");
+        fs::write(&library, "elf").expect("write library");
+        let outcomes = stripper(&dir, StripMode::Symbols, false)
+            .strip(&[dynamic_artifact(PlatformKey::LinuxX86_64, &library)])
+            .expect("strip");
+        assert_eq!(outcomes[0].debug_file, None);
+        assert_eq!(logged(&dir, "objcopy").len(), 1);
+        assert!(stripper(&dir, StripMode::None, true)
+            .strip(&[dynamic_artifact(PlatformKey::LinuxX86_64, &library)])
+            .expect("strip")
+            .is_empty());
+    }
+}
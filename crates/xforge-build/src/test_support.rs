@@ -0,0 +1,75 @@
+//! Fixtures shared by the unit tests. Each returns the plainest value that
+//! type checks; tests override only the fields they exercise.
+
+use std::path::Path;
+
+use xforge_core::artifact::naming::ArchiveKind;
+use xforge_core::artifact::{LibraryEntry, LibraryKind};
+use xforge_core::build_plan::{BuildPlan, BuildProfile, BuildTargetPlan, BuiltArtifact};
+use xforge_core::platform::PlatformKey;
+use xforge_core::toolchain::Toolchain;
+
+pub(crate) const BUILD_ID: &str = "b1-demo";
+
+/// Artifact of `platform` that records no libraries.
+pub(crate) fn artifact(platform: PlatformKey) -> BuiltArtifact {
+    BuiltArtifact {
+        platform,
+        build_id: BUILD_ID.to_string(),
+        archive_kind: ArchiveKind::TarGz,
+        artifact_name: "demo".to_string(),
+        output_dir: "dist".to_string(),
+        library_path: "libdemo.so".to_string(),
+        libraries: vec![],
+        include_dir: None,
+        manifest_path: "xforge-manifest.json".to_string(),
+        build_id_path: "build_id.txt".to_string(),
+    }
+}
+
+/// Artifact of `platform` whose dynamic library is `library`.
+pub(crate) fn dynamic_artifact(platform: PlatformKey, library: &Path) -> BuiltArtifact {
+    let library = library.to_string_lossy().into_owned();
+    BuiltArtifact {
+        library_path: library.clone(),
+        libraries: vec![LibraryEntry {
+            kind: LibraryKind::Dynamic,
+            path: library,
+        }],
+        ..artifact(platform)
+    }
+}
+
+/// Target for `triple` built from the current directory.
+pub(crate) fn target(triple: &str) -> BuildTargetPlan {
+    let platform = PlatformKey::from_rust_target(triple)[0];
+    BuildTargetPlan {
+        platform,
+        rust_target_triple: triple.to_string(),
+        working_dir: ".".to_string(),
+        cargo_manifest_path: "Cargo.toml".to_string(),
+        cargo_args: vec![],
+        cargo_features: vec![],
+        cross_image: None,
+        env: vec![],
+        timeout_secs: None,
+        artifact: artifact(platform),
+    }
+}
+
+/// Release plan of the `demo` package for `triples`.
+pub(crate) fn plan(triples: &[&str]) -> BuildPlan {
+    BuildPlan {
+        package_name: "demo".to_string(),
+        build_id: BUILD_ID.to_string(),
+        profile: BuildProfile {
+            name: "release".to_string(),
+            toolchain: Toolchain::default(),
+            cargo_args: vec![],
+            rustflags: vec![],
+            env: vec![],
+            env_allowlist: None,
+        },
+        targets: triples.iter().map(|triple| target(triple)).collect(),
+    }
+}
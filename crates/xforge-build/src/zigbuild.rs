@@ -1,8 +1,10 @@
 use std::process::{Command, Stdio};
 
-use xforge_core::build_plan::{BuildEnvVar, BuildPlan, BuiltArtifact};
+use xforge_core::build_plan::{BuildEnvVar, BuildPlan, BuildTargetPlan, BuiltArtifact};
 
 use crate::builder::{BuildError, BuildExecutor, BuildResult};
+use crate::deterministic::apply_env_allowlist;
+use crate::events::{message_format_args, observe_target, run_cargo, BuildObserver, NoopObserver};
 
 #[derive(Clone, Debug, Default)]
 pub struct ZigbuildExecutor;
@@ -15,46 +17,58 @@ impl ZigbuildExecutor {
 
 impl BuildExecutor for ZigbuildExecutor {
     fn execute(&self, plan: &BuildPlan) -> BuildResult<Vec<BuiltArtifact>> {
+        self.execute_observed(plan, &NoopObserver)
+    }
+
+    fn execute_observed(
+        &self,
+        plan: &BuildPlan,
+        observer: &dyn BuildObserver,
+    ) -> BuildResult<Vec<BuiltArtifact>> {
         ensure_zig_available()?;
         let mut artifacts = Vec::with_capacity(plan.targets.len());
         for target in &plan.targets {
-            let mut command = Command::new("cargo");
-            command
-                .arg("zigbuild")
-                .args(profile_args(&plan.profile.name))
-                .arg("--target")
-                .arg(&target.rust_target_triple)
-                .arg("--manifest-path")
-                .arg(&target.cargo_manifest_path)
-                .args(&plan.profile.cargo_args)
-                .args(&target.cargo_args)
-                .stdout(Stdio::inherit())
-                .stderr(Stdio::inherit())
-                .current_dir(&target.working_dir);
-            if !target.cargo_features.is_empty() {
-                command
-                    .arg("--features")
-                    .arg(target.cargo_features.join(","));
-            }
-            apply_rustflags(&plan.profile.rustflags, &mut command);
-            apply_env(&plan.profile.env, &mut command);
-            apply_env(&target.env, &mut command);
-            apply_toolchain(&plan.profile.toolchain.channel, &mut command);
-            let status = command
-                .status()
-                .map_err(|error| BuildError::new(format!("cargo zigbuild failed: {}", error)))?;
-            if !status.success() {
-                return Err(BuildError::new(format!(
-                    "cargo zigbuild exited with status {}",
-                    status
-                )));
-            }
-            artifacts.push(target.artifact.clone());
+            artifacts.extend(observe_target(observer, target, false, || {
+                build_target(plan, target, observer)
+            })?);
         }
         Ok(artifacts)
     }
 }
 
+fn build_target(
+    plan: &BuildPlan,
+    target: &BuildTargetPlan,
+    observer: &dyn BuildObserver,
+) -> BuildResult<Vec<BuiltArtifact>> {
+    let mut command = Command::new("cargo");
+    command
+        .arg("zigbuild")
+        .args(profile_args(&plan.profile.name))
+        .args(message_format_args(observer))
+        .arg("--target")
+        .arg(&target.rust_target_triple)
+        .arg("--manifest-path")
+        .arg(&target.cargo_manifest_path)
+        .args(&plan.profile.cargo_args)
+        .args(&target.cargo_args)
+        .current_dir(&target.working_dir);
+    if !target.cargo_features.is_empty() {
+        command
+            .arg("--features")
+            .arg(target.cargo_features.join(","));
+    }
+    apply_env_allowlist(&plan.profile.env_allowlist, &mut command);
+    apply_rustflags(&plan.profile.rustflags, &mut command);
+    apply_env(&plan.profile.env, &mut command);
+    apply_env(&target.env, &mut command);
+    apply_toolchain(&plan.profile.toolchain.channel, &mut command);
+    run_cargo(&mut command, target, observer)
+        .map_err(|error| BuildError::new(format!("cargo zigbuild failed: {}", error)))?
+        .check("cargo zigbuild")?;
+    Ok(vec![target.artifact.clone()])
+}
+
 fn ensure_zig_available() -> BuildResult<()> {
     let status = Command::new("zig")
         .arg("version")
@@ -82,12 +96,13 @@ fn profile_args(profile: &str) -> Vec<String> {
     }
 }
 
+/// Pass `flags` as `CARGO_ENCODED_RUSTFLAGS` so a flag holding a path with
+/// spaces, such as a `--remap-path-prefix`, stays one rustc argument.
 fn apply_rustflags(flags: &[String], command: &mut Command) {
     if flags.is_empty() {
         return;
     }
-    let joined = flags.join(" ");
-    command.env("RUSTFLAGS", joined);
+    command.env("CARGO_ENCODED_RUSTFLAGS", flags.join("\x1f"));
 }
 
 fn apply_env(values: &[BuildEnvVar], command: &mut Command) {
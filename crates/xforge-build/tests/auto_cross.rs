@@ -0,0 +1,101 @@
+#![cfg(unix)]
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+use xforge_build::{AutoExecutor, BuildExecutor, ExecutorKind, HostEnvironment};
+use xforge_core::artifact::naming::ArchiveKind;
+use xforge_core::build_plan::{BuildPlan, BuildProfile, BuildTargetPlan, BuiltArtifact};
+use xforge_core::platform::PlatformKey;
+use xforge_core::toolchain::Toolchain;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("xforge-auto-cross-{}-{}", name, stamp));
+    fs::create_dir_all(&dir).expect("create temp dir");
+    dir
+}
+
+fn windows_plan(dir: &std::path::Path) -> BuildPlan {
+    let platform = PlatformKey::WindowsX86_64Msvc;
+    let library = dir
+        .join("target/x86_64-pc-windows-msvc/release/demo.dll")
+        .to_string_lossy()
+        .into_owned();
+    BuildPlan {
+        package_name: "demo".to_string(),
+        build_id: "b1-demo".to_string(),
+        profile: BuildProfile {
+            name: "release".to_string(),
+            toolchain: Toolchain::default(),
+            cargo_args: vec![],
+            rustflags: vec![],
+            env: vec![],
+            env_allowlist: None,
+        },
+        targets: vec![BuildTargetPlan {
+            platform,
+            rust_target_triple: "x86_64-pc-windows-msvc".to_string(),
+            working_dir: dir.to_string_lossy().into_owned(),
+            cargo_manifest_path: dir.join("Cargo.toml").to_string_lossy().into_owned(),
+            cargo_args: vec![],
+            cargo_features: vec![],
+            cross_image: None,
+            env: vec![],
+            timeout_secs: None,
+            artifact: BuiltArtifact {
+                platform,
+                build_id: "b1-demo".to_string(),
+                archive_kind: ArchiveKind::Zip,
+                artifact_name: "demo".to_string(),
+                output_dir: "dist".to_string(),
+                library_path: library,
+                libraries: vec![],
+                include_dir: None,
+                manifest_path: "xforge-manifest.json".to_string(),
+                build_id_path: "build_id.txt".to_string(),
+            },
+        }],
+    }
+}
+
+#[test]
+fn auto_selected_cross_uses_the_default_image_for_the_triple() {
+    let dir = temp_dir("windows");
+    let log = dir.join("cross.log");
+    fs::write(
+        dir.join("cross"),
+        format!("#!/bin/sh\necho \"$@\" >> '{}'\nexit 0\n", log.display()),
+    )
+    .expect("write stub cross");
+    fs::set_permissions(dir.join("cross"), fs::Permissions::from_mode(0o755))
+        .expect("chmod stub cross");
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![dir.clone()];
+    paths.extend(std::env::split_paths(&path));
+    std::env::set_var("PATH", std::env::join_paths(paths).expect("join PATH"));
+
+    let host = HostEnvironment {
+        host_triple: "x86_64-unknown-linux-gnu".to_string(),
+        zigbuild: false,
+        cross: true,
+        container_engine: true,
+        ndk_home: None,
+    };
+    let executor = AutoExecutor::new(host, BTreeMap::new());
+    let plan = windows_plan(&dir);
+    let selections = executor.selections(&plan).expect("selections");
+    assert_eq!(selections[0].kind, ExecutorKind::Cross);
+
+    let built = executor.execute(&plan).expect("cross build");
+
+    assert_eq!(built.len(), 1);
+    let calls = fs::read_to_string(&log).expect("read log");
+    assert!(calls.contains("build --release --target x86_64-pc-windows-msvc"));
+    assert!(!calls.contains("--image"));
+}
@@ -4,7 +4,10 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::time::SystemTime;
 
-use xforge_build::{cargo::CargoExecutor, BuildExecutor};
+use xforge_build::{
+    cargo::CargoExecutor, BuildCache, BuildExecutor, CacheStatus, CachedExecutor, DeterministicEnv,
+    DeterministicExecutor,
+};
 use xforge_core::{
     artifact::naming::{artifact_name, ArchiveKind},
     build_id::{hash_build_inputs, release_hash, AbiInput, BuildInputs},
@@ -132,6 +135,7 @@ fn integration_build_flow_runs_host_build() {
             .join(format!("lib{}.rlib", crate_name))
             .to_string_lossy()
             .into_owned(),
+        libraries: vec![],
         include_dir: None,
         manifest_path: dir
             .join("xforge-manifest.json")
@@ -149,6 +153,7 @@ fn integration_build_flow_runs_host_build() {
             cargo_args: vec![],
             rustflags: vec![],
             env: vec![],
+            env_allowlist: None,
         },
         targets: vec![BuildTargetPlan {
             platform,
@@ -159,6 +164,7 @@ fn integration_build_flow_runs_host_build() {
             cargo_features: vec![],
             cross_image: None,
             env: vec![],
+            timeout_secs: None,
             artifact: built_artifact,
         }],
     };
@@ -170,3 +176,105 @@ fn integration_build_flow_runs_host_build() {
     assert_eq!(artifacts[0].artifact_name, artifact_name);
     assert_release_rlib_exists(&dir, &target, crate_name);
 }
+
+fn host_plan(dir: &Path, package_name: &str, crate_name: &str, target: &str) -> BuildPlan {
+    let inputs = BuildInputs::from_manifest_dir(dir, AbiInput::new(target.to_string()), None)
+        .expect("collect build inputs");
+    let build_id = hash_build_inputs(&inputs).expect("hash build inputs");
+    let platform = PlatformKey::from_rust_target(target)[0];
+    let release_dir = target_release_dir(dir, target);
+    BuildPlan {
+        package_name: package_name.to_string(),
+        build_id: build_id.clone(),
+        profile: BuildProfile {
+            name: "release".to_string(),
+            toolchain: Toolchain::default(),
+            cargo_args: vec![],
+            rustflags: vec![],
+            env: vec![],
+            env_allowlist: None,
+        },
+        targets: vec![BuildTargetPlan {
+            platform,
+            rust_target_triple: target.to_string(),
+            working_dir: dir.to_string_lossy().into_owned(),
+            cargo_manifest_path: dir.join("Cargo.toml").to_string_lossy().into_owned(),
+            cargo_args: vec![],
+            cargo_features: vec![],
+            cross_image: None,
+            env: vec![],
+            timeout_secs: None,
+            artifact: BuiltArtifact {
+                platform,
+                build_id: build_id.clone(),
+                archive_kind: ArchiveKind::TarGz,
+                artifact_name: artifact_name(
+                    package_name,
+                    &build_id,
+                    &platform,
+                    ArchiveKind::TarGz,
+                )
+                .expect("artifact name"),
+                output_dir: release_dir.to_string_lossy().into_owned(),
+                library_path: release_dir
+                    .join(format!("lib{}.rlib", crate_name))
+                    .to_string_lossy()
+                    .into_owned(),
+                libraries: vec![],
+                include_dir: None,
+                manifest_path: dir
+                    .join("xforge-manifest.json")
+                    .to_string_lossy()
+                    .into_owned(),
+                build_id_path: dir.join("build-id.txt").to_string_lossy().into_owned(),
+            },
+        }],
+    }
+}
+
+#[test]
+fn cached_executor_restores_library_without_rebuilding() {
+    let dir = temp_dir("build-cache");
+    let target = host_target_triple();
+    let package_name = "integration-cache";
+    let crate_name = "integration_cache";
+    init_sample_crate(&dir, package_name, &target);
+    let status = Command::new("cargo")
+        .arg("generate-lockfile")
+        .arg("--offline")
+        .current_dir(&dir)
+        .status()
+        .expect("run cargo generate-lockfile");
+    assert!(status.success(), "cargo generate-lockfile must succeed");
+    let plan = host_plan(&dir, package_name, crate_name, &target);
+    let library = PathBuf::from(&plan.targets[0].artifact.library_path);
+
+    let executor = CachedExecutor::new(CargoExecutor::new(), BuildCache::new(dir.join("cache")));
+    let (_, outcomes) = executor.execute_with_outcomes(&plan).expect("first build");
+    assert_eq!(outcomes[0].status, CacheStatus::Stored);
+    assert!(outcomes[0].build_id.starts_with("b1-"));
+
+    fs::remove_dir_all(dir.join("target")).expect("remove target dir");
+    let (artifacts, outcomes) = executor.execute_with_outcomes(&plan).expect("cached build");
+    assert_eq!(outcomes[0].status, CacheStatus::Hit);
+    assert_eq!(artifacts.len(), 1);
+    assert!(library.is_file());
+    assert!(!target_release_dir(&dir, &target).join("deps").exists());
+}
+
+#[test]
+fn deterministic_build_handles_paths_with_spaces() {
+    let dir = temp_dir("remap").join("with space");
+    let target = host_target_triple();
+    let package_name = "integration-remap";
+    let crate_name = "integration_remap";
+    init_sample_crate(&dir, package_name, &target);
+    let plan = host_plan(&dir, package_name, crate_name, &target);
+    let env = DeterministicEnv::new(&config::DeterministicSettings::default())
+        .expect("deterministic env");
+
+    DeterministicExecutor::new(CargoExecutor::new(), env)
+        .execute(&plan)
+        .expect("deterministic build");
+    assert_release_rlib_exists(&dir, &target, crate_name);
+}
@@ -0,0 +1,56 @@
+#![cfg(unix)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+use xforge_build::{ProvisionMode, Rustup};
+use xforge_core::toolchain::Toolchain;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("xforge-provision-{}-{}", name, stamp));
+    fs::create_dir_all(&dir).expect("create temp dir");
+    dir
+}
+
+#[test]
+fn rustup_is_resolved_from_path() {
+    let dir = temp_dir("path");
+    let log = dir.join("rustup.log");
+    fs::write(
+        dir.join("rustup"),
+        format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\n[ \"$1 $2\" = \"toolchain list\" ] && echo 'stable-x86_64-unknown-linux-gnu (default)'\nexit 0\n",
+            log.display()
+        ),
+    )
+    .expect("write stub rustup");
+    fs::set_permissions(dir.join("rustup"), fs::Permissions::from_mode(0o755))
+        .expect("chmod stub rustup");
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![dir.clone()];
+    paths.extend(std::env::split_paths(&path));
+    std::env::set_var("PATH", std::env::join_paths(paths).expect("join PATH"));
+
+    let rustup = Rustup::new();
+    assert!(rustup.is_available());
+    let report = rustup
+        .provision(
+            &Toolchain {
+                channel: Some("stable".to_string()),
+                targets: vec![],
+                components: vec![],
+            },
+            &["aarch64-unknown-linux-gnu".to_string()],
+            ProvisionMode::Install,
+        )
+        .expect("provision");
+    assert_eq!(report.missing_targets, vec!["aarch64-unknown-linux-gnu"]);
+    assert!(report.installed);
+    let calls = fs::read_to_string(&log).expect("read log");
+    assert!(calls.contains("target add --toolchain stable aarch64-unknown-linux-gnu"));
+}
@@ -0,0 +1,43 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use xforge_core::manifest::{deserialize_manifest, diff_exported_symbols, AbiDiff, Manifest};
+
+pub struct AbiDiffArgs {
+    pub old_manifest: PathBuf,
+    pub new_manifest: PathBuf,
+}
+
+/// Compare the exported symbols of two bundled releases. Removing a symbol
+/// is an error unless the package version carries a semver-major bump.
+pub fn run(args: AbiDiffArgs) -> Result<AbiDiff, String> {
+    let old = read_manifest(&args.old_manifest)?;
+    let new = read_manifest(&args.new_manifest)?;
+    let diff = diff_exported_symbols(&old, &new);
+    if diff.has_removals() && !diff.is_major_bump().map_err(|err| err.to_string())? {
+        let removed: Vec<String> = diff
+            .platforms
+            .iter()
+            .flat_map(|platform| {
+                platform
+                    .removed
+                    .iter()
+                    .map(move |symbol| format!("{} ({})", symbol, platform.platform))
+            })
+            .collect();
+        return Err(format!(
+            "{} -> {} removes exported symbols without a semver-major version bump: {}",
+            diff.old_version,
+            diff.new_version,
+            removed.join(", ")
+        ));
+    }
+    Ok(diff)
+}
+
+fn read_manifest(path: &Path) -> Result<Manifest, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("failed to read manifest '{}': {}", path.display(), err))?;
+    deserialize_manifest(&contents)
+        .map_err(|err| format!("failed to parse manifest '{}': {}", path.display(), err))
+}
@@ -1,24 +1,61 @@
+use std::cell::{Cell, RefCell};
 use std::path::{Path, PathBuf};
 
 use xforge_build::cargo::CargoExecutor;
 use xforge_build::cross::CrossExecutor;
+use xforge_build::ndk::NdkExecutor;
 use xforge_build::zigbuild::ZigbuildExecutor;
-use xforge_build::BuildExecutor;
-use xforge_core::artifact::layout::library_filename;
-use xforge_core::build_id::{hash_release_inputs, AbiInput, BuildInputs};
-use xforge_core::build_plan::{BuildPlan, BuildProfile, BuildTargetPlan, BuiltArtifact};
+use xforge_build::{
+    report_file_name, verify_reproducible, AttemptLog, AutoExecutor, BuildCache, BuildEvent,
+    BuildExecutor, BuildObserver, BuildReport, CacheOutcome, CachedExecutor, CommandExecutor,
+    DeterministicEnv, DeterministicExecutor, ExecutorKind, ExecutorSelection, FailureKind,
+    HeaderGenerator, HeaderOutcome, HostEnvironment, NoopObserver, ProvisionMode, ProvisionReport,
+    ReproducibilityOptions, ReproducibilityResult, RetryExecutor, Rustup, StripOutcome, Stripper,
+};
+use xforge_core::artifact::layout::{library_filename_for, select_library_kinds};
+use xforge_core::artifact::naming::variant_artifact_name;
+use xforge_core::artifact::{LibraryEntry, LibraryKind, INCLUDE_DIR_NAME};
+use xforge_core::build_id::{hash_release_inputs, AbiInput, BuildInputs, VariantInput};
+use xforge_core::build_plan::{
+    cargo_profile_dir, BuildPlan, BuildProfile, BuildTargetPlan, BuiltArtifact,
+};
 use xforge_core::config;
 use xforge_core::platform::PlatformKey;
 use xforge_core::toolchain::Toolchain;
 
-use crate::commands::bundle::package_metadata;
+use crate::commands::bundle::{library_crate_types, package_metadata};
+
+/// Directory under `target/` holding one cargo target dir per build variant.
+pub const VARIANTS_DIR_NAME: &str = "xforge-variants";
 
 pub struct BuildArgs {
     pub manifest_dir: PathBuf,
     pub target: Option<String>,
     pub profile: String,
+    /// Build the named `build.variants` entry instead of the default features.
+    pub variant: Option<String>,
     pub executor: BuildExecutorKind,
     pub cross_image: Option<String>,
+    pub no_cache: bool,
+    /// Execute a plan serialized by `xforge plan --json` instead of resolving one.
+    pub plan: Option<PathBuf>,
+    /// Skip checking rustup for the toolchain, targets and components.
+    pub no_provision: bool,
+    /// Only report missing toolchain pieces instead of installing them.
+    pub offline: bool,
+    /// Build every target twice more in scratch directories and compare.
+    pub verify_reproducible: Option<ReproducibilityCheck>,
+    /// Receives build progress; `None` leaves cargo's output as it is.
+    pub observer: Option<Box<dyn BuildObserver>>,
+}
+
+/// How the second build of `--verify-reproducible` differs from the first.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReproducibilityCheck {
+    /// Build from a copy of the crate at another path.
+    pub vary_path: bool,
+    /// Build with a different time zone and locale.
+    pub vary_environment: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -26,67 +63,466 @@ pub enum BuildExecutorKind {
     Cargo,
     Cross,
     Zigbuild,
+    Ndk,
+    Command,
+    Auto,
 }
 
 pub struct BuildOutcome {
     pub build_id: String,
     pub library_path: PathBuf,
+    pub executor_selections: Vec<ExecutorSelection>,
+    pub cache_outcomes: Vec<CacheOutcome>,
+    /// `None` when provisioning was skipped or rustup is not installed.
+    pub provision: Option<ProvisionReport>,
+    /// Empty unless `build.strip` is enabled.
+    pub strip_outcomes: Vec<StripOutcome>,
+    /// Empty unless `build.headers` is configured.
+    pub header_outcomes: Vec<HeaderOutcome>,
+    /// `None` unless `--verify-reproducible` was given.
+    pub reproducibility: Option<Vec<ReproducibilityResult>>,
+    pub report_path: PathBuf,
 }
 
 pub fn run(args: BuildArgs) -> Result<BuildOutcome, String> {
     let manifest_dir = args.manifest_dir;
-    let targets = resolve_targets(&manifest_dir, args.target)?;
+    let plan = match &args.plan {
+        Some(path) => load_plan(path, &manifest_dir, args.target.as_deref())?,
+        None => resolve_plan(
+            &manifest_dir,
+            args.target,
+            &args.profile,
+            args.variant.as_deref(),
+            args.cross_image.clone(),
+        )?,
+    };
+    let build_id = plan.build_id.clone();
+    let observer: &dyn BuildObserver = match &args.observer {
+        Some(observer) => observer.as_ref(),
+        None => &NoopObserver,
+    };
+    let build_settings = config::build_settings(&manifest_dir).map_err(|err| err.to_string())?;
+    let deterministic = DeterministicEnv::new(&build_settings.deterministic);
+    let dropped_env = deterministic
+        .as_ref()
+        .map(|env| env.dropped_env(&plan))
+        .unwrap_or_default();
+    observer.on_event(&BuildEvent::plan_resolved(&plan, dropped_env));
+
+    let mut executor_selections = Vec::new();
+    let executor: Box<dyn BuildExecutor> = match args.executor {
+        BuildExecutorKind::Cargo => Box::new(CargoExecutor::new()),
+        BuildExecutorKind::Cross => Box::new(CrossExecutor::new()),
+        BuildExecutorKind::Zigbuild => Box::new(ZigbuildExecutor::new()),
+        BuildExecutorKind::Ndk => Box::new(NdkExecutor::new()),
+        BuildExecutorKind::Command => {
+            let settings = build_settings.command.clone().ok_or_else(|| {
+                "--executor command requires build.command in xforge.yaml".to_string()
+            })?;
+            Box::new(CommandExecutor::new(settings))
+        }
+        BuildExecutorKind::Auto => {
+            let host = HostEnvironment::detect().map_err(|err| err.to_string())?;
+            let executor = AutoExecutor::new(host, build_settings.executor_overrides.clone())
+                .with_command(build_settings.command.clone());
+            executor_selections = executor.selections(&plan).map_err(|err| err.to_string())?;
+            Box::new(executor)
+        }
+    };
+    let executor: Box<dyn BuildExecutor> = match deterministic {
+        Some(env) => Box::new(DeterministicExecutor::new(executor, env)),
+        None => executor,
+    };
+    let executor = RetryExecutor::new(executor, build_settings.retry.clone());
+
+    let provision = if args.no_provision {
+        None
+    } else {
+        let mode = if args.offline {
+            ProvisionMode::ReportOnly
+        } else {
+            ProvisionMode::Install
+        };
+        provision_toolchain(&plan, &args.executor, &executor_selections, mode)?
+    };
+
+    let target_root = resolve_target_root(&manifest_dir);
+    let reproducibility = match args.verify_reproducible {
+        Some(check) => {
+            let options = ReproducibilityOptions {
+                scratch_dir: target_root.join("target").join("xforge-reproducible"),
+                vary_path: check.vary_path,
+                vary_environment: check.vary_environment,
+            };
+            Some(verify_reproducible(&executor, &plan, &options).map_err(|err| err.to_string())?)
+        }
+        None => None,
+    };
+
+    let mut cache_outcomes = Vec::new();
+    let attempt_log = AttemptLog::new(observer);
+    let built = match open_cache(&build_settings.cache, args.no_cache) {
+        Some(cache) => CachedExecutor::new(executor, cache)
+            .execute_with_outcomes_observed(&plan, &attempt_log)
+            .map(|(artifacts, outcomes)| {
+                cache_outcomes = outcomes;
+                artifacts
+            }),
+        None => executor.execute_observed(&plan, &attempt_log),
+    };
+    let report_path = target_root.join("target").join(report_file_name(
+        args.variant.as_deref().unwrap_or(&plan.profile.name),
+    ));
+    let write_report = || -> Result<(), String> {
+        let mut report = BuildReport::new(&plan).map_err(|err| err.to_string())?;
+        report.reproducibility = reproducibility.clone();
+        report.record_attempts(&attempt_log.attempts());
+        report.write(&report_path).map_err(|err| err.to_string())
+    };
+    let artifacts = match built {
+        Ok(artifacts) => artifacts,
+        Err(err) => {
+            write_report()?;
+            return Err(format!(
+                "{}; attempts recorded in {}",
+                err,
+                report_path.display()
+            ));
+        }
+    };
+    let strip_outcomes = Stripper::new(&build_settings.strip)
+        .strip(&artifacts)
+        .map_err(|err| err.to_string())?;
+    let header_outcomes = match &build_settings.headers {
+        Some(headers) => {
+            let (package_name, _package_version) = package_metadata(&manifest_dir)?;
+            HeaderGenerator::new(headers, &package_name)
+                .generate(&manifest_dir, &artifacts)
+                .map_err(|err| err.to_string())?
+        }
+        None => Vec::new(),
+    };
+    write_report()?;
+    if let Some(results) = &reproducibility {
+        let failures: Vec<String> = results
+            .iter()
+            .filter(|result| !result.reproducible)
+            .map(|result| {
+                let sections: Vec<&str> = result
+                    .libraries
+                    .iter()
+                    .flat_map(|library| library.differing_sections.iter().map(String::as_str))
+                    .collect();
+                if sections.is_empty() {
+                    result.rust_target_triple.clone()
+                } else {
+                    format!("{} ({})", result.rust_target_triple, sections.join(", "))
+                }
+            })
+            .collect();
+        if !failures.is_empty() {
+            return Err(format!(
+                "build {} is not reproducible for {}; see {}",
+                build_id,
+                failures.join("; "),
+                report_path.display()
+            ));
+        }
+    }
+
+    let first_library = plan
+        .targets
+        .first()
+        .map(|target| PathBuf::from(&target.artifact.library_path))
+        .unwrap_or_else(|| manifest_dir.join("target"));
+    Ok(BuildOutcome {
+        build_id,
+        library_path: first_library,
+        executor_selections,
+        cache_outcomes,
+        provision,
+        strip_outcomes,
+        header_outcomes,
+        reproducibility,
+        report_path,
+    })
+}
+
+/// Renders build events as one line per target attempt on stderr, with
+/// compiler diagnostics printed the way cargo would.
+#[derive(Default)]
+pub struct ProgressObserver {
+    total: Cell<usize>,
+    targets: RefCell<Vec<String>>,
+    verbose: bool,
+}
+
+impl ProgressObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also list the host variables a deterministic build drops.
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// `[index/total]` for `triple`, numbering targets as they first start.
+    fn position(&self, triple: &str) -> String {
+        let mut targets = self.targets.borrow_mut();
+        let index = match targets.iter().position(|known| known == triple) {
+            Some(index) => index,
+            None => {
+                targets.push(triple.to_string());
+                targets.len() - 1
+            }
+        };
+        format!("[{}/{}]", index + 1, self.total.get().max(targets.len()))
+    }
+}
+
+impl BuildObserver for ProgressObserver {
+    fn on_event(&self, event: &BuildEvent) {
+        match event {
+            BuildEvent::PlanResolved {
+                build_id,
+                profile,
+                targets,
+                dropped_env,
+            } => {
+                self.total.set(targets.len());
+                eprintln!(
+                    "building {} target(s) for {} ({})",
+                    targets.len(),
+                    build_id,
+                    profile
+                );
+                if self.verbose && !dropped_env.is_empty() {
+                    eprintln!(
+                        "deterministic build: not passing {} to cargo; add them to build.env_passthrough or set build.deterministic: false to keep them",
+                        dropped_env.join(", ")
+                    );
+                }
+            }
+            BuildEvent::TargetStarted {
+                rust_target_triple, ..
+            } => {
+                eprintln!(
+                    "{} {} started",
+                    self.position(rust_target_triple),
+                    rust_target_triple
+                );
+            }
+            BuildEvent::Diagnostic {
+                level,
+                message,
+                rendered,
+                ..
+            } => match rendered {
+                Some(rendered) => eprint!("{}", rendered),
+                None => eprintln!("{}: {}", level, message),
+            },
+            BuildEvent::TargetFinished {
+                rust_target_triple,
+                success,
+                cached,
+                duration_ms,
+                failure,
+                ..
+            } => {
+                let status = match (success, cached, failure) {
+                    (true, true, _) => "restored from cache",
+                    (true, false, _) => "finished",
+                    (false, _, Some(FailureKind::TimedOut)) => "timed out",
+                    (false, _, Some(FailureKind::Cancelled)) => "cancelled",
+                    (false, _, _) => "failed",
+                };
+                eprintln!(
+                    "{} {} {} in {:.1}s",
+                    self.position(rust_target_triple),
+                    rust_target_triple,
+                    status,
+                    *duration_ms as f64 / 1000.0
+                );
+            }
+            BuildEvent::TargetRetrying {
+                rust_target_triple,
+                attempt,
+                delay_ms,
+                reason,
+            } => {
+                eprintln!(
+                    "{} {} retrying (attempt {}) in {:.1}s: {}",
+                    self.position(rust_target_triple),
+                    rust_target_triple,
+                    attempt,
+                    *delay_ms as f64 / 1000.0,
+                    reason
+                );
+            }
+            BuildEvent::ArtifactLocated { kind, path, .. } => {
+                eprintln!("      {} {}", kind, path);
+            }
+        }
+    }
+}
+
+/// Prints every build event to stdout as a line of JSON.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonEventsObserver;
+
+impl BuildObserver for JsonEventsObserver {
+    fn on_event(&self, event: &BuildEvent) {
+        if let Ok(line) = serde_json::to_string(event) {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Make sure rustup has what the plan needs. Targets built inside cross
+/// containers are left to the image.
+fn provision_toolchain(
+    plan: &BuildPlan,
+    executor: &BuildExecutorKind,
+    selections: &[ExecutorSelection],
+    mode: ProvisionMode,
+) -> Result<Option<ProvisionReport>, String> {
+    let rustup = Rustup::new();
+    if !rustup.is_available() {
+        return Ok(None);
+    }
+    let targets: Vec<String> = plan
+        .targets
+        .iter()
+        .filter(|target| match executor {
+            BuildExecutorKind::Cross => false,
+            BuildExecutorKind::Auto => !selections.iter().any(|selection| {
+                selection.rust_target_triple == target.rust_target_triple
+                    && selection.kind == ExecutorKind::Cross
+            }),
+            _ => true,
+        })
+        .map(|target| target.rust_target_triple.clone())
+        .collect();
+    if targets.is_empty() {
+        return Ok(None);
+    }
+    rustup
+        .provision(&plan.profile.toolchain, &targets, mode)
+        .map(Some)
+        .map_err(|err| err.to_string())
+}
+
+/// Resolve the full build plan for `target` (or every configured target).
+/// With `variant` the plan builds that `build.variants` entry: its features
+/// and rustflags are added, it gets its own build id, and cargo writes to
+/// [`variant_target_dir`] so the default build's libraries are kept.
+pub fn resolve_plan(
+    manifest_dir: &Path,
+    target: Option<String>,
+    profile: &str,
+    variant: Option<&str>,
+    cross_image: Option<String>,
+) -> Result<BuildPlan, String> {
+    let targets = resolve_targets(manifest_dir, target)?;
     let toolchain_settings =
-        config::toolchain_settings(&manifest_dir).map_err(|err| err.to_string())?;
+        config::toolchain_settings(manifest_dir).map_err(|err| err.to_string())?;
+    let build_settings = config::build_settings(manifest_dir).map_err(|err| err.to_string())?;
+    let variant = variant
+        .map(|name| resolve_variant(&build_settings, name))
+        .transpose()?;
 
-    let (package_name, _package_version) = package_metadata(&manifest_dir)?;
+    let (package_name, _package_version) = package_metadata(manifest_dir)?;
     let first_target = targets
         .first()
         .ok_or_else(|| "no build targets configured".to_string())?;
     let build_inputs =
-        BuildInputs::from_manifest_dir(&manifest_dir, AbiInput::new(first_target.clone()), None)
-            .map_err(|err| format!("failed to read build inputs: {}", err))?;
-    let build_id = hash_release_inputs(&build_inputs)
+        BuildInputs::from_manifest_dir(manifest_dir, AbiInput::new(first_target.clone()), None)
+            .map_err(|err| format!("failed to read build inputs: {}", err))?
+            .with_profile(profile);
+    let release_id = hash_release_inputs(&build_inputs)
         .map_err(|err| format!("failed to hash release inputs: {}", err))?;
+    let build_id = match &variant {
+        Some(variant) => hash_release_inputs(&build_inputs.with_variant(variant.clone()))
+            .map_err(|err| format!("failed to hash release inputs: {}", err))?,
+        None => release_id.clone(),
+    };
 
-    let profile = BuildProfile {
-        name: args.profile.clone(),
+    let build_profile = BuildProfile {
+        name: profile.to_string(),
         toolchain: Toolchain {
             channel: toolchain_settings.channel.clone(),
             targets: toolchain_settings.targets.clone(),
             components: toolchain_settings.components.clone(),
         },
         cargo_args: vec![],
-        rustflags: vec![],
+        rustflags: variant
+            .as_ref()
+            .map(|variant| variant.rustflags.clone())
+            .unwrap_or_default(),
         env: vec![],
+        env_allowlist: None,
     };
 
+    let crate_types = library_crate_types(manifest_dir)?;
     let mut target_plans = Vec::new();
-    let target_root = resolve_target_root(&manifest_dir);
+    let target_root = resolve_target_root(manifest_dir);
+    let cargo_target_dir = match &variant {
+        Some(variant) => variant_target_dir(&target_root, &variant.name),
+        None => target_root.join("target"),
+    };
     for target in &targets {
         let rust_targets = PlatformKey::from_rust_target(target);
         if rust_targets.len() != 1 {
             return Err(format!("unsupported target '{}'", target));
         }
         let platform = rust_targets[0];
-        let target_dir = target_root.join("target").join(target).join(&args.profile);
-        let library_name = library_filename(&package_name, &platform);
-        let library_path = target_dir.join(&library_name);
-        let artifact_name = format!(
-            "{}-{}-{}.{}",
-            package_name,
-            build_id,
-            platform,
-            xforge_core::artifact::naming::ArchiveKind::TarGz.extension()
-        );
+        let target_dir = cargo_target_dir
+            .join(target)
+            .join(cargo_profile_dir(profile));
+        let libraries: Vec<LibraryEntry> =
+            resolve_library_kinds(&build_settings, &crate_types, target, platform)?
+                .into_iter()
+                .map(|kind| LibraryEntry {
+                    kind,
+                    path: target_dir
+                        .join(library_filename_for(&package_name, &platform, kind))
+                        .to_string_lossy()
+                        .into_owned(),
+                })
+                .collect();
+        let artifact_name = match &variant {
+            Some(variant) => variant_artifact_name(
+                &package_name,
+                &release_id,
+                &platform,
+                &variant.name,
+                xforge_core::artifact::naming::ArchiveKind::TarGz,
+            )
+            .map_err(|err| err.to_string())?,
+            None => format!(
+                "{}-{}-{}.{}",
+                package_name,
+                build_id,
+                platform,
+                xforge_core::artifact::naming::ArchiveKind::TarGz.extension()
+            ),
+        };
         let built_artifact = BuiltArtifact {
             platform,
             build_id: build_id.clone(),
             archive_kind: xforge_core::artifact::naming::ArchiveKind::TarGz,
             artifact_name,
             output_dir: target_dir.to_string_lossy().into_owned(),
-            library_path: library_path.to_string_lossy().into_owned(),
-            include_dir: None,
+            library_path: libraries[0].path.clone(),
+            libraries,
+            include_dir: build_settings.headers.as_ref().map(|_| {
+                target_dir
+                    .join(INCLUDE_DIR_NAME)
+                    .to_string_lossy()
+                    .into_owned()
+            }),
             manifest_path: manifest_dir
                 .join("xforge-manifest.json")
                 .to_string_lossy()
@@ -96,54 +532,69 @@ pub fn run(args: BuildArgs) -> Result<BuildOutcome, String> {
                 .to_string_lossy()
                 .into_owned(),
         };
-        let target_dir_arg = target_root.join("target").to_string_lossy().into_owned();
+        let target_dir_arg = cargo_target_dir.to_string_lossy().into_owned();
         target_plans.push(BuildTargetPlan {
             platform,
             rust_target_triple: target.clone(),
             working_dir: manifest_dir.to_string_lossy().into_owned(),
             cargo_manifest_path: "Cargo.toml".to_string(),
             cargo_args: vec!["--target-dir".to_string(), target_dir_arg.clone()],
-            cargo_features: vec![],
-            cross_image: args.cross_image.clone(),
+            cargo_features: variant
+                .as_ref()
+                .map(|variant| variant.features.clone())
+                .unwrap_or_default(),
+            cross_image: cross_image.clone(),
             env: vec![xforge_core::build_plan::BuildEnvVar {
                 key: "CARGO_TARGET_DIR".to_string(),
                 value: target_dir_arg,
             }],
+            timeout_secs: build_settings.timeout_secs_for(target),
             artifact: built_artifact,
         });
     }
 
-    let plan = BuildPlan {
+    Ok(BuildPlan {
         package_name,
-        build_id: build_id.clone(),
-        profile,
+        build_id,
+        profile: build_profile,
         targets: target_plans,
-    };
+    })
+}
 
-    match args.executor {
-        BuildExecutorKind::Cargo => {
-            let executor = CargoExecutor::new();
-            executor.execute(&plan).map_err(|err| err.to_string())?;
-        }
-        BuildExecutorKind::Cross => {
-            let executor = CrossExecutor::new();
-            executor.execute(&plan).map_err(|err| err.to_string())?;
-        }
-        BuildExecutorKind::Zigbuild => {
-            let executor = ZigbuildExecutor::new();
-            executor.execute(&plan).map_err(|err| err.to_string())?;
-        }
+/// Read a plan written by `xforge plan --json`, re-rooting its relative paths
+/// at this checkout and keeping only `target` when given.
+fn load_plan(path: &Path, manifest_dir: &Path, target: Option<&str>) -> Result<BuildPlan, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| format!("failed to read plan '{}': {}", path.display(), err))?;
+    let plan: BuildPlan = serde_json::from_str(&contents)
+        .map_err(|err| format!("failed to parse plan '{}': {}", path.display(), err))?;
+    let plan = plan.rooted_at(&resolve_target_root(manifest_dir));
+    match target {
+        Some(target) => plan
+            .for_target(target)
+            .ok_or_else(|| format!("plan '{}' has no target '{}'", path.display(), target)),
+        None => Ok(plan),
     }
+}
 
-    let first_library = plan
-        .targets
-        .first()
-        .map(|target| PathBuf::from(&target.artifact.library_path))
-        .unwrap_or_else(|| manifest_dir.join("target"));
-    Ok(BuildOutcome {
-        build_id,
-        library_path: first_library,
-    })
+/// Library outputs to build for one target: `build.outputs` from xforge.yaml
+/// checked against the Cargo.toml crate-type, or everything it produces.
+pub(crate) fn resolve_library_kinds(
+    settings: &config::BuildSettings,
+    crate_types: &[String],
+    target: &str,
+    platform: PlatformKey,
+) -> Result<Vec<LibraryKind>, String> {
+    select_library_kinds(crate_types, &platform, settings.library_outputs_for(target))
+        .map_err(|err| format!("{}: {}", target, err))
+}
+
+pub(crate) fn open_cache(settings: &config::CacheSettings, disabled: bool) -> Option<BuildCache> {
+    if disabled || !settings.enabled {
+        return None;
+    }
+    let root = settings.dir.clone().or_else(BuildCache::default_root)?;
+    Some(BuildCache::new(root).with_max_size(settings.max_size_bytes))
 }
 
 pub(crate) fn resolve_targets(
@@ -160,7 +611,40 @@ pub(crate) fn resolve_targets(
     Ok(targets)
 }
 
-fn resolve_target_root(manifest_dir: &Path) -> PathBuf {
+/// Cargo target directory of a `build.variants` entry, kept apart from the
+/// default build so both sets of libraries survive until bundling.
+pub(crate) fn variant_target_dir(target_root: &Path, variant: &str) -> PathBuf {
+    target_root
+        .join("target")
+        .join(VARIANTS_DIR_NAME)
+        .join(variant)
+}
+
+/// Look up `name` in `build.variants`.
+pub(crate) fn resolve_variant(
+    build_settings: &config::BuildSettings,
+    name: &str,
+) -> Result<VariantInput, String> {
+    let settings = build_settings.variants.get(name).ok_or_else(|| {
+        let declared: Vec<&str> = build_settings.variants.keys().map(String::as_str).collect();
+        format!(
+            "unknown build variant '{}'; build.variants declares: {}",
+            name,
+            if declared.is_empty() {
+                "none".to_string()
+            } else {
+                declared.join(", ")
+            }
+        )
+    })?;
+    Ok(VariantInput {
+        name: name.to_string(),
+        features: settings.features.clone(),
+        rustflags: settings.rustflags.clone(),
+    })
+}
+
+pub(crate) fn resolve_target_root(manifest_dir: &Path) -> PathBuf {
     let mut current = Some(manifest_dir);
     while let Some(dir) = current {
         if dir.join("Cargo.lock").exists() {
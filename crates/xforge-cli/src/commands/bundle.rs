@@ -1,31 +1,78 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use xforge_core::artifact::layout::{archive_layout, default_archive_kind};
-use xforge_core::artifact::naming::{artifact_name, ArchiveKind};
+use sha2::{Digest, Sha256};
+
+use xforge_build::{DeterministicEnv, STRIPPED_DIR_NAME};
+use xforge_core::artifact::layout::{
+    archive_layout_with_libraries, debug_info_filename, default_archive_kind, layout_variant,
+    library_filename_for, LayoutVariant,
+};
+use xforge_core::artifact::{LibraryEntry, LibraryKind, INCLUDE_DIR_NAME};
+use xforge_core::artifact::naming::{
+    artifact_name, debug_artifact_name, variant_artifact_name, variant_debug_artifact_name,
+    ArchiveKind,
+};
+use xforge_core::binary::{exported_symbols, inspect, required_glibc, BinaryFileType, BinaryInfo};
+use xforge_core::bindings::dotnet::runtime_identifier;
+use xforge_core::bindings::kotlin::{ndk_abi, GLUE_JVM_TARGET};
+use xforge_core::bindings::python::{wheel_platform_tag, WHEEL_ABI_TAG};
+use xforge_core::bindings::swift::{package_manifest, Podspec};
+use xforge_core::bindings::{DotnetBinding, KotlinBinding, PythonBinding, SwiftBinding};
 use xforge_core::build_id::{hash_build_inputs, hash_release_inputs, AbiInput, BuildInputs};
-use xforge_core::build_plan::BuiltArtifact;
-use xforge_core::config;
+use xforge_core::build_plan::{cargo_profile_dir, BuiltArtifact};
+use xforge_core::config::{self, BuildSettings};
 use xforge_core::manifest::{
-    ArtifactNaming, Artifacts, Bindings, Build, BuildIdentity, Manifest, Package, Platform,
-    Platforms,
+    ArtifactNaming, Artifacts, BindingDescriptor, Bindings, Build, BuildIdentity, HeaderDigest,
+    Manifest, Package, Platform, PlatformVariant, Platforms,
 };
-use xforge_core::platform::PlatformKey;
-use xforge_pack::{PackExecutor, PackFormat, PackInput, PackRequest, TarGzPacker, ZipPacker};
+use xforge_core::platform::{all_platform_keys, PlatformKey};
+use xforge_core::config::StripMode;
+use xforge_pack::{
+    pack_debug_info, write_maven_repository, write_universal, AarPacker, MavenPublication,
+    NugetPacker, PackExecutor, PackFormat, PackInput, PackRequest, TarGzPacker, TarXzPacker,
+    TarZstPacker, WheelPacker, XcframeworkPacker, ZipPacker,
+};
+
+use super::build::{resolve_library_kinds, resolve_targets, resolve_variant, variant_target_dir};
 
-use super::build::resolve_targets;
+const PACKAGE_SWIFT: &str = "Package.swift";
+/// Default Maven repository root inside the bundle output; a directory, so
+/// `xforge publish --assets-dir` does not upload it as release assets.
+const MAVEN_DIR_NAME: &str = "maven";
 
 pub struct BundleArgs {
     pub manifest_dir: PathBuf,
     pub target: Option<String>,
     pub output_dir: PathBuf,
-    pub profile: String,
+    /// Bundle only this cargo profile; `None` bundles every profile in
+    /// `build.profiles`, the first as the primary variant.
+    pub profile: Option<String>,
 }
 
 pub struct BundleOutcome {
     pub build_id: String,
     pub manifest_path: PathBuf,
     pub archive_paths: Vec<PathBuf>,
+    /// Split debug-info archives, listed as `debugArtifacts` in the manifest.
+    pub debug_archive_paths: Vec<PathBuf>,
+    /// Zipped XCFramework of the Apple targets when `build.xcframework` is set.
+    pub xcframework_path: Option<PathBuf>,
+    /// Every `Package.swift` written for `build.xcframework.spm`.
+    pub package_swift_paths: Vec<PathBuf>,
+    /// Every `.podspec` and `.podspec.json` written for
+    /// `build.xcframework.cocoapods`.
+    pub podspec_paths: Vec<PathBuf>,
+    /// Android archive of the Android targets when `build.aar` is set.
+    pub aar_path: Option<PathBuf>,
+    /// Root of the Maven repository holding the AAR when `build.maven` is
+    /// set.
+    pub maven_repository: Option<PathBuf>,
+    /// One Python wheel per target with a wheel tag when `build.wheel` is set.
+    pub wheel_paths: Vec<PathBuf>,
+    /// NuGet package of every target with a .NET runtime identifier when
+    /// `build.nuget` is set.
+    pub nupkg_path: Option<PathBuf>,
 }
 
 pub fn run(args: BundleArgs) -> Result<BundleOutcome, String> {
@@ -33,6 +80,24 @@ pub fn run(args: BundleArgs) -> Result<BundleOutcome, String> {
     let targets = resolve_targets(&manifest_dir, args.target)?;
     let toolchain_settings = config::toolchain_settings(&manifest_dir).map_err(|err| err.to_string())?;
     let (package_name, package_version) = package_metadata(&manifest_dir)?;
+    let build_settings = config::build_settings(&manifest_dir).map_err(|err| err.to_string())?;
+    let crate_types = library_crate_types(&manifest_dir)?;
+    // `--profile` bundles that profile alone; otherwise every profile and
+    // every feature variant goes into the release.
+    let (profiles, feature_variants) = match args.profile {
+        Some(profile) => (vec![profile], Vec::new()),
+        None => (
+            build_settings.release_profiles(),
+            build_settings
+                .variants
+                .keys()
+                .map(|name| resolve_variant(&build_settings, name))
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+    };
+    let (primary_profile, variant_profiles) = profiles
+        .split_first()
+        .ok_or_else(|| "no build profiles configured".to_string())?;
 
     let first_target = targets
         .first()
@@ -43,7 +108,7 @@ pub fn run(args: BundleArgs) -> Result<BundleOutcome, String> {
         None,
     )
     .map_err(|err| format!("failed to read build inputs: {}", err))?;
-    let build_id = hash_release_inputs(&build_inputs)
+    let build_id = hash_release_inputs(&build_inputs.with_profile(primary_profile))
         .map_err(|err| format!("failed to hash release inputs: {}", err))?;
 
     fs::create_dir_all(&args.output_dir)
@@ -61,24 +126,24 @@ pub fn run(args: BundleArgs) -> Result<BundleOutcome, String> {
 
     let mut platform_entries = Vec::new();
     let mut archive_paths = Vec::new();
+    let mut debug_archive_paths = Vec::new();
+    let mut apple_inputs = Vec::new();
+    let mut android_inputs = Vec::new();
+    let mut wheel_inputs = Vec::new();
+    let mut nuget_inputs = Vec::new();
 
     let manifest = Manifest {
         schema_version: xforge_core::manifest::schema::SCHEMA_VERSION.to_string(),
-        package: Package {
-            name: package_name.clone(),
-            version: package_version,
-            description: None,
-            license: None,
-            authors: vec![],
-            repository: None,
-        },
+        package: manifest_package(&manifest_dir, &package_name, package_version)?,
         build: Build {
             id: build_id.clone(),
             identity: BuildIdentity {
                 host,
                 toolchain,
-                profile: Some(args.profile.clone()),
+                profile: Some(primary_profile.clone()),
                 features: vec![],
+                deterministic: DeterministicEnv::new(&build_settings.deterministic)
+                    .map(|env| env.identity()),
             },
             timestamp: None,
             engine: None,
@@ -108,68 +173,561 @@ pub fn run(args: BundleArgs) -> Result<BundleOutcome, String> {
     fs::write(&manifest_path, manifest_contents)
         .map_err(|err| format!("failed to write manifest: {}", err))?;
 
+    let context = BundleContext {
+        package_name: &package_name,
+        build_id: &build_id,
+        target_root: resolve_target_root(&manifest_dir),
+        output_dir: &args.output_dir,
+        manifest_path: &manifest_path,
+        build_id_path: &build_id_path,
+        build_settings: &build_settings,
+        crate_types: &crate_types,
+    };
+    let mut bundle_targets = Vec::with_capacity(targets.len());
     for target in &targets {
         let rust_targets = PlatformKey::from_rust_target(target);
         if rust_targets.len() != 1 {
             return Err(format!("unsupported target '{}'", target));
         }
-        let platform = rust_targets[0];
+        bundle_targets.push((target.clone(), rust_targets[0]));
+    }
+    for universal in universal_platforms(&build_settings, &crate_types, &bundle_targets)? {
+        bundle_targets.push((universal.to_string(), universal));
+    }
+    for (target, platform) in &bundle_targets {
+        let platform = *platform;
         let per_target_inputs = BuildInputs::from_manifest_dir(
             &manifest_dir,
             AbiInput::new(target.clone()),
             None,
         )
-        .map_err(|err| format!("failed to read build inputs: {}", err))?;
+        .map_err(|err| format!("failed to read build inputs: {}", err))?
+        .with_profile(primary_profile);
         let per_target_build_id = hash_build_inputs(&per_target_inputs)
             .map_err(|err| format!("failed to hash build inputs: {}", err))?;
-        let archive_kind = default_archive_kind(&platform);
-        let archive_name =
-            artifact_name(&package_name, &build_id, &platform, archive_kind).map_err(|err| err.to_string())?;
-        let target_root = resolve_target_root(&manifest_dir);
-        let library_path = resolve_library_path(
-            &target_root,
-            target,
-            &args.profile,
-            &package_name,
-            platform,
-        );
-        let Some(library_path) = library_path else {
-            return Err(format!(
-                "library not found at '{}'; run xforge build first",
-                target_root
-                    .join("target")
-                    .join(target)
-                    .join(&args.profile)
-                    .join(xforge_core::artifact::layout::library_filename(
-                        &package_name,
-                        &platform,
-                    ))
-                    .display()
+
+        let default_dir = context.target_root.join("target");
+        let primary =
+            context.bundle_profile(target, platform, primary_profile, &default_dir, None)?;
+        archive_paths.push(primary.archive_path);
+        debug_archive_paths.extend(primary.debug_archive_path);
+        match layout_variant(&platform) {
+            LayoutVariant::Apple if platform.universal_slices().is_empty() => {
+                apple_inputs.push((target.clone(), primary.pack_input.clone()));
+            }
+            LayoutVariant::Android => {
+                android_inputs.push((target.clone(), primary.pack_input.clone()));
+            }
+            _ => {}
+        }
+        if let Some(platform_tag) = wheel_platform_tag(platform, primary.glibc) {
+            wheel_inputs.push((target.clone(), platform_tag, primary.pack_input.clone()));
+        }
+        if runtime_identifier(platform).is_some() {
+            nuget_inputs.push((target.clone(), primary.pack_input.clone()));
+        }
+        check_headers_match(&build_settings, &platform_entries, target, &primary.headers)?;
+        let mut variants = Vec::new();
+        for profile in variant_profiles {
+            let bundled =
+                context.bundle_profile(target, platform, profile, &default_dir, Some(profile))?;
+            archive_paths.push(bundled.archive_path);
+            debug_archive_paths.extend(bundled.debug_archive_path);
+            variants.push(PlatformVariant {
+                name: profile.clone(),
+                build_id: hash_build_inputs(&per_target_inputs.clone().with_profile(profile))
+                    .map_err(|err| format!("failed to hash build inputs: {}", err))?,
+                profile: Some(profile.clone()),
+                features: vec![],
+                rustflags: vec![],
+                artifacts: vec![bundled.archive_name],
+                libraries: bundled.libraries,
+                debug_artifacts: bundled.debug_artifacts,
+                binary: bundled.binary,
+                exported_symbols: bundled.exported_symbols,
+                headers: bundled.headers,
+            });
+        }
+        for variant in &feature_variants {
+            let variant_dir = variant_target_dir(&context.target_root, &variant.name);
+            let bundled = context.bundle_profile(
+                target,
+                platform,
+                primary_profile,
+                &variant_dir,
+                Some(&variant.name),
+            )?;
+            archive_paths.push(bundled.archive_path);
+            debug_archive_paths.extend(bundled.debug_archive_path);
+            let variant_build_id =
+                hash_build_inputs(&per_target_inputs.clone().with_variant(variant.clone()))
+                    .map_err(|err| format!("failed to hash build inputs: {}", err))?;
+            variants.push(PlatformVariant {
+                name: variant.name.clone(),
+                build_id: variant_build_id,
+                profile: Some(primary_profile.clone()),
+                features: variant.features.clone(),
+                rustflags: variant.rustflags.clone(),
+                artifacts: vec![bundled.archive_name],
+                libraries: bundled.libraries,
+                debug_artifacts: bundled.debug_artifacts,
+                binary: bundled.binary,
+                exported_symbols: bundled.exported_symbols,
+                headers: bundled.headers,
+            });
+        }
+
+        let triples = match platform.universal_slices() {
+            [] => vec![target.clone()],
+            slices => slices
+                .iter()
+                .flat_map(|slice| slice.rust_targets())
+                .map(|triple| triple.to_string())
+                .collect(),
+        };
+        platform_entries.push(Platform {
+            name: target.clone(),
+            build_id: per_target_build_id,
+            triples,
+            bindings: vec![],
+            artifacts: vec![primary.archive_name],
+            libraries: primary.libraries,
+            debug_artifacts: primary.debug_artifacts,
+            binary: primary.binary,
+            exported_symbols: primary.exported_symbols,
+            headers: primary.headers,
+            description: None,
+            profile: Some(primary_profile.clone()),
+            variants,
+        });
+    }
+
+    manifest.platforms.targets = platform_entries;
+    let mut package_swift_paths = Vec::new();
+    let mut podspec_paths = Vec::new();
+    let xcframework_path = match &build_settings.xcframework {
+        Some(settings) if !apple_inputs.is_empty() => {
+            let (targets, inputs): (Vec<String>, Vec<PackInput>) = apple_inputs.into_iter().unzip();
+            let module = settings.module_for(&package_name);
+            let packer = XcframeworkPacker::new(&module).with_xcodebuild(settings.xcodebuild);
+            let path = pack_binding(&packer, PackFormat::XCFramework, inputs, &args.output_dir)?;
+            let mut artifacts = vec![path.clone()];
+            if settings.spm.is_some() || settings.cocoapods.is_some() {
+                let release = released_xcframework(&manifest_dir, &build_id, &path)?;
+                if let Some(spm) = &settings.spm {
+                    let contents = package_manifest(
+                        &spm.package_for(&module),
+                        &module,
+                        &release.url,
+                        &release.sha256,
+                    );
+                    package_swift_paths = write_package_file(
+                        &args.output_dir,
+                        spm.asset,
+                        spm.output_dir.as_deref(),
+                        PACKAGE_SWIFT,
+                        &contents,
+                    )?;
+                    if spm.asset {
+                        artifacts.push(args.output_dir.join(PACKAGE_SWIFT));
+                    }
+                }
+                if let Some(cocoapods) = &settings.cocoapods {
+                    let name = cocoapods.name_for(&module);
+                    let podspec = Podspec {
+                        name: name.clone(),
+                        version: manifest.package.version.clone(),
+                        summary: manifest
+                            .package
+                            .description
+                            .clone()
+                            .unwrap_or_else(|| format!("Prebuilt {} framework", module)),
+                        homepage: manifest.package.repository.clone().unwrap_or_else(|| {
+                            format!("https://github.com/{}", release.repository)
+                        }),
+                        license: manifest.package.license.clone(),
+                        authors: manifest.package.authors.clone(),
+                        source_url: release.url.clone(),
+                        source_sha256: release.sha256.clone(),
+                        vendored_framework: format!("{}.xcframework", module),
+                        binding: SwiftBinding {
+                            toolchain: cocoapods.swift_version.clone(),
+                            deployment_target: cocoapods.deployment_target.clone(),
+                        },
+                    };
+                    podspec.binding.validate().map_err(|err| err.to_string())?;
+                    for (file_name, contents) in [
+                        (format!("{}.podspec", name), podspec.to_ruby()),
+                        (format!("{}.podspec.json", name), podspec.to_json()),
+                    ] {
+                        podspec_paths.extend(write_package_file(
+                            &args.output_dir,
+                            cocoapods.asset,
+                            cocoapods.output_dir.as_deref(),
+                            &file_name,
+                            &contents,
+                        )?);
+                        if cocoapods.asset {
+                            artifacts.push(args.output_dir.join(file_name));
+                        }
+                    }
+                }
+            }
+            let artifacts: Vec<&Path> = artifacts.iter().map(PathBuf::as_path).collect();
+            manifest.bindings.catalog.push(binding_descriptor(
+                "swift",
+                &manifest.package.version,
+                targets,
+                &artifacts,
+            ));
+            Some(path)
+        }
+        _ => None,
+    };
+    let aar_path = match &build_settings.aar {
+        Some(settings) if !android_inputs.is_empty() => {
+            let (targets, inputs): (Vec<String>, Vec<PackInput>) =
+                android_inputs.into_iter().unzip();
+            let binding = KotlinBinding {
+                min_sdk: settings.min_sdk,
+                jvm_target: GLUE_JVM_TARGET.to_string(),
+                ndk_abis: inputs
+                    .iter()
+                    .filter_map(|input| ndk_abi(input.artifact.platform))
+                    .map(str::to_string)
+                    .collect(),
+            };
+            let packer = AarPacker::new(settings.namespace_for(&package_name), binding)
+                .with_glue(settings.glue)
+                .with_proguard_rules(settings.proguard.clone());
+            let path = pack_binding(&packer, PackFormat::AAR, inputs, &args.output_dir)?;
+            manifest.bindings.catalog.push(binding_descriptor(
+                "kotlin",
+                &manifest.package.version,
+                targets,
+                &[&path],
+            ));
+            Some(path)
+        }
+        _ => None,
+    };
+    let maven_repository = match (&build_settings.maven, &aar_path) {
+        (Some(settings), Some(aar)) => {
+            let repository = settings
+                .repository
+                .clone()
+                .unwrap_or_else(|| args.output_dir.join(MAVEN_DIR_NAME));
+            let publication = MavenPublication {
+                group_id: settings.group_id.clone(),
+                artifact_id: settings.artifact_id_for(&package_name),
+                version: manifest.package.version.clone(),
+                name: package_name.clone(),
+                description: manifest.package.description.clone(),
+                url: manifest.package.repository.clone(),
+                license: manifest.package.license.clone(),
+            };
+            write_maven_repository(aar, &publication, &repository)
+                .map_err(|err| err.to_string())?;
+            Some(repository)
+        }
+        _ => None,
+    };
+    let mut wheel_paths = Vec::new();
+    if let Some(settings) = &build_settings.wheel {
+        let mut targets = Vec::new();
+        for (target, platform_tag, input) in wheel_inputs {
+            let binding = PythonBinding {
+                abi_tag: WHEEL_ABI_TAG.to_string(),
+                platform_tag,
+            };
+            let packer = WheelPacker::new(
+                settings.name_for(&package_name),
+                &manifest.package.version,
+                binding,
+            )
+            .with_loader(settings.loader);
+            wheel_paths.push(pack_binding(
+                &packer,
+                PackFormat::Wheel,
+                vec![input],
+                &args.output_dir,
+            )?);
+            targets.push(target);
+        }
+        if !wheel_paths.is_empty() {
+            let paths: Vec<&Path> = wheel_paths.iter().map(PathBuf::as_path).collect();
+            manifest.bindings.catalog.push(binding_descriptor(
+                "python",
+                &manifest.package.version,
+                targets,
+                &paths,
             ));
+        }
+    }
+    let nupkg_path = match &build_settings.nuget {
+        Some(settings) if !nuget_inputs.is_empty() => {
+            let (targets, inputs): (Vec<String>, Vec<PackInput>) = nuget_inputs.into_iter().unzip();
+            let binding = DotnetBinding {
+                runtime_identifiers: inputs
+                    .iter()
+                    .filter_map(|input| runtime_identifier(input.artifact.platform))
+                    .map(str::to_string)
+                    .collect(),
+            };
+            let packer = NugetPacker::new(
+                settings.id_for(&package_name),
+                &manifest.package.version,
+                binding,
+            )
+            .with_package_info(&manifest.package);
+            let path = pack_binding(&packer, PackFormat::Nupkg, inputs, &args.output_dir)?;
+            manifest.bindings.catalog.push(binding_descriptor(
+                "dotnet",
+                &manifest.package.version,
+                targets,
+                &[&path],
+            ));
+            Some(path)
+        }
+        _ => None,
+    };
+    let manifest_contents = xforge_core::manifest::serialize_manifest_pretty(&manifest)
+        .map_err(|err| err.to_string())?;
+    fs::write(&manifest_path, manifest_contents)
+        .map_err(|err| format!("failed to write manifest: {}", err))?;
+
+    Ok(BundleOutcome {
+        build_id,
+        manifest_path,
+        archive_paths,
+        debug_archive_paths,
+        xcframework_path,
+        package_swift_paths,
+        podspec_paths,
+        aar_path,
+        maven_repository,
+        wheel_paths,
+        nupkg_path,
+    })
+}
+
+/// Pack the primary artifacts of several targets into one binding package
+/// and return its path.
+fn pack_binding(
+    packer: &dyn PackExecutor,
+    format: PackFormat,
+    inputs: Vec<PackInput>,
+    output_dir: &Path,
+) -> Result<PathBuf, String> {
+    packer
+        .pack(&PackRequest {
+            format,
+            inputs,
+            output_dir: output_dir.to_string_lossy().into_owned(),
+        })
+        .map_err(|err| err.to_string())?
+        .output_paths
+        .first()
+        .map(PathBuf::from)
+        .ok_or_else(|| format!("missing {:?} output", format))
+}
+
+/// Where a published XCFramework zip is downloaded from, and the SHA-256
+/// SwiftPM and CocoaPods check the download against.
+struct ReleasedXcframework {
+    url: String,
+    sha256: String,
+    repository: String,
+}
+
+fn released_xcframework(
+    manifest_dir: &Path,
+    build_id: &str,
+    xcframework_path: &Path,
+) -> Result<ReleasedXcframework, String> {
+    let precompiled = config::precompiled_settings(manifest_dir)
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| {
+            "build.xcframework.spm and build.xcframework.cocoapods need precompiled_binaries in xforge.yaml for the download URL"
+                .to_string()
+        })?;
+    let bytes = fs::read(xcframework_path)
+        .map_err(|err| format!("failed to read '{}': {}", xcframework_path.display(), err))?;
+    let file_name = xcframework_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Ok(ReleasedXcframework {
+        url: precompiled.asset_url(build_id, &file_name),
+        sha256: hex::encode(Sha256::digest(&bytes)),
+        repository: precompiled.repository,
+    })
+}
+
+/// Write a package manager file to the bundle output when it ships as a
+/// release asset and to `extra_dir` when set; return every path written.
+fn write_package_file(
+    output_dir: &Path,
+    asset: bool,
+    extra_dir: Option<&Path>,
+    file_name: &str,
+    contents: &str,
+) -> Result<Vec<PathBuf>, String> {
+    let dirs = asset.then_some(output_dir).into_iter().chain(extra_dir);
+    let mut paths = Vec::new();
+    for dir in dirs {
+        fs::create_dir_all(dir)
+            .map_err(|err| format!("failed to create '{}': {}", dir.display(), err))?;
+        let path = dir.join(file_name);
+        fs::write(&path, contents)
+            .map_err(|err| format!("failed to write '{}': {}", path.display(), err))?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// `bindings.catalog` entry naming the binding packages built from `targets`.
+fn binding_descriptor(
+    name: &str,
+    version: &str,
+    platforms: Vec<String>,
+    paths: &[&Path],
+) -> BindingDescriptor {
+    let artifacts = paths
+        .iter()
+        .map(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+        .collect();
+    BindingDescriptor {
+        name: name.to_string(),
+        version: version.to_string(),
+        platforms,
+        artifacts,
+    }
+}
+
+/// Inputs shared by every archive of one bundle run.
+struct BundleContext<'a> {
+    package_name: &'a str,
+    build_id: &'a str,
+    target_root: PathBuf,
+    output_dir: &'a Path,
+    manifest_path: &'a Path,
+    build_id_path: &'a Path,
+    build_settings: &'a BuildSettings,
+    crate_types: &'a [String],
+}
+
+/// One cargo profile of one target, packed into the output directory.
+struct BundledProfile {
+    archive_name: String,
+    archive_path: PathBuf,
+    libraries: Vec<LibraryEntry>,
+    debug_artifacts: Vec<String>,
+    debug_archive_path: Option<PathBuf>,
+    binary: Option<BinaryInfo>,
+    exported_symbols: Vec<String>,
+    /// Newest `GLIBC_` version the dynamic library needs.
+    glibc: Option<(u32, u32)>,
+    headers: Option<HeaderDigest>,
+    /// What went into the archive, reused for multi-platform packages.
+    pack_input: PackInput,
+}
+
+impl BundleContext<'_> {
+    /// Pack the libraries `profile` left under
+    /// `<cargo_target_dir>/<target>/<profile>`. Variant archives carry the
+    /// variant name so they can sit beside the primary ones.
+    fn bundle_profile(
+        &self,
+        target: &str,
+        platform: PlatformKey,
+        profile: &str,
+        cargo_target_dir: &Path,
+        variant: Option<&str>,
+    ) -> Result<BundledProfile, String> {
+        let package_name = self.package_name;
+        let build_id = self.build_id;
+        let archive_kind = self
+            .build_settings
+            .archive_kind_for(target)
+            .unwrap_or_else(|| default_archive_kind(&platform));
+        let archive_name = match variant {
+            Some(variant) => {
+                variant_artifact_name(package_name, build_id, &platform, variant, archive_kind)
+            }
+            None => artifact_name(package_name, build_id, &platform, archive_kind),
+        }
+        .map_err(|err| err.to_string())?;
+        let stripped = self.build_settings.strip.mode != StripMode::None;
+        let profile_dir = cargo_target_dir
+            .join(target)
+            .join(cargo_profile_dir(profile));
+        // Universal platforms are merged from their slices' libraries and
+        // take the first slice's headers.
+        let (kinds, libraries, header_dir) = match platform.universal_slices() {
+            [] => {
+                let kinds =
+                    resolve_library_kinds(self.build_settings, self.crate_types, target, platform)?;
+                let libraries = self.resolve_libraries(&profile_dir, platform, &kinds)?;
+                (kinds, libraries, profile_dir.clone())
+            }
+            slices => {
+                self.merge_universal(platform, slices, profile, cargo_target_dir, &profile_dir)?
+            }
+        };
+        let include_dir = match &self.build_settings.headers {
+            Some(_) => {
+                let include_dir = header_dir.join(INCLUDE_DIR_NAME);
+                if !include_dir.is_dir() {
+                    return Err(format!(
+                        "generated headers not found at '{}'; run xforge build first",
+                        include_dir.display()
+                    ));
+                }
+                Some(include_dir)
+            }
+            None => None,
+        };
+        let headers = include_dir.as_deref().map(header_digest).transpose()?;
+        let inspected = inspect_dynamic_library(&libraries, platform)?;
+        let (binary, exported_symbols, glibc) = match inspected {
+            Some(library) => (
+                Some(library.binary),
+                library.exported_symbols,
+                library.glibc,
+            ),
+            None => (None, Vec::new(), None),
         };
+        let output_dir = self.output_dir.to_string_lossy().into_owned();
         let built_artifact = BuiltArtifact {
             platform,
-            build_id: build_id.clone(),
+            build_id: build_id.to_string(),
             archive_kind,
             artifact_name: archive_name.clone(),
-            output_dir: args.output_dir.to_string_lossy().into_owned(),
-            library_path: library_path.to_string_lossy().into_owned(),
-            include_dir: None,
-            manifest_path: manifest_path.to_string_lossy().into_owned(),
-            build_id_path: build_id_path.to_string_lossy().into_owned(),
+            output_dir: output_dir.clone(),
+            library_path: libraries[0].path.clone(),
+            libraries,
+            include_dir: include_dir.map(|dir| dir.to_string_lossy().into_owned()),
+            manifest_path: self.manifest_path.to_string_lossy().into_owned(),
+            build_id_path: self.build_id_path.to_string_lossy().into_owned(),
         };
-        let layout = archive_layout(&package_name, &platform);
+        let mut layout = archive_layout_with_libraries(package_name, &platform, &kinds);
+        if headers.is_some() {
+            layout.include_path = Some(INCLUDE_DIR_NAME.to_string());
+        }
+        let archived_libraries = layout.libraries.clone();
         let pack_input = PackInput {
             artifact: built_artifact,
             layout,
         };
         let pack_request = PackRequest {
-            format: match archive_kind {
-                ArchiveKind::TarGz => PackFormat::TarGz,
-                ArchiveKind::Zip => PackFormat::Zip,
-            },
-            inputs: vec![pack_input],
-            output_dir: args.output_dir.to_string_lossy().into_owned(),
+            format: PackFormat::from(archive_kind),
+            inputs: vec![pack_input.clone()],
+            output_dir,
         };
         let archive_path = match archive_kind {
             ArchiveKind::TarGz => {
@@ -184,36 +742,235 @@ pub fn run(args: BundleArgs) -> Result<BundleOutcome, String> {
                     .pack(&pack_request)
                     .map_err(|err| err.to_string())?
             }
+            ArchiveKind::TarZst => TarZstPacker
+                .pack(&pack_request)
+                .map_err(|err| err.to_string())?,
+            ArchiveKind::TarXz => TarXzPacker
+                .pack(&pack_request)
+                .map_err(|err| err.to_string())?,
         }
         .output_paths
-        .get(0)
+        .first()
         .ok_or_else(|| "missing archive output".to_string())?
         .clone();
 
-        archive_paths.push(PathBuf::from(archive_path));
-        platform_entries.push(Platform {
-            name: target.clone(),
-            build_id: per_target_build_id,
-            triples: vec![target.clone()],
-            bindings: vec![],
-            artifacts: vec![archive_name],
-            description: None,
-        });
+        let mut debug_artifacts = Vec::new();
+        let mut debug_archive_path = None;
+        let debug_file = profile_dir
+            .join(STRIPPED_DIR_NAME)
+            .join(debug_info_filename(package_name, &platform));
+        if stripped && kinds.contains(&LibraryKind::Dynamic) && debug_file.exists() {
+            let debug_name = match variant {
+                Some(variant) => variant_debug_artifact_name(
+                    package_name,
+                    build_id,
+                    &platform,
+                    variant,
+                    archive_kind,
+                ),
+                None => debug_artifact_name(package_name, build_id, &platform, archive_kind),
+            }
+            .map_err(|err| err.to_string())?;
+            let debug_archive = pack_debug_info(
+                pack_request.format,
+                &[debug_file],
+                &self.output_dir.join(&debug_name),
+            )
+            .map_err(|err| err.to_string())?;
+            debug_archive_path = Some(debug_archive);
+            debug_artifacts.push(debug_name);
+        }
+
+        Ok(BundledProfile {
+            archive_name,
+            archive_path: PathBuf::from(archive_path),
+            libraries: archived_libraries,
+            debug_artifacts,
+            debug_archive_path,
+            binary,
+            exported_symbols,
+            glibc,
+            headers,
+            pack_input,
+        })
     }
 
-    manifest.platforms.targets = platform_entries;
-    let manifest_contents = xforge_core::manifest::serialize_manifest_pretty(&manifest)
-        .map_err(|err| err.to_string())?;
-    fs::write(&manifest_path, manifest_contents)
-        .map_err(|err| format!("failed to write manifest: {}", err))?;
+    /// Find the `kinds` libraries the build left in `profile_dir`, preferring
+    /// the stripped copy of the dynamic library when stripping is on.
+    fn resolve_libraries(
+        &self,
+        profile_dir: &Path,
+        platform: PlatformKey,
+        kinds: &[LibraryKind],
+    ) -> Result<Vec<LibraryEntry>, String> {
+        let stripped = self.build_settings.strip.mode != StripMode::None;
+        let mut libraries = Vec::with_capacity(kinds.len());
+        for kind in kinds {
+            let file_name = library_filename_for(self.package_name, &platform, *kind);
+            if stripped && *kind == LibraryKind::Dynamic {
+                let path = profile_dir.join(STRIPPED_DIR_NAME).join(&file_name);
+                if !path.is_file() {
+                    return Err(format!(
+                        "stripped library not found at '{}'; run xforge build first",
+                        path.display()
+                    ));
+                }
+                libraries.push(LibraryEntry {
+                    kind: *kind,
+                    path: path.to_string_lossy().into_owned(),
+                });
+                continue;
+            }
+            let Some(path) = resolve_library_path(profile_dir, &file_name) else {
+                return Err(format!(
+                    "{} library not found at '{}'; run xforge build first",
+                    kind,
+                    profile_dir.join(&file_name).display()
+                ));
+            };
+            libraries.push(LibraryEntry {
+                kind: *kind,
+                path: path.to_string_lossy().into_owned(),
+            });
+        }
+        Ok(libraries)
+    }
 
-    Ok(BundleOutcome {
-        build_id,
-        manifest_path,
-        archive_paths,
+    /// Merge the dynamic library of every slice of `platform` into one fat
+    /// Mach-O under `profile_dir`. Returns the library and the directory of
+    /// the first slice, whose headers the universal archive ships.
+    fn merge_universal(
+        &self,
+        platform: PlatformKey,
+        slices: &[PlatformKey],
+        profile: &str,
+        cargo_target_dir: &Path,
+        profile_dir: &Path,
+    ) -> Result<(Vec<LibraryKind>, Vec<LibraryEntry>, PathBuf), String> {
+        let kinds = vec![LibraryKind::Dynamic];
+        let mut slice_dirs = Vec::with_capacity(slices.len());
+        let mut inputs = Vec::with_capacity(slices.len());
+        for slice in slices {
+            let slice_dir = cargo_target_dir
+                .join(slice.as_str())
+                .join(cargo_profile_dir(profile));
+            let library = self
+                .resolve_libraries(&slice_dir, *slice, &kinds)?
+                .remove(0);
+            inputs.push(PathBuf::from(library.path));
+            slice_dirs.push(slice_dir);
+        }
+        let output = profile_dir.join(library_filename_for(
+            self.package_name,
+            &platform,
+            LibraryKind::Dynamic,
+        ));
+        let inputs: Vec<&Path> = inputs.iter().map(PathBuf::as_path).collect();
+        write_universal(&inputs, &output).map_err(|err| format!("{}: {}", platform, err))?;
+        let libraries = vec![LibraryEntry {
+            kind: LibraryKind::Dynamic,
+            path: output.to_string_lossy().into_owned(),
+        }];
+        Ok((kinds, libraries, slice_dirs.remove(0)))
+    }
+}
+
+/// Universal pseudo-platforms to bundle: those whose slices are all among
+/// `bundled` and all build a dynamic library.
+fn universal_platforms(
+    build_settings: &BuildSettings,
+    crate_types: &[String],
+    bundled: &[(String, PlatformKey)],
+) -> Result<Vec<PlatformKey>, String> {
+    let mut universal = Vec::new();
+    'platforms: for platform in all_platform_keys() {
+        let slices = platform.universal_slices();
+        if slices.is_empty() {
+            continue;
+        }
+        for slice in slices {
+            let Some((target, _)) = bundled.iter().find(|(_, key)| key == slice) else {
+                continue 'platforms;
+            };
+            let kinds = resolve_library_kinds(build_settings, crate_types, target, *slice)?;
+            if !kinds.contains(&LibraryKind::Dynamic) {
+                continue 'platforms;
+            }
+        }
+        universal.push(platform);
+    }
+    Ok(universal)
+}
+
+/// Every target is expected to ship the same headers; a target whose
+/// generated headers differ from the first target's fails the bundle unless
+/// `build.headers.per_target` allows it.
+fn check_headers_match(
+    build_settings: &BuildSettings,
+    bundled: &[Platform],
+    target: &str,
+    headers: &Option<HeaderDigest>,
+) -> Result<(), String> {
+    let per_target = build_settings
+        .headers
+        .as_ref()
+        .map(|headers| headers.per_target)
+        .unwrap_or(true);
+    let (Some(headers), Some(first)) = (headers, bundled.first()) else {
+        return Ok(());
+    };
+    match &first.headers {
+        Some(expected) if !per_target && expected != headers => Err(format!(
+            "generated headers for '{}' differ from '{}' (sha256 {} vs {}); make the cbindgen output target-independent or set build.headers.per_target",
+            target, first.name, headers.sha256, expected.sha256
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Digest of the files under `include_dir`, hashing each relative path and
+/// its bytes in sorted order.
+fn header_digest(include_dir: &Path) -> Result<HeaderDigest, String> {
+    let mut files = Vec::new();
+    collect_header_files(include_dir, "", &mut files)?;
+    files.sort();
+    let mut hasher = Sha256::new();
+    for file in &files {
+        let path = include_dir.join(file);
+        let bytes = fs::read(&path)
+            .map_err(|err| format!("failed to read '{}': {}", path.display(), err))?;
+        hasher.update(file.as_bytes());
+        hasher.update([0]);
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(&bytes);
+    }
+    Ok(HeaderDigest {
+        path: INCLUDE_DIR_NAME.to_string(),
+        files,
+        sha256: hex::encode(hasher.finalize()),
     })
 }
 
+fn collect_header_files(dir: &Path, prefix: &str, files: &mut Vec<String>) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|err| format!("failed to read '{}': {}", dir.display(), err))?;
+    for entry in entries {
+        let entry = entry.map_err(|err| format!("failed to read '{}': {}", dir.display(), err))?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let relative = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        if entry.path().is_dir() {
+            collect_header_files(&entry.path(), &relative, files)?;
+        } else {
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
+
 fn resolve_target_root(manifest_dir: &Path) -> PathBuf {
     let mut current = Some(manifest_dir);
     while let Some(dir) = current {
@@ -225,30 +982,57 @@ fn resolve_target_root(manifest_dir: &Path) -> PathBuf {
     manifest_dir.to_path_buf()
 }
 
-fn resolve_library_path(
-    target_root: &Path,
-    target: &str,
-    profile: &str,
-    package_name: &str,
+/// What the bundle reads out of a target's dynamic library.
+struct InspectedLibrary {
+    binary: BinaryInfo,
+    exported_symbols: Vec<String>,
+    /// Newest `GLIBC_` version it needs, for the wheel tag.
+    glibc: Option<(u32, u32)>,
+}
+
+/// Refuse to package a dynamic library whose headers name another platform,
+/// and return what the headers say plus its exported symbols for the manifest.
+fn inspect_dynamic_library(
+    libraries: &[LibraryEntry],
     platform: PlatformKey,
-) -> Option<PathBuf> {
-    let file_name = xforge_core::artifact::layout::library_filename(package_name, &platform);
-    let profile_dir = target_root.join("target").join(target).join(profile);
-    let primary = profile_dir.join(&file_name);
+) -> Result<Option<InspectedLibrary>, String> {
+    let Some(library) = libraries
+        .iter()
+        .find(|entry| entry.kind == LibraryKind::Dynamic)
+    else {
+        return Ok(None);
+    };
+    let bytes = fs::read(&library.path)
+        .map_err(|err| format!("failed to read '{}': {}", library.path, err))?;
+    let info = inspect(&bytes).map_err(|err| format!("'{}': {}", library.path, err))?;
+    info.check(platform, BinaryFileType::SharedLibrary)
+        .map_err(|err| format!("'{}': {}", library.path, err))?;
+    let symbols =
+        exported_symbols(&bytes).map_err(|err| format!("'{}': {}", library.path, err))?;
+    let glibc = required_glibc(&bytes).map_err(|err| format!("'{}': {}", library.path, err))?;
+    Ok(Some(InspectedLibrary {
+        binary: info,
+        exported_symbols: symbols,
+        glibc,
+    }))
+}
+
+fn resolve_library_path(profile_dir: &Path, file_name: &str) -> Option<PathBuf> {
+    let primary = profile_dir.join(file_name);
     if primary.exists() {
         return Some(primary);
     }
 
     let deps_dir = profile_dir.join("deps");
-    let deps_exact = deps_dir.join(&file_name);
+    let deps_exact = deps_dir.join(file_name);
     if deps_exact.exists() {
         return Some(deps_exact);
     }
 
-    let extension = Path::new(&file_name)
+    let extension = Path::new(file_name)
         .extension()
         .map(|value| value.to_string_lossy().into_owned())?;
-    let stem = Path::new(&file_name)
+    let stem = Path::new(file_name)
         .file_stem()
         .map(|value| value.to_string_lossy().into_owned())?;
     let entries = fs::read_dir(deps_dir).ok()?;
@@ -295,6 +1079,74 @@ pub fn package_metadata(manifest_dir: &Path) -> Result<(String, String), String>
     Ok((parsed.package.name, parsed.package.version))
 }
 
+/// Manifest `package` block: the name and version plus the description,
+/// license, authors and repository Cargo.toml declares. Fields inherited
+/// from the workspace (`license.workspace = true`) are left out.
+fn manifest_package(manifest_dir: &Path, name: &str, version: String) -> Result<Package, String> {
+    let cargo_toml_path = manifest_dir.join("Cargo.toml");
+    let contents = fs::read_to_string(&cargo_toml_path).map_err(|err| {
+        format!(
+            "failed to read Cargo.toml '{}': {}",
+            cargo_toml_path.display(),
+            err
+        )
+    })?;
+    let parsed: toml::Value =
+        toml::from_str(&contents).map_err(|err| format!("failed to parse Cargo.toml: {}", err))?;
+    let package = parsed.get("package");
+    let field = |key: &str| {
+        package
+            .and_then(|package| package.get(key))
+            .and_then(toml::Value::as_str)
+            .map(str::to_string)
+    };
+    let authors = package
+        .and_then(|package| package.get("authors"))
+        .and_then(toml::Value::as_array)
+        .map(|authors| {
+            authors
+                .iter()
+                .filter_map(toml::Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(Package {
+        name: name.to_string(),
+        version,
+        description: field("description"),
+        license: field("license"),
+        authors,
+        repository: field("repository"),
+    })
+}
+
+/// The `[lib] crate-type` list from Cargo.toml; empty when not declared.
+pub fn library_crate_types(manifest_dir: &Path) -> Result<Vec<String>, String> {
+    #[derive(serde::Deserialize)]
+    struct CargoToml {
+        lib: Option<CargoLib>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct CargoLib {
+        #[serde(rename = "crate-type", alias = "crate_type", default)]
+        crate_type: Vec<String>,
+    }
+
+    let cargo_toml_path = manifest_dir.join("Cargo.toml");
+    let contents = fs::read_to_string(&cargo_toml_path).map_err(|err| {
+        format!(
+            "failed to read Cargo.toml '{}': {}",
+            cargo_toml_path.display(),
+            err
+        )
+    })?;
+    let parsed: CargoToml = toml::from_str(&contents)
+        .map_err(|err| format!("failed to parse Cargo.toml: {}", err))?;
+    Ok(parsed.lib.map(|lib| lib.crate_type).unwrap_or_default())
+}
+
 fn rustc_host_triple() -> Option<String> {
     let output = std::process::Command::new("rustc").arg("-vV").output().ok()?;
     if !output.status.success() {
@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use xforge_build::cache::CacheEntry;
+use xforge_core::config;
+
+use super::build::open_cache;
+
+pub struct CacheArgs {
+    pub manifest_dir: PathBuf,
+    pub action: CacheAction,
+}
+
+pub enum CacheAction {
+    List,
+    Prune { max_size_bytes: Option<u64> },
+    Clear,
+}
+
+pub enum CacheReport {
+    Entries(Vec<CacheEntry>),
+    Evicted(Vec<CacheEntry>),
+    Cleared(usize),
+}
+
+pub struct CacheOutcome {
+    pub root: PathBuf,
+    pub report: CacheReport,
+}
+
+pub fn run(args: CacheArgs) -> Result<CacheOutcome, String> {
+    let settings = config::build_settings(&args.manifest_dir).map_err(|err| err.to_string())?;
+    let cache = open_cache(&settings.cache, false)
+        .ok_or_else(|| "build cache is disabled or has no cache directory".to_string())?;
+    let report = match args.action {
+        CacheAction::List => CacheReport::Entries(cache.entries().map_err(|err| err.to_string())?),
+        CacheAction::Prune { max_size_bytes } => {
+            let max_size_bytes = max_size_bytes
+                .or(settings.cache.max_size_bytes)
+                .ok_or_else(|| {
+                    "no cache size limit configured; pass --max-size-mb or set build.cache.max_size_mb"
+                        .to_string()
+                })?;
+            CacheReport::Evicted(cache.prune(max_size_bytes).map_err(|err| err.to_string())?)
+        }
+        CacheAction::Clear => CacheReport::Cleared(cache.clear().map_err(|err| err.to_string())?),
+    };
+    Ok(CacheOutcome {
+        root: cache.root().to_path_buf(),
+        report,
+    })
+}
@@ -1,6 +1,9 @@
+pub mod abi_diff;
 pub mod build;
 pub mod bundle;
+pub mod cache;
 pub mod keygen;
+pub mod plan;
 pub mod publish;
 pub mod sign;
 pub mod verify;
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use xforge_build::github_matrix;
+use xforge_core::build_plan::BuildPlan;
+use xforge_core::config;
+
+use super::build::{resolve_plan, resolve_target_root};
+
+pub struct PlanArgs {
+    pub manifest_dir: PathBuf,
+    pub target: Option<String>,
+    pub profile: String,
+    /// Plan the named `build.variants` entry.
+    pub variant: Option<String>,
+    pub cross_image: Option<String>,
+    pub format: PlanFormat,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlanFormat {
+    Summary,
+    Json,
+    GithubMatrix,
+}
+
+pub struct PlanOutcome {
+    pub plan: BuildPlan,
+    /// Rendered output for `--json` and `--matrix`; `None` for the summary.
+    pub rendered: Option<String>,
+}
+
+pub fn run(args: PlanArgs) -> Result<PlanOutcome, String> {
+    let plan = resolve_plan(
+        &args.manifest_dir,
+        args.target,
+        &args.profile,
+        args.variant.as_deref(),
+        args.cross_image,
+    )?;
+    let rendered = match args.format {
+        PlanFormat::Summary => None,
+        PlanFormat::Json => {
+            let portable = plan.relative_to(&resolve_target_root(&args.manifest_dir));
+            Some(
+                serde_json::to_string_pretty(&portable)
+                    .map_err(|err| format!("failed to serialize plan: {}", err))?,
+            )
+        }
+        PlanFormat::GithubMatrix => {
+            let settings =
+                config::build_settings(&args.manifest_dir).map_err(|err| err.to_string())?;
+            let matrix = github_matrix(&plan, &settings.executor_overrides)
+                .map_err(|err| err.to_string())?;
+            Some(
+                serde_json::to_string(&matrix)
+                    .map_err(|err| format!("failed to serialize matrix: {}", err))?,
+            )
+        }
+    };
+    Ok(PlanOutcome { plan, rendered })
+}
@@ -21,15 +21,69 @@ enum Command {
         /// Target triple (overrides rust-toolchain.toml).
         #[arg(long)]
         target: Option<String>,
-        /// Cargo profile (default: release).
-        #[arg(long, default_value = "release")]
-        profile: String,
-        /// Build executor (cargo | cross | zigbuild).
+        /// Cargo profile (default: every profile in build.profiles, or release).
+        #[arg(long)]
+        profile: Option<String>,
+        /// Build one build.variants entry (default: the profiles, then every variant).
+        #[arg(long)]
+        variant: Option<String>,
+        /// Build executor (cargo | cross | zigbuild | ndk | command | auto).
         #[arg(long, default_value = "cargo")]
         executor: String,
         /// Cross image to use (required for cross builds).
         #[arg(long)]
         cross_image: Option<String>,
+        /// Always rebuild instead of restoring from the build cache.
+        #[arg(long)]
+        no_cache: bool,
+        /// Execute a plan written by `xforge plan --json` (combine with --target for one slice).
+        #[arg(long)]
+        plan: Option<PathBuf>,
+        /// Do not check or install the rustup toolchain, targets and components.
+        #[arg(long)]
+        no_provision: bool,
+        /// Report missing rustup toolchain pieces without installing them.
+        #[arg(long, conflicts_with = "no_provision")]
+        offline: bool,
+        /// Build every target twice in scratch directories and fail if the libraries differ.
+        #[arg(long)]
+        verify_reproducible: bool,
+        /// Run the second reproducibility build from a copy of the crate at another path.
+        #[arg(long, requires = "verify_reproducible")]
+        vary_path: bool,
+        /// Run the second reproducibility build with a different time zone and locale.
+        #[arg(long, requires = "verify_reproducible")]
+        vary_env: bool,
+        /// Build progress output (progress | json); json prints one event per line on stdout.
+        #[arg(long, default_value = "progress")]
+        events: String,
+        /// Also list the host environment variables a deterministic build drops.
+        #[arg(long)]
+        verbose: bool,
+    },
+    /// Resolve the build plan for every target without building.
+    Plan {
+        /// Manifest directory containing Cargo.toml.
+        #[arg(long, default_value = ".")]
+        manifest_dir: PathBuf,
+        /// Target triple (overrides rust-toolchain.toml).
+        #[arg(long)]
+        target: Option<String>,
+        /// Cargo profile (default: release).
+        #[arg(long, default_value = "release")]
+        profile: String,
+        /// Plan a build.variants entry instead of the default features.
+        #[arg(long)]
+        variant: Option<String>,
+        /// Cross image to record for cross builds.
+        #[arg(long)]
+        cross_image: Option<String>,
+        /// Print the full plan as JSON.
+        #[arg(long, conflicts_with = "matrix")]
+        json: bool,
+        /// Print a CI matrix grouped by runner and executor (github).
+        #[arg(long)]
+        matrix: Option<String>,
     },
     /// Bundle built artifacts into archives + manifest.
     Bundle {
@@ -42,9 +96,24 @@ enum Command {
         /// Target triple (overrides rust-toolchain.toml).
         #[arg(long)]
         target: Option<String>,
-        /// Cargo profile (default: release).
-        #[arg(long, default_value = "release")]
-        profile: String,
+        /// Cargo profile (default: every profile in build.profiles, the first as primary).
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Compare the exported symbols of two bundled manifests.
+    AbiDiff {
+        /// Manifest of the previous release.
+        old_manifest: PathBuf,
+        /// Manifest of the new release.
+        new_manifest: PathBuf,
+    },
+    /// Inspect or trim the local build cache.
+    Cache {
+        /// Manifest directory containing xforge.yaml.
+        #[arg(long, default_value = ".")]
+        manifest_dir: PathBuf,
+        #[command(subcommand)]
+        action: CacheCommand,
     },
     /// Generate an Ed25519 keypair (public + private hex).
     Keygen,
@@ -92,6 +161,20 @@ enum Command {
     },
 }
 
+#[derive(Subcommand)]
+enum CacheCommand {
+    /// List cached entries, most recently used first.
+    List,
+    /// Evict least recently used entries until the cache fits the size limit.
+    Prune {
+        /// Size limit in MiB (defaults to build.cache.max_size_mb).
+        #[arg(long)]
+        max_size_mb: Option<u64>,
+    },
+    /// Remove every cached entry.
+    Clear,
+}
+
 fn main() {
     if let Err(message) = run_cli() {
         let _ = exit_with_error(&message);
@@ -111,29 +194,188 @@ fn run_cli() -> Result<(), String> {
             manifest_dir,
             target,
             profile,
+            variant,
             executor,
             cross_image,
+            no_cache,
+            plan,
+            no_provision,
+            offline,
+            verify_reproducible,
+            vary_path,
+            vary_env,
+            events,
+            verbose,
         } => {
+            if events != "progress" && events != "json" {
+                return exit_with_error(&format!(
+                    "invalid events format '{}'; expected progress or json",
+                    events
+                ));
+            }
+            xforge_build::process::install_ctrl_c_handler();
             let executor = match executor.as_str() {
                 "cargo" => commands::build::BuildExecutorKind::Cargo,
                 "cross" => commands::build::BuildExecutorKind::Cross,
                 "zigbuild" => commands::build::BuildExecutorKind::Zigbuild,
+                "ndk" => commands::build::BuildExecutorKind::Ndk,
+                "command" => commands::build::BuildExecutorKind::Command,
+                "auto" => commands::build::BuildExecutorKind::Auto,
                 other => {
                     return exit_with_error(&format!(
-                        "invalid executor '{}'; expected cargo, cross, or zigbuild",
+                        "invalid executor '{}'; expected cargo, cross, zigbuild, ndk, command, or auto",
+                        other
+                    ));
+                }
+            };
+            // A plan file names its own profile and features. Without
+            // --profile or --variant every profile is built, then every
+            // variant with the primary profile.
+            let builds: Vec<(String, Option<String>)> = if plan.is_some() {
+                vec![(profile.unwrap_or_else(|| "release".to_string()), None)]
+            } else {
+                let settings = xforge_core::config::build_settings(&manifest_dir)
+                    .map_err(|err| err.to_string())?;
+                let profiles = settings.release_profiles();
+                match (profile, variant) {
+                    (Some(profile), variant) => vec![(profile, variant)],
+                    (None, Some(variant)) => vec![(profiles[0].clone(), Some(variant))],
+                    (None, None) => {
+                        let variants = settings
+                            .variants
+                            .keys()
+                            .map(|name| (profiles[0].clone(), Some(name.clone())));
+                        profiles
+                            .iter()
+                            .map(|profile| (profile.clone(), None))
+                            .chain(variants)
+                            .collect()
+                    }
+                }
+            };
+            for (profile, variant) in builds {
+                let observer: Box<dyn xforge_build::BuildObserver> = if events == "json" {
+                    Box::new(commands::build::JsonEventsObserver)
+                } else {
+                    Box::new(commands::build::ProgressObserver::new().verbose(verbose))
+                };
+                let outcome = commands::build::run(commands::build::BuildArgs {
+                    manifest_dir: manifest_dir.clone(),
+                    target: target.clone(),
+                    profile: profile.clone(),
+                    variant: variant.clone(),
+                    executor: executor.clone(),
+                    cross_image: cross_image.clone(),
+                    no_cache,
+                    plan: plan.clone(),
+                    no_provision,
+                    offline,
+                    verify_reproducible: verify_reproducible.then_some(
+                        commands::build::ReproducibilityCheck {
+                            vary_path,
+                            vary_environment: vary_env,
+                        },
+                    ),
+                    observer: Some(observer),
+                })?;
+                let mut lines = vec![format!("profile={}", profile)];
+                if let Some(variant) = &variant {
+                    lines.push(format!("variant={}", variant));
+                }
+                lines.extend(build_outcome_lines(&outcome));
+                // With --events json stdout carries nothing but events.
+                for line in lines {
+                    if events == "json" {
+                        eprintln!("{}", line);
+                    } else {
+                        println!("{}", line);
+                    }
+                }
+            }
+            Ok(())
+        }
+        Command::Plan {
+            manifest_dir,
+            target,
+            profile,
+            variant,
+            cross_image,
+            json,
+            matrix,
+        } => {
+            let format = match (json, matrix.as_deref()) {
+                (true, _) => commands::plan::PlanFormat::Json,
+                (false, Some("github")) => commands::plan::PlanFormat::GithubMatrix,
+                (false, Some(other)) => {
+                    return exit_with_error(&format!(
+                        "invalid matrix '{}'; expected github",
                         other
                     ));
                 }
+                (false, None) => commands::plan::PlanFormat::Summary,
             };
-            let outcome = commands::build::run(commands::build::BuildArgs {
+            let outcome = commands::plan::run(commands::plan::PlanArgs {
                 manifest_dir,
                 target,
                 profile,
-                executor,
+                variant,
                 cross_image,
+                format,
             })?;
-            println!("build_id={}", outcome.build_id);
-            println!("library={}", outcome.library_path.display());
+            match outcome.rendered {
+                Some(rendered) => println!("{}", rendered),
+                None => {
+                    println!("build_id={}", outcome.plan.build_id);
+                    for target in &outcome.plan.targets {
+                        println!(
+                            "target[{}]={}",
+                            target.rust_target_triple, target.artifact.artifact_name
+                        );
+                    }
+                }
+            }
+            Ok(())
+        }
+        Command::Cache {
+            manifest_dir,
+            action,
+        } => {
+            let action = match action {
+                CacheCommand::List => commands::cache::CacheAction::List,
+                CacheCommand::Prune { max_size_mb } => commands::cache::CacheAction::Prune {
+                    max_size_bytes: max_size_mb.map(|mb| mb * 1024 * 1024),
+                },
+                CacheCommand::Clear => commands::cache::CacheAction::Clear,
+            };
+            let outcome = commands::cache::run(commands::cache::CacheArgs {
+                manifest_dir,
+                action,
+            })?;
+            println!("cache_dir={}", outcome.root.display());
+            match outcome.report {
+                commands::cache::CacheReport::Entries(entries) => {
+                    for entry in entries {
+                        println!(
+                            "{} {} {} {} bytes",
+                            entry.build_id,
+                            entry.target,
+                            entry.profile,
+                            entry.size_bytes()
+                        );
+                    }
+                }
+                commands::cache::CacheReport::Evicted(entries) => {
+                    for entry in entries {
+                        println!(
+                            "evicted: {} {} {}",
+                            entry.build_id, entry.target, entry.profile
+                        );
+                    }
+                }
+                commands::cache::CacheReport::Cleared(count) => {
+                    println!("cleared={}", count);
+                }
+            }
             Ok(())
         }
         Command::Bundle {
@@ -153,6 +395,50 @@ fn run_cli() -> Result<(), String> {
             for archive in outcome.archive_paths {
                 println!("archive={}", archive.display());
             }
+            for archive in outcome.debug_archive_paths {
+                println!("debug_archive={}", archive.display());
+            }
+            if let Some(xcframework) = outcome.xcframework_path {
+                println!("xcframework={}", xcframework.display());
+            }
+            for package_swift in outcome.package_swift_paths {
+                println!("package_swift={}", package_swift.display());
+            }
+            for podspec in outcome.podspec_paths {
+                println!("podspec={}", podspec.display());
+            }
+            if let Some(aar) = outcome.aar_path {
+                println!("aar={}", aar.display());
+            }
+            if let Some(repository) = outcome.maven_repository {
+                println!("maven={}", repository.display());
+            }
+            for wheel in outcome.wheel_paths {
+                println!("wheel={}", wheel.display());
+            }
+            if let Some(nupkg) = outcome.nupkg_path {
+                println!("nupkg={}", nupkg.display());
+            }
+            Ok(())
+        }
+        Command::AbiDiff {
+            old_manifest,
+            new_manifest,
+        } => {
+            let diff = commands::abi_diff::run(commands::abi_diff::AbiDiffArgs {
+                old_manifest,
+                new_manifest,
+            })?;
+            println!("old_version={}", diff.old_version);
+            println!("new_version={}", diff.new_version);
+            for platform in diff.platforms {
+                for symbol in platform.removed {
+                    println!("removed[{}]={}", platform.platform, symbol);
+                }
+                for symbol in platform.added {
+                    println!("added[{}]={}", platform.platform, symbol);
+                }
+            }
             Ok(())
         }
         Command::Sign { file, out } => {
@@ -259,6 +545,71 @@ fn resolve_precompiled_settings(
     Ok(None)
 }
 
+fn build_outcome_lines(outcome: &commands::build::BuildOutcome) -> Vec<String> {
+    let mut lines = Vec::new();
+    for selection in &outcome.executor_selections {
+        lines.push(format!(
+            "executor[{}]={} ({})",
+            selection.rust_target_triple, selection.kind, selection.reason
+        ));
+    }
+    if let Some(report) = &outcome.provision {
+        if report.missing_toolchain {
+            if let Some(toolchain) = &report.toolchain {
+                lines.push(format!("missing[toolchain]={}", toolchain));
+            }
+        }
+        for target in &report.missing_targets {
+            lines.push(format!("missing[target]={}", target));
+        }
+        for component in &report.missing_components {
+            lines.push(format!("missing[component]={}", component));
+        }
+        if !report.is_complete() {
+            lines.push(format!("provisioned={}", report.installed));
+        }
+    }
+    for cache in &outcome.cache_outcomes {
+        lines.push(format!(
+            "cache[{}]={} ({})",
+            cache.rust_target_triple,
+            cache.status.as_str(),
+            cache.build_id
+        ));
+    }
+    for strip in &outcome.strip_outcomes {
+        lines.push(format!(
+            "stripped[{}]={}",
+            strip.platform,
+            strip.library.display()
+        ));
+        if let Some(debug_file) = &strip.debug_file {
+            lines.push(format!(
+                "debug_info[{}]={}",
+                strip.platform,
+                debug_file.display()
+            ));
+        }
+    }
+    for header in &outcome.header_outcomes {
+        lines.push(format!(
+            "header[{}]={}",
+            header.platform,
+            header.header.display()
+        ));
+    }
+    for result in outcome.reproducibility.iter().flatten() {
+        lines.push(format!(
+            "reproducible[{}]={}",
+            result.rust_target_triple, result.reproducible
+        ));
+    }
+    lines.push(format!("build_id={}", outcome.build_id));
+    lines.push(format!("library={}", outcome.library_path.display()));
+    lines.push(format!("report={}", outcome.report_path.display()));
+    lines
+}
+
 fn exit_with_error(message: &str) -> Result<(), String> {
     eprintln!("{}", message);
     std::process::exit(1);
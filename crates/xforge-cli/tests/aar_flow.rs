@@ -0,0 +1,243 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use xforge_cli::commands::bundle;
+use xforge_core::manifest::deserialize_manifest;
+
+const EM_AARCH64: u16 = 183;
+const EM_X86_64: u16 = 62;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-aar-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+/// A 64-bit ELF shared object for `machine` whose only sections are
+/// `.shstrtab` and the NDK's `.note.android.ident`.
+fn android_shared_object(machine: u16) -> Vec<u8> {
+    let names = b"\0.shstrtab\0.note.android.ident\0";
+    let mut bytes = vec![0u8; 96 + 3 * 64];
+    bytes[..4].copy_from_slice(b"\x7fELF");
+    bytes[4] = 2;
+    bytes[5] = 1;
+    bytes[6] = 1;
+    bytes[0x10..0x12].copy_from_slice(&3u16.to_le_bytes());
+    bytes[0x12..0x14].copy_from_slice(&machine.to_le_bytes());
+    bytes[0x28..0x30].copy_from_slice(&96u64.to_le_bytes());
+    bytes[0x3a..0x3c].copy_from_slice(&64u16.to_le_bytes());
+    bytes[0x3c..0x3e].copy_from_slice(&3u16.to_le_bytes());
+    bytes[0x3e..0x40].copy_from_slice(&1u16.to_le_bytes());
+    bytes[64..64 + names.len()].copy_from_slice(names);
+    for (index, (name, kind)) in [(1u32, 3u32), (11, 7)].into_iter().enumerate() {
+        let header = 96 + (index + 1) * 64;
+        bytes[header..header + 4].copy_from_slice(&name.to_le_bytes());
+        bytes[header + 4..header + 8].copy_from_slice(&kind.to_le_bytes());
+        bytes[header + 0x18..header + 0x20].copy_from_slice(&64u64.to_le_bytes());
+        if kind == 3 {
+            bytes[header + 0x20..header + 0x28]
+                .copy_from_slice(&(names.len() as u64).to_le_bytes());
+        }
+    }
+    bytes
+}
+
+/// A crate with prebuilt arm64 and x86_64 Android libraries.
+fn write_sample_crate(dir: &Path) {
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(dir.join("src").join("lib.rs"), "").expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"aar-demo\"\nversion = \"0.4.0\"\nedition = \"2021\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n",
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        "version = 3\n\n[[package]]\nname = \"aar-demo\"\nversion = \"0.4.0\"\n",
+    )
+    .expect("write Cargo.lock");
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        "[toolchain]\nchannel = \"stable\"\ntargets = [\"aarch64-linux-android\", \"x86_64-linux-android\"]\ncomponents = [\"rustfmt\"]\n",
+    )
+    .expect("write rust-toolchain.toml");
+    fs::write(
+        dir.join("xforge.yaml"),
+        "build:\n  aar:\n    namespace: com.example.aardemo\n    min_sdk: 24\n    glue: true\n    proguard: consumer-rules.pro\n",
+    )
+    .expect("write xforge.yaml");
+    fs::write(
+        dir.join("consumer-rules.pro"),
+        "-keep class com.example.aardemo.Api { *; }",
+    )
+    .expect("write proguard rules");
+    for (triple, machine) in [
+        ("aarch64-linux-android", EM_AARCH64),
+        ("x86_64-linux-android", EM_X86_64),
+    ] {
+        let release_dir = dir.join("target").join(triple).join("release");
+        fs::create_dir_all(&release_dir).expect("create release dir");
+        fs::write(
+            release_dir.join("libaar_demo.so"),
+            android_shared_object(machine),
+        )
+        .expect("write library");
+    }
+}
+
+fn bundle_into(dir: &Path, output: &str) -> bundle::BundleOutcome {
+    bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.to_path_buf(),
+        target: None,
+        output_dir: dir.join(output),
+        profile: Some("release".to_string()),
+    })
+    .expect("bundle")
+}
+
+fn read_entry<R: Read + std::io::Seek>(zip: &mut zip::ZipArchive<R>, name: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    zip.by_name(name)
+        .unwrap_or_else(|_| panic!("missing {}", name))
+        .read_to_end(&mut bytes)
+        .expect("read entry");
+    bytes
+}
+
+#[test]
+fn bundle_writes_one_deterministic_aar_for_every_abi() {
+    let dir = temp_dir("android");
+    write_sample_crate(&dir);
+
+    let first = bundle_into(&dir, "dist-a");
+    let second = bundle_into(&dir, "dist-b");
+    let path = first.aar_path.expect("aar");
+    let file_name = path
+        .file_name()
+        .expect("file name")
+        .to_string_lossy()
+        .into_owned();
+    assert_eq!(file_name, format!("aar-demo-{}.aar", first.build_id));
+    assert_eq!(
+        fs::read(&path).expect("read aar"),
+        fs::read(second.aar_path.expect("second aar")).expect("read aar")
+    );
+
+    let mut aar = zip::ZipArchive::new(fs::File::open(&path).expect("open aar")).expect("zip");
+    let names: Vec<String> = aar.file_names().map(str::to_string).collect();
+    for expected in [
+        "AndroidManifest.xml",
+        "classes.jar",
+        "proguard.txt",
+        "R.txt",
+        "jni/arm64-v8a/libaar_demo.so",
+        "jni/x86_64/libaar_demo.so",
+    ] {
+        assert!(
+            names.iter().any(|name| name == expected),
+            "{} in {:?}",
+            expected,
+            names
+        );
+    }
+
+    let android_manifest =
+        String::from_utf8(read_entry(&mut aar, "AndroidManifest.xml")).expect("utf8 manifest");
+    assert!(android_manifest.contains("package=\"com.example.aardemo\""));
+    assert!(android_manifest.contains("<uses-sdk android:minSdkVersion=\"24\" />"));
+    let proguard = String::from_utf8(read_entry(&mut aar, "proguard.txt")).expect("utf8 rules");
+    assert!(proguard.starts_with("-keep class com.example.aardemo.Api { *; }\n"));
+    assert!(proguard.contains("-keep class com.example.aardemo.NativeLoader { *; }"));
+
+    let classes = read_entry(&mut aar, "classes.jar");
+    let mut jar = zip::ZipArchive::new(std::io::Cursor::new(classes)).expect("classes.jar");
+    let class = read_entry(&mut jar, "com/example/aardemo/NativeLoader.class");
+    assert_eq!(class[..4], [0xca, 0xfe, 0xba, 0xbe]);
+    assert!(class.windows(8).any(|window| window == b"aar_demo"));
+    assert!(class.windows(11).any(|window| window == b"loadLibrary"));
+
+    let manifest =
+        deserialize_manifest(&fs::read_to_string(&first.manifest_path).expect("read manifest"))
+            .expect("parse manifest");
+    let kotlin = &manifest.bindings.catalog[0];
+    assert_eq!(kotlin.name, "kotlin");
+    assert_eq!(kotlin.version, "0.4.0");
+    assert_eq!(kotlin.artifacts, vec![file_name]);
+    assert_eq!(
+        kotlin.platforms,
+        vec!["aarch64-linux-android", "x86_64-linux-android"]
+    );
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn bundle_writes_the_aar_into_a_maven_repository() {
+    let dir = temp_dir("maven");
+    write_sample_crate(&dir);
+    fs::write(
+        dir.join("xforge.yaml"),
+        "build:\n  aar:\n    namespace: com.example.aardemo\n  maven:\n    group_id: com.example\n    artifact_id: aar-core\n",
+    )
+    .expect("write xforge.yaml");
+
+    let outcome = bundle_into(&dir, "dist");
+    let repository = outcome.maven_repository.expect("maven repository");
+    assert_eq!(repository, dir.join("dist").join("maven"));
+    let version_dir = repository.join("com/example/aar-core/0.4.0");
+    let aar = fs::read(version_dir.join("aar-core-0.4.0.aar")).expect("read aar");
+    assert_eq!(
+        aar,
+        fs::read(outcome.aar_path.expect("aar")).expect("read aar")
+    );
+    for file in [
+        "aar-core-0.4.0.aar",
+        "aar-core-0.4.0.pom",
+        "aar-core-0.4.0.module",
+    ] {
+        for algorithm in ["md5", "sha1", "sha256", "sha512"] {
+            assert!(
+                version_dir
+                    .join(format!("{}.{}", file, algorithm))
+                    .is_file(),
+                "{}.{}",
+                file,
+                algorithm
+            );
+        }
+    }
+    let sha256 = hex::encode(Sha256::digest(&aar));
+    assert_eq!(
+        fs::read_to_string(version_dir.join("aar-core-0.4.0.aar.sha256")).expect("read sha256"),
+        sha256
+    );
+
+    let pom = fs::read_to_string(version_dir.join("aar-core-0.4.0.pom")).expect("read pom");
+    assert!(pom.contains("<!-- do_not_remove: published-with-gradle-metadata -->"));
+    assert!(pom.contains(
+        "  <groupId>com.example</groupId>\n  <artifactId>aar-core</artifactId>\n  <version>0.4.0</version>\n  <packaging>aar</packaging>\n"
+    ));
+    let module: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(version_dir.join("aar-core-0.4.0.module")).expect("read module"),
+    )
+    .expect("parse module");
+    assert_eq!(module["formatVersion"], "1.1");
+    assert_eq!(module["component"]["group"], "com.example");
+    assert_eq!(module["component"]["module"], "aar-core");
+    let variants = module["variants"].as_array().expect("variants");
+    assert_eq!(variants.len(), 2);
+    for variant in variants {
+        assert_eq!(variant["files"][0]["url"], "aar-core-0.4.0.aar");
+        assert_eq!(variant["files"][0]["size"], aar.len());
+        assert_eq!(variant["files"][0]["sha256"], sha256.as_str());
+    }
+
+    fs::remove_dir_all(&dir).ok();
+}
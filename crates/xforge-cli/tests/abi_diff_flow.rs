@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use xforge_cli::commands::abi_diff;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-abi-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+fn write_manifest(dir: &Path, name: &str, version: &str, symbols: &[&str]) -> PathBuf {
+    let path = dir.join(name);
+    let json = format!(
+        r#"{{
+  "schemaVersion": "1.0.0",
+  "package": {{ "name": "abi-demo", "version": "{}" }},
+  "build": {{ "id": "b1-abi", "identity": {{ "host": "h", "toolchain": "stable" }} }},
+  "artifacts": {{
+    "naming": {{
+      "template": "{{package.name}}-{{build.id}}-{{platform}}",
+      "delimiter": "-",
+      "includePlatform": true,
+      "includeBinding": false
+    }}
+  }},
+  "bindings": {{ "catalog": [] }},
+  "platforms": {{
+    "default": "aarch64-apple-darwin",
+    "targets": [{{
+      "name": "aarch64-apple-darwin",
+      "buildId": "b1-abi",
+      "exportedSymbols": {}
+    }}]
+  }}
+}}"#,
+        version,
+        serde_json::to_string(symbols).expect("symbols")
+    );
+    fs::write(&path, json).expect("write manifest");
+    path
+}
+
+#[test]
+fn removals_need_a_major_bump() {
+    let dir = temp_dir("removed");
+    let old = write_manifest(&dir, "old.json", "1.2.0", &["abi_add", "abi_sub"]);
+    let minor = write_manifest(&dir, "minor.json", "1.3.0", &["abi_add"]);
+    let major = write_manifest(&dir, "major.json", "2.0.0", &["abi_add"]);
+
+    let error = abi_diff::run(abi_diff::AbiDiffArgs {
+        old_manifest: old.clone(),
+        new_manifest: minor,
+    })
+    .expect_err("minor bump must not remove symbols");
+    assert!(error.contains("abi_sub (aarch64-apple-darwin)"), "{}", error);
+
+    let diff = abi_diff::run(abi_diff::AbiDiffArgs {
+        old_manifest: old,
+        new_manifest: major,
+    })
+    .expect("major bump may remove symbols");
+    assert_eq!(diff.platforms[0].removed, vec!["abi_sub".to_string()]);
+}
+
+#[test]
+fn additions_are_reported_without_failing() {
+    let dir = temp_dir("added");
+    let old = write_manifest(&dir, "old.json", "0.4.0", &["abi_add"]);
+    let new = write_manifest(&dir, "new.json", "0.4.1", &["abi_add", "abi_mul"]);
+
+    let diff = abi_diff::run(abi_diff::AbiDiffArgs {
+        old_manifest: old,
+        new_manifest: new,
+    })
+    .expect("additions are compatible");
+    assert_eq!(diff.platforms[0].added, vec!["abi_mul".to_string()]);
+    assert!(diff.platforms[0].removed.is_empty());
+}
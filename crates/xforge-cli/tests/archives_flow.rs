@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use xforge_cli::commands::{build, bundle};
+use xforge_core::manifest::deserialize_manifest;
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+const XZ_MAGIC: [u8; 6] = [0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-archives-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+fn host_target_triple() -> String {
+    let output = std::process::Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .expect("rustc -vV");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("utf8");
+    for line in stdout.lines() {
+        if let Some(triple) = line.strip_prefix("host: ") {
+            return triple.trim().to_string();
+        }
+    }
+    panic!("missing host target");
+}
+
+fn write_sample_crate(dir: &Path, target: &str) {
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(
+        dir.join("src").join("lib.rs"),
+        "#[no_mangle]\npub extern \"C\" fn demo() -> u32 { 42 }\n",
+    )
+    .expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"archives-demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n",
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        "version = 3\n\n[[package]]\nname = \"archives-demo\"\nversion = \"0.1.0\"\n",
+    )
+    .expect("write Cargo.lock");
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        format!(
+            "[toolchain]\nchannel = \"stable\"\ntargets = [\"{}\"]\ncomponents = [\"rustfmt\"]\n",
+            target
+        ),
+    )
+    .expect("write rust-toolchain.toml");
+}
+
+fn bundle_into(dir: &Path, output: &str) -> bundle::BundleOutcome {
+    bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.to_path_buf(),
+        target: None,
+        output_dir: dir.join(output),
+        profile: Some("release".to_string()),
+    })
+    .expect("bundle")
+}
+
+#[test]
+fn configured_archive_kinds_are_compressed_deterministically() {
+    let dir = temp_dir("kinds");
+    let target = host_target_triple();
+    write_sample_crate(&dir, &target);
+    fs::write(
+        dir.join("xforge.yaml"),
+        "build:\n  archives:\n    default: tar.zst\n",
+    )
+    .expect("write xforge.yaml");
+
+    build::run(build::BuildArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        profile: "release".to_string(),
+        variant: None,
+        executor: build::BuildExecutorKind::Cargo,
+        cross_image: None,
+        no_cache: true,
+        plan: None,
+        no_provision: true,
+        offline: false,
+        verify_reproducible: None,
+        observer: None,
+    })
+    .expect("build");
+
+    let first = bundle_into(&dir, "dist-a");
+    let second = bundle_into(&dir, "dist-b");
+    let archive = &first.archive_paths[0];
+    assert!(archive.to_string_lossy().ends_with(".tar.zst"));
+    let bytes = fs::read(archive).expect("read archive");
+    assert_eq!(bytes[..4], ZSTD_MAGIC);
+    assert_eq!(
+        bytes,
+        fs::read(&second.archive_paths[0]).expect("read second archive")
+    );
+    let manifest =
+        deserialize_manifest(&fs::read_to_string(&first.manifest_path).expect("read manifest"))
+            .expect("parse manifest");
+    assert!(manifest.platforms.targets[0].artifacts[0].ends_with(".tar.zst"));
+
+    fs::write(
+        dir.join("xforge.yaml"),
+        format!(
+            "build:\n  archives:\n    default: tar.zst\n    {}: tar.xz\n",
+            target
+        ),
+    )
+    .expect("write xforge.yaml");
+    let xz = bundle_into(&dir, "dist-xz");
+    let archive = &xz.archive_paths[0];
+    assert!(archive.to_string_lossy().ends_with(".tar.xz"));
+    assert_eq!(fs::read(archive).expect("read archive")[..6], XZ_MAGIC);
+
+    fs::remove_dir_all(&dir).ok();
+}
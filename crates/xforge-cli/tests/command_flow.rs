@@ -0,0 +1,120 @@
+#![cfg(target_os = "linux")]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use xforge_cli::commands::{build, bundle};
+use xforge_core::manifest::deserialize_manifest;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-command-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+fn host_target_triple() -> String {
+    let output = std::process::Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .expect("rustc -vV");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("utf8");
+    for line in stdout.lines() {
+        if let Some(triple) = line.strip_prefix("host: ") {
+            return triple.trim().to_string();
+        }
+    }
+    panic!("missing host target");
+}
+
+/// A crate whose build goes through `xbuild.sh`, a wrapper that builds into a
+/// private directory and copies the library to `out/<target>/`.
+fn write_sample_crate(dir: &Path, name: &str, target: &str) {
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(
+        dir.join("src").join("lib.rs"),
+        "#[no_mangle]\npub extern \"C\" fn demo() -> u32 { 42 }\n",
+    )
+    .expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n",
+            name
+        ),
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        format!("version = 3\n\n[[package]]\nname = \"{}\"\nversion = \"0.1.0\"\n", name),
+    )
+    .expect("write Cargo.lock");
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        format!(
+            "[toolchain]\nchannel = \"stable\"\ntargets = [\"{}\"]\ncomponents = [\"rustfmt\"]\n",
+            target
+        ),
+    )
+    .expect("write rust-toolchain.toml");
+    let script = dir.join("xbuild.sh");
+    fs::write(
+        &script,
+        "#!/bin/sh\nset -e\necho \"$@\" > xbuild.log\n\
+         cargo build --release --target \"$1\" --target-dir \"$PWD/scratch\"\n\
+         mkdir -p \"out/$1\"\ncp scratch/\"$1\"/release/lib*.so \"out/$1/\"\n",
+    )
+    .expect("write xbuild.sh");
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).expect("chmod xbuild.sh");
+    fs::write(
+        dir.join("xforge.yaml"),
+        "build:\n  command:\n    run: ./xbuild.sh {target} {profile}\n    outputs: \"out/{target}/*.so\"\n",
+    )
+    .expect("write xforge.yaml");
+}
+
+#[test]
+fn command_executor_output_is_bundled() {
+    let dir = temp_dir("bundle");
+    let target = host_target_triple();
+    write_sample_crate(&dir, "command-demo", &target);
+
+    let built = build::run(build::BuildArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        profile: "release".to_string(),
+        variant: None,
+        executor: build::BuildExecutorKind::Command,
+        cross_image: None,
+        no_cache: true,
+        plan: None,
+        no_provision: true,
+        offline: false,
+        verify_reproducible: None,
+        observer: None,
+    })
+    .expect("build");
+    let log = fs::read_to_string(dir.join("xbuild.log")).expect("wrapper log");
+    assert_eq!(log.trim(), format!("{} release", target));
+    assert!(built.library_path.is_file());
+
+    let bundled = bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        output_dir: dir.join("dist"),
+        profile: Some("release".to_string()),
+    })
+    .expect("bundle");
+    let manifest =
+        deserialize_manifest(&fs::read_to_string(bundled.manifest_path).expect("read manifest"))
+            .expect("parse manifest");
+    assert!(manifest.platforms.targets[0]
+        .exported_symbols
+        .contains(&"demo".to_string()));
+}
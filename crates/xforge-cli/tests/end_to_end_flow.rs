@@ -77,8 +77,15 @@ fn end_to_end_build_bundle_publish_local() {
         manifest_dir: dir.clone(),
         target: None,
         profile: "release".to_string(),
+        variant: None,
         executor: build::BuildExecutorKind::Cargo,
         cross_image: None,
+        no_cache: true,
+        plan: None,
+        no_provision: true,
+        offline: false,
+        verify_reproducible: None,
+        observer: None,
     })
     .expect("build");
     assert!(build_outcome.build_id.starts_with("b1-"));
@@ -88,7 +95,7 @@ fn end_to_end_build_bundle_publish_local() {
         manifest_dir: dir.clone(),
         target: None,
         output_dir: dist_dir.clone(),
-        profile: "release".to_string(),
+        profile: Some("release".to_string()),
     })
     .expect("bundle");
     assert!(bundle_outcome.manifest_path.exists());
@@ -0,0 +1,157 @@
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use xforge_build::{BuildEvent, BuildObserver};
+use xforge_cli::commands::build;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-events-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+fn host_target_triple() -> String {
+    let output = std::process::Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .expect("rustc -vV");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("utf8");
+    for line in stdout.lines() {
+        if let Some(triple) = line.strip_prefix("host: ") {
+            return triple.trim().to_string();
+        }
+    }
+    panic!("missing host target");
+}
+
+fn write_sample_crate(dir: &Path, target: &str) {
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(
+        dir.join("src").join("lib.rs"),
+        "#[no_mangle]\npub extern \"C\" fn demo() -> u32 {\n    let unused = 1;\n    42\n}\n",
+    )
+    .expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"events-demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n",
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        "version = 3\n\n[[package]]\nname = \"events-demo\"\nversion = \"0.1.0\"\n",
+    )
+    .expect("write Cargo.lock");
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        format!(
+            "[toolchain]\nchannel = \"stable\"\ntargets = [\"{}\"]\ncomponents = [\"rustfmt\"]\n",
+            target
+        ),
+    )
+    .expect("write rust-toolchain.toml");
+    fs::write(dir.join("xforge.yaml"), "").expect("write xforge.yaml");
+}
+
+#[derive(Clone, Default)]
+struct Recorder {
+    events: Rc<RefCell<Vec<BuildEvent>>>,
+}
+
+impl BuildObserver for Recorder {
+    fn on_event(&self, event: &BuildEvent) {
+        self.events.borrow_mut().push(event.clone());
+    }
+}
+
+#[test]
+fn build_reports_progress_and_diagnostics_to_the_observer() {
+    let dir = temp_dir("observer");
+    let target = host_target_triple();
+    write_sample_crate(&dir, &target);
+    let recorder = Recorder::default();
+
+    build::run(build::BuildArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        profile: "release".to_string(),
+        variant: None,
+        executor: build::BuildExecutorKind::Cargo,
+        cross_image: None,
+        no_cache: true,
+        plan: None,
+        no_provision: true,
+        offline: false,
+        verify_reproducible: None,
+        observer: Some(Box::new(recorder.clone())),
+    })
+    .expect("build");
+
+    let events = recorder.events.borrow();
+    match events.first() {
+        Some(BuildEvent::PlanResolved { targets, .. }) => assert_eq!(targets, &vec![target.clone()]),
+        other => panic!("expected plan-resolved first, got {:?}", other),
+    }
+    assert!(matches!(
+        events.get(1),
+        Some(BuildEvent::TargetStarted { rust_target_triple, .. }) if rust_target_triple == &target
+    ));
+    assert!(events.iter().any(|event| matches!(
+        event,
+        BuildEvent::Diagnostic { level, message, .. }
+            if level == "warning" && message.contains("unused variable")
+    )));
+    assert!(events.iter().any(|event| matches!(
+        event,
+        BuildEvent::TargetFinished { success: true, cached: false, .. }
+    )));
+    assert!(events.iter().any(|event| matches!(
+        event,
+        BuildEvent::ArtifactLocated { path, .. } if Path::new(path).is_file()
+    )));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn json_events_keep_stdout_to_one_json_object_per_line() {
+    let dir = temp_dir("json");
+    let target = host_target_triple();
+    write_sample_crate(&dir, &target);
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_xforge-cli"))
+        .arg("build")
+        .arg("--manifest-dir")
+        .arg(&dir)
+        .args(["--executor", "cargo", "--no-cache", "--no-provision"])
+        .args(["--events", "json"])
+        .output()
+        .expect("run xforge build");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8(output.stdout).expect("utf8");
+    let mut events = Vec::new();
+    for line in stdout.lines() {
+        let event: serde_json::Value = serde_json::from_str(line)
+            .unwrap_or_else(|err| panic!("stdout line {:?} is not JSON: {}", line, err));
+        events.push(event["event"].as_str().expect("event name").to_string());
+    }
+    assert_eq!(events.first().map(String::as_str), Some("plan-resolved"));
+    assert!(events.iter().any(|event| event == "target-finished"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("profile=release\n"));
+    assert!(stderr.contains("build_id="));
+
+    fs::remove_dir_all(&dir).ok();
+}
@@ -125,6 +125,7 @@ fn full_flow_executes_core_plan_and_build_executor() {
         cargo_args: vec![],
         rustflags: vec![],
         env: vec![],
+        env_allowlist: None,
     };
 
     let plan = BuildPlan {
@@ -140,6 +141,7 @@ fn full_flow_executes_core_plan_and_build_executor() {
             cargo_features: vec![],
             cross_image: None,
             env: vec![],
+            timeout_secs: None,
             artifact: BuiltArtifact {
                 platform,
                 build_id: build_id.clone(),
@@ -153,6 +155,7 @@ fn full_flow_executes_core_plan_and_build_executor() {
                     .join(format!("lib{}.rlib", crate_name))
                     .to_string_lossy()
                     .into_owned(),
+                libraries: vec![],
                 include_dir: None,
                 manifest_path: dir
                     .join("xforge-manifest.json")
@@ -103,8 +103,15 @@ fn end_to_end_publish_github_if_configured() {
         manifest_dir: dir.clone(),
         target: None,
         profile: "release".to_string(),
+        variant: None,
         executor: build::BuildExecutorKind::Cargo,
         cross_image: None,
+        no_cache: true,
+        plan: None,
+        no_provision: true,
+        offline: false,
+        verify_reproducible: None,
+        observer: None,
     })
     .expect("build");
 
@@ -113,7 +120,7 @@ fn end_to_end_publish_github_if_configured() {
         manifest_dir: dir.clone(),
         target: None,
         output_dir: dist_dir.clone(),
-        profile: "release".to_string(),
+        profile: Some("release".to_string()),
     })
     .expect("bundle");
 
@@ -0,0 +1,169 @@
+#![cfg(target_os = "linux")]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use xforge_cli::commands::{build, bundle};
+use xforge_core::manifest::deserialize_manifest;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-headers-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+fn host_target_triple() -> String {
+    let output = std::process::Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .expect("rustc -vV");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("utf8");
+    for line in stdout.lines() {
+        if let Some(triple) = line.strip_prefix("host: ") {
+            return triple.trim().to_string();
+        }
+    }
+    panic!("missing host target");
+}
+
+/// Stands in for cbindgen: writes a fixed header to the `--output` path.
+fn write_stub_cbindgen(dir: &Path) -> PathBuf {
+    let path = dir.join("cbindgen-stub");
+    fs::write(
+        &path,
+        "#!/bin/sh\n\
+         while [ $# -gt 0 ]; do\n\
+         if [ \"$1\" = --output ]; then out=\"$2\"; fi\n\
+         shift\n\
+         done\n\
+         printf '#pragma once\\n#include <stdint.h>\\nuint32_t demo(void);\\n' > \"$out\"\n",
+    )
+    .expect("write stub");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("chmod stub");
+    path
+}
+
+fn write_sample_crate(dir: &Path, target: &str, cbindgen: &Path) {
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(
+        dir.join("src").join("lib.rs"),
+        "#[no_mangle]\npub extern \"C\" fn demo() -> u32 { 42 }\n",
+    )
+    .expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"headers-demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n",
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        "version = 3\n\n[[package]]\nname = \"headers-demo\"\nversion = \"0.1.0\"\n",
+    )
+    .expect("write Cargo.lock");
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        format!(
+            "[toolchain]\nchannel = \"stable\"\ntargets = [\"{}\"]\ncomponents = [\"rustfmt\"]\n",
+            target
+        ),
+    )
+    .expect("write rust-toolchain.toml");
+    fs::write(
+        dir.join("xforge.yaml"),
+        format!("build:\n  headers:\n    cbindgen: {}\n", cbindgen.display()),
+    )
+    .expect("write xforge.yaml");
+}
+
+#[test]
+fn bundle_packs_generated_headers_under_include() {
+    let dir = temp_dir("include");
+    let target = host_target_triple();
+    let cbindgen = write_stub_cbindgen(&dir);
+    write_sample_crate(&dir, &target, &cbindgen);
+
+    let outcome = build::run(build::BuildArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        profile: "release".to_string(),
+        variant: None,
+        executor: build::BuildExecutorKind::Cargo,
+        cross_image: None,
+        no_cache: true,
+        plan: None,
+        no_provision: true,
+        offline: false,
+        verify_reproducible: None,
+        observer: None,
+    })
+    .expect("build");
+    let header = &outcome.header_outcomes[0].header;
+    assert_eq!(
+        header,
+        &dir.join("target")
+            .join(&target)
+            .join("release")
+            .join("include")
+            .join("headers_demo.h")
+    );
+
+    let bundled = bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        output_dir: dir.join("dist"),
+        profile: Some("release".to_string()),
+    })
+    .expect("bundle");
+
+    let listing = std::process::Command::new("tar")
+        .arg("-tzf")
+        .arg(&bundled.archive_paths[0])
+        .output()
+        .expect("list archive");
+    let listing = String::from_utf8_lossy(&listing.stdout);
+    assert!(listing.lines().any(|line| line == "include/headers_demo.h"));
+
+    let manifest =
+        deserialize_manifest(&fs::read_to_string(&bundled.manifest_path).expect("read manifest"))
+            .expect("parse manifest");
+    let headers = manifest.platforms.targets[0]
+        .headers
+        .as_ref()
+        .expect("headers digest");
+    assert_eq!(headers.path, "include");
+    assert_eq!(headers.files, vec!["headers_demo.h".to_string()]);
+    assert_eq!(headers.sha256.len(), 64);
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn bundle_requires_generated_headers() {
+    let dir = temp_dir("missing");
+    let target = host_target_triple();
+    let cbindgen = write_stub_cbindgen(&dir);
+    write_sample_crate(&dir, &target, &cbindgen);
+    let release_dir = dir.join("target").join(&target).join("release");
+    fs::create_dir_all(&release_dir).expect("create release dir");
+    fs::write(release_dir.join("libheaders_demo.so"), "not built").expect("write library");
+
+    let err = match bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        output_dir: dir.join("dist"),
+        profile: Some("release".to_string()),
+    }) {
+        Ok(_) => panic!("bundle without headers should fail"),
+        Err(err) => err,
+    };
+    assert!(err.contains("generated headers not found"), "{}", err);
+
+    fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,181 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use xforge_cli::commands::{build, bundle};
+use xforge_core::artifact::LibraryKind;
+use xforge_core::binary::BinaryFileType;
+use xforge_core::manifest::deserialize_manifest;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-outputs-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+fn host_target_triple() -> String {
+    let output = std::process::Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .expect("rustc -vV");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("utf8");
+    for line in stdout.lines() {
+        if let Some(triple) = line.strip_prefix("host: ") {
+            return triple.trim().to_string();
+        }
+    }
+    panic!("missing host target");
+}
+
+fn write_sample_crate(dir: &Path, name: &str, target: &str, xforge_yaml: &str) {
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(
+        dir.join("src").join("lib.rs"),
+        "#[no_mangle]\npub extern \"C\" fn demo() -> u32 { 42 }\n",
+    )
+    .expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\ncrate-type = [\"cdylib\", \"staticlib\"]\n",
+            name
+        ),
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        format!("version = 3\n\n[[package]]\nname = \"{}\"\nversion = \"0.1.0\"\n", name),
+    )
+    .expect("write Cargo.lock");
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        format!(
+            "[toolchain]\nchannel = \"stable\"\ntargets = [\"{}\"]\ncomponents = [\"rustfmt\"]\n",
+            target
+        ),
+    )
+    .expect("write rust-toolchain.toml");
+    fs::write(dir.join("xforge.yaml"), xforge_yaml).expect("write xforge.yaml");
+}
+
+fn build_and_bundle(dir: &Path) -> xforge_core::manifest::Manifest {
+    build::run(build::BuildArgs {
+        manifest_dir: dir.to_path_buf(),
+        target: None,
+        profile: "release".to_string(),
+        variant: None,
+        executor: build::BuildExecutorKind::Cargo,
+        cross_image: None,
+        no_cache: true,
+        plan: None,
+        no_provision: true,
+        offline: false,
+        verify_reproducible: None,
+        observer: None,
+    })
+    .expect("build");
+    let outcome = bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.to_path_buf(),
+        target: None,
+        output_dir: dir.join("dist"),
+        profile: Some("release".to_string()),
+    })
+    .expect("bundle");
+    let contents = fs::read_to_string(outcome.manifest_path).expect("read manifest");
+    deserialize_manifest(&contents).expect("parse manifest")
+}
+
+#[test]
+fn bundle_lists_static_and_dynamic_outputs() {
+    let dir = temp_dir("all");
+    let target = host_target_triple();
+    write_sample_crate(&dir, "outputs-demo", &target, "build: {}\n");
+
+    let manifest = build_and_bundle(&dir);
+    let libraries = &manifest.platforms.targets[0].libraries;
+    let kinds: Vec<LibraryKind> = libraries.iter().map(|entry| entry.kind).collect();
+    assert_eq!(kinds[0], LibraryKind::Dynamic);
+    assert!(kinds.contains(&LibraryKind::Static));
+    assert!(libraries.iter().all(|entry| entry.path.starts_with("lib/")));
+}
+
+#[test]
+fn configured_outputs_narrow_the_bundle() {
+    let dir = temp_dir("static");
+    let target = host_target_triple();
+    write_sample_crate(
+        &dir,
+        "outputs-static",
+        &target,
+        "build:\n  outputs:\n    default: [static]\n",
+    );
+
+    let manifest = build_and_bundle(&dir);
+    let libraries = &manifest.platforms.targets[0].libraries;
+    assert_eq!(libraries.len(), 1);
+    assert_eq!(libraries[0].kind, LibraryKind::Static);
+}
+
+#[test]
+fn bundle_records_binary_properties_and_exports() {
+    let dir = temp_dir("binary");
+    let target = host_target_triple();
+    write_sample_crate(&dir, "outputs-binary", &target, "build: {}\n");
+
+    let manifest = build_and_bundle(&dir);
+    let binary = manifest.platforms.targets[0]
+        .binary
+        .as_ref()
+        .expect("binary properties");
+    assert_eq!(binary.slices[0].file_type, BinaryFileType::SharedLibrary);
+    assert!(manifest.platforms.targets[0]
+        .exported_symbols
+        .contains(&"demo".to_string()));
+}
+
+/// A 64-bit ELF shared object header for aarch64 with no sections.
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+fn aarch64_shared_object() -> Vec<u8> {
+    let mut bytes = vec![0u8; 64];
+    bytes[..4].copy_from_slice(b"\x7fELF");
+    bytes[4] = 2;
+    bytes[5] = 1;
+    bytes[6] = 1;
+    bytes[0x10..0x12].copy_from_slice(&3u16.to_le_bytes());
+    bytes[0x12..0x14].copy_from_slice(&183u16.to_le_bytes());
+    bytes
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn bundle_rejects_library_for_another_architecture() {
+    let dir = temp_dir("mismatch");
+    let target = host_target_triple();
+    write_sample_crate(&dir, "outputs-mismatch", &target, "build: {}\n");
+    build_and_bundle(&dir);
+    let library = dir
+        .join("target")
+        .join(&target)
+        .join("release")
+        .join("liboutputs_mismatch.so");
+    fs::write(&library, aarch64_shared_object()).expect("replace library");
+
+    let error = bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        output_dir: dir.join("dist"),
+        profile: Some("release".to_string()),
+    })
+    .err()
+    .expect("bundle should fail");
+    assert!(
+        error.contains("architecture is aarch64, expected x86_64"),
+        "{}",
+        error
+    );
+}
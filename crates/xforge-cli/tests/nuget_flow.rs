@@ -0,0 +1,153 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use xforge_cli::commands::bundle;
+use xforge_core::manifest::deserialize_manifest;
+
+const EM_AARCH64: u16 = 183;
+const EM_X86_64: u16 = 62;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-nuget-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+/// A 64-bit ELF shared object header for `machine` with no sections.
+fn linux_shared_object(machine: u16) -> Vec<u8> {
+    let mut bytes = vec![0u8; 64];
+    bytes[..4].copy_from_slice(b"\x7fELF");
+    bytes[4] = 2;
+    bytes[5] = 1;
+    bytes[6] = 1;
+    bytes[0x10..0x12].copy_from_slice(&3u16.to_le_bytes());
+    bytes[0x12..0x14].copy_from_slice(&machine.to_le_bytes());
+    bytes
+}
+
+/// A crate with prebuilt glibc x86_64 and musl aarch64 libraries.
+fn write_sample_crate(dir: &Path) {
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(dir.join("src").join("lib.rs"), "").expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"nuget-demo\"\nversion = \"1.2.0\"\nedition = \"2021\"\nlicense = \"MIT/Apache-2.0\"\nauthors = [\"Demo \\\"Core\\\" Team's <team@example.com>\"]\nrepository = \"https://github.com/owner/nuget-demo\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n",
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        "version = 3\n\n[[package]]\nname = \"nuget-demo\"\nversion = \"1.2.0\"\n",
+    )
+    .expect("write Cargo.lock");
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        "[toolchain]\nchannel = \"stable\"\ntargets = [\"x86_64-unknown-linux-gnu\", \"aarch64-unknown-linux-musl\"]\ncomponents = [\"rustfmt\"]\n",
+    )
+    .expect("write rust-toolchain.toml");
+    fs::write(
+        dir.join("xforge.yaml"),
+        "build:\n  nuget:\n    id: Demo.Native\n",
+    )
+    .expect("write xforge.yaml");
+    for (triple, machine) in [
+        ("x86_64-unknown-linux-gnu", EM_X86_64),
+        ("aarch64-unknown-linux-musl", EM_AARCH64),
+    ] {
+        let release_dir = dir.join("target").join(triple).join("release");
+        fs::create_dir_all(&release_dir).expect("create release dir");
+        fs::write(
+            release_dir.join("libnuget_demo.so"),
+            linux_shared_object(machine),
+        )
+        .expect("write library");
+    }
+}
+
+fn bundle_into(dir: &Path, output: &str) -> bundle::BundleOutcome {
+    bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.to_path_buf(),
+        target: None,
+        output_dir: dir.join(output),
+        profile: Some("release".to_string()),
+    })
+    .expect("bundle")
+}
+
+fn read_entry(zip: &mut zip::ZipArchive<fs::File>, name: &str) -> String {
+    let mut contents = String::new();
+    zip.by_name(name)
+        .unwrap_or_else(|_| panic!("missing {}", name))
+        .read_to_string(&mut contents)
+        .expect("read entry");
+    contents
+}
+
+#[test]
+fn bundle_packs_every_rid_into_one_nupkg() {
+    let dir = temp_dir("linux");
+    write_sample_crate(&dir);
+
+    let first = bundle_into(&dir, "dist-a");
+    let second = bundle_into(&dir, "dist-b");
+    let nupkg = first.nupkg_path.expect("nupkg path");
+    assert_eq!(
+        nupkg.file_name().expect("file name").to_string_lossy(),
+        "Demo.Native.1.2.0.nupkg"
+    );
+    assert_eq!(
+        fs::read(&nupkg).expect("read nupkg"),
+        fs::read(second.nupkg_path.expect("nupkg path")).expect("read nupkg")
+    );
+
+    let mut package =
+        zip::ZipArchive::new(fs::File::open(&nupkg).expect("open nupkg")).expect("zip");
+    let entries: Vec<String> = package.file_names().map(str::to_string).collect();
+    assert_eq!(
+        entries,
+        vec![
+            "Demo.Native.nuspec",
+            "runtimes/linux-musl-arm64/native/libnuget_demo.so",
+            "runtimes/linux-x64/native/libnuget_demo.so",
+            "_rels/.rels",
+            "[Content_Types].xml",
+        ]
+    );
+    let nuspec = read_entry(&mut package, "Demo.Native.nuspec");
+    assert!(nuspec.contains("    <id>Demo.Native</id>\n    <version>1.2.0</version>\n"));
+    assert!(nuspec.contains(
+        "    <authors>Demo &quot;Core&quot; Team&apos;s &lt;team@example.com&gt;</authors>\n"
+    ));
+    assert!(nuspec.contains("    <description>Native libraries of Demo.Native.</description>\n"));
+    assert!(nuspec.contains("    <license type=\"expression\">MIT OR Apache-2.0</license>\n"));
+    assert!(nuspec.contains("    <projectUrl>https://github.com/owner/nuget-demo</projectUrl>\n"));
+    let rels = read_entry(&mut package, "_rels/.rels");
+    assert!(rels.contains("Target=\"/Demo.Native.nuspec\""));
+    let content_types = read_entry(&mut package, "[Content_Types].xml");
+    assert!(content_types
+        .contains("<Default Extension=\"nuspec\" ContentType=\"application/octet\" />"));
+    assert!(
+        content_types.contains("<Default Extension=\"so\" ContentType=\"application/octet\" />")
+    );
+    assert!(content_types.contains(
+        "<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\" />"
+    ));
+
+    let manifest =
+        deserialize_manifest(&fs::read_to_string(&first.manifest_path).expect("read manifest"))
+            .expect("parse manifest");
+    let dotnet = &manifest.bindings.catalog[0];
+    assert_eq!(dotnet.name, "dotnet");
+    assert_eq!(dotnet.artifacts, vec!["Demo.Native.1.2.0.nupkg"]);
+    assert_eq!(
+        dotnet.platforms,
+        vec!["x86_64-unknown-linux-gnu", "aarch64-unknown-linux-musl"]
+    );
+
+    fs::remove_dir_all(&dir).ok();
+}
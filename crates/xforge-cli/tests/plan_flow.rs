@@ -0,0 +1,141 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use xforge_cli::commands::{build, plan};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-plan-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+fn write_sample_crate(dir: &Path, name: &str, targets: &[&str]) {
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(dir.join("src").join("lib.rs"), "pub fn demo() -> u32 { 42 }")
+        .expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", name),
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        format!("[[package]]\nname = \"{}\"\nversion = \"0.1.0\"\n", name),
+    )
+    .expect("write Cargo.lock");
+    let targets: Vec<String> = targets.iter().map(|target| format!("\"{}\"", target)).collect();
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        format!(
+            "[toolchain]\nchannel = \"stable\"\ntargets = [{}]\ncomponents = [\"rustfmt\"]\n",
+            targets.join(", ")
+        ),
+    )
+    .expect("write rust-toolchain.toml");
+}
+
+fn host_target_triple() -> String {
+    let output = std::process::Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .expect("rustc -vV");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("utf8");
+    for line in stdout.lines() {
+        if let Some(triple) = line.strip_prefix("host: ") {
+            return triple.trim().to_string();
+        }
+    }
+    panic!("missing host target");
+}
+
+fn plan_args(dir: &Path, format: plan::PlanFormat) -> plan::PlanArgs {
+    plan::PlanArgs {
+        manifest_dir: dir.to_path_buf(),
+        target: None,
+        profile: "release".to_string(),
+        variant: None,
+        cross_image: None,
+        format,
+    }
+}
+
+#[test]
+fn plan_json_slice_builds_in_another_checkout() {
+    let host = host_target_triple();
+    let planned = temp_dir("source");
+    let checkout = temp_dir("checkout");
+    let targets = [host.as_str(), "aarch64-linux-android"];
+    write_sample_crate(&planned, "plan-demo", &targets);
+    write_sample_crate(&checkout, "plan-demo", &targets);
+
+    let outcome = plan::run(plan_args(&planned, plan::PlanFormat::Json)).expect("plan");
+    assert_eq!(outcome.plan.targets.len(), 2);
+    let json = outcome.rendered.expect("json plan");
+    assert!(!json.contains(planned.to_string_lossy().as_ref()));
+    let plan_path = checkout.join("plan.json");
+    fs::write(&plan_path, json).expect("write plan");
+
+    let build_outcome = build::run(build::BuildArgs {
+        manifest_dir: checkout.clone(),
+        target: Some(host.clone()),
+        profile: "release".to_string(),
+        variant: None,
+        executor: build::BuildExecutorKind::Cargo,
+        cross_image: None,
+        no_cache: true,
+        plan: Some(plan_path.clone()),
+        no_provision: true,
+        offline: false,
+        verify_reproducible: None,
+        observer: None,
+    })
+    .expect("build plan slice");
+    assert_eq!(build_outcome.build_id, outcome.plan.build_id);
+    assert!(build_outcome.library_path.starts_with(&checkout));
+    assert!(checkout.join("target").join(&host).join("release").is_dir());
+
+    let missing = build::run(build::BuildArgs {
+        manifest_dir: checkout.clone(),
+        target: Some("x86_64-pc-windows-msvc".to_string()),
+        profile: "release".to_string(),
+        variant: None,
+        executor: build::BuildExecutorKind::Cargo,
+        cross_image: None,
+        no_cache: true,
+        plan: Some(plan_path),
+        no_provision: true,
+        offline: false,
+        verify_reproducible: None,
+        observer: None,
+    });
+    assert!(missing.is_err());
+}
+
+#[test]
+fn plan_github_matrix_groups_targets() {
+    let dir = temp_dir("matrix");
+    write_sample_crate(
+        &dir,
+        "plan-matrix",
+        &[
+            "x86_64-unknown-linux-gnu",
+            "aarch64-apple-darwin",
+            "aarch64-apple-ios",
+        ],
+    );
+    let outcome = plan::run(plan_args(&dir, plan::PlanFormat::GithubMatrix)).expect("matrix");
+    let matrix: serde_json::Value =
+        serde_json::from_str(&outcome.rendered.expect("matrix json")).expect("parse matrix");
+    let include = matrix["include"].as_array().expect("include");
+    assert_eq!(include.len(), 2);
+    assert_eq!(include[0]["os"], "ubuntu-latest");
+    assert_eq!(include[1]["os"], "macos-latest");
+    assert_eq!(include[1]["executor"], "cargo");
+    assert_eq!(include[1]["targets"].as_array().expect("targets").len(), 2);
+}
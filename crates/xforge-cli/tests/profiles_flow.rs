@@ -0,0 +1,268 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use xforge_cli::commands::{build, bundle};
+use xforge_core::manifest::deserialize_manifest;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-profiles-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+fn host_target_triple() -> String {
+    let output = std::process::Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .expect("rustc -vV");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("utf8");
+    for line in stdout.lines() {
+        if let Some(triple) = line.strip_prefix("host: ") {
+            return triple.trim().to_string();
+        }
+    }
+    panic!("missing host target");
+}
+
+fn write_sample_crate(dir: &Path, target: &str) {
+    write_crate(
+        dir,
+        target,
+        "[profile.release-with-asserts]\ninherits = \"release\"\ndebug-assertions = true\n",
+        "build:\n  profiles: [release, release-with-asserts]\n",
+    );
+}
+
+fn write_crate(dir: &Path, target: &str, cargo_extra: &str, xforge_yaml: &str) {
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(
+        dir.join("src").join("lib.rs"),
+        "#[no_mangle]\npub extern \"C\" fn demo() -> u32 {\n    debug_assert!(true);\n    42\n}\n\n#[cfg(feature = \"gpu\")]\n#[no_mangle]\npub extern \"C\" fn demo_gpu() -> u32 {\n    7\n}\n",
+    )
+    .expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"profiles-demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n\n{}",
+            cargo_extra
+        ),
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        "version = 3\n\n[[package]]\nname = \"profiles-demo\"\nversion = \"0.1.0\"\n",
+    )
+    .expect("write Cargo.lock");
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        format!(
+            "[toolchain]\nchannel = \"stable\"\ntargets = [\"{}\"]\ncomponents = [\"rustfmt\"]\n",
+            target
+        ),
+    )
+    .expect("write rust-toolchain.toml");
+    fs::write(dir.join("xforge.yaml"), xforge_yaml).expect("write xforge.yaml");
+}
+
+#[test]
+fn bundle_records_each_profile_as_a_variant() {
+    let dir = temp_dir("variants");
+    let target = host_target_triple();
+    write_sample_crate(&dir, &target);
+
+    for profile in ["release", "release-with-asserts"] {
+        let outcome = build::run(build::BuildArgs {
+            manifest_dir: dir.clone(),
+            target: None,
+            profile: profile.to_string(),
+            variant: None,
+            executor: build::BuildExecutorKind::Cargo,
+            cross_image: None,
+            no_cache: true,
+            plan: None,
+            no_provision: true,
+            offline: false,
+            verify_reproducible: None,
+            observer: None,
+        })
+        .expect("build");
+        assert!(outcome.report_path.is_file());
+    }
+    assert!(dir
+        .join("target")
+        .join("xforge-build-report-release-with-asserts.json")
+        .is_file());
+
+    let outcome = bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        output_dir: dir.join("dist"),
+        profile: None,
+    })
+    .expect("bundle");
+    assert_eq!(outcome.archive_paths.len(), 2);
+    assert!(outcome.archive_paths.iter().all(|path| path.is_file()));
+
+    let contents = fs::read_to_string(&outcome.manifest_path).expect("read manifest");
+    let manifest = deserialize_manifest(&contents).expect("parse manifest");
+    assert_eq!(manifest.build.identity.profile.as_deref(), Some("release"));
+    let platform = &manifest.platforms.targets[0];
+    assert_eq!(platform.profile.as_deref(), Some("release"));
+    assert_eq!(platform.variants.len(), 1);
+    let variant = platform
+        .variant("release-with-asserts")
+        .expect("release-with-asserts variant");
+    assert_eq!(variant.profile.as_deref(), Some("release-with-asserts"));
+    assert!(variant.artifacts[0].contains(&format!("{}-release-with-asserts.", target)));
+    assert_ne!(variant.artifacts, platform.artifacts);
+    assert!(!variant.libraries.is_empty());
+    xforge_core::manifest::validate(&manifest).expect("valid manifest");
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn dev_profile_is_read_from_cargos_debug_dir() {
+    let dir = temp_dir("dev");
+    let target = host_target_triple();
+    write_crate(&dir, &target, "", "build:\n  profiles: [release, dev]\n");
+
+    let mut build_ids = Vec::new();
+    for profile in ["release", "dev"] {
+        let outcome = build::run(build::BuildArgs {
+            manifest_dir: dir.clone(),
+            target: None,
+            profile: profile.to_string(),
+            variant: None,
+            executor: build::BuildExecutorKind::Cargo,
+            cross_image: None,
+            no_cache: true,
+            plan: None,
+            no_provision: true,
+            offline: false,
+            verify_reproducible: None,
+            observer: None,
+        })
+        .expect("build");
+        build_ids.push(outcome.build_id);
+    }
+    assert_ne!(build_ids[0], build_ids[1]);
+    assert!(dir.join("target").join(&target).join("debug").is_dir());
+    assert!(!dir.join("target").join(&target).join("dev").exists());
+
+    let outcome = bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        output_dir: dir.join("dist"),
+        profile: None,
+    })
+    .expect("bundle");
+    assert_eq!(outcome.archive_paths.len(), 2);
+
+    let contents = fs::read_to_string(&outcome.manifest_path).expect("read manifest");
+    let manifest = deserialize_manifest(&contents).expect("parse manifest");
+    let platform = &manifest.platforms.targets[0];
+    let dev = platform.variant("dev").expect("dev variant");
+    assert_eq!(dev.profile.as_deref(), Some("dev"));
+    assert_ne!(dev.build_id, platform.build_id);
+    assert!(!dev.libraries.is_empty());
+    xforge_core::manifest::validate(&manifest).expect("valid manifest");
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn feature_variant_builds_separately_with_its_own_build_id() {
+    let dir = temp_dir("features");
+    let target = host_target_triple();
+    write_crate(
+        &dir,
+        &target,
+        "[features]\ngpu = []\n",
+        "build:\n  variants:\n    gpu:\n      features: [gpu]\n",
+    );
+
+    let mut build_ids = Vec::new();
+    for variant in [None, Some("gpu".to_string())] {
+        let outcome = build::run(build::BuildArgs {
+            manifest_dir: dir.clone(),
+            target: None,
+            profile: "release".to_string(),
+            variant,
+            executor: build::BuildExecutorKind::Cargo,
+            cross_image: None,
+            no_cache: true,
+            plan: None,
+            no_provision: true,
+            offline: false,
+            verify_reproducible: None,
+            observer: None,
+        })
+        .expect("build");
+        build_ids.push(outcome.build_id);
+    }
+    assert_ne!(build_ids[0], build_ids[1]);
+    assert!(dir
+        .join("target")
+        .join("xforge-variants")
+        .join("gpu")
+        .join(&target)
+        .join("release")
+        .is_dir());
+
+    let outcome = bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        output_dir: dir.join("dist"),
+        profile: None,
+    })
+    .expect("bundle");
+    assert_eq!(outcome.archive_paths.len(), 2);
+
+    let contents = fs::read_to_string(&outcome.manifest_path).expect("read manifest");
+    let manifest = deserialize_manifest(&contents).expect("parse manifest");
+    let platform = &manifest.platforms.targets[0];
+    let gpu = platform.variant("gpu").expect("gpu variant");
+    assert_eq!(gpu.features, vec!["gpu".to_string()]);
+    assert_eq!(gpu.profile.as_deref(), Some("release"));
+    assert_ne!(gpu.build_id, platform.build_id);
+    assert!(gpu.artifacts[0].contains(&format!("{}-gpu.", target)));
+    if !platform.exported_symbols.is_empty() {
+        assert!(!platform
+            .exported_symbols
+            .iter()
+            .any(|name| name.contains("demo_gpu")));
+        assert!(gpu
+            .exported_symbols
+            .iter()
+            .any(|name| name.contains("demo_gpu")));
+    }
+    xforge_core::manifest::validate(&manifest).expect("valid manifest");
+
+    let unknown = build::run(build::BuildArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        profile: "release".to_string(),
+        variant: Some("simd".to_string()),
+        executor: build::BuildExecutorKind::Cargo,
+        cross_image: None,
+        no_cache: true,
+        plan: None,
+        no_provision: true,
+        offline: false,
+        verify_reproducible: None,
+        observer: None,
+    });
+    match unknown {
+        Err(message) => assert!(message.contains("unknown build variant 'simd'")),
+        Ok(_) => panic!("unknown variant should fail"),
+    }
+
+    fs::remove_dir_all(&dir).ok();
+}
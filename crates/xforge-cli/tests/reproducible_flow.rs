@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use xforge_cli::commands::build;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-repro-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+fn host_target_triple() -> String {
+    let output = std::process::Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .expect("rustc -vV");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("utf8");
+    for line in stdout.lines() {
+        if let Some(triple) = line.strip_prefix("host: ") {
+            return triple.trim().to_string();
+        }
+    }
+    panic!("missing host target");
+}
+
+fn write_sample_crate(dir: &Path, name: &str, lib_rs: &str) {
+    let target = host_target_triple();
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(dir.join("src").join("lib.rs"), lib_rs).expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n",
+            name
+        ),
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        format!("version = 3\n\n[[package]]\nname = \"{}\"\nversion = \"0.1.0\"\n", name),
+    )
+    .expect("write Cargo.lock");
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        format!(
+            "[toolchain]\nchannel = \"stable\"\ntargets = [\"{}\"]\ncomponents = [\"rustfmt\"]\n",
+            target
+        ),
+    )
+    .expect("write rust-toolchain.toml");
+}
+
+fn build_args(dir: &Path, check: build::ReproducibilityCheck) -> build::BuildArgs {
+    build::BuildArgs {
+        manifest_dir: dir.to_path_buf(),
+        target: None,
+        profile: "release".to_string(),
+        variant: None,
+        executor: build::BuildExecutorKind::Cargo,
+        cross_image: None,
+        no_cache: true,
+        plan: None,
+        no_provision: true,
+        offline: false,
+        verify_reproducible: Some(check),
+        observer: None,
+    }
+}
+
+#[test]
+fn deterministic_crate_passes_and_is_recorded_in_report() {
+    let dir = temp_dir("pass");
+    write_sample_crate(
+        &dir,
+        "repro-pass",
+        "#[no_mangle]\npub extern \"C\" fn demo() -> u32 { 42 }\n",
+    );
+    let outcome = build::run(build_args(
+        &dir,
+        build::ReproducibilityCheck {
+            vary_path: false,
+            vary_environment: true,
+        },
+    ))
+    .expect("reproducible build");
+    let results = outcome.reproducibility.expect("results");
+    assert!(results[0].reproducible);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&outcome.report_path).expect("read report"))
+            .expect("parse report");
+    assert_eq!(report["buildId"], outcome.build_id.as_str());
+    assert_eq!(report["reproducibility"][0]["reproducible"], true);
+    assert!(report["targets"][0]["libraries"][0]["sha256"].is_string());
+}
+
+#[test]
+fn embedded_source_path_fails_when_path_varies() {
+    let dir = temp_dir("leak");
+    write_sample_crate(
+        &dir,
+        "repro-leak",
+        "#[no_mangle]\npub extern \"C\" fn manifest_dir() -> *const u8 {\n    concat!(env!(\"CARGO_MANIFEST_DIR\"), \"\\0\").as_ptr()\n}\n",
+    );
+    let error = build::run(build_args(
+        &dir,
+        build::ReproducibilityCheck {
+            vary_path: true,
+            vary_environment: false,
+        },
+    ))
+    .err()
+    .expect("path leak detected");
+    assert!(error.contains("not reproducible"), "{}", error);
+
+    let report_path = dir.join("target").join("xforge-build-report.json");
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(report_path).expect("read report"))
+            .expect("parse report");
+    let result = &report["reproducibility"][0];
+    assert_eq!(result["reproducible"], false);
+    let library = &result["libraries"][0];
+    assert_ne!(library["firstSha256"], library["secondSha256"]);
+}
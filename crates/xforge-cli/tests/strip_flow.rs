@@ -0,0 +1,130 @@
+#![cfg(target_os = "linux")]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use xforge_cli::commands::{build, bundle};
+use xforge_core::manifest::deserialize_manifest;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-strip-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+fn host_target_triple() -> String {
+    let output = std::process::Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .expect("rustc -vV");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("utf8");
+    for line in stdout.lines() {
+        if let Some(triple) = line.strip_prefix("host: ") {
+            return triple.trim().to_string();
+        }
+    }
+    panic!("missing host target");
+}
+
+fn has_objcopy() -> bool {
+    ["llvm-objcopy", "objcopy"].iter().any(|program| {
+        std::process::Command::new(program)
+            .arg("--version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    })
+}
+
+fn write_sample_crate(dir: &Path, name: &str, target: &str) {
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(
+        dir.join("src").join("lib.rs"),
+        "#[no_mangle]\npub extern \"C\" fn demo() -> u32 { 42 }\n",
+    )
+    .expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n\n[profile.release]\ndebug = true\n",
+            name
+        ),
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        format!("version = 3\n\n[[package]]\nname = \"{}\"\nversion = \"0.1.0\"\n", name),
+    )
+    .expect("write Cargo.lock");
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        format!(
+            "[toolchain]\nchannel = \"stable\"\ntargets = [\"{}\"]\ncomponents = [\"rustfmt\"]\n",
+            target
+        ),
+    )
+    .expect("write rust-toolchain.toml");
+    fs::write(
+        dir.join("xforge.yaml"),
+        "build:\n  strip:\n    mode: debuginfo\n",
+    )
+    .expect("write xforge.yaml");
+}
+
+#[test]
+fn bundle_ships_stripped_library_and_separate_debug_archive() {
+    if !has_objcopy() {
+        return;
+    }
+    let dir = temp_dir("split");
+    let target = host_target_triple();
+    write_sample_crate(&dir, "strip-demo", &target);
+
+    let outcome = build::run(build::BuildArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        profile: "release".to_string(),
+        variant: None,
+        executor: build::BuildExecutorKind::Cargo,
+        cross_image: None,
+        no_cache: true,
+        plan: None,
+        no_provision: true,
+        offline: false,
+        verify_reproducible: None,
+        observer: None,
+    })
+    .expect("build");
+    let strip = &outcome.strip_outcomes[0];
+    let original = fs::metadata(&outcome.library_path).expect("original").len();
+    let stripped = fs::metadata(&strip.library).expect("stripped").len();
+    assert!(stripped < original);
+    let debug_file = strip.debug_file.as_ref().expect("debug file");
+    assert!(debug_file.ends_with("libstrip_demo.so.debug"));
+
+    let bundled = bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        output_dir: dir.join("dist"),
+        profile: Some("release".to_string()),
+    })
+    .expect("bundle");
+    assert_eq!(bundled.debug_archive_paths.len(), 1);
+    assert!(bundled.debug_archive_paths[0].is_file());
+
+    let manifest = deserialize_manifest(
+        &fs::read_to_string(&bundled.manifest_path).expect("read manifest"),
+    )
+    .expect("parse manifest");
+    let platform = &manifest.platforms.targets[0];
+    assert_eq!(platform.artifacts.len(), 1);
+    assert_eq!(platform.debug_artifacts.len(), 1);
+    assert!(platform.debug_artifacts[0].ends_with("-debug.tar.gz"));
+    assert!(!platform.artifacts.contains(&platform.debug_artifacts[0]));
+}
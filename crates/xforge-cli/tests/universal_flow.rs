@@ -0,0 +1,154 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use xforge_cli::commands::bundle;
+use xforge_core::binary::{inspect, Architecture, BinaryOs};
+use xforge_core::manifest::deserialize_manifest;
+use xforge_pack::universal_binary;
+
+const ARM64: u32 = 0x0100_000c;
+const X86_64: u32 = 0x0100_0007;
+const PLATFORM_MACOS: u32 = 1;
+const PLATFORM_IOS_SIMULATOR: u32 = 7;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-universal-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+/// A thin 64-bit Mach-O dylib header for `cputype` with one
+/// `LC_BUILD_VERSION` naming `platform`.
+fn thin_dylib(cputype: u32, platform: u32) -> Vec<u8> {
+    let mut bytes = vec![0u8; 0x60];
+    bytes[..4].copy_from_slice(&0xfeed_facfu32.to_le_bytes());
+    bytes[4..8].copy_from_slice(&cputype.to_le_bytes());
+    bytes[12..16].copy_from_slice(&6u32.to_le_bytes());
+    bytes[16..20].copy_from_slice(&1u32.to_le_bytes());
+    bytes[32..36].copy_from_slice(&0x32u32.to_le_bytes());
+    bytes[36..40].copy_from_slice(&24u32.to_le_bytes());
+    bytes[40..44].copy_from_slice(&platform.to_le_bytes());
+    bytes
+}
+
+fn write_sample_crate(dir: &Path) {
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(dir.join("src").join("lib.rs"), "").expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"universal-demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n",
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        "version = 3\n\n[[package]]\nname = \"universal-demo\"\nversion = \"0.1.0\"\n",
+    )
+    .expect("write Cargo.lock");
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        "[toolchain]\nchannel = \"stable\"\ntargets = [\"aarch64-apple-darwin\", \"x86_64-apple-darwin\"]\ncomponents = [\"rustfmt\"]\n",
+    )
+    .expect("write rust-toolchain.toml");
+    fs::write(
+        dir.join("xforge.yaml"),
+        "build:\n  archives:\n    default: tar.gz\n",
+    )
+    .expect("write xforge.yaml");
+    for (triple, cputype) in [
+        ("aarch64-apple-darwin", ARM64),
+        ("x86_64-apple-darwin", X86_64),
+    ] {
+        let release_dir = dir.join("target").join(triple).join("release");
+        fs::create_dir_all(&release_dir).expect("create release dir");
+        fs::write(
+            release_dir.join("libuniversal_demo.dylib"),
+            thin_dylib(cputype, PLATFORM_MACOS),
+        )
+        .expect("write library");
+    }
+}
+
+#[test]
+fn bundle_merges_macos_slices_into_a_universal_archive() {
+    let dir = temp_dir("macos");
+    write_sample_crate(&dir);
+
+    let bundled = bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        output_dir: dir.join("dist"),
+        profile: Some("release".to_string()),
+    })
+    .expect("bundle");
+
+    assert_eq!(bundled.archive_paths.len(), 3);
+    let archive = &bundled.archive_paths[2];
+    assert!(archive
+        .to_string_lossy()
+        .ends_with("-macos-universal.tar.gz"));
+    let extracted = std::process::Command::new("tar")
+        .arg("-xzOf")
+        .arg(archive)
+        .arg("lib/libuniversal_demo.dylib")
+        .output()
+        .expect("extract library");
+    assert!(extracted.status.success());
+    let library = extracted.stdout;
+    assert_eq!(library[..4], [0xca, 0xfe, 0xba, 0xbe]);
+    let info = inspect(&library).expect("inspect universal");
+    assert!(info.universal);
+    let architectures: Vec<Architecture> =
+        info.slices.iter().map(|slice| slice.architecture).collect();
+    assert_eq!(
+        architectures,
+        vec![Architecture::X86_64, Architecture::Aarch64]
+    );
+
+    let manifest =
+        deserialize_manifest(&fs::read_to_string(&bundled.manifest_path).expect("read manifest"))
+            .expect("parse manifest");
+    let universal = &manifest.platforms.targets[2];
+    assert_eq!(universal.name, "macos-universal");
+    assert_eq!(
+        universal.triples,
+        vec![
+            "aarch64-apple-darwin".to_string(),
+            "x86_64-apple-darwin".to_string()
+        ]
+    );
+    assert!(universal.binary.as_ref().expect("binary info").universal);
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn universal_writer_is_deterministic_and_validates_slices() {
+    let arm64 = thin_dylib(ARM64, PLATFORM_IOS_SIMULATOR);
+    let x86_64 = thin_dylib(X86_64, PLATFORM_IOS_SIMULATOR);
+    let merged = universal_binary(&[&arm64, &x86_64]).expect("merge");
+    assert_eq!(
+        merged,
+        universal_binary(&[&x86_64, &arm64]).expect("merge reversed")
+    );
+    let info = inspect(&merged).expect("inspect");
+    assert!(info
+        .slices
+        .iter()
+        .all(|slice| slice.os == Some(BinaryOs::IosSimulator)));
+    // x86_64 sits on a 4 KiB boundary, arm64 on 16 KiB.
+    assert_eq!(merged[0x10..0x14], 0x1000u32.to_be_bytes());
+    assert_eq!(merged[0x24..0x28], 0x4000u32.to_be_bytes());
+
+    let err = universal_binary(&[&arm64, &arm64]).expect_err("duplicate architecture");
+    assert!(err.to_string().contains("more than one slice"), "{}", err);
+    let err =
+        universal_binary(&[&arm64, &thin_dylib(X86_64, PLATFORM_MACOS)]).expect_err("mixed OS");
+    assert!(err.to_string().contains("disagree on OS"), "{}", err);
+    let err = universal_binary(&[&arm64, &merged]).expect_err("fat slice");
+    assert!(err.to_string().contains("already a universal"), "{}", err);
+}
@@ -0,0 +1,166 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use xforge_cli::commands::bundle;
+use xforge_core::manifest::deserialize_manifest;
+
+const EM_AARCH64: u16 = 183;
+const EM_X86_64: u16 = 62;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-wheel-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+/// A 64-bit ELF shared object header for `machine` with no sections.
+fn linux_shared_object(machine: u16) -> Vec<u8> {
+    let mut bytes = vec![0u8; 64];
+    bytes[..4].copy_from_slice(b"\x7fELF");
+    bytes[4] = 2;
+    bytes[5] = 1;
+    bytes[6] = 1;
+    bytes[0x10..0x12].copy_from_slice(&3u16.to_le_bytes());
+    bytes[0x12..0x14].copy_from_slice(&machine.to_le_bytes());
+    bytes
+}
+
+/// A crate with prebuilt glibc x86_64 and musl aarch64 libraries.
+fn write_sample_crate(dir: &Path) {
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(dir.join("src").join("lib.rs"), "").expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"wheel-demo\"\nversion = \"0.5.0-rc.1\"\nedition = \"2021\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n",
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        "version = 3\n\n[[package]]\nname = \"wheel-demo\"\nversion = \"0.5.0-rc.1\"\n",
+    )
+    .expect("write Cargo.lock");
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        "[toolchain]\nchannel = \"stable\"\ntargets = [\"x86_64-unknown-linux-gnu\", \"aarch64-unknown-linux-musl\"]\ncomponents = [\"rustfmt\"]\n",
+    )
+    .expect("write rust-toolchain.toml");
+    fs::write(
+        dir.join("xforge.yaml"),
+        "build:\n  wheel:\n    loader: true\n",
+    )
+    .expect("write xforge.yaml");
+    for (triple, machine) in [
+        ("x86_64-unknown-linux-gnu", EM_X86_64),
+        ("aarch64-unknown-linux-musl", EM_AARCH64),
+    ] {
+        let release_dir = dir.join("target").join(triple).join("release");
+        fs::create_dir_all(&release_dir).expect("create release dir");
+        fs::write(
+            release_dir.join("libwheel_demo.so"),
+            linux_shared_object(machine),
+        )
+        .expect("write library");
+    }
+}
+
+fn bundle_into(dir: &Path, output: &str) -> bundle::BundleOutcome {
+    bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.to_path_buf(),
+        target: None,
+        output_dir: dir.join(output),
+        profile: Some("release".to_string()),
+    })
+    .expect("bundle")
+}
+
+fn read_entry(zip: &mut zip::ZipArchive<fs::File>, name: &str) -> String {
+    let mut contents = String::new();
+    zip.by_name(name)
+        .unwrap_or_else(|_| panic!("missing {}", name))
+        .read_to_string(&mut contents)
+        .expect("read entry");
+    contents
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .expect("file name")
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn bundle_writes_a_tagged_wheel_per_platform() {
+    let dir = temp_dir("linux");
+    write_sample_crate(&dir);
+
+    let first = bundle_into(&dir, "dist-a");
+    let second = bundle_into(&dir, "dist-b");
+    let names: Vec<String> = first
+        .wheel_paths
+        .iter()
+        .map(|path| file_name(path))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "wheel_demo-0.5.0rc1-py3-none-manylinux_2_17_x86_64.manylinux2014_x86_64.whl",
+            "wheel_demo-0.5.0rc1-py3-none-musllinux_1_2_aarch64.whl",
+        ]
+    );
+    for (left, right) in first.wheel_paths.iter().zip(&second.wheel_paths) {
+        assert_eq!(
+            fs::read(left).expect("read wheel"),
+            fs::read(right).expect("read wheel")
+        );
+    }
+
+    let mut wheel =
+        zip::ZipArchive::new(fs::File::open(&first.wheel_paths[0]).expect("open wheel"))
+            .expect("zip");
+    let entries: Vec<String> = wheel.file_names().map(str::to_string).collect();
+    assert_eq!(
+        entries,
+        vec![
+            "wheel_demo/__init__.py",
+            "wheel_demo/libwheel_demo.so",
+            "wheel_demo-0.5.0rc1.dist-info/METADATA",
+            "wheel_demo-0.5.0rc1.dist-info/WHEEL",
+            "wheel_demo-0.5.0rc1.dist-info/RECORD",
+        ]
+    );
+    let metadata = read_entry(&mut wheel, "wheel_demo-0.5.0rc1.dist-info/METADATA");
+    assert!(metadata.contains("Name: wheel-demo\nVersion: 0.5.0rc1\n"));
+    let tags = read_entry(&mut wheel, "wheel_demo-0.5.0rc1.dist-info/WHEEL");
+    assert!(tags.contains("Root-Is-Purelib: false\n"));
+    assert!(
+        tags.contains("Tag: py3-none-manylinux_2_17_x86_64\nTag: py3-none-manylinux2014_x86_64\n")
+    );
+    let loader = read_entry(&mut wheel, "wheel_demo/__init__.py");
+    assert!(loader.contains("with_name(\"libwheel_demo.so\")"));
+    let record = read_entry(&mut wheel, "wheel_demo-0.5.0rc1.dist-info/RECORD");
+    let rows: Vec<&str> = record.lines().collect();
+    assert_eq!(rows.len(), 5);
+    assert!(rows[1].starts_with("wheel_demo/libwheel_demo.so,sha256="));
+    assert!(rows[1].ends_with(",64"));
+    assert_eq!(rows[4], "wheel_demo-0.5.0rc1.dist-info/RECORD,,");
+
+    let manifest =
+        deserialize_manifest(&fs::read_to_string(&first.manifest_path).expect("read manifest"))
+            .expect("parse manifest");
+    let python = &manifest.bindings.catalog[0];
+    assert_eq!(python.name, "python");
+    assert_eq!(python.artifacts, names);
+    assert_eq!(
+        python.platforms,
+        vec!["x86_64-unknown-linux-gnu", "aarch64-unknown-linux-musl"]
+    );
+
+    fs::remove_dir_all(&dir).ok();
+}
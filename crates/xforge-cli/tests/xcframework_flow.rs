@@ -0,0 +1,313 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use xforge_cli::commands::bundle;
+use xforge_core::binary::{inspect, Architecture};
+use xforge_core::manifest::deserialize_manifest;
+
+const ARM64: u32 = 0x0100_000c;
+const X86_64: u32 = 0x0100_0007;
+const PLATFORM_IOS: u32 = 2;
+const PLATFORM_IOS_SIMULATOR: u32 = 7;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("time")
+        .as_nanos();
+    path.push(format!("xforge-xcframework-{}-{}", name, stamp));
+    fs::create_dir_all(&path).expect("create temp dir");
+    path
+}
+
+/// A thin 64-bit Mach-O dylib header for `cputype` with one
+/// `LC_BUILD_VERSION` naming `platform`.
+fn thin_dylib(cputype: u32, platform: u32) -> Vec<u8> {
+    let mut bytes = vec![0u8; 0x60];
+    bytes[..4].copy_from_slice(&0xfeed_facfu32.to_le_bytes());
+    bytes[4..8].copy_from_slice(&cputype.to_le_bytes());
+    bytes[12..16].copy_from_slice(&6u32.to_le_bytes());
+    bytes[16..20].copy_from_slice(&1u32.to_le_bytes());
+    bytes[32..36].copy_from_slice(&0x32u32.to_le_bytes());
+    bytes[36..40].copy_from_slice(&24u32.to_le_bytes());
+    bytes[40..44].copy_from_slice(&platform.to_le_bytes());
+    bytes
+}
+
+/// A crate with prebuilt iOS device and simulator libraries and the
+/// headers `xforge build` would have generated beside them.
+fn write_sample_crate(dir: &Path) {
+    fs::create_dir_all(dir.join("src")).expect("create src");
+    fs::write(dir.join("src").join("lib.rs"), "").expect("write lib.rs");
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"xcf-demo\"\nversion = \"0.3.0\"\nedition = \"2021\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n",
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("Cargo.lock"),
+        "version = 3\n\n[[package]]\nname = \"xcf-demo\"\nversion = \"0.3.0\"\n",
+    )
+    .expect("write Cargo.lock");
+    fs::write(
+        dir.join("rust-toolchain.toml"),
+        "[toolchain]\nchannel = \"stable\"\ntargets = [\"aarch64-apple-ios\", \"aarch64-apple-ios-sim\", \"x86_64-apple-ios\"]\ncomponents = [\"rustfmt\"]\n",
+    )
+    .expect("write rust-toolchain.toml");
+    fs::write(
+        dir.join("xforge.yaml"),
+        "build:\n  headers: {}\n  xcframework:\n    module: XcfDemo\n",
+    )
+    .expect("write xforge.yaml");
+    for (triple, cputype, platform) in [
+        ("aarch64-apple-ios", ARM64, PLATFORM_IOS),
+        ("aarch64-apple-ios-sim", ARM64, PLATFORM_IOS_SIMULATOR),
+        ("x86_64-apple-ios", X86_64, PLATFORM_IOS_SIMULATOR),
+    ] {
+        let release_dir = dir.join("target").join(triple).join("release");
+        fs::create_dir_all(release_dir.join("include")).expect("create include dir");
+        fs::write(
+            release_dir.join("libxcf_demo.dylib"),
+            thin_dylib(cputype, platform),
+        )
+        .expect("write library");
+        fs::write(
+            release_dir.join("include").join("xcf_demo.h"),
+            "#pragma once\n",
+        )
+        .expect("write header");
+    }
+}
+
+fn bundle_into(dir: &Path, output: &str) -> bundle::BundleOutcome {
+    bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.to_path_buf(),
+        target: None,
+        output_dir: dir.join(output),
+        profile: Some("release".to_string()),
+    })
+    .expect("bundle")
+}
+
+fn read_entry(zip: &mut zip::ZipArchive<fs::File>, name: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    zip.by_name(name)
+        .unwrap_or_else(|_| panic!("missing {}", name))
+        .read_to_end(&mut bytes)
+        .expect("read entry");
+    bytes
+}
+
+#[test]
+fn bundle_assembles_a_deterministic_xcframework_zip() {
+    let dir = temp_dir("ios");
+    write_sample_crate(&dir);
+
+    let first = bundle_into(&dir, "dist-a");
+    let second = bundle_into(&dir, "dist-b");
+    let path = first.xcframework_path.expect("xcframework");
+    let file_name = path
+        .file_name()
+        .expect("file name")
+        .to_string_lossy()
+        .into_owned();
+    assert!(file_name.starts_with(&format!("xcf-demo-{}", first.build_id)));
+    assert!(file_name.ends_with(".xcframework.zip"));
+    assert_eq!(
+        fs::read(&path).expect("read zip"),
+        fs::read(second.xcframework_path.expect("second xcframework")).expect("read zip")
+    );
+
+    let mut zip = zip::ZipArchive::new(fs::File::open(&path).expect("open zip")).expect("zip");
+    let names: Vec<String> = zip.file_names().map(str::to_string).collect();
+    for expected in [
+        "XcfDemo.xcframework/Info.plist",
+        "XcfDemo.xcframework/ios-arm64/libxcf_demo.dylib",
+        "XcfDemo.xcframework/ios-arm64/Headers/xcf_demo.h",
+        "XcfDemo.xcframework/ios-arm64_x86_64-simulator/libxcf_demo.dylib",
+        "XcfDemo.xcframework/ios-arm64_x86_64-simulator/Headers/module.modulemap",
+    ] {
+        assert!(
+            names.iter().any(|name| name == expected),
+            "{} in {:?}",
+            expected,
+            names
+        );
+    }
+
+    let plist = String::from_utf8(read_entry(&mut zip, "XcfDemo.xcframework/Info.plist"))
+        .expect("utf8 plist");
+    assert!(plist.contains("<string>ios-arm64_x86_64-simulator</string>"));
+    assert!(plist.contains("<key>SupportedPlatformVariant</key>\n\t\t\t<string>simulator</string>"));
+    assert!(plist.contains("<key>HeadersPath</key>\n\t\t\t<string>Headers</string>"));
+    let module_map = read_entry(
+        &mut zip,
+        "XcfDemo.xcframework/ios-arm64/Headers/module.modulemap",
+    );
+    assert_eq!(
+        String::from_utf8(module_map).expect("utf8 module map"),
+        "module XcfDemo {\n    header \"xcf_demo.h\"\n    export *\n}\n"
+    );
+    let simulator = read_entry(
+        &mut zip,
+        "XcfDemo.xcframework/ios-arm64_x86_64-simulator/libxcf_demo.dylib",
+    );
+    let info = inspect(&simulator).expect("inspect simulator library");
+    assert!(info.universal);
+    let architectures: Vec<Architecture> =
+        info.slices.iter().map(|slice| slice.architecture).collect();
+    assert_eq!(
+        architectures,
+        vec![Architecture::X86_64, Architecture::Aarch64]
+    );
+
+    let manifest =
+        deserialize_manifest(&fs::read_to_string(&first.manifest_path).expect("read manifest"))
+            .expect("parse manifest");
+    let swift = &manifest.bindings.catalog[0];
+    assert_eq!(swift.name, "swift");
+    assert_eq!(swift.version, "0.3.0");
+    assert_eq!(swift.artifacts, vec![file_name]);
+    assert_eq!(swift.platforms.len(), 3);
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn bundle_writes_a_package_swift_for_the_released_zip() {
+    let dir = temp_dir("spm");
+    write_sample_crate(&dir);
+    fs::write(
+        dir.join("xforge.yaml"),
+        "precompiled_binaries:\n  repository: owner/xcf-demo\n  public_key: deadbeef\nbuild:\n  xcframework:\n    module: XcfDemo\n    spm:\n      package: XcfDemoKit\n      output_dir: swift-repo\n",
+    )
+    .expect("write xforge.yaml");
+
+    let outcome = bundle_into(&dir, "dist");
+    let path = outcome.xcframework_path.expect("xcframework");
+    let file_name = path
+        .file_name()
+        .expect("file name")
+        .to_string_lossy()
+        .into_owned();
+    assert_eq!(
+        outcome.package_swift_paths,
+        vec![
+            dir.join("dist").join("Package.swift"),
+            dir.join("swift-repo").join("Package.swift"),
+        ]
+    );
+    let package = fs::read_to_string(&outcome.package_swift_paths[0]).expect("read Package.swift");
+    assert_eq!(
+        package,
+        fs::read_to_string(&outcome.package_swift_paths[1]).expect("read Package.swift")
+    );
+    let checksum = hex::encode(Sha256::digest(fs::read(&path).expect("read zip")));
+    assert!(package.starts_with("// swift-tools-version:5.3\n"));
+    assert!(package.contains(".library(name: \"XcfDemoKit\", targets: [\"XcfDemo\"])"));
+    assert!(package.contains(&format!(
+        "url: \"https://github.com/owner/xcf-demo/releases/download/{}/{}\",\n            checksum: \"{}\"\n",
+        outcome.build_id, file_name, checksum
+    )));
+
+    let manifest =
+        deserialize_manifest(&fs::read_to_string(&outcome.manifest_path).expect("read manifest"))
+            .expect("parse manifest");
+    assert_eq!(
+        manifest.bindings.catalog[0].artifacts,
+        vec![file_name, "Package.swift".to_string()]
+    );
+
+    fs::write(
+        dir.join("xforge.yaml"),
+        "build:\n  xcframework:\n    spm: {}\n",
+    )
+    .expect("write xforge.yaml");
+    let error = bundle::run(bundle::BundleArgs {
+        manifest_dir: dir.clone(),
+        target: None,
+        output_dir: dir.join("dist-b"),
+        profile: Some("release".to_string()),
+    })
+    .err()
+    .expect("bundle without precompiled_binaries");
+    assert!(error.contains("precompiled_binaries"), "{}", error);
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn bundle_writes_podspecs_from_the_package_info() {
+    let dir = temp_dir("cocoapods");
+    write_sample_crate(&dir);
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"xcf-demo\"\nversion = \"0.3.0\"\nedition = \"2021\"\ndescription = \"Demo bindings\"\nlicense = \"MIT\"\nauthors = [\"Demo Team <team@example.com>\"]\nrepository = \"https://github.com/owner/xcf-demo\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n",
+    )
+    .expect("write Cargo.toml");
+    fs::write(
+        dir.join("xforge.yaml"),
+        "precompiled_binaries:\n  repository: owner/xcf-demo\n  public_key: deadbeef\nbuild:\n  xcframework:\n    module: XcfDemo\n    cocoapods:\n      deployment_target: '13.0'\n      output_dir: specs\n",
+    )
+    .expect("write xforge.yaml");
+
+    let outcome = bundle_into(&dir, "dist");
+    let path = outcome.xcframework_path.expect("xcframework");
+    let file_name = path
+        .file_name()
+        .expect("file name")
+        .to_string_lossy()
+        .into_owned();
+    assert_eq!(
+        outcome.podspec_paths,
+        vec![
+            dir.join("dist").join("XcfDemo.podspec"),
+            dir.join("specs").join("XcfDemo.podspec"),
+            dir.join("dist").join("XcfDemo.podspec.json"),
+            dir.join("specs").join("XcfDemo.podspec.json"),
+        ]
+    );
+    let url = format!(
+        "https://github.com/owner/xcf-demo/releases/download/{}/{}",
+        outcome.build_id, file_name
+    );
+    let checksum = hex::encode(Sha256::digest(fs::read(&path).expect("read zip")));
+    let ruby = fs::read_to_string(&outcome.podspec_paths[0]).expect("read podspec");
+    assert!(ruby.contains("  s.summary = 'Demo bindings'\n"));
+    assert!(ruby.contains("  s.authors = ['Demo Team <team@example.com>']\n"));
+    assert!(ruby.contains(&format!(
+        "  s.source = {{ :http => '{}', :sha256 => '{}' }}\n",
+        url, checksum
+    )));
+    assert!(ruby.contains("  s.platforms = { :ios => '13.0' }\n"));
+    assert!(ruby.contains("  s.vendored_frameworks = 'XcfDemo.xcframework'\n"));
+
+    let json: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(&outcome.podspec_paths[2]).expect("read podspec.json"),
+    )
+    .expect("parse podspec.json");
+    assert_eq!(json["name"], "XcfDemo");
+    assert_eq!(json["version"], "0.3.0");
+    assert_eq!(json["homepage"], "https://github.com/owner/xcf-demo");
+    assert_eq!(json["license"]["type"], "MIT");
+    assert_eq!(json["source"]["http"], url.as_str());
+    assert_eq!(json["source"]["sha256"], checksum.as_str());
+
+    let manifest =
+        deserialize_manifest(&fs::read_to_string(&outcome.manifest_path).expect("read manifest"))
+            .expect("parse manifest");
+    assert_eq!(manifest.package.license.as_deref(), Some("MIT"));
+    assert_eq!(
+        manifest.bindings.catalog[0].artifacts,
+        vec![
+            file_name,
+            "XcfDemo.podspec".to_string(),
+            "XcfDemo.podspec.json".to_string()
+        ]
+    );
+
+    fs::remove_dir_all(&dir).ok();
+}
@@ -1,5 +1,7 @@
 use std::collections::HashSet;
 
+use serde::{Deserialize, Serialize};
+
 use crate::platform::PlatformKey;
 
 pub const MANIFEST_FILE_NAME: &str = "manifest.json";
@@ -7,40 +9,194 @@ pub const BUILD_ID_FILE_NAME: &str = "build_id.txt";
 pub const METADATA_DIR_NAME: &str = "metadata";
 pub const LIB_DIR_NAME: &str = "lib";
 pub const INCLUDE_DIR_NAME: &str = "include";
+pub const DEBUG_DIR_NAME: &str = "debug";
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ArchiveLayout {
     pub layout: LayoutVariant,
     pub manifest_path: String,
     pub build_id_path: String,
-    pub library_path: String,
+    /// Every shipped library, primary output first.
+    pub libraries: Vec<LibraryEntry>,
     pub include_path: Option<String>,
 }
 
+impl ArchiveLayout {
+    pub fn library(&self, kind: LibraryKind) -> Option<&LibraryEntry> {
+        self.libraries.iter().find(|entry| entry.kind == kind)
+    }
+}
+
+/// Linkable outputs a crate can ship for one platform.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LibraryKind {
+    /// `cdylib`: `.so`, `.dylib` or `.dll`.
+    Dynamic,
+    /// `staticlib`: `.a`, or `.lib` on Windows.
+    Static,
+    /// The `.dll.lib` import library Windows links a `cdylib` through.
+    Import,
+}
+
+impl LibraryKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LibraryKind::Dynamic => "dynamic",
+            LibraryKind::Static => "static",
+            LibraryKind::Import => "import",
+        }
+    }
+}
+
+impl std::fmt::Display for LibraryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for LibraryKind {
+    type Err = LayoutValidationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "dynamic" => Ok(LibraryKind::Dynamic),
+            "static" => Ok(LibraryKind::Static),
+            "import" => Ok(LibraryKind::Import),
+            other => Err(LayoutValidationError::UnknownLibraryKind(other.to_string())),
+        }
+    }
+}
+
+/// A library and where it lives: an archive entry in [`ArchiveLayout`], a
+/// file on disk in [`crate::build_plan::BuiltArtifact`].
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryEntry {
+    pub kind: LibraryKind,
+    pub path: String,
+}
+
+/// Layout for a crate that ships only its dynamic library.
 pub fn archive_layout(lib_name: &str, platform_key: &PlatformKey) -> ArchiveLayout {
+    archive_layout_with_libraries(lib_name, platform_key, &[LibraryKind::Dynamic])
+}
+
+pub fn archive_layout_with_libraries(
+    lib_name: &str,
+    platform_key: &PlatformKey,
+    kinds: &[LibraryKind],
+) -> ArchiveLayout {
     let layout = layout_variant(platform_key);
     ArchiveLayout {
         layout,
         manifest_path: metadata_path(MANIFEST_FILE_NAME),
         build_id_path: metadata_path(BUILD_ID_FILE_NAME),
-        library_path: format!(
-            "{}/{}",
-            LIB_DIR_NAME,
-            library_filename(lib_name, platform_key)
-        ),
+        libraries: kinds
+            .iter()
+            .map(|kind| LibraryEntry {
+                kind: *kind,
+                path: format!(
+                    "{}/{}",
+                    LIB_DIR_NAME,
+                    library_filename_for(lib_name, platform_key, *kind)
+                ),
+            })
+            .collect(),
         include_path: None,
     }
 }
 
 pub fn library_filename(lib_name: &str, platform_key: &PlatformKey) -> String {
+    library_filename_for(lib_name, platform_key, LibraryKind::Dynamic)
+}
+
+pub fn library_filename_for(lib_name: &str, platform_key: &PlatformKey, kind: LibraryKind) -> String {
     let lib_name = lib_name.replace('-', "_");
+    match kind {
+        LibraryKind::Dynamic => {
+            if is_windows(platform_key) {
+                return format!("{}.dll", lib_name);
+            }
+            if is_macos(platform_key) || is_ios(platform_key) {
+                return format!("lib{}.dylib", lib_name);
+            }
+            format!("lib{}.so", lib_name)
+        }
+        LibraryKind::Static => {
+            if is_windows(platform_key) {
+                return format!("{}.lib", lib_name);
+            }
+            format!("lib{}.a", lib_name)
+        }
+        LibraryKind::Import => format!("{}.dll.lib", lib_name),
+    }
+}
+
+/// Side file holding the debug info split out of a platform's dynamic
+/// library: `lib<name>.so.debug`, a `lib<name>.dylib.dSYM` bundle, or the
+/// `<name>.pdb` the MSVC linker writes.
+pub fn debug_info_filename(lib_name: &str, platform_key: &PlatformKey) -> String {
+    let library = library_filename(lib_name, platform_key);
     if is_windows(platform_key) {
-        return format!("{}.dll", lib_name);
+        return format!("{}.pdb", lib_name.replace('-', "_"));
     }
     if is_macos(platform_key) || is_ios(platform_key) {
-        return format!("lib{}.dylib", lib_name);
+        return format!("{}.dSYM", library);
+    }
+    format!("{}.debug", library)
+}
+
+/// Libraries the Cargo.toml `crate-type` list produces for a platform. A crate
+/// without an explicit list is treated as a `cdylib`.
+pub fn available_library_kinds(crate_types: &[String], platform_key: &PlatformKey) -> Vec<LibraryKind> {
+    let dynamic = crate_types.is_empty() || crate_types.iter().any(|value| value == "cdylib");
+    let mut kinds = Vec::new();
+    if dynamic {
+        kinds.push(LibraryKind::Dynamic);
+    }
+    if crate_types.iter().any(|value| value == "staticlib") {
+        kinds.push(LibraryKind::Static);
+    }
+    if dynamic && is_windows(platform_key) {
+        kinds.push(LibraryKind::Import);
+    }
+    kinds
+}
+
+/// Resolve the libraries to ship: every available output unless `requested`
+/// narrows the list, which must only name outputs the crate produces.
+pub fn select_library_kinds(
+    crate_types: &[String],
+    platform_key: &PlatformKey,
+    requested: Option<&[LibraryKind]>,
+) -> Result<Vec<LibraryKind>, LayoutValidationError> {
+    let available = available_library_kinds(crate_types, platform_key);
+    let Some(requested) = requested else {
+        if available.is_empty() {
+            return Err(LayoutValidationError::NoLibraryOutputs);
+        }
+        return Ok(available);
+    };
+    let mut kinds = Vec::new();
+    for kind in requested {
+        if !available.contains(kind) {
+            return Err(LayoutValidationError::UnavailableLibrary {
+                kind: *kind,
+                platform: *platform_key,
+            });
+        }
+        if !kinds.contains(kind) {
+            kinds.push(*kind);
+        }
+    }
+    if kinds.is_empty() {
+        return Err(LayoutValidationError::NoLibraryOutputs);
     }
-    format!("lib{}.so", lib_name)
+    kinds.sort();
+    Ok(kinds)
 }
 
 pub fn default_archive_kind(platform_key: &PlatformKey) -> super::naming::ArchiveKind {
@@ -82,7 +238,10 @@ fn is_ios(platform_key: &PlatformKey) -> bool {
 }
 
 fn is_macos(platform_key: &PlatformKey) -> bool {
-    matches!(platform_key, PlatformKey::MacosArm64 | PlatformKey::MacosX86_64)
+    matches!(
+        platform_key,
+        PlatformKey::MacosArm64 | PlatformKey::MacosX86_64 | PlatformKey::MacosUniversal
+    )
 }
 
 fn is_windows(platform_key: &PlatformKey) -> bool {
@@ -93,11 +252,8 @@ fn is_windows(platform_key: &PlatformKey) -> bool {
 }
 
 pub fn required_entries(layout: &ArchiveLayout) -> Vec<String> {
-    let mut entries = vec![
-        layout.manifest_path.clone(),
-        layout.build_id_path.clone(),
-        layout.library_path.clone(),
-    ];
+    let mut entries = vec![layout.manifest_path.clone(), layout.build_id_path.clone()];
+    entries.extend(layout.libraries.iter().map(|entry| entry.path.clone()));
     if let Some(include_path) = &layout.include_path {
         entries.push(include_path.clone());
     }
@@ -127,6 +283,12 @@ where
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum LayoutValidationError {
     MissingEntry(String),
+    UnknownLibraryKind(String),
+    UnavailableLibrary {
+        kind: LibraryKind,
+        platform: PlatformKey,
+    },
+    NoLibraryOutputs,
 }
 
 impl std::fmt::Display for LayoutValidationError {
@@ -135,6 +297,19 @@ impl std::fmt::Display for LayoutValidationError {
             LayoutValidationError::MissingEntry(path) => {
                 write!(f, "archive missing required entry '{}'", path)
             }
+            LayoutValidationError::UnknownLibraryKind(value) => write!(
+                f,
+                "unknown library output '{}'; expected dynamic, static, or import",
+                value
+            ),
+            LayoutValidationError::UnavailableLibrary { kind, platform } => write!(
+                f,
+                "{} library requested for {} but Cargo.toml crate-type does not produce it",
+                kind, platform
+            ),
+            LayoutValidationError::NoLibraryOutputs => {
+                write!(f, "crate-type produces no cdylib or staticlib output")
+            }
         }
     }
 }
@@ -154,7 +329,7 @@ mod tests {
     fn linux_layout_uses_so() {
         let key = PlatformKey::LinuxX86_64;
         let layout = archive_layout("demo", &key);
-        assert_eq!(layout.library_path, "lib/libdemo.so");
+        assert_eq!(layout.libraries[0].path, "lib/libdemo.so");
         assert_eq!(layout.manifest_path, "metadata/manifest.json");
         assert_eq!(layout.build_id_path, "metadata/build_id.txt");
     }
@@ -177,4 +352,54 @@ mod tests {
         ];
         assert!(validate_archive_entries(&layout, entries).is_ok());
     }
+
+    #[test]
+    fn windows_cdylib_ships_import_library() {
+        let key = PlatformKey::WindowsX86_64Msvc;
+        let kinds = select_library_kinds(&["cdylib".to_string()], &key, None).expect("kinds");
+        assert_eq!(kinds, vec![LibraryKind::Dynamic, LibraryKind::Import]);
+        let layout = archive_layout_with_libraries("demo-lib", &key, &kinds);
+        assert_eq!(layout.libraries[0].path, "lib/demo_lib.dll");
+        assert_eq!(layout.libraries[1].path, "lib/demo_lib.dll.lib");
+    }
+
+    #[test]
+    fn staticlib_outputs_use_platform_archive_names() {
+        let crate_types = vec!["staticlib".to_string(), "cdylib".to_string()];
+        let ios = PlatformKey::IosArm64;
+        let kinds =
+            select_library_kinds(&crate_types, &ios, Some(&[LibraryKind::Static])).expect("kinds");
+        assert_eq!(kinds, vec![LibraryKind::Static]);
+        let layout = archive_layout_with_libraries("demo", &ios, &kinds);
+        assert_eq!(layout.libraries[0].path, "lib/libdemo.a");
+        assert_eq!(
+            library_filename_for("demo", &PlatformKey::WindowsArm64Msvc, LibraryKind::Static),
+            "demo.lib"
+        );
+    }
+
+    #[test]
+    fn debug_info_side_files_follow_platform_conventions() {
+        assert_eq!(
+            debug_info_filename("demo-lib", &PlatformKey::AndroidArm64),
+            "libdemo_lib.so.debug"
+        );
+        assert_eq!(
+            debug_info_filename("demo-lib", &PlatformKey::IosArm64),
+            "libdemo_lib.dylib.dSYM"
+        );
+        assert_eq!(
+            debug_info_filename("demo-lib", &PlatformKey::WindowsX86_64Msvc),
+            "demo_lib.pdb"
+        );
+    }
+
+    #[test]
+    fn requesting_unbuilt_output_fails() {
+        let key = PlatformKey::LinuxX86_64;
+        let err = select_library_kinds(&["cdylib".to_string()], &key, Some(&[LibraryKind::Static]))
+            .expect_err("staticlib not declared");
+        assert!(matches!(err, LayoutValidationError::UnavailableLibrary { .. }));
+        assert!(select_library_kinds(&["rlib".to_string()], &key, None).is_err());
+    }
 }
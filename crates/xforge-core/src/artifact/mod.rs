@@ -2,6 +2,8 @@ pub mod layout;
 pub mod naming;
 
 pub use layout::{
-    archive_layout, default_archive_kind, ArchiveLayout, BUILD_ID_FILE_NAME, MANIFEST_FILE_NAME,
+    archive_layout, archive_layout_with_libraries, debug_info_filename, default_archive_kind,
+    ArchiveLayout, LibraryEntry, LibraryKind, BUILD_ID_FILE_NAME, DEBUG_DIR_NAME,
+    INCLUDE_DIR_NAME, MANIFEST_FILE_NAME,
 };
-pub use naming::{artifact_name, ArchiveKind, ArtifactNameError};
+pub use naming::{artifact_name, debug_artifact_name, ArchiveKind, ArtifactNameError};
@@ -1,18 +1,41 @@
+use serde::{Deserialize, Serialize};
+
 use crate::platform::PlatformKey;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub enum ArchiveKind {
     TarGz,
     Zip,
+    /// Zstandard-compressed tar; smaller and faster to unpack than gzip.
+    TarZst,
+    /// xz-compressed tar.
+    TarXz,
 }
 
 impl ArchiveKind {
+    pub const ALL: [ArchiveKind; 4] = [
+        ArchiveKind::TarGz,
+        ArchiveKind::Zip,
+        ArchiveKind::TarZst,
+        ArchiveKind::TarXz,
+    ];
+
     pub fn extension(self) -> &'static str {
         match self {
             ArchiveKind::TarGz => "tar.gz",
             ArchiveKind::Zip => "zip",
+            ArchiveKind::TarZst => "tar.zst",
+            ArchiveKind::TarXz => "tar.xz",
         }
     }
+
+    /// The kind whose [`extension`](Self::extension) is `extension`.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.extension() == extension)
+    }
 }
 
 
@@ -34,6 +57,79 @@ pub fn artifact_name(
     ))
 }
 
+/// Name of the zipped XCFramework that carries every Apple platform of a
+/// release.
+pub fn xcframework_artifact_name(
+    lib_name: &str,
+    build_id: &str,
+) -> Result<String, ArtifactNameError> {
+    validate_component("package", lib_name)?;
+    validate_component("build_id", build_id)?;
+    validate_build_id(build_id)?;
+    Ok(format!("{}-{}.xcframework.zip", lib_name, build_id))
+}
+
+/// Name of the Android archive that carries every Android ABI of a release.
+pub fn aar_artifact_name(lib_name: &str, build_id: &str) -> Result<String, ArtifactNameError> {
+    validate_component("package", lib_name)?;
+    validate_component("build_id", build_id)?;
+    validate_build_id(build_id)?;
+    Ok(format!("{}-{}.aar", lib_name, build_id))
+}
+
+/// Suffix that marks a split debug-info archive.
+pub const DEBUG_SUFFIX: &str = "debug";
+
+/// Suffixes this module appends after the platform; a variant may not use
+/// one as its name or its archive would collide with another.
+pub const RESERVED_SUFFIXES: [&str; 1] = [DEBUG_SUFFIX];
+
+/// Name of the archive carrying split debug info for a platform; it sits
+/// beside the runtime artifact and is never fetched by adapters by default.
+pub fn debug_artifact_name(
+    lib_name: &str,
+    build_id: &str,
+    platform_key: &PlatformKey,
+    archive: ArchiveKind,
+) -> Result<String, ArtifactNameError> {
+    let name = artifact_name(lib_name, build_id, platform_key, archive)?;
+    Ok(with_suffix(&name, DEBUG_SUFFIX, archive))
+}
+
+/// Name of the archive for a non-primary variant such as a second cargo
+/// profile; the variant is appended after the platform.
+pub fn variant_artifact_name(
+    lib_name: &str,
+    build_id: &str,
+    platform_key: &PlatformKey,
+    variant: &str,
+    archive: ArchiveKind,
+) -> Result<String, ArtifactNameError> {
+    validate_component("variant", variant)?;
+    let name = artifact_name(lib_name, build_id, platform_key, archive)?;
+    Ok(with_suffix(&name, variant, archive))
+}
+
+/// Split debug-info archive for a non-primary variant.
+pub fn variant_debug_artifact_name(
+    lib_name: &str,
+    build_id: &str,
+    platform_key: &PlatformKey,
+    variant: &str,
+    archive: ArchiveKind,
+) -> Result<String, ArtifactNameError> {
+    let name = variant_artifact_name(lib_name, build_id, platform_key, variant, archive)?;
+    Ok(with_suffix(&name, DEBUG_SUFFIX, archive))
+}
+
+fn with_suffix(name: &str, suffix: &str, archive: ArchiveKind) -> String {
+    let stem = name
+        .strip_suffix(archive.extension())
+        .and_then(|stem| stem.strip_suffix('.'))
+        .unwrap_or(name);
+    format!("{}-{}.{}", stem, suffix, archive.extension())
+}
+
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ArtifactNameError {
@@ -119,6 +215,57 @@ mod tests {
         assert_eq!(name, "libname-b1-abc123-x86_64-unknown-linux-gnu.tar.gz");
     }
 
+    #[test]
+    fn debug_artifact_name_appends_suffix() {
+        let key = PlatformKey::MacosArm64;
+        let name = debug_artifact_name("libname", "b1-abc123", &key, ArchiveKind::Zip).expect("name");
+        assert_eq!(name, "libname-b1-abc123-aarch64-apple-darwin-debug.zip");
+    }
+
+    #[test]
+    fn variant_artifact_name_appends_variant() {
+        let key = PlatformKey::LinuxX86_64;
+        let name = variant_artifact_name(
+            "libname",
+            "b1-abc123",
+            &key,
+            "release-with-asserts",
+            ArchiveKind::TarGz,
+        )
+        .expect("name");
+        assert_eq!(
+            name,
+            "libname-b1-abc123-x86_64-unknown-linux-gnu-release-with-asserts.tar.gz"
+        );
+        let debug =
+            variant_debug_artifact_name("libname", "b1-abc123", &key, "dev", ArchiveKind::TarGz)
+                .expect("name");
+        assert_eq!(
+            debug,
+            "libname-b1-abc123-x86_64-unknown-linux-gnu-dev-debug.tar.gz"
+        );
+        assert!(
+            variant_artifact_name("libname", "b1-abc123", &key, "Dev", ArchiveKind::TarGz).is_err()
+        );
+    }
+
+    #[test]
+    fn compressed_tar_kinds_round_trip_their_extension() {
+        let key = PlatformKey::AndroidArm64;
+        let name =
+            variant_debug_artifact_name("libname", "b1-abc123", &key, "gpu", ArchiveKind::TarZst)
+                .expect("name");
+        assert_eq!(
+            name,
+            "libname-b1-abc123-aarch64-linux-android-gpu-debug.tar.zst"
+        );
+        assert_eq!(
+            ArchiveKind::from_extension("tar.xz"),
+            Some(ArchiveKind::TarXz)
+        );
+        assert_eq!(ArchiveKind::from_extension("tgz"), None);
+    }
+
     #[test]
     fn invalid_component_rejected() {
         let key = PlatformKey::LinuxX86_64;
@@ -0,0 +1,636 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::sections::{
+    fat_slice_count, read_sections, read_u16, read_u32, slice, BinaryError, ELF_MAGIC, FAT_MAGIC,
+    MH_MAGIC, MH_MAGIC_64,
+};
+use crate::platform::PlatformKey;
+
+const ANDROID_NOTE_SECTION: &str = ".note.android.ident";
+const LC_VERSION_MIN_MACOSX: u32 = 0x24;
+const LC_VERSION_MIN_IPHONEOS: u32 = 0x25;
+const LC_BUILD_VERSION: u32 = 0x32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BinaryFormat {
+    #[serde(rename = "elf")]
+    Elf,
+    #[serde(rename = "mach-o")]
+    MachO,
+    #[serde(rename = "pe")]
+    Pe,
+}
+
+impl BinaryFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BinaryFormat::Elf => "elf",
+            BinaryFormat::MachO => "mach-o",
+            BinaryFormat::Pe => "pe",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Architecture {
+    #[serde(rename = "x86")]
+    X86,
+    #[serde(rename = "x86_64")]
+    X86_64,
+    #[serde(rename = "arm")]
+    Arm,
+    #[serde(rename = "aarch64")]
+    Aarch64,
+    #[serde(rename = "unknown")]
+    Unknown,
+}
+
+impl Architecture {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Architecture::X86 => "x86",
+            Architecture::X86_64 => "x86_64",
+            Architecture::Arm => "arm",
+            Architecture::Aarch64 => "aarch64",
+            Architecture::Unknown => "unknown",
+        }
+    }
+}
+
+/// Operating system a binary was linked for, as far as its headers say.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BinaryOs {
+    Linux,
+    Android,
+    Macos,
+    Ios,
+    IosSimulator,
+    Windows,
+}
+
+impl BinaryOs {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BinaryOs::Linux => "linux",
+            BinaryOs::Android => "android",
+            BinaryOs::Macos => "macos",
+            BinaryOs::Ios => "ios",
+            BinaryOs::IosSimulator => "ios-simulator",
+            BinaryOs::Windows => "windows",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BinaryFileType {
+    SharedLibrary,
+    Executable,
+    Object,
+    Other,
+}
+
+impl BinaryFileType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BinaryFileType::SharedLibrary => "shared-library",
+            BinaryFileType::Executable => "executable",
+            BinaryFileType::Object => "object",
+            BinaryFileType::Other => "other",
+        }
+    }
+}
+
+/// Header properties of one architecture in a binary.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinarySlice {
+    pub architecture: Architecture,
+    pub bits: u8,
+    /// `None` when the headers name no OS or one xforge does not target.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os: Option<BinaryOs>,
+    pub file_type: BinaryFileType,
+}
+
+impl fmt::Display for BinarySlice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}-bit {} {}",
+            self.architecture.as_str(),
+            self.bits,
+            self.os.map(BinaryOs::as_str).unwrap_or("unknown-os"),
+            self.file_type.as_str()
+        )
+    }
+}
+
+/// What [`inspect`] found in a binary's headers; fat Mach-O files have one
+/// slice per architecture.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryInfo {
+    pub format: BinaryFormat,
+    #[serde(default)]
+    pub universal: bool,
+    pub slices: Vec<BinarySlice>,
+}
+
+/// A binary whose headers do not match the platform it is packaged for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlatformMismatch {
+    pub platform: PlatformKey,
+    pub problems: Vec<String>,
+}
+
+impl fmt::Display for PlatformMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "binary does not match platform '{}': {}",
+            self.platform,
+            self.problems.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for PlatformMismatch {}
+
+impl BinaryInfo {
+    /// Check that the binary is a `file_type` built for `platform`. A fat
+    /// Mach-O passes when one of its slices matches; a universal platform
+    /// needs a matching slice for every platform it merges.
+    pub fn check(
+        &self,
+        platform: PlatformKey,
+        file_type: BinaryFileType,
+    ) -> Result<(), PlatformMismatch> {
+        let mut problems = Vec::new();
+        match platform.universal_slices() {
+            [] => self.check_slice(platform, file_type, &mut problems),
+            slices => {
+                if !self.universal {
+                    problems.push("binary is thin, expected a universal binary".to_string());
+                }
+                for slice in slices {
+                    self.check_slice(*slice, file_type, &mut problems);
+                }
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(PlatformMismatch { platform, problems })
+        }
+    }
+
+    fn check_slice(
+        &self,
+        platform: PlatformKey,
+        file_type: BinaryFileType,
+        problems: &mut Vec<String>,
+    ) {
+        let (format, expected) = expected_slice(platform, file_type);
+        if self.format != format {
+            problems.push(format!(
+                "format is {}, expected {}",
+                self.format.as_str(),
+                format.as_str()
+            ));
+        }
+        let slice = self
+            .slices
+            .iter()
+            .find(|slice| slice.architecture == expected.architecture)
+            .or_else(|| self.slices.first());
+        match slice {
+            Some(slice) if slice.architecture != expected.architecture => {
+                let found: Vec<&str> = self
+                    .slices
+                    .iter()
+                    .map(|slice| slice.architecture.as_str())
+                    .collect();
+                problems.push(format!(
+                    "architecture is {}, expected {}",
+                    found.join("+"),
+                    expected.architecture.as_str()
+                ));
+            }
+            Some(slice) => {
+                if slice.bits != expected.bits {
+                    problems.push(format!(
+                        "binary is {}-bit, expected {}-bit",
+                        slice.bits, expected.bits
+                    ));
+                }
+                if slice.os != expected.os {
+                    problems.push(format!(
+                        "OS/ABI is {}, expected {}",
+                        slice.os.map(BinaryOs::as_str).unwrap_or("unknown"),
+                        expected.os.map(BinaryOs::as_str).unwrap_or("unknown")
+                    ));
+                }
+                if slice.file_type != expected.file_type {
+                    problems.push(format!(
+                        "file type is {}, expected {}",
+                        slice.file_type.as_str(),
+                        expected.file_type.as_str()
+                    ));
+                }
+            }
+            None => problems.push("binary has no architecture slices".to_string()),
+        }
+    }
+}
+
+/// Read the format, architecture, bitness, OS/ABI and file type of an ELF,
+/// Mach-O (thin or fat) or PE/COFF binary.
+pub fn inspect(bytes: &[u8]) -> Result<BinaryInfo, BinaryError> {
+    if bytes.starts_with(ELF_MAGIC) {
+        Ok(BinaryInfo {
+            format: BinaryFormat::Elf,
+            universal: false,
+            slices: vec![elf_slice(bytes)?],
+        })
+    } else if bytes.starts_with(b"MZ") {
+        Ok(BinaryInfo {
+            format: BinaryFormat::Pe,
+            universal: false,
+            slices: vec![pe_slice(bytes)?],
+        })
+    } else if read_u32(bytes, 0, true).ok() == Some(FAT_MAGIC) {
+        let nfat = fat_slice_count(bytes)?;
+        let mut slices = Vec::with_capacity(nfat);
+        for index in 0..nfat {
+            let offset = read_u32(bytes, 8 + index * 20 + 8, true)? as usize;
+            slices.push(macho_slice(bytes, offset)?);
+        }
+        Ok(BinaryInfo {
+            format: BinaryFormat::MachO,
+            universal: true,
+            slices,
+        })
+    } else {
+        Ok(BinaryInfo {
+            format: BinaryFormat::MachO,
+            universal: false,
+            slices: vec![macho_slice(bytes, 0)?],
+        })
+    }
+}
+
+/// The slice a thin `platform` binary must have; universal platforms are
+/// checked slice by slice.
+fn expected_slice(
+    platform: PlatformKey,
+    file_type: BinaryFileType,
+) -> (BinaryFormat, BinarySlice) {
+    use Architecture::{Aarch64, Arm, X86_64};
+    let (format, architecture, bits, os) = match platform {
+        PlatformKey::LinuxX86_64 | PlatformKey::LinuxX86_64Musl => {
+            (BinaryFormat::Elf, X86_64, 64, BinaryOs::Linux)
+        }
+        PlatformKey::LinuxAarch64 | PlatformKey::LinuxAarch64Musl => {
+            (BinaryFormat::Elf, Aarch64, 64, BinaryOs::Linux)
+        }
+        PlatformKey::MacosArm64 => (BinaryFormat::MachO, Aarch64, 64, BinaryOs::Macos),
+        PlatformKey::MacosX86_64 | PlatformKey::MacosUniversal => {
+            (BinaryFormat::MachO, X86_64, 64, BinaryOs::Macos)
+        }
+        PlatformKey::IosArm64 => (BinaryFormat::MachO, Aarch64, 64, BinaryOs::Ios),
+        PlatformKey::IosSimulatorArm64 => {
+            (BinaryFormat::MachO, Aarch64, 64, BinaryOs::IosSimulator)
+        }
+        PlatformKey::IosSimulatorX86_64 => {
+            (BinaryFormat::MachO, X86_64, 64, BinaryOs::IosSimulator)
+        }
+        PlatformKey::AndroidArm64 => (BinaryFormat::Elf, Aarch64, 64, BinaryOs::Android),
+        PlatformKey::AndroidArmv7 => (BinaryFormat::Elf, Arm, 32, BinaryOs::Android),
+        PlatformKey::AndroidX86_64 => (BinaryFormat::Elf, X86_64, 64, BinaryOs::Android),
+        PlatformKey::WindowsX86_64Msvc => (BinaryFormat::Pe, X86_64, 64, BinaryOs::Windows),
+        PlatformKey::WindowsArm64Msvc => (BinaryFormat::Pe, Aarch64, 64, BinaryOs::Windows),
+    };
+    (
+        format,
+        BinarySlice {
+            architecture,
+            bits,
+            os: Some(os),
+            file_type,
+        },
+    )
+}
+
+fn elf_slice(bytes: &[u8]) -> Result<BinarySlice, BinaryError> {
+    let bits = match bytes.get(4) {
+        Some(1) => 32,
+        Some(2) => 64,
+        _ => return Err(BinaryError::Truncated { what: "ELF header" }),
+    };
+    let big_endian = bytes.get(5) == Some(&2);
+    let os_abi = *bytes.get(7).ok_or(BinaryError::Truncated { what: "ELF header" })?;
+    let file_type = match read_u16(bytes, 0x10, big_endian)? {
+        1 => BinaryFileType::Object,
+        2 => BinaryFileType::Executable,
+        3 => BinaryFileType::SharedLibrary,
+        _ => BinaryFileType::Other,
+    };
+    let architecture = match read_u16(bytes, 0x12, big_endian)? {
+        3 => Architecture::X86,
+        62 => Architecture::X86_64,
+        40 => Architecture::Arm,
+        183 => Architecture::Aarch64,
+        _ => Architecture::Unknown,
+    };
+    // Android and glibc binaries both leave EI_OSABI at SYSV (or GNU); the NDK's
+    // crtbegin adds an identification note that tells them apart.
+    let android = read_sections(bytes)?
+        .iter()
+        .any(|section| section.name == ANDROID_NOTE_SECTION);
+    let os = match os_abi {
+        0 | 3 if android => Some(BinaryOs::Android),
+        0 | 3 => Some(BinaryOs::Linux),
+        _ => None,
+    };
+    Ok(BinarySlice {
+        architecture,
+        bits,
+        os,
+        file_type,
+    })
+}
+
+fn macho_slice(bytes: &[u8], base: usize) -> Result<BinarySlice, BinaryError> {
+    let magic = read_u32(bytes, base, false)?;
+    let (bits, big_endian) = match magic {
+        MH_MAGIC => (32, false),
+        MH_MAGIC_64 => (64, false),
+        _ if magic.swap_bytes() == MH_MAGIC => (32, true),
+        _ if magic.swap_bytes() == MH_MAGIC_64 => (64, true),
+        _ => return Err(BinaryError::UnknownFormat),
+    };
+    let architecture = match read_u32(bytes, base + 4, big_endian)? {
+        0x7 => Architecture::X86,
+        0x0100_0007 => Architecture::X86_64,
+        0xc => Architecture::Arm,
+        0x0100_000c => Architecture::Aarch64,
+        _ => Architecture::Unknown,
+    };
+    let file_type = match read_u32(bytes, base + 12, big_endian)? {
+        1 => BinaryFileType::Object,
+        2 => BinaryFileType::Executable,
+        6 => BinaryFileType::SharedLibrary,
+        _ => BinaryFileType::Other,
+    };
+    let ncmds = read_u32(bytes, base + 16, big_endian)? as usize;
+    let mut command = base + if bits == 64 { 32 } else { 28 };
+    let mut os = None;
+    for _ in 0..ncmds {
+        let cmd = read_u32(bytes, command, big_endian)?;
+        let cmdsize = read_u32(bytes, command + 4, big_endian)? as usize;
+        if cmdsize == 0 {
+            return Err(BinaryError::Truncated {
+                what: "Mach-O load commands",
+            });
+        }
+        let found = match cmd {
+            LC_BUILD_VERSION => match read_u32(bytes, command + 8, big_endian)? {
+                1 => Some(BinaryOs::Macos),
+                2 => Some(BinaryOs::Ios),
+                7 => Some(BinaryOs::IosSimulator),
+                _ => None,
+            },
+            LC_VERSION_MIN_MACOSX => Some(BinaryOs::Macos),
+            // Before LC_BUILD_VERSION simulator builds used the iOS command;
+            // only the architecture told them apart.
+            LC_VERSION_MIN_IPHONEOS => match architecture {
+                Architecture::X86 | Architecture::X86_64 => Some(BinaryOs::IosSimulator),
+                _ => Some(BinaryOs::Ios),
+            },
+            _ => None,
+        };
+        if found.is_some() {
+            os = found;
+            break;
+        }
+        command += cmdsize;
+    }
+    Ok(BinarySlice {
+        architecture,
+        bits,
+        os,
+        file_type,
+    })
+}
+
+fn pe_slice(bytes: &[u8]) -> Result<BinarySlice, BinaryError> {
+    let pe = read_u32(bytes, 0x3c, false)? as usize;
+    if slice(bytes, pe, 4, "PE header")? != b"PE\0\0" {
+        return Err(BinaryError::UnknownFormat);
+    }
+    let coff = pe + 4;
+    let architecture = match read_u16(bytes, coff, false)? {
+        0x14c => Architecture::X86,
+        0x8664 => Architecture::X86_64,
+        0x1c0 | 0x1c4 => Architecture::Arm,
+        0xaa64 => Architecture::Aarch64,
+        _ => Architecture::Unknown,
+    };
+    let characteristics = read_u16(bytes, coff + 18, false)?;
+    let file_type = if characteristics & 0x2000 != 0 {
+        BinaryFileType::SharedLibrary
+    } else if characteristics & 0x0002 != 0 {
+        BinaryFileType::Executable
+    } else {
+        BinaryFileType::Other
+    };
+    let optional_size = read_u16(bytes, coff + 16, false)?;
+    let bits = match optional_size {
+        0 => match architecture {
+            Architecture::X86 | Architecture::Arm => 32,
+            _ => 64,
+        },
+        _ => match read_u16(bytes, coff + 20, false)? {
+            0x20b => 64,
+            _ => 32,
+        },
+    };
+    Ok(BinarySlice {
+        architecture,
+        bits,
+        os: Some(BinaryOs::Windows),
+        file_type,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 64-bit little-endian Mach-O header of `filetype` for `cputype` with a
+    /// single `LC_BUILD_VERSION` naming `platform`.
+    fn macho(cputype: u32, filetype: u32, platform: u32) -> Vec<u8> {
+        let mut bytes = vec![0u8; 0x60];
+        bytes[..4].copy_from_slice(&MH_MAGIC_64.to_le_bytes());
+        bytes[4..8].copy_from_slice(&cputype.to_le_bytes());
+        bytes[12..16].copy_from_slice(&filetype.to_le_bytes());
+        bytes[16..20].copy_from_slice(&1u32.to_le_bytes());
+        bytes[32..36].copy_from_slice(&LC_BUILD_VERSION.to_le_bytes());
+        bytes[36..40].copy_from_slice(&24u32.to_le_bytes());
+        bytes[40..44].copy_from_slice(&platform.to_le_bytes());
+        bytes
+    }
+
+    fn fat(slices: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&FAT_MAGIC.to_be_bytes());
+        bytes.extend_from_slice(&(slices.len() as u32).to_be_bytes());
+        let mut offset = 0x100u32;
+        for slice in slices {
+            bytes.extend_from_slice(&[0u8; 8]);
+            bytes.extend_from_slice(&offset.to_be_bytes());
+            bytes.extend_from_slice(&(slice.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(&[0u8; 4]);
+            offset += slice.len() as u32;
+        }
+        bytes.resize(0x100, 0);
+        for slice in slices {
+            bytes.extend_from_slice(slice);
+        }
+        bytes
+    }
+
+    /// A PE32+ DLL header for `machine`.
+    fn pe_dll(machine: u16) -> Vec<u8> {
+        let mut bytes = vec![0u8; 0x100];
+        bytes[..2].copy_from_slice(b"MZ");
+        bytes[0x3c..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        bytes[0x40..0x44].copy_from_slice(b"PE\0\0");
+        bytes[0x44..0x46].copy_from_slice(&machine.to_le_bytes());
+        bytes[0x54..0x56].copy_from_slice(&0xf0u16.to_le_bytes());
+        bytes[0x56..0x58].copy_from_slice(&0x2022u16.to_le_bytes());
+        bytes[0x58..0x5a].copy_from_slice(&0x20bu16.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn inspects_macho_and_pe_headers() {
+        let info = inspect(&macho(0x0100_000c, 6, 7)).expect("macho");
+        assert_eq!(info.format, BinaryFormat::MachO);
+        assert_eq!(
+            info.slices,
+            vec![BinarySlice {
+                architecture: Architecture::Aarch64,
+                bits: 64,
+                os: Some(BinaryOs::IosSimulator),
+                file_type: BinaryFileType::SharedLibrary,
+            }]
+        );
+        let info = inspect(&pe_dll(0x8664)).expect("pe");
+        assert_eq!(info.slices[0].architecture, Architecture::X86_64);
+        assert_eq!(info.slices[0].bits, 64);
+        assert_eq!(info.slices[0].file_type, BinaryFileType::SharedLibrary);
+    }
+
+    #[test]
+    fn check_reports_every_mismatch() {
+        let info = inspect(&macho(0x0100_0007, 2, 1)).expect("macho");
+        let mismatch = info
+            .check(PlatformKey::IosArm64, BinaryFileType::SharedLibrary)
+            .expect_err("mismatch");
+        assert_eq!(
+            mismatch.problems,
+            vec!["architecture is x86_64, expected aarch64".to_string()]
+        );
+        let info = inspect(&macho(0x0100_000c, 2, 1)).expect("macho");
+        let mismatch = info
+            .check(PlatformKey::IosArm64, BinaryFileType::SharedLibrary)
+            .expect_err("mismatch");
+        assert_eq!(
+            mismatch.problems,
+            vec![
+                "OS/ABI is macos, expected ios".to_string(),
+                "file type is executable, expected shared-library".to_string(),
+            ]
+        );
+        assert!(inspect(&pe_dll(0xaa64))
+            .expect("pe")
+            .check(PlatformKey::WindowsArm64Msvc, BinaryFileType::SharedLibrary)
+            .is_ok());
+        assert!(inspect(&pe_dll(0xaa64))
+            .expect("pe")
+            .check(PlatformKey::LinuxAarch64, BinaryFileType::SharedLibrary)
+            .is_err());
+    }
+
+    #[test]
+    fn fat_binaries_match_any_slice() {
+        let info = inspect(&fat(&[macho(0x0100_0007, 6, 1), macho(0x0100_000c, 6, 1)]))
+            .expect("fat");
+        assert!(info.universal);
+        assert_eq!(info.slices.len(), 2);
+        assert!(info
+            .check(PlatformKey::MacosArm64, BinaryFileType::SharedLibrary)
+            .is_ok());
+        assert!(info
+            .check(PlatformKey::IosArm64, BinaryFileType::SharedLibrary)
+            .is_err());
+        assert!(info
+            .check(PlatformKey::MacosUniversal, BinaryFileType::SharedLibrary)
+            .is_ok());
+    }
+
+    #[test]
+    fn rejects_a_fat_header_larger_than_the_file() {
+        let mut bytes = fat(&[macho(0x0100_000c, 6, 1)]);
+        bytes[4..8].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(
+            inspect(&bytes),
+            Err(BinaryError::Truncated { what: "fat header" })
+        );
+        assert_eq!(
+            crate::binary::exported_symbols(&bytes),
+            Err(BinaryError::Truncated { what: "fat header" })
+        );
+        assert!(crate::binary::read_sections(&bytes).is_err());
+    }
+
+    #[test]
+    fn universal_platforms_need_every_slice() {
+        let info = inspect(&fat(&[macho(0x0100_000c, 6, 1)])).expect("fat");
+        let mismatch = info
+            .check(PlatformKey::MacosUniversal, BinaryFileType::SharedLibrary)
+            .expect_err("mismatch");
+        assert_eq!(
+            mismatch.problems,
+            vec!["architecture is aarch64, expected x86_64".to_string()]
+        );
+        let info = inspect(&macho(0x0100_000c, 6, 1)).expect("macho");
+        let mismatch = info
+            .check(PlatformKey::MacosUniversal, BinaryFileType::SharedLibrary)
+            .expect_err("mismatch");
+        assert_eq!(
+            mismatch.problems[0],
+            "binary is thin, expected a universal binary"
+        );
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    #[test]
+    fn inspects_the_test_binary() {
+        let exe = std::fs::read(std::env::current_exe().expect("exe")).expect("read exe");
+        let info = inspect(&exe).expect("elf");
+        assert_eq!(info.format, BinaryFormat::Elf);
+        assert_eq!(info.slices[0].architecture, Architecture::X86_64);
+        assert_eq!(info.slices[0].os, Some(BinaryOs::Linux));
+        let mismatch = info
+            .check(PlatformKey::LinuxAarch64, BinaryFileType::SharedLibrary)
+            .expect_err("mismatch");
+        assert!(mismatch.problems[0].starts_with("architecture is x86_64"));
+    }
+}
@@ -0,0 +1,10 @@
+pub mod inspect;
+pub mod sections;
+pub mod symbols;
+
+pub use inspect::{
+    inspect, Architecture, BinaryFileType, BinaryFormat, BinaryInfo, BinaryOs, BinarySlice,
+    PlatformMismatch,
+};
+pub use sections::{diff_sections, read_sections, BinaryError, Section, SectionDiff};
+pub use symbols::{exported_symbols, required_glibc};
@@ -0,0 +1,458 @@
+use std::ops::Range;
+
+/// Pseudo-section covering the file headers and load commands before the
+/// first section's data; timestamps and UUIDs that differ between builds live
+/// here.
+pub const HEADERS_SECTION: &str = "(headers)";
+
+pub(super) const ELF_MAGIC: &[u8] = b"\x7fELF";
+pub(super) const MH_MAGIC: u32 = 0xfeed_face;
+pub(super) const MH_MAGIC_64: u32 = 0xfeed_facf;
+pub(super) const FAT_MAGIC: u32 = 0xcafe_babe;
+const LC_SEGMENT: u32 = 0x1;
+const LC_SEGMENT_64: u32 = 0x19;
+const SHT_NOBITS: u32 = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BinaryError {
+    UnknownFormat,
+    Truncated { what: &'static str },
+}
+
+impl std::fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BinaryError::UnknownFormat => write!(f, "not an ELF, Mach-O or PE binary"),
+            BinaryError::Truncated { what } => write!(f, "binary is truncated in its {}", what),
+        }
+    }
+}
+
+impl std::error::Error for BinaryError {}
+
+/// A named region of a binary. `data` is the byte range in the file; sections
+/// that occupy no file space (`.bss`, zero-fill) have an empty range.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Section {
+    pub name: String,
+    pub data: Range<usize>,
+}
+
+/// A section whose contents differ between two builds; a `None` size means
+/// the section is missing on that side.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionDiff {
+    pub name: String,
+    pub left_size: Option<u64>,
+    pub right_size: Option<u64>,
+}
+
+/// List the sections of an ELF, Mach-O (thin or fat) or PE binary, preceded by
+/// [`HEADERS_SECTION`]. Sections of a fat Mach-O slice are prefixed with
+/// `[<slice index>]`.
+pub fn read_sections(bytes: &[u8]) -> Result<Vec<Section>, BinaryError> {
+    let mut sections = if bytes.starts_with(ELF_MAGIC) {
+        elf_sections(bytes)?
+    } else if bytes.starts_with(b"MZ") {
+        pe_sections(bytes)?
+    } else if read_u32(bytes, 0, true).ok() == Some(FAT_MAGIC) {
+        return fat_sections(bytes);
+    } else {
+        macho_sections(bytes, 0)?
+    };
+    let headers_end = sections
+        .iter()
+        .filter(|section| !section.data.is_empty())
+        .map(|section| section.data.start)
+        .min()
+        .unwrap_or(bytes.len());
+    sections.insert(
+        0,
+        Section {
+            name: HEADERS_SECTION.to_string(),
+            data: 0..headers_end,
+        },
+    );
+    Ok(sections)
+}
+
+/// Compare two builds of the same binary section by section, returning every
+/// section whose bytes differ or that only one side has.
+pub fn diff_sections(left: &[u8], right: &[u8]) -> Result<Vec<SectionDiff>, BinaryError> {
+    let left_sections = read_sections(left)?;
+    let right_sections = read_sections(right)?;
+    let mut diffs = Vec::new();
+    for section in &left_sections {
+        let other = right_sections
+            .iter()
+            .find(|candidate| candidate.name == section.name);
+        let differs = match other {
+            Some(other) => left[section.data.clone()] != right[other.data.clone()],
+            None => true,
+        };
+        if differs {
+            diffs.push(SectionDiff {
+                name: section.name.clone(),
+                left_size: Some(section.data.len() as u64),
+                right_size: other.map(|other| other.data.len() as u64),
+            });
+        }
+    }
+    for section in &right_sections {
+        if !left_sections
+            .iter()
+            .any(|candidate| candidate.name == section.name)
+        {
+            diffs.push(SectionDiff {
+                name: section.name.clone(),
+                left_size: None,
+                right_size: Some(section.data.len() as u64),
+            });
+        }
+    }
+    Ok(diffs)
+}
+
+fn elf_sections(bytes: &[u8]) -> Result<Vec<Section>, BinaryError> {
+    let wide = match bytes.get(4) {
+        Some(1) => false,
+        Some(2) => true,
+        _ => return Err(BinaryError::Truncated { what: "ELF header" }),
+    };
+    let big_endian = bytes.get(5) == Some(&2);
+    let (shoff, shentsize, shnum, shstrndx) = if wide {
+        (
+            read_u64(bytes, 0x28, big_endian)? as usize,
+            read_u16(bytes, 0x3a, big_endian)? as usize,
+            read_u16(bytes, 0x3c, big_endian)? as usize,
+            read_u16(bytes, 0x3e, big_endian)? as usize,
+        )
+    } else {
+        (
+            read_u32(bytes, 0x20, big_endian)? as usize,
+            read_u16(bytes, 0x2e, big_endian)? as usize,
+            read_u16(bytes, 0x30, big_endian)? as usize,
+            read_u16(bytes, 0x32, big_endian)? as usize,
+        )
+    };
+    let header = |index: usize| -> Result<(u32, u32, Range<usize>), BinaryError> {
+        let base = shoff + index * shentsize;
+        let name = read_u32(bytes, base, big_endian)?;
+        let kind = read_u32(bytes, base + 4, big_endian)?;
+        let (offset, size) = if wide {
+            (
+                read_u64(bytes, base + 0x18, big_endian)? as usize,
+                read_u64(bytes, base + 0x20, big_endian)? as usize,
+            )
+        } else {
+            (
+                read_u32(bytes, base + 0x10, big_endian)? as usize,
+                read_u32(bytes, base + 0x14, big_endian)? as usize,
+            )
+        };
+        let data = if kind == SHT_NOBITS {
+            offset..offset
+        } else {
+            file_range(bytes, offset, size, "ELF section")?
+        };
+        Ok((name, kind, data))
+    };
+    if shnum == 0 {
+        return Ok(Vec::new());
+    }
+    let (_, _, names) = header(shstrndx)?;
+    let names = &bytes[names];
+    let mut sections = Vec::with_capacity(shnum);
+    for index in 1..shnum {
+        let (name, _, data) = header(index)?;
+        sections.push(Section {
+            name: c_string(names.get(name as usize..).unwrap_or_default()),
+            data,
+        });
+    }
+    Ok(sections)
+}
+
+fn macho_sections(bytes: &[u8], base: usize) -> Result<Vec<Section>, BinaryError> {
+    let magic = read_u32(bytes, base, false)?;
+    let (wide, big_endian) = match magic {
+        MH_MAGIC => (false, false),
+        MH_MAGIC_64 => (true, false),
+        _ if magic.swap_bytes() == MH_MAGIC => (false, true),
+        _ if magic.swap_bytes() == MH_MAGIC_64 => (true, true),
+        _ => return Err(BinaryError::UnknownFormat),
+    };
+    let ncmds = read_u32(bytes, base + 16, big_endian)? as usize;
+    let mut command = base + if wide { 32 } else { 28 };
+    let mut sections = Vec::new();
+    for _ in 0..ncmds {
+        let cmd = read_u32(bytes, command, big_endian)?;
+        let cmdsize = read_u32(bytes, command + 4, big_endian)? as usize;
+        if cmdsize == 0 {
+            return Err(BinaryError::Truncated {
+                what: "Mach-O load commands",
+            });
+        }
+        if cmd == LC_SEGMENT || cmd == LC_SEGMENT_64 {
+            let segment_wide = cmd == LC_SEGMENT_64;
+            let nsects_at = command + if segment_wide { 64 } else { 48 };
+            let nsects = read_u32(bytes, nsects_at, big_endian)? as usize;
+            let mut section = nsects_at + 8;
+            for _ in 0..nsects {
+                let sectname = c_string(slice(bytes, section, 16, "Mach-O section")?);
+                let segname = c_string(slice(bytes, section + 16, 16, "Mach-O section")?);
+                let (size, offset, flags) = if segment_wide {
+                    (
+                        read_u64(bytes, section + 40, big_endian)? as usize,
+                        read_u32(bytes, section + 48, big_endian)? as usize,
+                        read_u32(bytes, section + 64, big_endian)?,
+                    )
+                } else {
+                    (
+                        read_u32(bytes, section + 36, big_endian)? as usize,
+                        read_u32(bytes, section + 40, big_endian)? as usize,
+                        read_u32(bytes, section + 56, big_endian)?,
+                    )
+                };
+                // S_ZEROFILL, S_GB_ZEROFILL and S_THREAD_LOCAL_ZEROFILL.
+                let data = if matches!(flags & 0xff, 0x1 | 0xc | 0x12) {
+                    base + offset..base + offset
+                } else {
+                    file_range(bytes, base + offset, size, "Mach-O section")?
+                };
+                sections.push(Section {
+                    name: format!("{},{}", segname, sectname),
+                    data,
+                });
+                section += if segment_wide { 80 } else { 68 };
+            }
+        }
+        command += cmdsize;
+    }
+    Ok(sections)
+}
+
+fn fat_sections(bytes: &[u8]) -> Result<Vec<Section>, BinaryError> {
+    let nfat = fat_slice_count(bytes)?;
+    let mut sections = vec![Section {
+        name: HEADERS_SECTION.to_string(),
+        data: 0..8 + nfat * 20,
+    }];
+    for index in 0..nfat {
+        let entry = 8 + index * 20;
+        let offset = read_u32(bytes, entry + 8, true)? as usize;
+        let size = read_u32(bytes, entry + 12, true)? as usize;
+        let range = file_range(bytes, offset, size, "fat Mach-O slice")?;
+        let slice_sections = macho_sections(&bytes[..range.end], range.start)?;
+        let headers_end = slice_sections
+            .iter()
+            .filter(|section| !section.data.is_empty())
+            .map(|section| section.data.start)
+            .min()
+            .unwrap_or(range.end);
+        sections.push(Section {
+            name: format!("[{}]{}", index, HEADERS_SECTION),
+            data: range.start..headers_end,
+        });
+        sections.extend(slice_sections.into_iter().map(|section| Section {
+            name: format!("[{}]{}", index, section.name),
+            data: section.data,
+        }));
+    }
+    Ok(sections)
+}
+
+fn pe_sections(bytes: &[u8]) -> Result<Vec<Section>, BinaryError> {
+    let pe = read_u32(bytes, 0x3c, false)? as usize;
+    if slice(bytes, pe, 4, "PE header")? != b"PE\0\0" {
+        return Err(BinaryError::UnknownFormat);
+    }
+    let coff = pe + 4;
+    let count = read_u16(bytes, coff + 2, false)? as usize;
+    let optional_size = read_u16(bytes, coff + 16, false)? as usize;
+    let table = coff + 20 + optional_size;
+    let mut sections = Vec::with_capacity(count);
+    for index in 0..count {
+        let header = table + index * 40;
+        let name = c_string(slice(bytes, header, 8, "PE section table")?);
+        let size = read_u32(bytes, header + 16, false)? as usize;
+        let offset = read_u32(bytes, header + 20, false)? as usize;
+        sections.push(Section {
+            name,
+            data: file_range(bytes, offset, size, "PE section")?,
+        });
+    }
+    Ok(sections)
+}
+
+fn file_range(
+    bytes: &[u8],
+    offset: usize,
+    size: usize,
+    what: &'static str,
+) -> Result<Range<usize>, BinaryError> {
+    let end = offset
+        .checked_add(size)
+        .filter(|end| *end <= bytes.len())
+        .ok_or(BinaryError::Truncated { what })?;
+    Ok(offset..end)
+}
+
+/// Number of slices a fat Mach-O header lists, once its `fat_arch` table is
+/// known to fit in the file, so a corrupt count cannot drive an allocation
+/// or a loop past the end.
+pub(super) fn fat_slice_count(bytes: &[u8]) -> Result<usize, BinaryError> {
+    let nfat = read_u32(bytes, 4, true)? as usize;
+    nfat.checked_mul(20)
+        .and_then(|table| table.checked_add(8))
+        .filter(|end| *end <= bytes.len())
+        .ok_or(BinaryError::Truncated { what: "fat header" })?;
+    Ok(nfat)
+}
+
+pub(super) fn slice<'a>(
+    bytes: &'a [u8],
+    offset: usize,
+    len: usize,
+    what: &'static str,
+) -> Result<&'a [u8], BinaryError> {
+    let range = file_range(bytes, offset, len, what)?;
+    Ok(&bytes[range])
+}
+
+pub(super) fn c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|byte| *byte == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+pub(super) fn read_u16(
+    bytes: &[u8],
+    offset: usize,
+    big_endian: bool,
+) -> Result<u16, BinaryError> {
+    let raw: [u8; 2] = slice(bytes, offset, 2, "header")?
+        .try_into()
+        .map_err(|_| BinaryError::Truncated { what: "header" })?;
+    Ok(if big_endian {
+        u16::from_be_bytes(raw)
+    } else {
+        u16::from_le_bytes(raw)
+    })
+}
+
+pub(super) fn read_u32(
+    bytes: &[u8],
+    offset: usize,
+    big_endian: bool,
+) -> Result<u32, BinaryError> {
+    let raw: [u8; 4] = slice(bytes, offset, 4, "header")?
+        .try_into()
+        .map_err(|_| BinaryError::Truncated { what: "header" })?;
+    Ok(if big_endian {
+        u32::from_be_bytes(raw)
+    } else {
+        u32::from_le_bytes(raw)
+    })
+}
+
+pub(super) fn read_u64(
+    bytes: &[u8],
+    offset: usize,
+    big_endian: bool,
+) -> Result<u64, BinaryError> {
+    let raw: [u8; 8] = slice(bytes, offset, 8, "header")?
+        .try_into()
+        .map_err(|_| BinaryError::Truncated { what: "header" })?;
+    Ok(if big_endian {
+        u64::from_be_bytes(raw)
+    } else {
+        u64::from_le_bytes(raw)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A PE image with one `.text` section holding `text`.
+    fn pe(text: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 0x200];
+        bytes[..2].copy_from_slice(b"MZ");
+        bytes[0x3c..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        bytes[0x40..0x44].copy_from_slice(b"PE\0\0");
+        bytes[0x46..0x48].copy_from_slice(&1u16.to_le_bytes());
+        let header = 0x40 + 4 + 20;
+        bytes[header..header + 5].copy_from_slice(b".text");
+        bytes[header + 16..header + 20].copy_from_slice(&(text.len() as u32).to_le_bytes());
+        bytes[header + 20..header + 24].copy_from_slice(&0x200u32.to_le_bytes());
+        bytes.extend_from_slice(text);
+        bytes
+    }
+
+    /// A 64-bit Mach-O with one `__TEXT,__text` section holding `text`.
+    fn macho(text: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 0x100];
+        bytes[..4].copy_from_slice(&MH_MAGIC_64.to_le_bytes());
+        bytes[16..20].copy_from_slice(&1u32.to_le_bytes());
+        let command = 32;
+        bytes[command..command + 4].copy_from_slice(&LC_SEGMENT_64.to_le_bytes());
+        bytes[command + 4..command + 8].copy_from_slice(&(72u32 + 80).to_le_bytes());
+        bytes[command + 64..command + 68].copy_from_slice(&1u32.to_le_bytes());
+        let section = command + 72;
+        bytes[section..section + 6].copy_from_slice(b"__text");
+        bytes[section + 16..section + 22].copy_from_slice(b"__TEXT");
+        bytes[section + 40..section + 48].copy_from_slice(&(text.len() as u64).to_le_bytes());
+        bytes[section + 48..section + 52].copy_from_slice(&0x100u32.to_le_bytes());
+        bytes.extend_from_slice(text);
+        bytes
+    }
+
+    #[test]
+    fn reads_pe_and_macho_section_tables() {
+        let names: Vec<String> = read_sections(&pe(b"code"))
+            .expect("pe")
+            .into_iter()
+            .map(|section| section.name)
+            .collect();
+        assert_eq!(names, vec![HEADERS_SECTION, ".text"]);
+        let sections = read_sections(&macho(b"code")).expect("macho");
+        assert_eq!(sections[1].name, "__TEXT,__text");
+        assert_eq!(sections[1].data, 0x100..0x104);
+        assert_eq!(sections[0].data, 0..0x100);
+    }
+
+    #[test]
+    fn diff_names_only_changed_sections() {
+        assert!(diff_sections(&pe(b"code"), &pe(b"code")).expect("diff").is_empty());
+        let diffs = diff_sections(&macho(b"code"), &macho(b"c0de")).expect("diff");
+        assert_eq!(
+            diffs,
+            vec![SectionDiff {
+                name: "__TEXT,__text".to_string(),
+                left_size: Some(4),
+                right_size: Some(4),
+            }]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn reads_elf_sections_of_the_test_binary() {
+        let exe = std::fs::read(std::env::current_exe().expect("exe")).expect("read exe");
+        let sections = read_sections(&exe).expect("elf");
+        let text = sections
+            .iter()
+            .find(|section| section.name == ".text")
+            .expect(".text");
+        let mut changed = exe.clone();
+        changed[text.data.start] ^= 0xff;
+        let diffs = diff_sections(&exe, &changed).expect("diff");
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].name, ".text");
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        assert_eq!(read_sections(b"!<arch>\n"), Err(BinaryError::UnknownFormat));
+    }
+}
@@ -0,0 +1,458 @@
+use std::collections::BTreeSet;
+
+use super::sections::{
+    c_string, fat_slice_count, read_sections, read_u16, read_u32, slice, BinaryError, ELF_MAGIC,
+    FAT_MAGIC, MH_MAGIC, MH_MAGIC_64,
+};
+
+const LC_SYMTAB: u32 = 0x2;
+const LC_DYLD_INFO: u32 = 0x22;
+const LC_DYLD_INFO_ONLY: u32 = 0x8000_0022;
+const LC_DYLD_EXPORTS_TRIE: u32 = 0x8000_0033;
+const PE32_PLUS_MAGIC: u16 = 0x20b;
+
+/// Sorted names a dynamic library exports: defined, default-visibility
+/// `.dynsym` entries for ELF, the export trie (or external symbols) for
+/// Mach-O, and the export name table for PE. Mach-O names lose the leading
+/// `_` the Apple toolchain adds, so every platform lists the same C names.
+pub fn exported_symbols(bytes: &[u8]) -> Result<Vec<String>, BinaryError> {
+    let mut symbols = BTreeSet::new();
+    if bytes.starts_with(ELF_MAGIC) {
+        elf_exports(bytes, &mut symbols)?;
+    } else if bytes.starts_with(b"MZ") {
+        pe_exports(bytes, &mut symbols)?;
+    } else if read_u32(bytes, 0, true).ok() == Some(FAT_MAGIC) {
+        let nfat = fat_slice_count(bytes)?;
+        for index in 0..nfat {
+            let offset = read_u32(bytes, 8 + index * 20 + 8, true)? as usize;
+            macho_exports(bytes, offset, &mut symbols)?;
+        }
+    } else {
+        macho_exports(bytes, 0, &mut symbols)?;
+    }
+    Ok(symbols.into_iter().collect())
+}
+
+fn elf_exports(bytes: &[u8], symbols: &mut BTreeSet<String>) -> Result<(), BinaryError> {
+    let wide = bytes.get(4) == Some(&2);
+    let big_endian = bytes.get(5) == Some(&2);
+    let sections = read_sections(bytes)?;
+    let find = |name: &str| sections.iter().find(|section| section.name == name);
+    let (Some(dynsym), Some(dynstr)) = (find(".dynsym"), find(".dynstr")) else {
+        return Ok(());
+    };
+    let strings = &bytes[dynstr.data.clone()];
+    let entry_size = if wide { 24 } else { 16 };
+    // Entry 0 is the reserved undefined symbol.
+    let mut entry = dynsym.data.start + entry_size;
+    while entry + entry_size <= dynsym.data.end {
+        let name = read_u32(bytes, entry, big_endian)? as usize;
+        let (info, other, shndx) = if wide {
+            let shndx = read_u16(bytes, entry + 6, big_endian)?;
+            (bytes[entry + 4], bytes[entry + 5], shndx)
+        } else {
+            let shndx = read_u16(bytes, entry + 14, big_endian)?;
+            (bytes[entry + 12], bytes[entry + 13], shndx)
+        };
+        let binding = info >> 4;
+        let kind = info & 0xf;
+        // Global or weak, not a section/file symbol, default or protected
+        // visibility, and defined in this object.
+        let exported = matches!(binding, 1 | 2)
+            && !matches!(kind, 3 | 4)
+            && matches!(other & 0x3, 0 | 3)
+            && shndx != 0;
+        if exported {
+            let name = c_string(strings.get(name..).unwrap_or_default());
+            if !name.is_empty() {
+                symbols.insert(name);
+            }
+        }
+        entry += entry_size;
+    }
+    Ok(())
+}
+
+/// The newest `GLIBC_<major>.<minor>` symbol version an ELF object needs,
+/// read from its `.gnu.version_r` table. `None` for objects that need no
+/// versioned glibc symbols, including musl builds and non-ELF input.
+pub fn required_glibc(bytes: &[u8]) -> Result<Option<(u32, u32)>, BinaryError> {
+    const TRUNCATED: BinaryError = BinaryError::Truncated {
+        what: "ELF version needs",
+    };
+    if !bytes.starts_with(ELF_MAGIC) {
+        return Ok(None);
+    }
+    let big_endian = bytes.get(5) == Some(&2);
+    let sections = read_sections(bytes)?;
+    let find = |name: &str| sections.iter().find(|section| section.name == name);
+    let (Some(needs), Some(dynstr)) = (find(".gnu.version_r"), find(".dynstr")) else {
+        return Ok(None);
+    };
+    let strings = &bytes[dynstr.data.clone()];
+    let end = needs.data.end;
+    let fits = |offset: usize| offset.checked_add(16).is_some_and(|next| next <= end);
+    let mut newest = None;
+    // Elf_Verneed and Elf_Vernaux records are 16 bytes on both ELF classes
+    // and chain through relative `next` offsets; a zero offset ends a chain.
+    let mut need = needs.data.start;
+    while fits(need) {
+        let count = read_u16(bytes, need + 2, big_endian)?;
+        let mut aux = need
+            .checked_add(read_u32(bytes, need + 8, big_endian)? as usize)
+            .ok_or(TRUNCATED)?;
+        for _ in 0..count {
+            if !fits(aux) {
+                return Err(TRUNCATED);
+            }
+            let name = read_u32(bytes, aux + 8, big_endian)? as usize;
+            let name = c_string(strings.get(name..).unwrap_or_default());
+            newest = newest.max(glibc_version(&name));
+            aux = aux
+                .checked_add(read_u32(bytes, aux + 12, big_endian)? as usize)
+                .ok_or(TRUNCATED)?;
+        }
+        match read_u32(bytes, need + 12, big_endian)? {
+            0 => break,
+            next => need = need.checked_add(next as usize).ok_or(TRUNCATED)?,
+        }
+    }
+    Ok(newest)
+}
+
+/// `(2, 34)` for `GLIBC_2.34`; `GLIBC_2.2.5` counts as 2.2.
+fn glibc_version(name: &str) -> Option<(u32, u32)> {
+    let mut parts = name.strip_prefix("GLIBC_")?.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+fn macho_exports(
+    bytes: &[u8],
+    base: usize,
+    symbols: &mut BTreeSet<String>,
+) -> Result<(), BinaryError> {
+    let magic = read_u32(bytes, base, false)?;
+    let (wide, big_endian) = match magic {
+        MH_MAGIC => (false, false),
+        MH_MAGIC_64 => (true, false),
+        _ if magic.swap_bytes() == MH_MAGIC => (false, true),
+        _ if magic.swap_bytes() == MH_MAGIC_64 => (true, true),
+        _ => return Err(BinaryError::UnknownFormat),
+    };
+    let ncmds = read_u32(bytes, base + 16, big_endian)? as usize;
+    let mut command = base + if wide { 32 } else { 28 };
+    let mut trie = None;
+    let mut symtab = None;
+    for _ in 0..ncmds {
+        let cmd = read_u32(bytes, command, big_endian)?;
+        let cmdsize = read_u32(bytes, command + 4, big_endian)? as usize;
+        if cmdsize == 0 {
+            return Err(BinaryError::Truncated {
+                what: "Mach-O load commands",
+            });
+        }
+        match cmd {
+            LC_DYLD_INFO | LC_DYLD_INFO_ONLY => {
+                let offset = read_u32(bytes, command + 40, big_endian)? as usize;
+                let size = read_u32(bytes, command + 44, big_endian)? as usize;
+                if size > 0 {
+                    trie = Some((offset, size));
+                }
+            }
+            LC_DYLD_EXPORTS_TRIE => {
+                let offset = read_u32(bytes, command + 8, big_endian)? as usize;
+                let size = read_u32(bytes, command + 12, big_endian)? as usize;
+                if size > 0 {
+                    trie = Some((offset, size));
+                }
+            }
+            LC_SYMTAB => {
+                symtab = Some((
+                    read_u32(bytes, command + 8, big_endian)? as usize,
+                    read_u32(bytes, command + 12, big_endian)? as usize,
+                    read_u32(bytes, command + 16, big_endian)? as usize,
+                    read_u32(bytes, command + 20, big_endian)? as usize,
+                ));
+            }
+            _ => {}
+        }
+        command += cmdsize;
+    }
+    let mut names = Vec::new();
+    if let Some((offset, size)) = trie {
+        let trie = slice(bytes, base + offset, size, "Mach-O export trie")?;
+        walk_export_trie(trie, 0, String::new(), &mut names, 0)?;
+    } else if let Some((symoff, nsyms, stroff, strsize)) = symtab {
+        let strings = slice(bytes, base + stroff, strsize, "Mach-O string table")?;
+        let entry_size = if wide { 16 } else { 12 };
+        for index in 0..nsyms {
+            let entry = base + symoff + index * entry_size;
+            let name = read_u32(bytes, entry, big_endian)? as usize;
+            let n_type = slice(bytes, entry + 4, 1, "Mach-O symbol table")?[0];
+            // Not a debugging entry, not private extern, defined in a
+            // section (N_SECT) and external (N_EXT).
+            if n_type & 0xf0 == 0 && n_type & 0x0f == 0x0f {
+                names.push(c_string(strings.get(name..).unwrap_or_default()));
+            }
+        }
+    }
+    for name in names {
+        let name = name.strip_prefix('_').map(str::to_string).unwrap_or(name);
+        if !name.is_empty() {
+            symbols.insert(name);
+        }
+    }
+    Ok(())
+}
+
+/// Depth-first walk of a dyld export trie; terminal nodes are exports.
+fn walk_export_trie(
+    trie: &[u8],
+    node: usize,
+    prefix: String,
+    names: &mut Vec<String>,
+    depth: usize,
+) -> Result<(), BinaryError> {
+    const TRUNCATED: BinaryError = BinaryError::Truncated {
+        what: "Mach-O export trie",
+    };
+    if depth > 128 {
+        return Err(TRUNCATED);
+    }
+    let (terminal_size, mut cursor) = read_uleb128(trie, node)?;
+    if terminal_size > 0 {
+        names.push(prefix.clone());
+    }
+    cursor = usize::try_from(terminal_size)
+        .ok()
+        .and_then(|size| cursor.checked_add(size))
+        .ok_or(TRUNCATED)?;
+    let children = *trie.get(cursor).ok_or(TRUNCATED)?;
+    cursor += 1;
+    for _ in 0..children {
+        let label_end = trie
+            .get(cursor..)
+            .ok_or(TRUNCATED)?
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or(TRUNCATED)?;
+        let label = String::from_utf8_lossy(&trie[cursor..cursor + label_end]).into_owned();
+        cursor += label_end + 1;
+        let (child, next) = read_uleb128(trie, cursor)?;
+        cursor = next;
+        if child as usize >= trie.len() || child == 0 {
+            return Err(TRUNCATED);
+        }
+        let path = format!("{}{}", prefix, label);
+        walk_export_trie(trie, child as usize, path, names, depth + 1)?;
+    }
+    Ok(())
+}
+
+fn read_uleb128(bytes: &[u8], mut offset: usize) -> Result<(u64, usize), BinaryError> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(offset).ok_or(BinaryError::Truncated {
+            what: "Mach-O export trie",
+        })?;
+        offset += 1;
+        if shift < 64 {
+            value |= u64::from(byte & 0x7f) << shift;
+        }
+        if byte & 0x80 == 0 {
+            return Ok((value, offset));
+        }
+        shift += 7;
+    }
+}
+
+fn pe_exports(bytes: &[u8], symbols: &mut BTreeSet<String>) -> Result<(), BinaryError> {
+    let pe = read_u32(bytes, 0x3c, false)? as usize;
+    if slice(bytes, pe, 4, "PE header")? != b"PE\0\0" {
+        return Err(BinaryError::UnknownFormat);
+    }
+    let coff = pe + 4;
+    let count = read_u16(bytes, coff + 2, false)? as usize;
+    let optional_size = read_u16(bytes, coff + 16, false)? as usize;
+    let optional = coff + 20;
+    if optional_size == 0 {
+        return Ok(());
+    }
+    let directories = if read_u16(bytes, optional, false)? == PE32_PLUS_MAGIC {
+        optional + 112
+    } else {
+        optional + 96
+    };
+    let export_rva = read_u32(bytes, directories, false)?;
+    if export_rva == 0 {
+        return Ok(());
+    }
+    let table = optional + optional_size;
+    let mut section_map = Vec::with_capacity(count);
+    for index in 0..count {
+        let header = table + index * 40;
+        let virtual_size = read_u32(bytes, header + 8, false)?;
+        let virtual_address = read_u32(bytes, header + 12, false)?;
+        let raw_size = read_u32(bytes, header + 16, false)?;
+        let raw_offset = read_u32(bytes, header + 20, false)?;
+        section_map.push((virtual_address, virtual_size.max(raw_size), raw_offset));
+    }
+    let to_offset = |rva: u32| -> Result<usize, BinaryError> {
+        section_map
+            .iter()
+            .find(|(address, size, _)| rva.checked_sub(*address).is_some_and(|delta| delta < *size))
+            .and_then(|(address, _, raw)| (rva - address).checked_add(*raw))
+            .map(|offset| offset as usize)
+            .ok_or(BinaryError::Truncated {
+                what: "PE export table",
+            })
+    };
+    let directory = to_offset(export_rva)?;
+    let names = read_u32(bytes, directory + 24, false)? as usize;
+    let name_table = to_offset(read_u32(bytes, directory + 32, false)?)?;
+    for index in 0..names {
+        let name = to_offset(read_u32(bytes, name_table + index * 4, false)?)?;
+        let name = c_string(bytes.get(name..).unwrap_or_default());
+        if !name.is_empty() {
+            symbols.insert(name);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A PE32+ image whose export table names `names`, all in one section.
+    fn pe_with_exports(names: &[&str]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 0x200];
+        bytes[..2].copy_from_slice(b"MZ");
+        bytes[0x3c..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        bytes[0x40..0x44].copy_from_slice(b"PE\0\0");
+        let coff = 0x44;
+        bytes[coff + 2..coff + 4].copy_from_slice(&1u16.to_le_bytes());
+        bytes[coff + 16..coff + 18].copy_from_slice(&0xf0u16.to_le_bytes());
+        let optional = coff + 20;
+        bytes[optional..optional + 2].copy_from_slice(&PE32_PLUS_MAGIC.to_le_bytes());
+        // Export directory at RVA 0x1000, mapped to file offset 0x200.
+        bytes[optional + 112..optional + 116].copy_from_slice(&0x1000u32.to_le_bytes());
+        let header = optional + 0xf0;
+        bytes[header..header + 6].copy_from_slice(b".edata");
+        bytes[header + 8..header + 12].copy_from_slice(&0x200u32.to_le_bytes());
+        bytes[header + 12..header + 16].copy_from_slice(&0x1000u32.to_le_bytes());
+        bytes[header + 16..header + 20].copy_from_slice(&0x200u32.to_le_bytes());
+        bytes[header + 20..header + 24].copy_from_slice(&0x200u32.to_le_bytes());
+
+        let mut section = vec![0u8; 40];
+        section[24..28].copy_from_slice(&(names.len() as u32).to_le_bytes());
+        section[32..36].copy_from_slice(&0x1028u32.to_le_bytes());
+        let strings_start = 40 + names.len() * 4;
+        let mut strings = Vec::new();
+        for name in names {
+            let rva = 0x1000 + (strings_start + strings.len()) as u32;
+            section.extend_from_slice(&rva.to_le_bytes());
+            strings.extend_from_slice(name.as_bytes());
+            strings.push(0);
+        }
+        section.extend_from_slice(&strings);
+        section.resize(0x200, 0);
+        bytes.extend_from_slice(&section);
+        bytes
+    }
+
+    #[test]
+    fn reads_pe_export_names_sorted() {
+        let symbols = exported_symbols(&pe_with_exports(&["zeta", "alpha"])).expect("pe");
+        assert_eq!(symbols, vec!["alpha", "zeta"]);
+    }
+
+    #[test]
+    fn walks_macho_export_trie() {
+        // root -> "_demo_" -> { "add" (terminal), "sub" (terminal) }
+        let trie = [
+            0x00, 0x01, b'_', b'd', b'e', b'm', b'o', b'_', 0x00, 0x0b, // root
+            0x00, // unused
+            0x00, 0x02, b'a', b'd', b'd', 0x00, 0x17, b's', b'u', b'b', 0x00, 0x1b, //
+            0x02, 0x00, 0x00, 0x00, // "add" terminal
+            0x02, 0x00, 0x00, 0x00, // "sub" terminal
+        ];
+        let mut names = Vec::new();
+        walk_export_trie(&trie, 0, String::new(), &mut names, 0).expect("trie");
+        assert_eq!(names, vec!["_demo_add", "_demo_sub"]);
+    }
+
+    #[test]
+    fn rejects_an_export_trie_pointing_past_usize() {
+        // A terminal size of u64::MAX.
+        let trie = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        let mut names = Vec::new();
+        assert_eq!(
+            walk_export_trie(&trie, 0, String::new(), &mut names, 0),
+            Err(BinaryError::Truncated {
+                what: "Mach-O export trie"
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_pe_sections_that_wrap_around() {
+        let header = 0x44 + 20 + 0xf0;
+        let directories = 0x44 + 20 + 112;
+        let mut wrapping = pe_with_exports(&["demo"]);
+        wrapping[header + 8..header + 12].copy_from_slice(&0x2000u32.to_le_bytes());
+        wrapping[header + 12..header + 16].copy_from_slice(&0xffff_f000u32.to_le_bytes());
+        wrapping[directories..directories + 4].copy_from_slice(&0xffff_f800u32.to_le_bytes());
+        assert!(exported_symbols(&wrapping).is_err());
+
+        let mut far = pe_with_exports(&["demo"]);
+        far[header + 20..header + 24].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(exported_symbols(&far).is_err());
+    }
+
+    #[test]
+    fn parses_glibc_symbol_versions() {
+        assert_eq!(glibc_version("GLIBC_2.34"), Some((2, 34)));
+        assert_eq!(glibc_version("GLIBC_2.2.5"), Some((2, 2)));
+        assert_eq!(glibc_version("GLIBC_PRIVATE"), None);
+        assert_eq!(glibc_version("GCC_3.0"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn reads_the_glibc_a_linux_executable_needs() {
+        let Ok(bytes) = std::fs::read("/proc/self/exe") else {
+            return;
+        };
+        if cfg!(target_env = "gnu") {
+            let (major, minor) = required_glibc(&bytes).expect("elf").expect("glibc");
+            assert_eq!(major, 2);
+            assert!(minor >= 2);
+        } else {
+            assert_eq!(required_glibc(&bytes).expect("elf"), None);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn reads_elf_dynamic_exports_of_libc() {
+        let candidates = [
+            "/lib/x86_64-linux-gnu/libc.so.6",
+            "/lib/aarch64-linux-gnu/libc.so.6",
+            "/lib64/libc.so.6",
+            "/usr/lib/libc.so.6",
+        ];
+        let Some(libc) = candidates
+            .iter()
+            .find(|path| std::path::Path::new(path).is_file())
+        else {
+            return;
+        };
+        let symbols = exported_symbols(&std::fs::read(libc).expect("read libc")).expect("elf");
+        assert!(symbols.iter().any(|symbol| symbol == "malloc"));
+        assert!(symbols.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
@@ -0,0 +1,59 @@
+use crate::platform::PlatformKey;
+
+/// .NET runtime identifier of a platform, the `runtimes/<rid>/native/`
+/// directory NuGet picks native libraries from. `None` for
+/// `macos-universal`, whose thin slices are packed under their own RIDs.
+pub fn runtime_identifier(platform: PlatformKey) -> Option<&'static str> {
+    match platform {
+        PlatformKey::LinuxX86_64 => Some("linux-x64"),
+        PlatformKey::LinuxAarch64 => Some("linux-arm64"),
+        PlatformKey::LinuxX86_64Musl => Some("linux-musl-x64"),
+        PlatformKey::LinuxAarch64Musl => Some("linux-musl-arm64"),
+        PlatformKey::MacosArm64 => Some("osx-arm64"),
+        PlatformKey::MacosX86_64 => Some("osx-x64"),
+        PlatformKey::WindowsX86_64Msvc => Some("win-x64"),
+        PlatformKey::WindowsArm64Msvc => Some("win-arm64"),
+        PlatformKey::IosArm64 => Some("ios-arm64"),
+        PlatformKey::IosSimulatorArm64 => Some("iossimulator-arm64"),
+        PlatformKey::IosSimulatorX86_64 => Some("iossimulator-x64"),
+        PlatformKey::AndroidArm64 => Some("android-arm64"),
+        PlatformKey::AndroidArmv7 => Some("android-arm"),
+        PlatformKey::AndroidX86_64 => Some("android-x64"),
+        PlatformKey::MacosUniversal => None,
+    }
+}
+
+/// NuGet package file name, `<id>.<version>.nupkg`.
+pub fn nupkg_file_name(id: &str, version: &str) -> String {
+    format!("{}.{}.nupkg", id, version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_platforms_to_runtime_identifiers() {
+        assert_eq!(
+            runtime_identifier(PlatformKey::LinuxX86_64),
+            Some("linux-x64")
+        );
+        assert_eq!(
+            runtime_identifier(PlatformKey::LinuxAarch64Musl),
+            Some("linux-musl-arm64")
+        );
+        assert_eq!(
+            runtime_identifier(PlatformKey::MacosArm64),
+            Some("osx-arm64")
+        );
+        assert_eq!(
+            runtime_identifier(PlatformKey::IosSimulatorArm64),
+            Some("iossimulator-arm64")
+        );
+        assert_eq!(
+            runtime_identifier(PlatformKey::AndroidArmv7),
+            Some("android-arm")
+        );
+        assert_eq!(runtime_identifier(PlatformKey::MacosUniversal), None);
+    }
+}
@@ -0,0 +1,26 @@
+use crate::platform::PlatformKey;
+
+/// JVM target of the glue classes xforge writes into an AAR's `classes.jar`.
+pub const GLUE_JVM_TARGET: &str = "1.8";
+
+/// Android ABI directory (`jni/<abi>/`) for an Android platform.
+pub fn ndk_abi(platform: PlatformKey) -> Option<&'static str> {
+    match platform {
+        PlatformKey::AndroidArm64 => Some("arm64-v8a"),
+        PlatformKey::AndroidArmv7 => Some("armeabi-v7a"),
+        PlatformKey::AndroidX86_64 => Some("x86_64"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_android_platforms_to_ndk_abis() {
+        assert_eq!(ndk_abi(PlatformKey::AndroidArm64), Some("arm64-v8a"));
+        assert_eq!(ndk_abi(PlatformKey::AndroidArmv7), Some("armeabi-v7a"));
+        assert_eq!(ndk_abi(PlatformKey::IosArm64), None);
+    }
+}
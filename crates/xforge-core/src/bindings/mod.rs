@@ -1,4 +1,5 @@
 pub mod dart;
+pub mod dotnet;
 pub mod kotlin;
 pub mod model;
 pub mod python;
@@ -7,5 +8,5 @@ pub mod uniffi;
 
 pub use model::{
     BindingLanguage, BindingMetadata, BindingMetadataError, BindingMetadataSet, DartBinding,
-    KotlinBinding, PythonBinding, SwiftBinding,
+    DotnetBinding, KotlinBinding, PythonBinding, SwiftBinding,
 };
@@ -4,6 +4,7 @@ pub enum BindingLanguage {
     Swift,
     Python,
     Dart,
+    Dotnet,
 }
 
 impl BindingLanguage {
@@ -13,6 +14,7 @@ impl BindingLanguage {
             BindingLanguage::Swift => "swift",
             BindingLanguage::Python => "python",
             BindingLanguage::Dart => "dart",
+            BindingLanguage::Dotnet => "dotnet",
         }
     }
 }
@@ -32,6 +34,7 @@ impl std::str::FromStr for BindingLanguage {
             "swift" => Ok(BindingLanguage::Swift),
             "python" => Ok(BindingLanguage::Python),
             "dart" => Ok(BindingLanguage::Dart),
+            "dotnet" => Ok(BindingLanguage::Dotnet),
             _ => Err(BindingMetadataError::UnknownBinding(value.to_string())),
         }
     }
@@ -43,6 +46,7 @@ pub enum BindingMetadata {
     Kotlin(KotlinBinding),
     Python(PythonBinding),
     Dart(DartBinding),
+    Dotnet(DotnetBinding),
 }
 
 impl BindingMetadata {
@@ -52,6 +56,7 @@ impl BindingMetadata {
             BindingMetadata::Kotlin(_) => BindingLanguage::Kotlin,
             BindingMetadata::Python(_) => BindingLanguage::Python,
             BindingMetadata::Dart(_) => BindingLanguage::Dart,
+            BindingMetadata::Dotnet(_) => BindingLanguage::Dotnet,
         }
     }
 
@@ -61,6 +66,7 @@ impl BindingMetadata {
             BindingMetadata::Kotlin(binding) => binding.validate(),
             BindingMetadata::Python(binding) => binding.validate(),
             BindingMetadata::Dart(binding) => binding.validate(),
+            BindingMetadata::Dotnet(binding) => binding.validate(),
         }
     }
 
@@ -70,6 +76,7 @@ impl BindingMetadata {
             BindingMetadata::Kotlin(binding) => binding.canonical_string(),
             BindingMetadata::Python(binding) => binding.canonical_string(),
             BindingMetadata::Dart(binding) => binding.canonical_string(),
+            BindingMetadata::Dotnet(binding) => binding.canonical_string(),
         }
     }
 }
@@ -216,6 +223,41 @@ impl DartBinding {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DotnetBinding {
+    pub runtime_identifiers: Vec<String>,
+}
+
+impl DotnetBinding {
+    pub fn validate(&self) -> Result<(), BindingMetadataError> {
+        if self.runtime_identifiers.is_empty()
+            || self
+                .runtime_identifiers
+                .iter()
+                .any(|value| value.trim().is_empty())
+        {
+            return Err(BindingMetadataError::MissingField {
+                binding: "dotnet",
+                field: "runtime_identifiers",
+            });
+        }
+        Ok(())
+    }
+
+    pub fn canonical_string(&self) -> String {
+        let mut runtime_identifiers = self
+            .runtime_identifiers
+            .iter()
+            .map(|value| value.trim().to_string())
+            .collect::<Vec<String>>();
+        runtime_identifiers.sort();
+        format!(
+            "dotnet:runtime_identifiers={}",
+            runtime_identifiers.join(",")
+        )
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct BindingMetadataSet {
     pub bindings: Vec<BindingMetadata>,
@@ -288,5 +330,13 @@ mod tests {
             binding.canonical_string(),
             "python:abi_tag=cp311;platform_tag=manylinux_2_28"
         );
+
+        let binding = BindingMetadata::Dotnet(DotnetBinding {
+            runtime_identifiers: vec!["win-x64".to_string(), "linux-arm64".to_string()],
+        });
+        assert_eq!(
+            binding.canonical_string(),
+            "dotnet:runtime_identifiers=linux-arm64,win-x64"
+        );
     }
 }
@@ -0,0 +1,178 @@
+use crate::platform::PlatformKey;
+
+/// Python tag of the wheels xforge writes; the native library is loaded
+/// through `ctypes`, so any Python 3 works.
+pub const WHEEL_PYTHON_TAG: &str = "py3";
+/// ABI tag of those wheels; they do not link against libpython.
+pub const WHEEL_ABI_TAG: &str = "none";
+
+/// Wheel platform tag (PEP 425) for a platform, as a compressed tag set
+/// where pip knows the platform under more than one name. glibc wheels are
+/// tagged for `glibc`, the newest `GLIBC_` version the library needs (see
+/// [`crate::binary::required_glibc`]), and never older than 2.17, the
+/// oldest the Rust targets support. The musl and macOS versions are the
+/// oldest the Rust targets support. `None` for platforms pip does not
+/// install wheels on.
+pub fn wheel_platform_tag(platform: PlatformKey, glibc: Option<(u32, u32)>) -> Option<String> {
+    let tag = match platform {
+        PlatformKey::LinuxX86_64 => return Some(manylinux_tag("x86_64", glibc)),
+        PlatformKey::LinuxAarch64 => return Some(manylinux_tag("aarch64", glibc)),
+        PlatformKey::LinuxX86_64Musl => "musllinux_1_2_x86_64",
+        PlatformKey::LinuxAarch64Musl => "musllinux_1_2_aarch64",
+        PlatformKey::MacosArm64 => "macosx_11_0_arm64",
+        PlatformKey::MacosX86_64 => "macosx_10_12_x86_64",
+        PlatformKey::MacosUniversal => "macosx_10_12_universal2",
+        PlatformKey::WindowsX86_64Msvc => "win_amd64",
+        PlatformKey::WindowsArm64Msvc => "win_arm64",
+        PlatformKey::IosArm64
+        | PlatformKey::IosSimulatorArm64
+        | PlatformKey::IosSimulatorX86_64
+        | PlatformKey::AndroidArm64
+        | PlatformKey::AndroidArmv7
+        | PlatformKey::AndroidX86_64 => return None,
+    };
+    Some(tag.to_string())
+}
+
+/// PEP 600 tag for a library needing `glibc`, with the `manylinux2014`
+/// alias older pips need when that is glibc 2.17.
+fn manylinux_tag(arch: &str, glibc: Option<(u32, u32)>) -> String {
+    let (major, minor) = glibc.unwrap_or((2, 17)).max((2, 17));
+    if (major, minor) == (2, 17) {
+        format!("manylinux_2_17_{}.manylinux2014_{}", arch, arch)
+    } else {
+        format!("manylinux_{}_{}_{}", major, minor, arch)
+    }
+}
+
+/// Wheel file name (`{distribution}-{version}-{python}-{abi}-{platform}.whl`)
+/// with the distribution name normalized and `-` escaped as PEP 427 asks.
+pub fn wheel_file_name(
+    distribution: &str,
+    version: &str,
+    python_tag: &str,
+    abi_tag: &str,
+    platform_tag: &str,
+) -> String {
+    format!(
+        "{}-{}-{}-{}-{}.whl",
+        normalize_distribution(distribution),
+        version.replace('-', "_"),
+        python_tag,
+        abi_tag,
+        platform_tag
+    )
+}
+
+/// Runs of `-`, `_` and `.` collapsed to `_` and lowercased, the form of a
+/// distribution name used in wheel and `.dist-info` names.
+pub fn normalize_distribution(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for ch in name.chars() {
+        if matches!(ch, '-' | '_' | '.') {
+            if !normalized.ends_with('_') {
+                normalized.push('_');
+            }
+        } else {
+            normalized.push(ch.to_ascii_lowercase());
+        }
+    }
+    normalized
+}
+
+/// PEP 440 form of a Cargo version. Pre-releases named `alpha`, `beta` or
+/// `rc` (optionally numbered, as in `1.0.0-rc.1`) map to `a`, `b` and `rc`;
+/// any other pre-release has no PEP 440 equivalent. Build metadata becomes
+/// a local version label.
+pub fn pep440_version(version: &str) -> Option<String> {
+    let (version, local) = match version.split_once('+') {
+        Some((version, local)) => (version, Some(local)),
+        None => (version, None),
+    };
+    let mut pep440 = match version.split_once('-') {
+        None => version.to_string(),
+        Some((release, pre)) => {
+            let (label, number) = pre.split_once('.').unwrap_or((pre, "0"));
+            let label = match label {
+                "alpha" | "a" => "a",
+                "beta" | "b" => "b",
+                "rc" => "rc",
+                _ => return None,
+            };
+            if number.is_empty() || !number.chars().all(|ch| ch.is_ascii_digit()) {
+                return None;
+            }
+            format!("{}{}{}", release, label, number)
+        }
+    };
+    if let Some(local) = local {
+        if local.is_empty()
+            || !local
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '.')
+        {
+            return None;
+        }
+        pep440.push('+');
+        pep440.push_str(local);
+    }
+    Some(pep440)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_platforms_to_wheel_tags() {
+        assert_eq!(
+            wheel_platform_tag(PlatformKey::LinuxAarch64, None).as_deref(),
+            Some("manylinux_2_17_aarch64.manylinux2014_aarch64")
+        );
+        assert_eq!(
+            wheel_platform_tag(PlatformKey::LinuxX86_64Musl, None).as_deref(),
+            Some("musllinux_1_2_x86_64")
+        );
+        assert_eq!(
+            wheel_platform_tag(PlatformKey::MacosUniversal, None).as_deref(),
+            Some("macosx_10_12_universal2")
+        );
+        assert_eq!(
+            wheel_platform_tag(PlatformKey::WindowsX86_64Msvc, None).as_deref(),
+            Some("win_amd64")
+        );
+        assert_eq!(wheel_platform_tag(PlatformKey::AndroidArm64, None), None);
+    }
+
+    #[test]
+    fn tags_glibc_wheels_with_the_glibc_they_need() {
+        assert_eq!(
+            wheel_platform_tag(PlatformKey::LinuxX86_64, Some((2, 2))).as_deref(),
+            Some("manylinux_2_17_x86_64.manylinux2014_x86_64")
+        );
+        assert_eq!(
+            wheel_platform_tag(PlatformKey::LinuxX86_64, Some((2, 34))).as_deref(),
+            Some("manylinux_2_34_x86_64")
+        );
+        assert_eq!(
+            wheel_platform_tag(PlatformKey::LinuxAarch64, Some((2, 28))).as_deref(),
+            Some("manylinux_2_28_aarch64")
+        );
+    }
+
+    #[test]
+    fn names_wheels_per_pep_427() {
+        assert_eq!(
+            wheel_file_name("Demo.FFI-py", "1.2.0", "py3", "none", "win_amd64"),
+            "demo_ffi_py-1.2.0-py3-none-win_amd64.whl"
+        );
+        assert_eq!(pep440_version("1.2.0").as_deref(), Some("1.2.0"));
+        assert_eq!(pep440_version("1.2.0-rc.2").as_deref(), Some("1.2.0rc2"));
+        assert_eq!(pep440_version("1.2.0-beta").as_deref(), Some("1.2.0b0"));
+        assert_eq!(
+            pep440_version("1.2.0+git.1a2b").as_deref(),
+            Some("1.2.0+git.1a2b")
+        );
+        assert_eq!(pep440_version("1.2.0-nightly"), None);
+    }
+}
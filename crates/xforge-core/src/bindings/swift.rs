@@ -0,0 +1,193 @@
+use crate::bindings::SwiftBinding;
+
+/// Oldest `swift-tools-version` that understands `.binaryTarget(url:checksum:)`.
+pub const SPM_TOOLS_VERSION: &str = "5.3";
+
+/// `Package.swift` of a Swift package whose only target is the released
+/// XCFramework zip at `url`. `checksum` is the SHA-256 SwiftPM checks the
+/// download against, as printed by `swift package compute-checksum`.
+pub fn package_manifest(package: &str, module: &str, url: &str, checksum: &str) -> String {
+    format!(
+        "// swift-tools-version:{tools}\n\
+         import PackageDescription\n\
+         \n\
+         let package = Package(\n    \
+             name: \"{package}\",\n    \
+             products: [\n        \
+                 .library(name: \"{package}\", targets: [\"{module}\"]),\n    \
+             ],\n    \
+             targets: [\n        \
+                 .binaryTarget(\n            \
+                     name: \"{module}\",\n            \
+                     url: \"{url}\",\n            \
+                     checksum: \"{checksum}\"\n        \
+                 ),\n    \
+             ]\n\
+         )\n",
+        tools = SPM_TOOLS_VERSION,
+        package = swift_string(package),
+        module = swift_string(module),
+        url = swift_string(url),
+        checksum = checksum,
+    )
+}
+
+/// CocoaPods spec of a pod that downloads a released XCFramework zip and
+/// vendors the framework inside it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Podspec {
+    pub name: String,
+    pub version: String,
+    pub summary: String,
+    pub homepage: String,
+    pub license: Option<String>,
+    pub authors: Vec<String>,
+    /// Download URL of the XCFramework zip.
+    pub source_url: String,
+    /// SHA-256 of the zip, checked by CocoaPods after the download.
+    pub source_sha256: String,
+    /// Framework directory at the root of the zip, `<module>.xcframework`.
+    pub vendored_framework: String,
+    /// iOS deployment target and Swift version of the pod.
+    pub binding: SwiftBinding,
+}
+
+impl Podspec {
+    /// The spec in the Ruby DSL of a `.podspec` file.
+    pub fn to_ruby(&self) -> String {
+        let mut ruby = String::from("Pod::Spec.new do |s|\n");
+        ruby.push_str(&format!("  s.name = {}\n", ruby_string(&self.name)));
+        ruby.push_str(&format!("  s.version = {}\n", ruby_string(&self.version)));
+        ruby.push_str(&format!("  s.summary = {}\n", ruby_string(&self.summary)));
+        ruby.push_str(&format!("  s.homepage = {}\n", ruby_string(&self.homepage)));
+        if let Some(license) = &self.license {
+            ruby.push_str(&format!(
+                "  s.license = {{ :type => {} }}\n",
+                ruby_string(license)
+            ));
+        }
+        if !self.authors.is_empty() {
+            let authors: Vec<String> = self
+                .authors
+                .iter()
+                .map(|author| ruby_string(author))
+                .collect();
+            ruby.push_str(&format!("  s.authors = [{}]\n", authors.join(", ")));
+        }
+        ruby.push_str(&format!(
+            "  s.source = {{ :http => {}, :sha256 => {} }}\n",
+            ruby_string(&self.source_url),
+            ruby_string(&self.source_sha256)
+        ));
+        ruby.push_str(&format!(
+            "  s.platforms = {{ :ios => {} }}\n",
+            ruby_string(&self.binding.deployment_target)
+        ));
+        ruby.push_str(&format!(
+            "  s.swift_versions = [{}]\n",
+            ruby_string(&self.binding.toolchain)
+        ));
+        ruby.push_str(&format!(
+            "  s.vendored_frameworks = {}\n",
+            ruby_string(&self.vendored_framework)
+        ));
+        ruby.push_str("end\n");
+        ruby
+    }
+
+    /// The same spec as `.podspec.json`, the form trunk and `pod ipc spec`
+    /// use.
+    pub fn to_json(&self) -> String {
+        let mut spec = serde_json::json!({
+            "name": self.name,
+            "version": self.version,
+            "summary": self.summary,
+            "homepage": self.homepage,
+            "source": {
+                "http": self.source_url,
+                "sha256": self.source_sha256,
+            },
+            "platforms": {
+                "ios": self.binding.deployment_target,
+            },
+            "swift_versions": [self.binding.toolchain],
+            "vendored_frameworks": self.vendored_framework,
+        });
+        if let Some(license) = &self.license {
+            spec["license"] = serde_json::json!({ "type": license });
+        }
+        if !self.authors.is_empty() {
+            spec["authors"] = serde_json::json!(self.authors);
+        }
+        let mut json = serde_json::to_string_pretty(&spec).expect("podspec json");
+        json.push('\n');
+        json
+    }
+}
+
+/// Body of a Swift string literal holding `value`.
+fn swift_string(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Single-quoted Ruby string literal holding `value`.
+fn ruby_string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_a_binary_target_package() {
+        let manifest = package_manifest(
+            "demo-ffi",
+            "DemoFFI",
+            "https://example.com/download/abc/DemoFFI.xcframework.zip",
+            "00ff",
+        );
+        assert!(manifest.starts_with("// swift-tools-version:5.3\nimport PackageDescription\n"));
+        assert!(
+            manifest.contains("        .library(name: \"demo-ffi\", targets: [\"DemoFFI\"]),\n")
+        );
+        assert!(manifest.contains(
+            "            url: \"https://example.com/download/abc/DemoFFI.xcframework.zip\",\n            checksum: \"00ff\"\n"
+        ));
+        assert!(package_manifest("a\"b", "M", "u", "c").contains("name: \"a\\\"b\""));
+    }
+
+    #[test]
+    fn renders_a_podspec_in_both_forms() {
+        let podspec = Podspec {
+            name: "DemoFFI".to_string(),
+            version: "1.0.0".to_string(),
+            summary: "Demo's FFI".to_string(),
+            homepage: "https://github.com/owner/demo".to_string(),
+            license: Some("MIT".to_string()),
+            authors: Vec::new(),
+            source_url: "https://example.com/abc/demo.xcframework.zip".to_string(),
+            source_sha256: "00ff".to_string(),
+            vendored_framework: "DemoFFI.xcframework".to_string(),
+            binding: SwiftBinding {
+                toolchain: "5.0".to_string(),
+                deployment_target: "12.0".to_string(),
+            },
+        };
+        let ruby = podspec.to_ruby();
+        assert!(ruby.starts_with("Pod::Spec.new do |s|\n  s.name = 'DemoFFI'\n"));
+        assert!(ruby.contains("  s.summary = 'Demo\\'s FFI'\n"));
+        assert!(ruby.contains("  s.license = { :type => 'MIT' }\n"));
+        assert!(!ruby.contains("s.authors"));
+        assert!(ruby.contains(
+            "  s.source = { :http => 'https://example.com/abc/demo.xcframework.zip', :sha256 => '00ff' }\n"
+        ));
+        assert!(ruby.ends_with("  s.vendored_frameworks = 'DemoFFI.xcframework'\nend\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&podspec.to_json()).expect("json");
+        assert_eq!(json["source"]["sha256"], "00ff");
+        assert_eq!(json["platforms"]["ios"], "12.0");
+        assert_eq!(json["license"]["type"], "MIT");
+        assert!(json.get("authors").is_none());
+    }
+}
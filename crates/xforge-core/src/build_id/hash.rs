@@ -89,7 +89,7 @@ mod tests {
     use super::*;
     use crate::build_id::{
         AbiInput, CargoLockfile, NormalizedCargoToml, NormalizedRustToolchain,
-        NormalizedXforgeConfig, NormalizedUdl, UniFfiInput,
+        NormalizedXforgeConfig, NormalizedUdl, UniFfiInput, VariantInput,
     };
 
     fn sample_inputs() -> BuildInputs {
@@ -112,6 +112,8 @@ mod tests {
             xforge_yaml: Some(AbiInput::new(NormalizedXforgeConfig(
                 "precompiled_binaries:\n  repository: demo/repo\n  public_key: demo-public-key\n".to_string(),
             ))),
+            variant: None,
+            profile: None,
         }
     }
 
@@ -139,4 +141,42 @@ mod tests {
         let original = hash_build_inputs(&sample_inputs()).expect("hash should succeed");
         assert_ne!(hash, original);
     }
+
+    #[test]
+    fn variant_changes_build_and_release_hash() {
+        let variant = VariantInput {
+            name: "gpu".to_string(),
+            features: vec!["gpu".to_string()],
+            rustflags: vec![],
+        };
+        let inputs = sample_inputs().with_variant(variant);
+        assert!(canonical_json(&inputs)
+            .expect("json should serialize")
+            .contains("build.variant"));
+        assert!(!canonical_json(&sample_inputs())
+            .expect("json should serialize")
+            .contains("build.variant"));
+        assert_ne!(
+            hash_build_inputs(&inputs).expect("hash should succeed"),
+            hash_build_inputs(&sample_inputs()).expect("hash should succeed")
+        );
+        assert_ne!(
+            hash_release_inputs(&inputs).expect("hash should succeed"),
+            hash_release_inputs(&sample_inputs()).expect("hash should succeed")
+        );
+    }
+
+    #[test]
+    fn non_release_profiles_change_the_hash() {
+        let release = sample_inputs().with_profile("release");
+        assert_eq!(release, sample_inputs());
+        let dev = sample_inputs().with_profile("dev");
+        assert!(canonical_json(&dev)
+            .expect("json should serialize")
+            .contains("build.profile"));
+        assert_ne!(
+            hash_build_inputs(&dev).expect("hash should succeed"),
+            hash_build_inputs(&sample_inputs()).expect("hash should succeed")
+        );
+    }
 }
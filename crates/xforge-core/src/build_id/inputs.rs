@@ -23,6 +23,12 @@ pub struct BuildInputs {
     /// ABI-affecting: xforge.yaml config.
     /// This captures precompiled binary metadata.
     pub xforge_yaml: Option<AbiInput<NormalizedXforgeConfig>>,
+    /// ABI-affecting: the `build.variants` entry being built.
+    /// Only hashed when present, so the default build keeps its identity.
+    pub variant: Option<AbiInput<VariantInput>>,
+    /// ABI-affecting: the cargo profile, for profiles other than `release`.
+    /// Only hashed when present, so release builds keep their identity.
+    pub profile: Option<AbiInput<String>>,
 }
 
 impl BuildInputs {
@@ -46,12 +52,27 @@ impl BuildInputs {
             uniffi,
             rust_toolchain,
             xforge_yaml,
+            variant: None,
+            profile: None,
         })
     }
 
+    /// The same inputs for the build variant `variant`.
+    pub fn with_variant(mut self, variant: VariantInput) -> Self {
+        self.variant = Some(AbiInput::new(variant));
+        self
+    }
+
+    /// The same inputs built with the cargo profile `profile`; `release`
+    /// is the default and leaves the identity unchanged.
+    pub fn with_profile(mut self, profile: &str) -> Self {
+        self.profile = (profile != "release").then(|| AbiInput::new(profile.to_string()));
+        self
+    }
+
     /// Enumerate every ABI-affecting field with explicit presence.
     pub fn fields(&self) -> Vec<BuildInputField> {
-        vec![
+        let mut fields = vec![
             BuildInputField::abi(
                 "cargo.toml",
                 BuildInputValue::Present(self.cargo_toml.value.0.clone()),
@@ -83,7 +104,20 @@ impl BuildInputs {
                     .map(|value| BuildInputValue::Present(value.value.0.clone()))
                     .unwrap_or(BuildInputValue::Absent),
             ),
-        ]
+        ];
+        if let Some(variant) = &self.variant {
+            fields.push(BuildInputField::abi(
+                "build.variant",
+                BuildInputValue::Present(variant.value.canonical()),
+            ));
+        }
+        if let Some(profile) = &self.profile {
+            fields.push(BuildInputField::abi(
+                "build.profile",
+                BuildInputValue::Present(profile.value.clone()),
+            ));
+        }
+        fields
     }
 
     /// Enumerate ABI-affecting fields but omit the rust target triple.
@@ -119,6 +153,28 @@ pub struct UniFfiInput {
     pub udl: Option<NormalizedUdl>,
 }
 
+/// A named build variant: cargo features and rustflags on top of the
+/// default build.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariantInput {
+    pub name: String,
+    pub features: Vec<String>,
+    pub rustflags: Vec<String>,
+}
+
+impl VariantInput {
+    fn canonical(&self) -> String {
+        let mut features = self.features.clone();
+        features.sort();
+        format!(
+            "name={}\nfeatures={}\nrustflags={}",
+            self.name,
+            features.join(","),
+            self.rustflags.join(" ")
+        )
+    }
+}
+
 /// Normalized Cargo.toml contents.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct NormalizedCargoToml(pub String);
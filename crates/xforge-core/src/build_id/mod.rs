@@ -6,7 +6,7 @@ pub use hash::{
 };
 pub use inputs::{
     AbiInput, BuildInputField, BuildInputValue, BuildInputs, CargoLockfile, NormalizedCargoToml,
-    NormalizedXforgeConfig, NormalizedUdl, UniFfiInput,
+    NormalizedRustToolchain, NormalizedUdl, NormalizedXforgeConfig, UniFfiInput, VariantInput,
 };
 
 /// Release hash used for precompiled artifact lookup.
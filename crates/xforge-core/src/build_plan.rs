@@ -1,8 +1,13 @@
-use crate::artifact::ArchiveKind;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::artifact::{ArchiveKind, LibraryEntry, LibraryKind};
 use crate::platform::PlatformKey;
 use crate::toolchain::Toolchain;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BuildPlan {
     pub package_name: String,
     pub build_id: String,
@@ -10,16 +15,95 @@ pub struct BuildPlan {
     pub targets: Vec<BuildTargetPlan>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+impl BuildPlan {
+    /// Narrow the plan to a single target so one CI job can execute its slice
+    /// of a shared plan.
+    pub fn for_target(&self, rust_target_triple: &str) -> Option<BuildPlan> {
+        let target = self
+            .targets
+            .iter()
+            .find(|target| target.rust_target_triple == rust_target_triple)?;
+        Some(BuildPlan {
+            package_name: self.package_name.clone(),
+            build_id: self.build_id.clone(),
+            profile: self.profile.clone(),
+            targets: vec![target.clone()],
+        })
+    }
+
+    /// Rewrite every path under `root` as `./`-prefixed and `/`-separated so
+    /// a serialized plan can be replayed from another checkout location.
+    pub fn relative_to(&self, root: &Path) -> BuildPlan {
+        self.map_paths(&|value| relativize(root, value))
+    }
+
+    /// Inverse of [`BuildPlan::relative_to`]: resolve `./` paths against `root`.
+    pub fn rooted_at(&self, root: &Path) -> BuildPlan {
+        self.map_paths(&|value| {
+            if value == "." {
+                return root.to_string_lossy().into_owned();
+            }
+            match value.strip_prefix("./") {
+                Some(relative) => root.join(relative).to_string_lossy().into_owned(),
+                None => value.to_string(),
+            }
+        })
+    }
+
+    fn map_paths(&self, map: &dyn Fn(&str) -> String) -> BuildPlan {
+        let mut plan = self.clone();
+        for env in &mut plan.profile.env {
+            env.value = map(&env.value);
+        }
+        for target in &mut plan.targets {
+            target.working_dir = map(&target.working_dir);
+            for arg in &mut target.cargo_args {
+                *arg = map(arg);
+            }
+            for env in &mut target.env {
+                env.value = map(&env.value);
+            }
+            let artifact = &mut target.artifact;
+            artifact.output_dir = map(&artifact.output_dir);
+            artifact.library_path = map(&artifact.library_path);
+            for library in &mut artifact.libraries {
+                library.path = map(&library.path);
+            }
+            artifact.include_dir = artifact.include_dir.as_deref().map(map);
+            artifact.manifest_path = map(&artifact.manifest_path);
+            artifact.build_id_path = map(&artifact.build_id_path);
+        }
+        plan
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BuildProfile {
     pub name: String,
     pub toolchain: Toolchain,
     pub cargo_args: Vec<String>,
     pub rustflags: Vec<String>,
     pub env: Vec<BuildEnvVar>,
+    /// Names (or `PREFIX_*` patterns) of variables executors pass through from
+    /// the invoking environment; `None` passes everything through.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_allowlist: Option<Vec<String>>,
+}
+
+/// Directory cargo writes `profile` to under `<target_dir>/<triple>/`:
+/// `dev` and `test` build into `debug`, `bench` into `release`, and every
+/// other profile into a directory of its own name.
+pub fn cargo_profile_dir(profile: &str) -> &str {
+    match profile {
+        "dev" | "test" => "debug",
+        "bench" => "release",
+        other => other,
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BuildTargetPlan {
     pub platform: PlatformKey,
     pub rust_target_triple: String,
@@ -29,24 +113,183 @@ pub struct BuildTargetPlan {
     pub cargo_features: Vec<String>,
     pub cross_image: Option<String>,
     pub env: Vec<BuildEnvVar>,
+    /// Seconds one build attempt may run before its process tree is killed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
     pub artifact: BuiltArtifact,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BuiltArtifact {
     pub platform: PlatformKey,
     pub build_id: String,
     pub archive_kind: ArchiveKind,
     pub artifact_name: String,
     pub output_dir: String,
+    /// Primary library; the first entry of `libraries` when that is set.
     pub library_path: String,
+    /// Every library the target ships. Empty means `library_path` is the
+    /// only, dynamic, output.
+    #[serde(default)]
+    pub libraries: Vec<LibraryEntry>,
     pub include_dir: Option<String>,
     pub manifest_path: String,
     pub build_id_path: String,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+impl BuiltArtifact {
+    pub fn library(&self, kind: LibraryKind) -> Option<&str> {
+        if self.libraries.is_empty() {
+            return (kind == LibraryKind::Dynamic).then_some(self.library_path.as_str());
+        }
+        self.libraries
+            .iter()
+            .find(|entry| entry.kind == kind)
+            .map(|entry| entry.path.as_str())
+    }
+
+    pub fn library_paths(&self) -> Vec<&str> {
+        if self.libraries.is_empty() {
+            return vec![self.library_path.as_str()];
+        }
+        self.libraries.iter().map(|entry| entry.path.as_str()).collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BuildEnvVar {
     pub key: String,
     pub value: String,
 }
+
+fn relativize(root: &Path, value: &str) -> String {
+    let Ok(relative) = Path::new(value).strip_prefix(root) else {
+        return value.to_string();
+    };
+    let parts: Vec<String> = relative
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        format!("./{}", parts.join("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::PlatformKey;
+
+    fn target(platform: PlatformKey, triple: &str) -> BuildTargetPlan {
+        BuildTargetPlan {
+            platform,
+            rust_target_triple: triple.to_string(),
+            working_dir: ".".to_string(),
+            cargo_manifest_path: "Cargo.toml".to_string(),
+            cargo_args: vec![],
+            cargo_features: vec!["ffi".to_string()],
+            cross_image: None,
+            env: vec![BuildEnvVar {
+                key: "CARGO_TARGET_DIR".to_string(),
+                value: "target".to_string(),
+            }],
+            timeout_secs: None,
+            artifact: BuiltArtifact {
+                platform,
+                build_id: "b1-abc".to_string(),
+                archive_kind: ArchiveKind::TarGz,
+                artifact_name: format!("demo-b1-abc-{}.tar.gz", platform),
+                output_dir: format!("target/{}/release", triple),
+                library_path: format!("target/{}/release/libdemo.so", triple),
+                libraries: vec![],
+                include_dir: None,
+                manifest_path: "xforge-manifest.json".to_string(),
+                build_id_path: "build_id.txt".to_string(),
+            },
+        }
+    }
+
+    fn plan() -> BuildPlan {
+        BuildPlan {
+            package_name: "demo".to_string(),
+            build_id: "b1-abc".to_string(),
+            profile: BuildProfile {
+                name: "release".to_string(),
+                toolchain: Toolchain {
+                    channel: Some("stable".to_string()),
+                    targets: vec![],
+                    components: vec![],
+                },
+                cargo_args: vec![],
+                rustflags: vec![],
+                env: vec![],
+                env_allowlist: None,
+            },
+            targets: vec![
+                target(PlatformKey::LinuxX86_64, "x86_64-unknown-linux-gnu"),
+                target(PlatformKey::AndroidArm64, "aarch64-linux-android"),
+            ],
+        }
+    }
+
+    #[test]
+    fn built_in_profiles_map_to_cargo_output_dirs() {
+        assert_eq!(cargo_profile_dir("dev"), "debug");
+        assert_eq!(cargo_profile_dir("test"), "debug");
+        assert_eq!(cargo_profile_dir("bench"), "release");
+        assert_eq!(cargo_profile_dir("release"), "release");
+        assert_eq!(cargo_profile_dir("custom"), "custom");
+    }
+
+    #[test]
+    fn plan_round_trips_through_json() {
+        let plan = plan();
+        let json = serde_json::to_string(&plan).expect("serialize plan");
+        assert!(json.contains("\"rustTargetTriple\":\"aarch64-linux-android\""));
+        assert!(json.contains("\"archiveKind\":\"tarGz\""));
+        let parsed: BuildPlan = serde_json::from_str(&json).expect("parse plan");
+        assert_eq!(parsed, plan);
+    }
+
+    #[test]
+    fn for_target_keeps_only_the_requested_slice() {
+        let slice = plan()
+            .for_target("aarch64-linux-android")
+            .expect("slice exists");
+        assert_eq!(slice.targets.len(), 1);
+        assert_eq!(slice.targets[0].platform, PlatformKey::AndroidArm64);
+        assert!(plan().for_target("wasm32-unknown-unknown").is_none());
+    }
+
+    #[test]
+    fn relative_plan_can_be_rooted_elsewhere() {
+        let root = Path::new("/work/demo");
+        let mut plan = plan();
+        plan.targets[0].working_dir = "/work/demo".to_string();
+        plan.targets[0].cargo_args = vec!["--target-dir".to_string(), "/work/demo/target".to_string()];
+        plan.targets[0].artifact.library_path =
+            "/work/demo/target/x86_64-unknown-linux-gnu/release/libdemo.so".to_string();
+
+        let relative = plan.relative_to(root);
+        let target = &relative.targets[0];
+        assert_eq!(target.working_dir, ".");
+        assert_eq!(target.cargo_args, vec!["--target-dir", "./target"]);
+        assert_eq!(
+            target.artifact.library_path,
+            "./target/x86_64-unknown-linux-gnu/release/libdemo.so"
+        );
+        assert_eq!(target.cargo_manifest_path, "Cargo.toml");
+
+        let rooted = relative.rooted_at(Path::new("/ci/checkout"));
+        assert_eq!(
+            Path::new(&rooted.targets[0].artifact.library_path),
+            Path::new("/ci/checkout/target/x86_64-unknown-linux-gnu/release/libdemo.so")
+        );
+        assert_eq!(rooted.targets[0].working_dir, "/ci/checkout");
+        assert_eq!(rooted.targets[0].cargo_args[0], "--target-dir");
+    }
+}
@@ -1,7 +1,10 @@
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 use serde::Deserialize;
 
+use crate::artifact::naming::RESERVED_SUFFIXES;
+use crate::artifact::{ArchiveKind, LibraryKind};
 use crate::platform::is_supported_rust_target;
 
 #[derive(Debug)]
@@ -12,6 +15,17 @@ pub enum ConfigError {
     MissingToolchainFile,
     MissingToolchainField { field: &'static str, path: String },
     InvalidTarget { target: String },
+    InvalidProfile { profile: String },
+    InvalidVariant { variant: String },
+    InvalidHeaderFileName { file_name: String },
+    InvalidArchiveKind { target: String, value: String },
+    InvalidXcframeworkModule { module: String },
+    InvalidAarNamespace { namespace: String },
+    InvalidWheelName { name: String },
+    InvalidPodName { name: String },
+    InvalidMavenCoordinate { field: &'static str, value: String },
+    MavenWithoutAar,
+    InvalidNugetId { id: String },
     MissingPrecompiledField { field: &'static str },
 }
 
@@ -32,6 +46,59 @@ impl std::fmt::Display for ConfigError {
             ConfigError::InvalidTarget { target } => {
                 write!(f, "invalid build target '{}'", target)
             }
+            ConfigError::InvalidProfile { profile } => write!(
+                f,
+                "invalid build profile '{}'; profiles must be unique and use lowercase letters, digits and '-'",
+                profile
+            ),
+            ConfigError::InvalidVariant { variant } => write!(
+                f,
+                "invalid build variant '{}'; variant names use lowercase letters, digits and '-' and must differ from the build profiles and from 'release' and 'debug'",
+                variant
+            ),
+            ConfigError::InvalidHeaderFileName { file_name } => write!(
+                f,
+                "invalid build.headers.file_name '{}'; expected a file name without directories",
+                file_name
+            ),
+            ConfigError::InvalidArchiveKind { target, value } => write!(
+                f,
+                "invalid build.archives entry '{}: {}'; expected tar.gz, tar.zst, tar.xz or zip",
+                target, value
+            ),
+            ConfigError::InvalidXcframeworkModule { module } => write!(
+                f,
+                "invalid build.xcframework.module '{}'; expected a C identifier",
+                module
+            ),
+            ConfigError::InvalidAarNamespace { namespace } => write!(
+                f,
+                "invalid build.aar.namespace '{}'; expected a Java package name",
+                namespace
+            ),
+            ConfigError::InvalidWheelName { name } => write!(
+                f,
+                "invalid build.wheel.name '{}'; expected a Python distribution name",
+                name
+            ),
+            ConfigError::InvalidPodName { name } => write!(
+                f,
+                "invalid build.xcframework.cocoapods.name '{}'; expected letters, digits, '-' and '_'",
+                name
+            ),
+            ConfigError::InvalidMavenCoordinate { field, value } => write!(
+                f,
+                "invalid build.maven.{} '{}'; expected letters, digits, '.', '-' and '_'",
+                field, value
+            ),
+            ConfigError::MavenWithoutAar => {
+                write!(f, "build.maven publishes the AAR, so it needs build.aar")
+            }
+            ConfigError::InvalidNugetId { id } => write!(
+                f,
+                "invalid build.nuget.id '{}'; expected letters, digits and '_' separated by '.' or '-'",
+                id
+            ),
             ConfigError::MissingPrecompiledField { field } => {
                 write!(f, "precompiled_binaries missing required field '{}'", field)
             }
@@ -42,10 +109,14 @@ impl std::fmt::Display for ConfigError {
 impl std::error::Error for ConfigError {}
 
 #[derive(Debug, Default, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "snake_case")]
 struct XforgeConfig {
-    #[serde(default)]
+    /// Releases before `build` existed read the block as
+    /// `precompiledBinaries`; keep accepting that spelling.
+    #[serde(default, alias = "precompiledBinaries")]
     precompiled_binaries: Option<PrecompiledBinariesConfig>,
+    #[serde(default)]
+    build: Option<BuildConfig>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -68,6 +139,153 @@ struct PrecompiledBinariesConfig {
     public_key: Option<String>,
 }
 
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildConfig {
+    #[serde(default)]
+    executors: BTreeMap<String, String>,
+    #[serde(default)]
+    cache: Option<BuildCacheConfig>,
+    #[serde(default)]
+    outputs: BTreeMap<String, Vec<LibraryKind>>,
+    #[serde(default)]
+    strip: Option<BuildStripConfig>,
+    #[serde(default)]
+    deterministic: Option<bool>,
+    #[serde(default)]
+    env_passthrough: Vec<String>,
+    #[serde(default)]
+    command: Option<BuildCommandConfig>,
+    #[serde(default)]
+    timeout_secs: BTreeMap<String, u64>,
+    #[serde(default)]
+    retry: Option<BuildRetryConfig>,
+    #[serde(default)]
+    profiles: Vec<String>,
+    #[serde(default)]
+    variants: BTreeMap<String, BuildVariantConfig>,
+    #[serde(default)]
+    headers: Option<BuildHeadersConfig>,
+    #[serde(default)]
+    archives: BTreeMap<String, String>,
+    #[serde(default)]
+    xcframework: Option<BuildXcframeworkConfig>,
+    #[serde(default)]
+    aar: Option<BuildAarConfig>,
+    #[serde(default)]
+    wheel: Option<BuildWheelConfig>,
+    #[serde(default)]
+    maven: Option<BuildMavenConfig>,
+    #[serde(default)]
+    nuget: Option<BuildNugetConfig>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildXcframeworkConfig {
+    module: Option<String>,
+    xcodebuild: Option<bool>,
+    spm: Option<BuildSpmConfig>,
+    cocoapods: Option<BuildCocoapodsConfig>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildSpmConfig {
+    package: Option<String>,
+    asset: Option<bool>,
+    output_dir: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildCocoapodsConfig {
+    name: Option<String>,
+    deployment_target: Option<String>,
+    swift_version: Option<String>,
+    asset: Option<bool>,
+    output_dir: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildAarConfig {
+    namespace: Option<String>,
+    min_sdk: Option<u32>,
+    glue: Option<bool>,
+    proguard: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildMavenConfig {
+    group_id: String,
+    artifact_id: Option<String>,
+    repository: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildNugetConfig {
+    id: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildWheelConfig {
+    name: Option<String>,
+    loader: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildHeadersConfig {
+    cbindgen: Option<String>,
+    config: Option<String>,
+    language: Option<HeaderLanguage>,
+    file_name: Option<String>,
+    per_target: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildVariantConfig {
+    #[serde(default)]
+    features: Vec<String>,
+    #[serde(default)]
+    rustflags: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildRetryConfig {
+    attempts: Option<u32>,
+    delay_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildCommandConfig {
+    run: String,
+    outputs: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildStripConfig {
+    mode: Option<StripMode>,
+    split_debuginfo: Option<bool>,
+    objcopy: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct BuildCacheConfig {
+    enabled: Option<bool>,
+    dir: Option<String>,
+    max_size_mb: Option<u64>,
+}
+
 #[derive(Debug, Default)]
 pub struct ToolchainSettings {
     pub channel: Option<String>,
@@ -82,6 +300,377 @@ pub struct PrecompiledSettings {
     pub public_key: String,
 }
 
+impl PrecompiledSettings {
+    /// Download URL of a release asset: `<url_prefix><build_id>/<file_name>`.
+    pub fn asset_url(&self, build_id: &str, file_name: &str) -> String {
+        format!("{}{}/{}", self.url_prefix, build_id, file_name)
+    }
+}
+
+/// Build options declared under the `build` block of `xforge.yaml`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BuildSettings {
+    /// Executor name per rust target triple, consulted by automatic executor selection.
+    pub executor_overrides: BTreeMap<String, String>,
+    pub cache: CacheSettings,
+    /// Library outputs to ship per rust target triple, with an optional
+    /// `default` entry; unset means every output the crate-type produces.
+    pub library_outputs: BTreeMap<String, Vec<LibraryKind>>,
+    pub strip: StripSettings,
+    pub deterministic: DeterministicSettings,
+    /// Custom build command for the `command` executor.
+    pub command: Option<CommandSettings>,
+    /// Seconds a single build attempt may run per rust target triple, with an
+    /// optional `default` entry; unset means no limit.
+    pub timeout_secs: BTreeMap<String, u64>,
+    pub retry: RetrySettings,
+    /// Cargo profiles released together, primary first; empty means only
+    /// `release`.
+    pub profiles: Vec<String>,
+    /// Named feature builds from `build.variants`, released beside the
+    /// primary profile.
+    pub variants: BTreeMap<String, VariantSettings>,
+    /// C header generation from `build.headers`; `None` ships no headers.
+    pub headers: Option<HeaderSettings>,
+    /// Archive format per rust target triple, with an optional `default`
+    /// entry; unset means the platform's default archive kind.
+    pub archives: BTreeMap<String, ArchiveKind>,
+    /// XCFramework assembly from `build.xcframework`; `None` bundles none.
+    pub xcframework: Option<XcframeworkSettings>,
+    /// Android archive assembly from `build.aar`; `None` bundles none.
+    pub aar: Option<AarSettings>,
+    /// Python wheel assembly from `build.wheel`; `None` bundles none.
+    pub wheel: Option<WheelSettings>,
+    /// Maven repository output from `build.maven`; `None` writes none.
+    pub maven: Option<MavenSettings>,
+    /// NuGet package assembly from `build.nuget`; `None` bundles none.
+    pub nuget: Option<NugetSettings>,
+}
+
+/// Extra cargo features and rustflags of one `build.variants` entry.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VariantSettings {
+    pub features: Vec<String>,
+    pub rustflags: Vec<String>,
+}
+
+impl BuildSettings {
+    pub fn library_outputs_for(&self, rust_target_triple: &str) -> Option<&[LibraryKind]> {
+        self.library_outputs
+            .get(rust_target_triple)
+            .or_else(|| self.library_outputs.get(DEFAULT_OUTPUTS_KEY))
+            .map(Vec::as_slice)
+    }
+
+    pub fn timeout_secs_for(&self, rust_target_triple: &str) -> Option<u64> {
+        self.timeout_secs
+            .get(rust_target_triple)
+            .or_else(|| self.timeout_secs.get(DEFAULT_OUTPUTS_KEY))
+            .copied()
+    }
+
+    pub fn archive_kind_for(&self, rust_target_triple: &str) -> Option<ArchiveKind> {
+        self.archives
+            .get(rust_target_triple)
+            .or_else(|| self.archives.get(DEFAULT_OUTPUTS_KEY))
+            .copied()
+    }
+
+    /// The configured profiles, or `release` alone when none are declared.
+    pub fn release_profiles(&self) -> Vec<String> {
+        if self.profiles.is_empty() {
+            vec!["release".to_string()]
+        } else {
+            self.profiles.clone()
+        }
+    }
+}
+
+/// Retry policy for transient build failures from `build.retry`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetrySettings {
+    /// Attempts per target, including the first; 1 disables retries.
+    pub attempts: u32,
+    pub delay_secs: u64,
+}
+
+impl Default for RetrySettings {
+    fn default() -> Self {
+        Self {
+            attempts: 2,
+            delay_secs: 10,
+        }
+    }
+}
+
+/// Templated command line and output glob from `build.command`; both accept
+/// `{target}`, `{profile}`, `{target_dir}` and `{features}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandSettings {
+    pub run: String,
+    pub outputs: String,
+}
+
+/// Build environment normalisation from `build.deterministic` and
+/// `build.env_passthrough`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeterministicSettings {
+    pub enabled: bool,
+    /// Variables (or `PREFIX_*` patterns) to pass through on top of the
+    /// built-in allowlist.
+    pub env_passthrough: Vec<String>,
+}
+
+impl Default for DeterministicSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            env_passthrough: Vec::new(),
+        }
+    }
+}
+
+/// How much of a dynamic library's symbol table survives the post-build strip.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StripMode {
+    /// Ship libraries exactly as cargo produced them.
+    #[default]
+    None,
+    /// Drop DWARF debug sections, keep the symbol table.
+    Debuginfo,
+    /// Drop debug sections and every symbol not needed for dynamic linking.
+    Symbols,
+}
+
+/// Post-build stripping options from `build.strip`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StripSettings {
+    pub mode: StripMode,
+    /// Keep the removed debug info as a `.debug`, `.dSYM` or `.pdb` side file.
+    pub split_debuginfo: bool,
+    /// `objcopy` to run; `None` prefers `llvm-objcopy` and falls back to
+    /// `objcopy`, or to `llvm-strip` for Mach-O, which GNU `objcopy` cannot write.
+    pub objcopy: Option<PathBuf>,
+}
+
+impl Default for StripSettings {
+    fn default() -> Self {
+        Self {
+            mode: StripMode::None,
+            split_debuginfo: true,
+            objcopy: None,
+        }
+    }
+}
+
+/// Language cbindgen emits the generated header in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeaderLanguage {
+    #[default]
+    C,
+    #[serde(rename = "c++")]
+    Cxx,
+    Cython,
+}
+
+impl HeaderLanguage {
+    /// Value of cbindgen's `--lang` flag.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HeaderLanguage::C => "c",
+            HeaderLanguage::Cxx => "c++",
+            HeaderLanguage::Cython => "cython",
+        }
+    }
+}
+
+/// Header generation options from `build.headers`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderSettings {
+    /// `cbindgen` binary to run; `None` looks it up on `PATH`.
+    pub cbindgen: Option<PathBuf>,
+    /// cbindgen configuration, resolved against the manifest directory.
+    pub config: Option<PathBuf>,
+    pub language: HeaderLanguage,
+    /// Header file written under `include/`; `None` means `<crate>.h`
+    /// (`.hpp` for C++, `.pxd` for Cython).
+    pub file_name: Option<String>,
+    /// Accept headers that differ between targets instead of failing the bundle.
+    pub per_target: bool,
+}
+
+impl HeaderSettings {
+    pub fn file_name_for(&self, package_name: &str) -> String {
+        if let Some(file_name) = &self.file_name {
+            return file_name.clone();
+        }
+        let extension = match self.language {
+            HeaderLanguage::C => "h",
+            HeaderLanguage::Cxx => "hpp",
+            HeaderLanguage::Cython => "pxd",
+        };
+        format!("{}.{}", package_name.replace('-', "_"), extension)
+    }
+}
+
+/// XCFramework options from `build.xcframework`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct XcframeworkSettings {
+    /// Clang module and framework name; `None` means the crate name with
+    /// `-` replaced by `_`.
+    pub module: Option<String>,
+    /// Assemble with `xcodebuild -create-xcframework` instead of the
+    /// built-in writer; needs Xcode on the host.
+    pub xcodebuild: bool,
+    /// Swift package from `build.xcframework.spm`; `None` writes none.
+    pub spm: Option<SpmSettings>,
+    /// Pod spec from `build.xcframework.cocoapods`; `None` writes none.
+    pub cocoapods: Option<CocoapodsSettings>,
+}
+
+impl XcframeworkSettings {
+    pub fn module_for(&self, package_name: &str) -> String {
+        self.module
+            .clone()
+            .unwrap_or_else(|| package_name.replace('-', "_"))
+    }
+}
+
+/// Swift package options from `build.xcframework.spm`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpmSettings {
+    /// Package and library product name; `None` means the module name.
+    pub package: Option<String>,
+    /// Write `Package.swift` to the bundle output so it is published with
+    /// the release.
+    pub asset: bool,
+    /// Also write `Package.swift` here, typically a checkout of a separate
+    /// Swift package repository; resolved against the manifest directory.
+    pub output_dir: Option<PathBuf>,
+}
+
+impl SpmSettings {
+    pub fn package_for(&self, module: &str) -> String {
+        self.package.clone().unwrap_or_else(|| module.to_string())
+    }
+}
+
+/// CocoaPods options from `build.xcframework.cocoapods`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CocoapodsSettings {
+    /// Pod name; `None` means the module name.
+    pub name: Option<String>,
+    /// Minimum iOS version of the pod.
+    pub deployment_target: String,
+    /// Swift version the pod declares.
+    pub swift_version: String,
+    /// Write the spec to the bundle output so it is published with the
+    /// release.
+    pub asset: bool,
+    /// Also write the spec here, typically a checkout of a specs
+    /// repository; resolved against the manifest directory.
+    pub output_dir: Option<PathBuf>,
+}
+
+impl CocoapodsSettings {
+    pub fn name_for(&self, module: &str) -> String {
+        self.name.clone().unwrap_or_else(|| module.to_string())
+    }
+}
+
+/// Android archive options from `build.aar`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AarSettings {
+    /// Java package of the AAR; `None` means the crate name with `-`
+    /// replaced by `_`.
+    pub namespace: Option<String>,
+    /// `minSdkVersion` written to `AndroidManifest.xml`.
+    pub min_sdk: u32,
+    /// Ship a `NativeLoader` class that loads the library.
+    pub glue: bool,
+    /// Consumer ProGuard rules copied into `proguard.txt`, resolved against
+    /// the manifest directory.
+    pub proguard: Option<PathBuf>,
+}
+
+impl AarSettings {
+    pub fn namespace_for(&self, package_name: &str) -> String {
+        self.namespace
+            .clone()
+            .unwrap_or_else(|| package_name.replace('-', "_"))
+    }
+}
+
+/// Python wheel options from `build.wheel`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WheelSettings {
+    /// Distribution name; `None` means the crate name.
+    pub name: Option<String>,
+    /// Ship an `__init__.py` that loads the library with `ctypes`.
+    pub loader: bool,
+}
+
+impl WheelSettings {
+    pub fn name_for(&self, package_name: &str) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| package_name.to_string())
+    }
+}
+
+/// Maven repository options from `build.maven`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MavenSettings {
+    pub group_id: String,
+    /// `None` means the crate name.
+    pub artifact_id: Option<String>,
+    /// Repository root; `None` means `maven/` in the bundle output
+    /// directory. Resolved against the manifest directory.
+    pub repository: Option<PathBuf>,
+}
+
+impl MavenSettings {
+    pub fn artifact_id_for(&self, package_name: &str) -> String {
+        self.artifact_id
+            .clone()
+            .unwrap_or_else(|| package_name.to_string())
+    }
+}
+
+/// NuGet package options from `build.nuget`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NugetSettings {
+    /// Package id; `None` means the crate name.
+    pub id: Option<String>,
+}
+
+impl NugetSettings {
+    pub fn id_for(&self, package_name: &str) -> String {
+        self.id.clone().unwrap_or_else(|| package_name.to_string())
+    }
+}
+
+/// Local build cache options from `build.cache`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheSettings {
+    pub enabled: bool,
+    /// Cache root; `None` means the platform default cache directory.
+    pub dir: Option<PathBuf>,
+    pub max_size_bytes: Option<u64>,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: None,
+            max_size_bytes: None,
+        }
+    }
+}
+
 pub fn build_targets(manifest_dir: &Path) -> Result<Vec<String>, ConfigError> {
     let settings = toolchain_settings(manifest_dir)?;
     Ok(settings.targets)
@@ -162,6 +751,298 @@ pub fn precompiled_settings(
     }))
 }
 
+const DEFAULT_OUTPUTS_KEY: &str = "default";
+/// Lowest API level the NDK still supports.
+const DEFAULT_AAR_MIN_SDK: u32 = 21;
+/// Lowest iOS version current Xcode releases still deploy to.
+const DEFAULT_POD_DEPLOYMENT_TARGET: &str = "12.0";
+const DEFAULT_POD_SWIFT_VERSION: &str = "5.0";
+
+pub fn build_settings(manifest_dir: &Path) -> Result<BuildSettings, ConfigError> {
+    let (_path, contents) = match read_optional_xforge_config(manifest_dir)? {
+        Some(value) => value,
+        None => return Ok(BuildSettings::default()),
+    };
+    let config: XforgeConfig = serde_yaml::from_str(&contents).map_err(ConfigError::Yaml)?;
+    let build = match config.build {
+        Some(value) => value,
+        None => return Ok(BuildSettings::default()),
+    };
+    let output_targets = build
+        .outputs
+        .keys()
+        .chain(build.timeout_secs.keys())
+        .chain(build.archives.keys())
+        .filter(|key| key.as_str() != DEFAULT_OUTPUTS_KEY);
+    for target in build.executors.keys().chain(output_targets) {
+        if !is_supported_rust_target(target) {
+            return Err(ConfigError::InvalidTarget {
+                target: target.clone(),
+            });
+        }
+    }
+    for (index, profile) in build.profiles.iter().enumerate() {
+        if !is_canonical_name(profile) || build.profiles[..index].contains(profile) {
+            return Err(ConfigError::InvalidProfile {
+                profile: profile.clone(),
+            });
+        }
+    }
+    if let Some(file_name) = build
+        .headers
+        .as_ref()
+        .and_then(|headers| headers.file_name.as_ref())
+    {
+        if file_name.is_empty() || file_name.contains(['/', '\\']) {
+            return Err(ConfigError::InvalidHeaderFileName {
+                file_name: file_name.clone(),
+            });
+        }
+    }
+    if let Some(module) = build
+        .xcframework
+        .as_ref()
+        .and_then(|xcframework| xcframework.module.as_ref())
+    {
+        if !is_c_identifier(module) {
+            return Err(ConfigError::InvalidXcframeworkModule {
+                module: module.clone(),
+            });
+        }
+    }
+    if let Some(name) = build
+        .xcframework
+        .as_ref()
+        .and_then(|xcframework| xcframework.cocoapods.as_ref())
+        .and_then(|cocoapods| cocoapods.name.as_ref())
+    {
+        if !is_pod_name(name) {
+            return Err(ConfigError::InvalidPodName { name: name.clone() });
+        }
+    }
+    if let Some(namespace) = build.aar.as_ref().and_then(|aar| aar.namespace.as_ref()) {
+        if !namespace.split('.').all(is_java_identifier) {
+            return Err(ConfigError::InvalidAarNamespace {
+                namespace: namespace.clone(),
+            });
+        }
+    }
+    if let Some(name) = build.wheel.as_ref().and_then(|wheel| wheel.name.as_ref()) {
+        if !is_python_distribution_name(name) {
+            return Err(ConfigError::InvalidWheelName { name: name.clone() });
+        }
+    }
+    if let Some(maven) = &build.maven {
+        if build.aar.is_none() {
+            return Err(ConfigError::MavenWithoutAar);
+        }
+        for (field, value) in [
+            ("group_id", Some(&maven.group_id)),
+            ("artifact_id", maven.artifact_id.as_ref()),
+        ] {
+            if let Some(value) = value {
+                if !is_maven_coordinate(value) {
+                    return Err(ConfigError::InvalidMavenCoordinate {
+                        field,
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+    }
+    if let Some(id) = build.nuget.as_ref().and_then(|nuget| nuget.id.as_ref()) {
+        if !is_nuget_id(id) {
+            return Err(ConfigError::InvalidNugetId { id: id.clone() });
+        }
+    }
+    for variant in build.variants.keys() {
+        if !is_canonical_name(variant)
+            || build.profiles.contains(variant)
+            || variant == "release"
+            || RESERVED_SUFFIXES.contains(&variant.as_str())
+        {
+            return Err(ConfigError::InvalidVariant {
+                variant: variant.clone(),
+            });
+        }
+    }
+    let mut archives = BTreeMap::new();
+    for (target, value) in &build.archives {
+        let kind =
+            ArchiveKind::from_extension(value).ok_or_else(|| ConfigError::InvalidArchiveKind {
+                target: target.clone(),
+                value: value.clone(),
+            })?;
+        archives.insert(target.clone(), kind);
+    }
+    let cache = build
+        .cache
+        .map(|cache| CacheSettings {
+            enabled: cache.enabled.unwrap_or(true),
+            dir: cache.dir.map(|dir| manifest_dir.join(dir)),
+            max_size_bytes: cache.max_size_mb.map(|value| value * 1024 * 1024),
+        })
+        .unwrap_or_default();
+    let strip = build
+        .strip
+        .map(|strip| StripSettings {
+            mode: strip.mode.unwrap_or_default(),
+            split_debuginfo: strip.split_debuginfo.unwrap_or(true),
+            objcopy: strip.objcopy.map(PathBuf::from),
+        })
+        .unwrap_or_default();
+    Ok(BuildSettings {
+        executor_overrides: build.executors,
+        cache,
+        library_outputs: build.outputs,
+        strip,
+        deterministic: DeterministicSettings {
+            enabled: build.deterministic.unwrap_or(true),
+            env_passthrough: build.env_passthrough,
+        },
+        command: build.command.map(|command| CommandSettings {
+            run: command.run,
+            outputs: command.outputs,
+        }),
+        timeout_secs: build.timeout_secs,
+        retry: build
+            .retry
+            .map(|retry| {
+                let defaults = RetrySettings::default();
+                RetrySettings {
+                    attempts: retry.attempts.unwrap_or(defaults.attempts).max(1),
+                    delay_secs: retry.delay_secs.unwrap_or(defaults.delay_secs),
+                }
+            })
+            .unwrap_or_default(),
+        profiles: build.profiles,
+        variants: build
+            .variants
+            .into_iter()
+            .map(|(name, variant)| {
+                let settings = VariantSettings {
+                    features: variant.features,
+                    rustflags: variant.rustflags,
+                };
+                (name, settings)
+            })
+            .collect(),
+        headers: build.headers.map(|headers| HeaderSettings {
+            cbindgen: headers.cbindgen.map(PathBuf::from),
+            config: headers.config.map(|config| manifest_dir.join(config)),
+            language: headers.language.unwrap_or_default(),
+            file_name: headers.file_name,
+            per_target: headers.per_target.unwrap_or(false),
+        }),
+        archives,
+        xcframework: build.xcframework.map(|xcframework| XcframeworkSettings {
+            module: xcframework.module,
+            xcodebuild: xcframework.xcodebuild.unwrap_or(false),
+            spm: xcframework.spm.map(|spm| SpmSettings {
+                package: spm.package,
+                asset: spm.asset.unwrap_or(true),
+                output_dir: spm.output_dir.map(|dir| manifest_dir.join(dir)),
+            }),
+            cocoapods: xcframework.cocoapods.map(|cocoapods| CocoapodsSettings {
+                name: cocoapods.name,
+                deployment_target: cocoapods
+                    .deployment_target
+                    .unwrap_or_else(|| DEFAULT_POD_DEPLOYMENT_TARGET.to_string()),
+                swift_version: cocoapods
+                    .swift_version
+                    .unwrap_or_else(|| DEFAULT_POD_SWIFT_VERSION.to_string()),
+                asset: cocoapods.asset.unwrap_or(true),
+                output_dir: cocoapods.output_dir.map(|dir| manifest_dir.join(dir)),
+            }),
+        }),
+        aar: build.aar.map(|aar| AarSettings {
+            namespace: aar.namespace,
+            min_sdk: aar.min_sdk.unwrap_or(DEFAULT_AAR_MIN_SDK),
+            glue: aar.glue.unwrap_or(false),
+            proguard: aar.proguard.map(|path| manifest_dir.join(path)),
+        }),
+        wheel: build.wheel.map(|wheel| WheelSettings {
+            name: wheel.name,
+            loader: wheel.loader.unwrap_or(false),
+        }),
+        maven: build.maven.map(|maven| MavenSettings {
+            group_id: maven.group_id,
+            artifact_id: maven.artifact_id,
+            repository: maven.repository.map(|dir| manifest_dir.join(dir)),
+        }),
+        nuget: build.nuget.map(|nuget| NugetSettings { id: nuget.id }),
+    })
+}
+
+/// Profile and variant names end up in archive names, so they are limited to
+/// lowercase letters, digits and '-'.
+fn is_canonical_name(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-')
+}
+
+fn is_c_identifier(value: &str) -> bool {
+    let mut chars = value.chars();
+    matches!(chars.next(), Some(ch) if ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// PEP 508 names: ASCII letters and digits, with `.`, `_` and `-` inside.
+fn is_python_distribution_name(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    match (bytes.first(), bytes.last()) {
+        (Some(first), Some(last)) => {
+            first.is_ascii_alphanumeric()
+                && last.is_ascii_alphanumeric()
+                && bytes
+                    .iter()
+                    .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'.' | b'_' | b'-'))
+        }
+        _ => false,
+    }
+}
+
+/// Maven group and artifact ids: ASCII letters, digits, `.`, `-` and `_`,
+/// starting with a letter or digit.
+fn is_maven_coordinate(value: &str) -> bool {
+    value
+        .bytes()
+        .next()
+        .is_some_and(|byte| byte.is_ascii_alphanumeric())
+        && value
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'.' | b'_' | b'-'))
+}
+
+/// NuGet package ids: runs of ASCII letters, digits and `_` joined by
+/// single `.` or `-`, at most 100 characters.
+fn is_nuget_id(value: &str) -> bool {
+    value.len() <= 100
+        && value.split(['.', '-']).all(|part| {
+            !part.is_empty()
+                && part
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+        })
+}
+
+/// Pod names: ASCII letters, digits, `-` and `_`, so they are also valid
+/// `.podspec` file names.
+fn is_pod_name(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'-'))
+}
+
+fn is_java_identifier(value: &str) -> bool {
+    let mut chars = value.chars();
+    matches!(chars.next(), Some(ch) if ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
 fn read_optional_xforge_config(
     manifest_dir: &Path,
 ) -> Result<Option<(String, String)>, ConfigError> {
@@ -269,6 +1150,483 @@ mod tests {
         assert!(message.contains("invalid build target"));
     }
 
+    #[test]
+    fn reads_the_camel_case_precompiled_block() {
+        let dir = temp_dir("precompiled-camel");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "precompiledBinaries:\n  repository: owner/repo\n  public_key: deadbeef\n",
+        )
+        .expect("write config");
+        let settings = precompiled_settings(&dir)
+            .expect("settings")
+            .expect("precompiled block");
+        assert_eq!(settings.repository, "owner/repo");
+    }
+
+    #[test]
+    fn reads_precompiled_settings_from_xforge_yaml() {
+        let dir = temp_dir("precompiled");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "precompiled_binaries:\n  repository: owner/repo\n  public_key: deadbeef\n",
+        )
+        .expect("write config");
+        let settings = precompiled_settings(&dir)
+            .expect("settings")
+            .expect("precompiled block");
+        assert_eq!(settings.repository, "owner/repo");
+        assert_eq!(
+            settings.url_prefix,
+            "https://github.com/owner/repo/releases/download/"
+        );
+        assert_eq!(
+            settings.asset_url("abc123", "demo.zip"),
+            "https://github.com/owner/repo/releases/download/abc123/demo.zip"
+        );
+    }
+
+    #[test]
+    fn reads_executor_overrides_from_xforge_yaml() {
+        let dir = temp_dir("executors");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  executors:\n    aarch64-linux-android: cross\n",
+        )
+        .expect("write config");
+        let settings = build_settings(&dir).expect("build settings");
+        assert_eq!(
+            settings
+                .executor_overrides
+                .get("aarch64-linux-android")
+                .map(String::as_str),
+            Some("cross")
+        );
+    }
+
+    #[test]
+    fn reads_cache_settings_relative_to_manifest_dir() {
+        let dir = temp_dir("cache");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  cache:\n    dir: .xforge-cache\n    max_size_mb: 2\n",
+        )
+        .expect("write config");
+        let settings = build_settings(&dir).expect("build settings");
+        assert!(settings.cache.enabled);
+        assert_eq!(settings.cache.dir, Some(dir.join(".xforge-cache")));
+        assert_eq!(settings.cache.max_size_bytes, Some(2 * 1024 * 1024));
+    }
+
+    #[test]
+    fn reads_timeouts_and_retry_policy() {
+        let dir = temp_dir("timeouts");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  timeout_secs:\n    default: 1800\n    aarch64-linux-android: 3600\n  retry:\n    attempts: 0\n",
+        )
+        .expect("write config");
+        let settings = build_settings(&dir).expect("build settings");
+        assert_eq!(settings.timeout_secs_for("aarch64-linux-android"), Some(3600));
+        assert_eq!(settings.timeout_secs_for("x86_64-unknown-linux-gnu"), Some(1800));
+        assert_eq!(
+            settings.retry,
+            RetrySettings {
+                attempts: 1,
+                delay_secs: 10,
+            }
+        );
+        assert_eq!(BuildSettings::default().timeout_secs_for("x86_64-unknown-linux-gnu"), None);
+    }
+
+    #[test]
+    fn reads_release_profiles_primary_first() {
+        let dir = temp_dir("profiles");
+        assert_eq!(
+            BuildSettings::default().release_profiles(),
+            vec!["release".to_string()]
+        );
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  profiles: [release, release-with-asserts]\n",
+        )
+        .expect("write config");
+        let settings = build_settings(&dir).expect("build settings");
+        assert_eq!(
+            settings.release_profiles(),
+            vec!["release".to_string(), "release-with-asserts".to_string()]
+        );
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  profiles: [release, release]\n",
+        )
+        .expect("write config");
+        assert!(matches!(
+            build_settings(&dir),
+            Err(ConfigError::InvalidProfile { profile }) if profile == "release"
+        ));
+    }
+
+    #[test]
+    fn reads_feature_variants() {
+        let dir = temp_dir("variants");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  variants:\n    gpu:\n      features: [gpu]\n      rustflags: [\"-Ctarget-cpu=native\"]\n",
+        )
+        .expect("write config");
+        let settings = build_settings(&dir).expect("build settings");
+        assert_eq!(
+            settings.variants.get("gpu"),
+            Some(&VariantSettings {
+                features: vec!["gpu".to_string()],
+                rustflags: vec!["-Ctarget-cpu=native".to_string()],
+            })
+        );
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  profiles: [release, fast]\n  variants:\n    fast:\n      features: [simd]\n",
+        )
+        .expect("write config");
+        assert!(matches!(
+            build_settings(&dir),
+            Err(ConfigError::InvalidVariant { variant }) if variant == "fast"
+        ));
+    }
+
+    #[test]
+    fn debug_variant_name_is_reserved() {
+        let dir = temp_dir("debug-variant");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  variants:\n    debug:\n      features: [trace]\n",
+        )
+        .expect("write config");
+        assert!(matches!(
+            build_settings(&dir),
+            Err(ConfigError::InvalidVariant { variant }) if variant == "debug"
+        ));
+    }
+
+    #[test]
+    fn reads_archive_kinds_with_default_fallback() {
+        let dir = temp_dir("archives");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  archives:\n    default: tar.zst\n    x86_64-unknown-linux-gnu: tar.xz\n",
+        )
+        .expect("write config");
+        let settings = build_settings(&dir).expect("build settings");
+        assert_eq!(
+            settings.archive_kind_for("x86_64-unknown-linux-gnu"),
+            Some(ArchiveKind::TarXz)
+        );
+        assert_eq!(
+            settings.archive_kind_for("aarch64-linux-android"),
+            Some(ArchiveKind::TarZst)
+        );
+        assert_eq!(
+            BuildSettings::default().archive_kind_for("aarch64-linux-android"),
+            None
+        );
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  archives:\n    default: tar.bz2\n",
+        )
+        .expect("write config");
+        assert!(matches!(
+            build_settings(&dir),
+            Err(ConfigError::InvalidArchiveKind { value, .. }) if value == "tar.bz2"
+        ));
+    }
+
+    #[test]
+    fn reads_header_settings() {
+        let dir = temp_dir("headers");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  headers:\n    config: cbindgen.toml\n    language: c++\n",
+        )
+        .expect("write config");
+        let headers = build_settings(&dir)
+            .expect("build settings")
+            .headers
+            .expect("headers");
+        assert_eq!(headers.config, Some(dir.join("cbindgen.toml")));
+        assert_eq!(headers.language, HeaderLanguage::Cxx);
+        assert!(!headers.per_target);
+        assert_eq!(headers.file_name_for("demo-ffi"), "demo_ffi.hpp");
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  headers:\n    file_name: include/demo.h\n",
+        )
+        .expect("write config");
+        assert!(matches!(
+            build_settings(&dir),
+            Err(ConfigError::InvalidHeaderFileName { file_name }) if file_name == "include/demo.h"
+        ));
+    }
+
+    #[test]
+    fn reads_xcframework_settings() {
+        let dir = temp_dir("xcframework");
+        std::fs::write(dir.join("xforge.yaml"), "build:\n  xcframework: {}\n")
+            .expect("write config");
+        let xcframework = build_settings(&dir)
+            .expect("build settings")
+            .xcframework
+            .expect("xcframework");
+        assert!(!xcframework.xcodebuild);
+        assert_eq!(xcframework.module_for("demo-ffi"), "demo_ffi");
+        assert_eq!(xcframework.spm, None);
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  xcframework:\n    spm:\n      output_dir: ../demo-swift\n",
+        )
+        .expect("write config");
+        let spm = build_settings(&dir)
+            .expect("build settings")
+            .xcframework
+            .and_then(|xcframework| xcframework.spm)
+            .expect("spm");
+        assert_eq!(spm.package, None);
+        assert!(spm.asset);
+        assert_eq!(spm.output_dir, Some(dir.join("../demo-swift")));
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  xcframework:\n    cocoapods:\n      deployment_target: '13.0'\n",
+        )
+        .expect("write config");
+        let cocoapods = build_settings(&dir)
+            .expect("build settings")
+            .xcframework
+            .and_then(|xcframework| xcframework.cocoapods)
+            .expect("cocoapods");
+        assert_eq!(cocoapods.name_for("DemoFFI"), "DemoFFI");
+        assert_eq!(cocoapods.deployment_target, "13.0");
+        assert_eq!(cocoapods.swift_version, "5.0");
+        assert!(cocoapods.asset);
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  xcframework:\n    cocoapods:\n      name: Demo FFI\n",
+        )
+        .expect("write config");
+        assert!(matches!(
+            build_settings(&dir),
+            Err(ConfigError::InvalidPodName { name }) if name == "Demo FFI"
+        ));
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  xcframework:\n    module: Demo-FFI\n",
+        )
+        .expect("write config");
+        assert!(matches!(
+            build_settings(&dir),
+            Err(ConfigError::InvalidXcframeworkModule { module }) if module == "Demo-FFI"
+        ));
+    }
+
+    #[test]
+    fn reads_aar_settings() {
+        let dir = temp_dir("aar");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  aar:\n    glue: true\n    proguard: rules.pro\n",
+        )
+        .expect("write config");
+        let aar = build_settings(&dir)
+            .expect("build settings")
+            .aar
+            .expect("aar");
+        assert_eq!(aar.min_sdk, 21);
+        assert!(aar.glue);
+        assert_eq!(aar.proguard, Some(dir.join("rules.pro")));
+        assert_eq!(aar.namespace_for("demo-ffi"), "demo_ffi");
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  aar:\n    namespace: com.example.1demo\n",
+        )
+        .expect("write config");
+        assert!(matches!(
+            build_settings(&dir),
+            Err(ConfigError::InvalidAarNamespace { namespace }) if namespace == "com.example.1demo"
+        ));
+    }
+
+    #[test]
+    fn reads_wheel_settings() {
+        let dir = temp_dir("wheel");
+        std::fs::write(dir.join("xforge.yaml"), "build:\n  wheel: {}\n").expect("write config");
+        let wheel = build_settings(&dir)
+            .expect("build settings")
+            .wheel
+            .expect("wheel");
+        assert!(!wheel.loader);
+        assert_eq!(wheel.name_for("demo-ffi"), "demo-ffi");
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  wheel:\n    name: demo_\n",
+        )
+        .expect("write config");
+        assert!(matches!(
+            build_settings(&dir),
+            Err(ConfigError::InvalidWheelName { name }) if name == "demo_"
+        ));
+    }
+
+    #[test]
+    fn reads_nuget_settings() {
+        let dir = temp_dir("nuget");
+        std::fs::write(dir.join("xforge.yaml"), "build:\n  nuget: {}\n").expect("write config");
+        let nuget = build_settings(&dir)
+            .expect("build settings")
+            .nuget
+            .expect("nuget");
+        assert_eq!(nuget.id_for("demo-ffi"), "demo-ffi");
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  nuget:\n    id: Acme..Core\n",
+        )
+        .expect("write config");
+        assert!(matches!(
+            build_settings(&dir),
+            Err(ConfigError::InvalidNugetId { id }) if id == "Acme..Core"
+        ));
+    }
+
+    #[test]
+    fn reads_maven_settings() {
+        let dir = temp_dir("maven");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  aar: {}\n  maven:\n    group_id: com.acme\n",
+        )
+        .expect("write config");
+        let maven = build_settings(&dir)
+            .expect("build settings")
+            .maven
+            .expect("maven");
+        assert_eq!(maven.group_id, "com.acme");
+        assert_eq!(maven.artifact_id_for("core"), "core");
+        assert_eq!(maven.repository, None);
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  aar: {}\n  maven:\n    group_id: com.acme\n    artifact_id: core/ffi\n",
+        )
+        .expect("write config");
+        assert!(matches!(
+            build_settings(&dir),
+            Err(ConfigError::InvalidMavenCoordinate { field: "artifact_id", value }) if value == "core/ffi"
+        ));
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  maven:\n    group_id: com.acme\n",
+        )
+        .expect("write config");
+        assert!(matches!(
+            build_settings(&dir),
+            Err(ConfigError::MavenWithoutAar)
+        ));
+    }
+
+    #[test]
+    fn reads_library_outputs_with_default_fallback() {
+        let dir = temp_dir("outputs");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  outputs:\n    default: [dynamic]\n    aarch64-apple-ios: [static]\n",
+        )
+        .expect("write config");
+        let settings = build_settings(&dir).expect("build settings");
+        assert_eq!(
+            settings.library_outputs_for("aarch64-apple-ios"),
+            Some(&[LibraryKind::Static][..])
+        );
+        assert_eq!(
+            settings.library_outputs_for("x86_64-unknown-linux-gnu"),
+            Some(&[LibraryKind::Dynamic][..])
+        );
+
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  outputs:\n    aarch64-apple-ios: [shared]\n",
+        )
+        .expect("write config");
+        assert!(matches!(build_settings(&dir), Err(ConfigError::Yaml(_))));
+    }
+
+    #[test]
+    fn reads_strip_settings() {
+        let dir = temp_dir("strip");
+        assert_eq!(build_settings(&dir).expect("defaults").strip, StripSettings::default());
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  strip:\n    mode: symbols\n    split_debuginfo: false\n    objcopy: /opt/llvm/bin/llvm-objcopy\n",
+        )
+        .expect("write config");
+        let strip = build_settings(&dir).expect("build settings").strip;
+        assert_eq!(strip.mode, StripMode::Symbols);
+        assert!(!strip.split_debuginfo);
+        assert_eq!(strip.objcopy, Some(PathBuf::from("/opt/llvm/bin/llvm-objcopy")));
+    }
+
+    #[test]
+    fn deterministic_builds_are_on_unless_disabled() {
+        let dir = temp_dir("deterministic");
+        assert!(build_settings(&dir).expect("defaults").deterministic.enabled);
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  deterministic: false\n  env_passthrough: [RUSTC_WRAPPER, SCCACHE_*]\n",
+        )
+        .expect("write config");
+        let deterministic = build_settings(&dir).expect("build settings").deterministic;
+        assert!(!deterministic.enabled);
+        assert_eq!(deterministic.env_passthrough, vec!["RUSTC_WRAPPER", "SCCACHE_*"]);
+    }
+
+    #[test]
+    fn reads_build_command() {
+        let dir = temp_dir("command");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  command:\n    run: make TARGET={target}\n    outputs: \"out/{target}/*.so\"\n",
+        )
+        .expect("write config");
+        let command = build_settings(&dir).expect("build settings").command;
+        assert_eq!(
+            command,
+            Some(CommandSettings {
+                run: "make TARGET={target}".to_string(),
+                outputs: "out/{target}/*.so".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn executor_override_for_unknown_target_is_rejected() {
+        let dir = temp_dir("executors-invalid");
+        std::fs::write(
+            dir.join("xforge.yaml"),
+            "build:\n  executors:\n    mips-unknown-linux-gnu: cargo\n",
+        )
+        .expect("write config");
+        let error = build_settings(&dir).expect_err("error");
+        assert!(error.to_string().contains("invalid build target"));
+    }
+
     #[test]
     fn missing_toolchain_fields_are_rejected() {
         let dir = temp_dir("missing-fields");
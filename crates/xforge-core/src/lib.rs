@@ -1,4 +1,5 @@
 pub mod artifact;
+pub mod binary;
 pub mod bindings;
 pub mod build_id;
 pub mod build_plan;
@@ -0,0 +1,190 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use super::schema::Manifest;
+
+/// Exported-symbol changes between two releases of the same package.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbiDiff {
+    pub old_version: String,
+    pub new_version: String,
+    pub platforms: Vec<PlatformAbiDiff>,
+}
+
+/// Symbol changes for one platform of the old manifest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlatformAbiDiff {
+    pub platform: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AbiDiffError {
+    InvalidVersion(String),
+}
+
+impl fmt::Display for AbiDiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbiDiffError::InvalidVersion(version) => {
+                write!(f, "package version '{}' is not a semver version", version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AbiDiffError {}
+
+impl AbiDiff {
+    pub fn has_removals(&self) -> bool {
+        self.platforms
+            .iter()
+            .any(|platform| !platform.removed.is_empty())
+    }
+
+    /// Whether the new version is allowed to break the ABI under Cargo's
+    /// semver rules: a major bump, a minor bump while the major is 0, or any
+    /// bump while the version is `0.0.x`.
+    pub fn is_major_bump(&self) -> Result<bool, AbiDiffError> {
+        let old = parse_version(&self.old_version)?;
+        let new = parse_version(&self.new_version)?;
+        Ok(match (old, new) {
+            ((0, 0, old_patch), (0, 0, new_patch)) => new_patch > old_patch,
+            ((0, 0, _), (0, _, _)) => true,
+            ((0, old_minor, _), (0, new_minor, _)) => new_minor > old_minor,
+            ((old_major, _, _), (new_major, _, _)) => new_major > old_major,
+        })
+    }
+}
+
+/// Compare the `exportedSymbols` of every platform of the old manifest with
+/// the same platform in the new one. A platform missing from the new manifest,
+/// or exporting nothing there, has every old symbol removed. Platforms without
+/// a symbol list in the old manifest (written before symbols were recorded)
+/// are skipped.
+pub fn diff_exported_symbols(old: &Manifest, new: &Manifest) -> AbiDiff {
+    let mut platforms = Vec::new();
+    for old_platform in &old.platforms.targets {
+        if old_platform.exported_symbols.is_empty() {
+            continue;
+        }
+        let new_symbols: BTreeSet<&String> = new
+            .platforms
+            .targets
+            .iter()
+            .find(|candidate| candidate.name == old_platform.name)
+            .map(|new_platform| new_platform.exported_symbols.iter().collect())
+            .unwrap_or_default();
+        let old_symbols: BTreeSet<&String> = old_platform.exported_symbols.iter().collect();
+        platforms.push(PlatformAbiDiff {
+            platform: old_platform.name.clone(),
+            added: new_symbols
+                .difference(&old_symbols)
+                .map(|symbol| symbol.to_string())
+                .collect(),
+            removed: old_symbols
+                .difference(&new_symbols)
+                .map(|symbol| symbol.to_string())
+                .collect(),
+        });
+    }
+    AbiDiff {
+        old_version: old.package.version.clone(),
+        new_version: new.package.version.clone(),
+        platforms,
+    }
+}
+
+fn parse_version(version: &str) -> Result<(u64, u64, u64), AbiDiffError> {
+    let invalid = || AbiDiffError::InvalidVersion(version.to_string());
+    let core = version.split(['-', '+']).next().unwrap_or_default();
+    let mut parts = core.split('.').map(|part| part.parse::<u64>());
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch)), None) => Ok((major, minor, patch)),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::deserialize_manifest;
+
+    fn manifest(version: &str, symbols: &[&str]) -> Manifest {
+        let json = format!(
+            r#"{{
+  "schemaVersion": "1.0.0",
+  "package": {{ "name": "demo", "version": "{}" }},
+  "build": {{ "id": "b1-demo", "identity": {{ "host": "h", "toolchain": "stable" }} }},
+  "artifacts": {{ "naming": {{ "template": "t", "delimiter": "-", "includePlatform": true, "includeBinding": false }} }},
+  "bindings": {{ "catalog": [] }},
+  "platforms": {{
+    "default": "x86_64-unknown-linux-gnu",
+    "targets": [{{
+      "name": "x86_64-unknown-linux-gnu",
+      "buildId": "b1-demo",
+      "exportedSymbols": {}
+    }}]
+  }}
+}}"#,
+            version,
+            serde_json::to_string(symbols).expect("symbols")
+        );
+        deserialize_manifest(&json).expect("manifest")
+    }
+
+    #[test]
+    fn reports_added_and_removed_symbols() {
+        let diff = diff_exported_symbols(
+            &manifest("1.2.0", &["demo_add", "demo_sub"]),
+            &manifest("1.3.0", &["demo_add", "demo_mul"]),
+        );
+        assert_eq!(
+            diff.platforms,
+            vec![PlatformAbiDiff {
+                platform: "x86_64-unknown-linux-gnu".to_string(),
+                added: vec!["demo_mul".to_string()],
+                removed: vec!["demo_sub".to_string()],
+            }]
+        );
+        assert!(diff.has_removals());
+        assert_eq!(diff.is_major_bump(), Ok(false));
+    }
+
+    #[test]
+    fn dropped_platforms_and_empty_exports_remove_every_symbol() {
+        let old = manifest("1.2.0", &["demo_add"]);
+        let mut dropped = manifest("1.3.0", &["demo_add"]);
+        dropped.platforms.targets.clear();
+        let emptied = manifest("1.3.0", &[]);
+        for new in [dropped, emptied] {
+            let diff = diff_exported_symbols(&old, &new);
+            assert_eq!(
+                diff.platforms,
+                vec![PlatformAbiDiff {
+                    platform: "x86_64-unknown-linux-gnu".to_string(),
+                    added: vec![],
+                    removed: vec!["demo_add".to_string()],
+                }]
+            );
+        }
+
+        let legacy = diff_exported_symbols(&manifest("1.2.0", &[]), &manifest("1.3.0", &["a"]));
+        assert!(legacy.platforms.is_empty());
+    }
+
+    #[test]
+    fn major_bump_follows_cargo_semver_rules() {
+        let bump = |old: &str, new: &str| {
+            diff_exported_symbols(&manifest(old, &["a"]), &manifest(new, &["a"])).is_major_bump()
+        };
+        assert_eq!(bump("1.4.2", "2.0.0"), Ok(true));
+        assert_eq!(bump("1.4.2", "1.5.0"), Ok(false));
+        assert_eq!(bump("0.3.1", "0.4.0"), Ok(true));
+        assert_eq!(bump("0.3.1", "0.3.2"), Ok(false));
+        assert_eq!(bump("0.0.3", "0.0.4"), Ok(true));
+        assert_eq!(bump("1.0.0-rc.1", "2.0.0"), Ok(true));
+        assert!(bump("1.0", "2.0.0").is_err());
+    }
+}
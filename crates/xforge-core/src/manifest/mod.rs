@@ -1,10 +1,12 @@
+pub mod abi;
 pub mod schema;
 pub mod serialize;
 pub mod validate;
 
+pub use abi::{diff_exported_symbols, AbiDiff, AbiDiffError, PlatformAbiDiff};
 pub use schema::{
-    ArtifactNaming, Artifacts, BindingDescriptor, Bindings, Build, BuildIdentity, Manifest,
-    Package, Platform, Platforms, Signing,
+    ArtifactNaming, Artifacts, BindingDescriptor, Bindings, Build, BuildIdentity,
+    DeterministicBuild, HeaderDigest, Manifest, Package, Platform, PlatformVariant, Platforms, Signing,
 };
 pub use serialize::{
     deserialize_manifest, serialize_manifest, serialize_manifest_pretty, signing_payload,
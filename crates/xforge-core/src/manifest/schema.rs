@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+use crate::artifact::LibraryEntry;
+use crate::binary::BinaryInfo;
+
 pub const SCHEMA_VERSION: &str = "xforge.manifest.v1";
 
 /// The canonical `xforge.manifest.v1` contract.
@@ -74,7 +77,9 @@ pub struct Build {
 ///
 /// `host` and `toolchain` are required. `profile` is optional and `features` is
 /// an optional list that defaults to an empty vector so callers can push
-/// additional tags without missing the field entirely.
+/// additional tags without missing the field entirely. `deterministic` records
+/// the environment normalisation the build ran under and is absent when it was
+/// disabled.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BuildIdentity {
@@ -84,6 +89,23 @@ pub struct BuildIdentity {
     pub profile: Option<String>,
     #[serde(default)]
     pub features: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deterministic: Option<DeterministicBuild>,
+}
+
+/// Environment settings applied to make a build reproducible.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeterministicBuild {
+    pub source_date_epoch: u64,
+    /// Prefixes that workspace, cargo home and rustup home paths were
+    /// remapped to with `--remap-path-prefix`.
+    #[serde(default)]
+    pub remapped_path_prefixes: Vec<String>,
+    pub cargo_incremental: bool,
+    /// Variables passed through from the build host's environment.
+    #[serde(default)]
+    pub env_allowlist: Vec<String>,
 }
 
 /// Describes how artifacts are named.
@@ -175,10 +197,13 @@ pub struct Platforms {
 
 /// Details for a single platform.
 ///
-/// `name` is required. The `triples`, `bindings`, and `artifacts` lists default
-/// to empty collections so they can be omitted and still produce a valid
-/// manifest. `description` is optional and may describe selection or ordering
-/// hints.
+/// `name` is required. The `triples`, `bindings`, `artifacts`, `libraries`,
+/// and `debug_artifacts` lists default to empty collections so they can be
+/// omitted and still produce a valid manifest. `libraries` names each linkable
+/// output inside the archive so adapters can pick static or dynamic linking.
+/// `debug_artifacts` lists split debug-info archives that adapters skip unless
+/// asked for symbols. `description` is optional and may describe selection or
+/// ordering hints.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Platform {
@@ -191,7 +216,81 @@ pub struct Platform {
     #[serde(default)]
     pub artifacts: Vec<String>,
     #[serde(default)]
+    pub libraries: Vec<LibraryEntry>,
+    #[serde(default)]
+    pub debug_artifacts: Vec<String>,
+    /// Header properties of the dynamic library, checked against the platform
+    /// key when bundling.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary: Option<BinaryInfo>,
+    /// Sorted names the dynamic library exports, compared by `xforge abi-diff`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exported_symbols: Vec<String>,
+    /// Generated C headers packed under `include/` in every archive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HeaderDigest>,
+    #[serde(default)]
     pub description: Option<String>,
+    /// Cargo profile of the top-level artifacts, which are the primary
+    /// variant adapters use unless asked for another one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Additional builds of the same platform, such as a second profile.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<PlatformVariant>,
+}
+
+impl Platform {
+    /// The variant called `name`, if the platform carries one.
+    pub fn variant(&self, name: &str) -> Option<&PlatformVariant> {
+        self.variants.iter().find(|variant| variant.name == name)
+    }
+}
+
+/// An alternative build of a platform's library.
+///
+/// `name` is required and unique within the platform; adapters select a
+/// variant by it and otherwise use the platform's top-level artifacts. A
+/// variant is either another cargo profile or a `build.variants` entry, in
+/// which case `features` and `rustflags` record what it adds to the primary
+/// build. The remaining fields mirror those of [`Platform`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformVariant {
+    pub name: String,
+    pub build_id: String,
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rustflags: Vec<String>,
+    #[serde(default)]
+    pub artifacts: Vec<String>,
+    #[serde(default)]
+    pub libraries: Vec<LibraryEntry>,
+    #[serde(default)]
+    pub debug_artifacts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary: Option<BinaryInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exported_symbols: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HeaderDigest>,
+}
+
+/// The header files an archive carries and a digest of their content.
+///
+/// `sha256` covers every file's relative path and bytes in sorted order, so
+/// two platforms shipping the same headers record the same digest.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeaderDigest {
+    /// Directory inside the archive, `include`.
+    pub path: String,
+    /// Files relative to `path`, sorted.
+    pub files: Vec<String>,
+    pub sha256: String,
 }
 
 /// Optional manifest signing metadata.
@@ -263,7 +362,28 @@ mod tests {
         "triples": ["x86_64-unknown-linux-gnu"],
         "bindings": ["dart", "python"],
         "artifacts": ["bundle", "wheel"],
-        "description": "Primary developer linux target"
+        "description": "Primary developer linux target",
+        "profile": "release",
+        "variants": [
+          {
+            "name": "release-with-asserts",
+            "buildId": "b1-demo",
+            "profile": "release-with-asserts",
+            "artifacts": ["bundle-release-with-asserts"]
+          },
+          {
+            "name": "gpu",
+            "buildId": "b1-demo-gpu",
+            "profile": "release",
+            "features": ["gpu"],
+            "artifacts": ["bundle-gpu"]
+          }
+        ],
+        "headers": {
+          "path": "include",
+          "files": ["xforge_cargo.h"],
+          "sha256": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+        }
       },
       {
         "name": "aarch64-linux-android",
@@ -292,5 +412,22 @@ mod tests {
             .targets
             .iter()
             .any(|platform| platform.name == "aarch64-linux-android"));
+        let linux = &manifest.platforms.targets[0];
+        assert_eq!(linux.profile.as_deref(), Some("release"));
+        assert_eq!(
+            linux
+                .variant("release-with-asserts")
+                .map(|variant| variant.artifacts.clone()),
+            Some(vec!["bundle-release-with-asserts".to_string()])
+        );
+        assert_eq!(
+            linux.variant("gpu").map(|variant| variant.features.clone()),
+            Some(vec!["gpu".to_string()])
+        );
+        assert_eq!(
+            linux.headers.as_ref().map(|headers| headers.files.clone()),
+            Some(vec!["xforge_cargo.h".to_string()])
+        );
+        assert!(manifest.platforms.targets[1].variants.is_empty());
     }
 }
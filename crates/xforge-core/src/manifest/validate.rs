@@ -39,6 +39,10 @@ pub enum ManifestError {
     MissingPlatformBuildId {
         platform: String,
     },
+    DuplicateVariant {
+        platform: String,
+        variant: String,
+    },
 }
 
 impl std::fmt::Display for ManifestError {
@@ -90,6 +94,11 @@ impl std::fmt::Display for ManifestError {
             ManifestError::MissingPlatformBuildId { platform } => {
                 write!(f, "platform '{}' missing build_id", platform)
             }
+            ManifestError::DuplicateVariant { platform, variant } => write!(
+                f,
+                "platform '{}' declares variant '{}' more than once",
+                platform, variant
+            ),
         }
     }
 }
@@ -103,11 +112,25 @@ pub fn validate(manifest: &Manifest) -> Result<(), ManifestError> {
                 platform: platform.name.clone(),
             });
         }
-        if platform.build_id.trim().is_empty() {
+        if platform.build_id.trim().is_empty()
+            || platform
+                .variants
+                .iter()
+                .any(|variant| variant.build_id.trim().is_empty())
+        {
             return Err(ManifestError::MissingPlatformBuildId {
                 platform: platform.name.clone(),
             });
         }
+        let mut variant_names = HashSet::new();
+        for variant in &platform.variants {
+            if variant.name.trim().is_empty() || !variant_names.insert(variant.name.as_str()) {
+                return Err(ManifestError::DuplicateVariant {
+                    platform: platform.name.clone(),
+                    variant: variant.name.clone(),
+                });
+            }
+        }
     }
 
     if manifest.platforms.default.parse::<PlatformKey>().is_err() {
@@ -152,7 +175,16 @@ pub fn validate(manifest: &Manifest) -> Result<(), ManifestError> {
 
     let mut artifact_platforms: HashMap<String, String> = HashMap::new();
     for platform in &manifest.platforms.targets {
-        for artifact in &platform.artifacts {
+        let variant_artifacts = platform
+            .variants
+            .iter()
+            .flat_map(|variant| variant.artifacts.iter().chain(&variant.debug_artifacts));
+        for artifact in platform
+            .artifacts
+            .iter()
+            .chain(&platform.debug_artifacts)
+            .chain(variant_artifacts)
+        {
             if artifact.trim().is_empty() {
                 return Err(ManifestError::EmptyArtifactIdentifier {
                     platform: platform.name.clone(),
@@ -217,7 +249,7 @@ mod tests {
     use super::*;
     use crate::manifest::{
         ArtifactNaming, Artifacts, BindingDescriptor, Bindings, Build, BuildIdentity, Manifest,
-        Package, Platform, Platforms,
+        Package, Platform, PlatformVariant, Platforms,
     };
 
     fn sample_manifest() -> Manifest {
@@ -239,6 +271,7 @@ mod tests {
                     toolchain: "rustc 1.78.0".to_string(),
                     profile: Some("release".to_string()),
                     features: vec!["feature-a".to_string()],
+                    deterministic: None,
                 },
                 timestamp: None,
                 engine: None,
@@ -268,7 +301,14 @@ mod tests {
                     triples: vec!["x86_64-unknown-linux-gnu".to_string()],
                     bindings: vec!["dart".to_string()],
                     artifacts: vec!["bundle".to_string()],
+                    libraries: vec![],
+                    debug_artifacts: vec![],
+                    binary: None,
+                    exported_symbols: vec![],
+                    headers: None,
                     description: None,
+                    profile: None,
+                    variants: vec![],
                 }],
             },
         }
@@ -277,7 +317,7 @@ mod tests {
     #[test]
     fn invalid_default_platform_fails() {
         let mut manifest = sample_manifest();
-        manifest.platforms.default = "ios-arm64".to_string();
+        manifest.platforms.default = "aarch64-apple-ios".to_string();
 
         let result = validate(&manifest);
         assert!(matches!(
@@ -286,6 +326,18 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn unknown_default_platform_key_fails() {
+        let mut manifest = sample_manifest();
+        manifest.platforms.default = "ios-arm64".to_string();
+
+        let result = validate(&manifest);
+        assert!(matches!(
+            result,
+            Err(ManifestError::InvalidPlatformKey { .. })
+        ));
+    }
+
     #[test]
     fn invalid_platform_key_fails() {
         let mut manifest = sample_manifest();
@@ -319,7 +371,14 @@ mod tests {
             triples: vec!["aarch64-linux-android".to_string()],
             bindings: vec!["dart".to_string()],
             artifacts: vec!["bundle".to_string()],
+            libraries: vec![],
+            debug_artifacts: vec![],
+            binary: None,
+            exported_symbols: vec![],
+            headers: None,
             description: None,
+            profile: None,
+            variants: vec![],
         });
 
         let result = validate(&manifest);
@@ -329,6 +388,43 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn variant_artifacts_share_the_identifier_namespace() {
+        let variant = PlatformVariant {
+            name: "release-with-asserts".to_string(),
+            build_id: "b1-demo".to_string(),
+            profile: Some("release-with-asserts".to_string()),
+            features: vec![],
+            rustflags: vec![],
+            artifacts: vec!["bundle-release-with-asserts".to_string()],
+            libraries: vec![],
+            debug_artifacts: vec![],
+            binary: None,
+            exported_symbols: vec![],
+            headers: None,
+        };
+        let mut manifest = sample_manifest();
+        manifest.platforms.targets[0].variants = vec![variant.clone()];
+        manifest.bindings.catalog[0]
+            .artifacts
+            .push("bundle-release-with-asserts".to_string());
+        assert!(validate(&manifest).is_ok());
+
+        manifest.platforms.targets[0].variants.push(variant.clone());
+        assert!(matches!(
+            validate(&manifest),
+            Err(ManifestError::DuplicateVariant { .. })
+        ));
+
+        let mut clashing = variant;
+        clashing.artifacts = vec!["bundle".to_string()];
+        manifest.platforms.targets[0].variants = vec![clashing];
+        assert!(matches!(
+            validate(&manifest),
+            Err(ManifestError::DuplicateArtifactIdentifier { .. })
+        ));
+    }
+
     #[test]
     fn artifact_missing_platform_fails() {
         let mut manifest = sample_manifest();
@@ -349,7 +445,14 @@ mod tests {
             triples: vec!["aarch64-linux-android".to_string()],
             bindings: vec!["dart".to_string()],
             artifacts: vec![],
+            libraries: vec![],
+            debug_artifacts: vec![],
+            binary: None,
+            exported_symbols: vec![],
+            headers: None,
             description: None,
+            profile: None,
+            variants: vec![],
             build_id: "b1-demo-android".to_string(),
         });
         manifest.bindings.catalog[0].platforms = vec!["aarch64-linux-android".to_string()];
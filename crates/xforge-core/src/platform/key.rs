@@ -1,14 +1,21 @@
 use std::fmt;
 use std::str::FromStr;
 
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 use crate::bindings::BindingLanguage;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum PlatformKey {
     LinuxX86_64,
     LinuxAarch64,
+    LinuxX86_64Musl,
+    LinuxAarch64Musl,
     MacosArm64,
     MacosX86_64,
+    /// Pseudo-platform for the fat Mach-O merged from both macOS slices at
+    /// bundle time; nothing is built for it directly.
+    MacosUniversal,
     IosArm64,
     IosSimulatorArm64,
     IosSimulatorX86_64,
@@ -32,6 +39,15 @@ impl PlatformKey {
         self.descriptor().rust_targets
     }
 
+    /// Thin platforms whose libraries are merged into this platform's fat
+    /// Mach-O; empty for every platform that is built directly.
+    pub fn universal_slices(self) -> &'static [PlatformKey] {
+        match self {
+            PlatformKey::MacosUniversal => MACOS_UNIVERSAL_SLICES,
+            _ => &[],
+        }
+    }
+
     pub fn packaging(self) -> PackagingSupport {
         self.descriptor().packaging
     }
@@ -69,6 +85,19 @@ impl FromStr for PlatformKey {
     }
 }
 
+impl Serialize for PlatformKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for PlatformKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum PackagingFormat {
     TarGz,
@@ -122,6 +151,7 @@ pub struct PlatformDescriptor {
 
 const SUPPORTED_BINDINGS: &[BindingLanguage] = &[
     BindingLanguage::Dart,
+    BindingLanguage::Dotnet,
     BindingLanguage::Kotlin,
     BindingLanguage::Python,
     BindingLanguage::Swift,
@@ -135,8 +165,11 @@ const IOS_RUST_TARGETS_SIMULATOR_ARM64: &[&str] = &["aarch64-apple-ios-sim"];
 const IOS_RUST_TARGETS_SIMULATOR_X86_64: &[&str] = &["x86_64-apple-ios"];
 const LINUX_RUST_TARGETS_X86_64: &[&str] = &["x86_64-unknown-linux-gnu"];
 const LINUX_RUST_TARGETS_AARCH64: &[&str] = &["aarch64-unknown-linux-gnu"];
+const LINUX_RUST_TARGETS_X86_64_MUSL: &[&str] = &["x86_64-unknown-linux-musl"];
+const LINUX_RUST_TARGETS_AARCH64_MUSL: &[&str] = &["aarch64-unknown-linux-musl"];
 const MACOS_RUST_TARGETS_ARM64: &[&str] = &["aarch64-apple-darwin"];
 const MACOS_RUST_TARGETS_X86_64: &[&str] = &["x86_64-apple-darwin"];
+const MACOS_UNIVERSAL_SLICES: &[PlatformKey] = &[PlatformKey::MacosArm64, PlatformKey::MacosX86_64];
 const WINDOWS_RUST_TARGETS_X86_64_MSVC: &[&str] = &["x86_64-pc-windows-msvc"];
 const WINDOWS_RUST_TARGETS_ARM64_MSVC: &[&str] = &["aarch64-pc-windows-msvc"];
 
@@ -169,6 +202,20 @@ static PLATFORM_REGISTRY: &[PlatformDescriptor] = &[
         packaging: DEFAULT_LINUX_PACKAGING,
         bindings: DEFAULT_BINDINGS,
     },
+    PlatformDescriptor {
+        key: PlatformKey::LinuxX86_64Musl,
+        key_str: "x86_64-unknown-linux-musl",
+        rust_targets: LINUX_RUST_TARGETS_X86_64_MUSL,
+        packaging: DEFAULT_LINUX_PACKAGING,
+        bindings: DEFAULT_BINDINGS,
+    },
+    PlatformDescriptor {
+        key: PlatformKey::LinuxAarch64Musl,
+        key_str: "aarch64-unknown-linux-musl",
+        rust_targets: LINUX_RUST_TARGETS_AARCH64_MUSL,
+        packaging: DEFAULT_LINUX_PACKAGING,
+        bindings: DEFAULT_BINDINGS,
+    },
     PlatformDescriptor {
         key: PlatformKey::MacosArm64,
         key_str: "aarch64-apple-darwin",
@@ -183,6 +230,13 @@ static PLATFORM_REGISTRY: &[PlatformDescriptor] = &[
         packaging: DEFAULT_APPLE_PACKAGING,
         bindings: DEFAULT_BINDINGS,
     },
+    PlatformDescriptor {
+        key: PlatformKey::MacosUniversal,
+        key_str: "macos-universal",
+        rust_targets: &[],
+        packaging: DEFAULT_APPLE_PACKAGING,
+        bindings: DEFAULT_BINDINGS,
+    },
     PlatformDescriptor {
         key: PlatformKey::IosArm64,
         key_str: "aarch64-apple-ios",
@@ -252,7 +306,7 @@ pub fn all_platform_keys() -> Vec<PlatformKey> {
 pub fn platforms_for_rust_target(triple: &str) -> Vec<PlatformKey> {
     registry()
         .iter()
-        .filter(|entry| entry.rust_targets.iter().any(|target| *target == triple))
+        .filter(|entry| entry.rust_targets.contains(&triple))
         .map(|entry| entry.key)
         .collect()
 }
@@ -272,7 +326,7 @@ pub fn all_rust_targets() -> Vec<&'static str> {
 pub fn is_supported_rust_target(triple: &str) -> bool {
     registry()
         .iter()
-        .any(|entry| entry.rust_targets.iter().any(|target| *target == triple))
+        .any(|entry| entry.rust_targets.contains(&triple))
 }
 
 pub fn binding_support(platform: PlatformKey, binding: &str) -> SupportStatus {
@@ -282,7 +336,7 @@ pub fn binding_support(platform: PlatformKey, binding: &str) -> SupportStatus {
     };
     match platform.bindings() {
         BindingSupport::Known(entries) => {
-            if entries.iter().any(|item| *item == binding) {
+            if entries.contains(&binding) {
                 SupportStatus::Supported
             } else {
                 SupportStatus::Unsupported
@@ -295,7 +349,7 @@ pub fn binding_support(platform: PlatformKey, binding: &str) -> SupportStatus {
 pub fn packaging_support(platform: PlatformKey, packaging: PackagingFormat) -> SupportStatus {
     match platform.packaging() {
         PackagingSupport::Known(entries) => {
-            if entries.iter().any(|item| *item == packaging) {
+            if entries.contains(&packaging) {
                 SupportStatus::Supported
             } else {
                 SupportStatus::Unsupported
@@ -353,6 +407,33 @@ mod tests {
         assert_eq!(keys, vec![PlatformKey::IosArm64]);
     }
 
+    #[test]
+    fn universal_platform_is_not_a_rust_target() {
+        let key: PlatformKey = "macos-universal".parse().expect("should parse");
+        assert_eq!(key, PlatformKey::MacosUniversal);
+        assert!(key.rust_targets().is_empty());
+        assert_eq!(
+            key.universal_slices(),
+            &[PlatformKey::MacosArm64, PlatformKey::MacosX86_64]
+        );
+        assert_eq!(
+            PlatformKey::from_rust_target("aarch64-apple-darwin"),
+            vec![PlatformKey::MacosArm64]
+        );
+    }
+
+    #[test]
+    fn musl_targets_have_their_own_keys() {
+        assert_eq!(
+            PlatformKey::from_rust_target("x86_64-unknown-linux-musl"),
+            vec![PlatformKey::LinuxX86_64Musl]
+        );
+        assert_eq!(
+            PlatformKey::from_rust_target("aarch64-unknown-linux-gnu"),
+            vec![PlatformKey::LinuxAarch64]
+        );
+    }
+
     #[test]
     fn invalid_key_rejected() {
         let result: Result<PlatformKey, _> = "linux".parse();
@@ -35,9 +35,9 @@ pub fn parse_public_key_hex(hex: &str) -> Result<[u8; 32], SigningError> {
         return Err(SigningError::InvalidPublicKeyLength { len: bytes.len() });
     }
     let len = bytes.len();
-    Ok(bytes
+    bytes
         .try_into()
-        .map_err(|_| SigningError::InvalidPublicKeyLength { len })?)
+        .map_err(|_| SigningError::InvalidPublicKeyLength { len })
 }
 
 pub fn parse_private_key_hex(hex: &str) -> Result<[u8; 64], SigningError> {
@@ -46,9 +46,9 @@ pub fn parse_private_key_hex(hex: &str) -> Result<[u8; 64], SigningError> {
         return Err(SigningError::InvalidPrivateKeyLength { len: bytes.len() });
     }
     let len = bytes.len();
-    Ok(bytes
+    bytes
         .try_into()
-        .map_err(|_| SigningError::InvalidPrivateKeyLength { len })?)
+        .map_err(|_| SigningError::InvalidPrivateKeyLength { len })
 }
 
 pub fn sign(private_key: &[u8; 64], payload: &[u8]) -> Result<Vec<u8>, SigningError> {
@@ -1,8 +1,11 @@
 use std::path::Path;
 
+use serde::{Deserialize, Serialize};
+
 use crate::config::{toolchain_settings, ConfigError};
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Toolchain {
     pub channel: Option<String>,
     pub targets: Vec<String>,
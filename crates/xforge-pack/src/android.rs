@@ -1,12 +1,168 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use xforge_core::platform::PlatformKey;
+use xforge_core::artifact::naming::aar_artifact_name;
+use xforge_core::artifact::LibraryKind;
+use xforge_core::bindings::kotlin::ndk_abi;
+use xforge_core::bindings::KotlinBinding;
 
-use crate::common::{derive_package_name, entries_from_dir, replace_extension, write_zip};
-use crate::{PackError, PackExecutor, PackFormat, PackRequest, PackResult};
+use crate::common::{derive_package_name, entries_from_dir, pack_io, write_zip};
+use crate::{PackError, PackExecutor, PackFormat, PackInput, PackRequest, PackResult};
 
-pub struct AarPacker;
+/// Simple name of the glue class written into `classes.jar`.
+const LOADER_CLASS_NAME: &str = "NativeLoader";
+/// Class file major version for Java 8, the oldest target D8 still accepts.
+const CLASS_FILE_MAJOR_VERSION: u16 = 52;
+
+/// Assembles one `.aar` holding the shared library of every Android ABI,
+/// with `AndroidManifest.xml` built from a [`KotlinBinding`].
+pub struct AarPacker {
+    namespace: String,
+    binding: KotlinBinding,
+    glue: bool,
+    proguard_rules: Option<PathBuf>,
+}
+
+impl AarPacker {
+    pub fn new(namespace: impl Into<String>, binding: KotlinBinding) -> Self {
+        Self {
+            namespace: namespace.into(),
+            binding,
+            glue: false,
+            proguard_rules: None,
+        }
+    }
+
+    /// Add a `<namespace>.NativeLoader` class whose static `load()` calls
+    /// `System.loadLibrary` for the packed library.
+    pub fn with_glue(mut self, glue: bool) -> Self {
+        self.glue = glue;
+        self
+    }
+
+    /// Consumer ProGuard rules to ship as `proguard.txt`.
+    pub fn with_proguard_rules(mut self, path: Option<PathBuf>) -> Self {
+        self.proguard_rules = path;
+        self
+    }
+
+    fn write_android_manifest(&self, root: &Path) -> Result<(), PackError> {
+        let contents = format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <manifest xmlns:android=\"http://schemas.android.com/apk/res/android\"\n    \
+             package=\"{}\">\n\n    \
+             <uses-sdk android:minSdkVersion=\"{}\" />\n\n\
+             </manifest>\n",
+            self.namespace, self.binding.min_sdk
+        );
+        fs::write(root.join("AndroidManifest.xml"), contents).map_err(pack_io)
+    }
+
+    fn write_classes_jar(&self, root: &Path, library_name: &str) -> Result<(), PackError> {
+        let classes = tempfile::tempdir().map_err(pack_io)?;
+        if self.glue {
+            let class_dir = classes.path().join(self.namespace.replace('.', "/"));
+            fs::create_dir_all(&class_dir).map_err(pack_io)?;
+            let internal_name =
+                format!("{}/{}", self.namespace.replace('.', "/"), LOADER_CLASS_NAME);
+            fs::write(
+                class_dir.join(format!("{}.class", LOADER_CLASS_NAME)),
+                loader_class(&internal_name, library_name),
+            )
+            .map_err(pack_io)?;
+            let meta_inf = classes.path().join("META-INF");
+            fs::create_dir_all(&meta_inf).map_err(pack_io)?;
+            fs::write(
+                meta_inf.join("MANIFEST.MF"),
+                "Manifest-Version: 1.0\r\n\r\n",
+            )
+            .map_err(pack_io)?;
+        }
+        let entries = entries_from_dir(classes.path())?;
+        write_zip(&root.join("classes.jar"), &entries)
+    }
+
+    fn write_proguard(&self, root: &Path) -> Result<(), PackError> {
+        let mut rules = String::new();
+        if let Some(path) = &self.proguard_rules {
+            rules = fs::read_to_string(path).map_err(|err| PackError::Io {
+                message: format!("failed to read '{}': {}", path.display(), err),
+            })?;
+            if !rules.is_empty() && !rules.ends_with('\n') {
+                rules.push('\n');
+            }
+        }
+        if self.glue {
+            rules.push_str(&format!(
+                "-keep class {}.{} {{ *; }}\n\
+                 -keepclasseswithmembernames,includedescriptorclasses class * {{\n    \
+                 native <methods>;\n\
+                 }}\n",
+                self.namespace, LOADER_CLASS_NAME
+            ));
+        }
+        if rules.is_empty() {
+            return Ok(());
+        }
+        fs::write(root.join("proguard.txt"), rules).map_err(pack_io)
+    }
+
+    fn write_jni_libs(&self, root: &Path, inputs: &[PackInput]) -> Result<String, PackError> {
+        let jni_root = root.join("jni");
+        let mut file_name = None;
+        for input in inputs {
+            let platform = input.artifact.platform;
+            let abi = ndk_abi(platform).ok_or_else(|| PackError::InvalidRequest {
+                message: format!("non-android platform '{}'", platform),
+            })?;
+            if !self.binding.ndk_abis.iter().any(|value| value == abi) {
+                return Err(PackError::InvalidRequest {
+                    message: format!("abi '{}' is not listed in the kotlin binding", abi),
+                });
+            }
+            let library = input
+                .artifact
+                .library(LibraryKind::Dynamic)
+                .ok_or_else(|| PackError::InvalidRequest {
+                    message: format!("aar slice '{}' has no shared library", platform),
+                })?;
+            let name = Path::new(library)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .ok_or_else(|| PackError::InvalidRequest {
+                    message: "android library path missing filename".to_string(),
+                })?;
+            match &file_name {
+                Some(expected) if *expected != name => {
+                    return Err(PackError::InvalidRequest {
+                        message: format!(
+                            "android libraries disagree on name: '{}' vs '{}'",
+                            expected, name
+                        ),
+                    });
+                }
+                _ => file_name = Some(name.clone()),
+            }
+            let abi_dir = jni_root.join(abi);
+            let destination = abi_dir.join(&name);
+            if destination.exists() {
+                return Err(PackError::InvalidRequest {
+                    message: format!("duplicate abi entry '{}'", abi),
+                });
+            }
+            fs::create_dir_all(&abi_dir).map_err(pack_io)?;
+            fs::copy(library, &destination).map_err(pack_io)?;
+        }
+        let file_name = file_name.unwrap_or_default();
+        let library_name = file_name
+            .strip_prefix("lib")
+            .and_then(|name| name.strip_suffix(".so"))
+            .ok_or_else(|| PackError::InvalidRequest {
+                message: format!("'{}' is not named lib<name>.so", file_name),
+            })?;
+        Ok(library_name.to_string())
+    }
+}
 
 impl PackExecutor for AarPacker {
     fn pack(&self, request: &PackRequest) -> Result<PackResult, PackError> {
@@ -20,61 +176,44 @@ impl PackExecutor for AarPacker {
                 message: "aar packer expects at least one input".to_string(),
             });
         }
+        if !is_valid_android_package(&self.namespace) {
+            return Err(PackError::InvalidRequest {
+                message: format!("invalid android namespace '{}'", self.namespace),
+            });
+        }
+        self.binding
+            .validate()
+            .map_err(|err| PackError::InvalidRequest {
+                message: err.to_string(),
+            })?;
         let first = &request.inputs[0];
-        let temp = tempfile::tempdir().map_err(|err| PackError::Io {
-            message: err.to_string(),
-        })?;
+        let temp = tempfile::tempdir().map_err(pack_io)?;
         let root = temp.path();
-        create_classes_jar(root)?;
-        let package_name = android_package_name(&first.artifact)?;
-        write_android_manifest(root, &package_name)?;
+        let library_name = self.write_jni_libs(root, &request.inputs)?;
+        self.write_android_manifest(root)?;
+        self.write_classes_jar(root, &library_name)?;
+        self.write_proguard(root)?;
+        fs::write(root.join("R.txt"), "").map_err(pack_io)?;
         write_metadata(root, &first.layout, &first.artifact)?;
-        write_jni_libs(root, &request.inputs)?;
         let entries = entries_from_dir(root)?;
-        let mut output_dir = PathBuf::from(&request.output_dir);
-        fs::create_dir_all(&output_dir).map_err(|err| PackError::Io {
+        let output_name = aar_artifact_name(
+            &derive_package_name(&first.artifact),
+            &first.artifact.build_id,
+        )
+        .map_err(|err| PackError::InvalidRequest {
             message: err.to_string(),
         })?;
-        let output_name = replace_extension(&first.artifact.artifact_name, "aar");
-        output_dir.push(output_name);
-        write_zip(&output_dir, &entries)?;
+        let mut output_path = PathBuf::from(&request.output_dir);
+        fs::create_dir_all(&output_path).map_err(pack_io)?;
+        output_path.push(output_name);
+        write_zip(&output_path, &entries)?;
         Ok(PackResult {
             format: PackFormat::AAR,
-            output_paths: vec![output_dir.to_string_lossy().into_owned()],
+            output_paths: vec![output_path.to_string_lossy().into_owned()],
         })
     }
 }
 
-fn write_jni_libs(root: &Path, inputs: &[crate::PackInput]) -> Result<(), PackError> {
-    let jni_root = root.join("jni");
-    fs::create_dir_all(&jni_root).map_err(|err| PackError::Io {
-        message: err.to_string(),
-    })?;
-    for input in inputs {
-        let abi = android_abi(input.artifact.platform)?;
-        let abi_dir = jni_root.join(abi);
-        fs::create_dir_all(&abi_dir).map_err(|err| PackError::Io {
-            message: err.to_string(),
-        })?;
-        let library_path = PathBuf::from(&input.artifact.library_path);
-        let filename = library_path
-            .file_name()
-            .ok_or_else(|| PackError::InvalidRequest {
-                message: "android library path missing filename".to_string(),
-            })?;
-        let destination = abi_dir.join(filename);
-        if destination.exists() {
-            return Err(PackError::InvalidRequest {
-                message: format!("duplicate abi entry '{}'", abi),
-            });
-        }
-        fs::copy(&library_path, &destination).map_err(|err| PackError::Io {
-            message: err.to_string(),
-        })?;
-    }
-    Ok(())
-}
-
 fn write_metadata(
     root: &Path,
     layout: &xforge_core::artifact::layout::ArchiveLayout,
@@ -101,30 +240,102 @@ fn write_metadata(
     Ok(())
 }
 
-fn write_android_manifest(root: &Path, package_name: &str) -> Result<(), PackError> {
-    let contents = format!(
-        "<manifest xmlns:android=\"http://schemas.android.com/apk/res/android\" package=\"{}\"></manifest>",
-        package_name
-    );
-    fs::write(root.join("AndroidManifest.xml"), contents).map_err(|err| PackError::Io {
-        message: err.to_string(),
-    })?;
-    Ok(())
+/// Class file of `public final class NativeLoader` with a `LIBRARY_NAME`
+/// constant and a static `load()` that calls `System.loadLibrary` with it.
+/// Written by hand so bundling needs no JDK.
+fn loader_class(internal_name: &str, library_name: &str) -> Vec<u8> {
+    let mut pool = ConstantPool::default();
+    let this_class = pool.class(internal_name);
+    let super_class = pool.class("java/lang/Object");
+    let library = pool.string(library_name);
+    let load_library = pool.method_ref("java/lang/System", "loadLibrary", "(Ljava/lang/String;)V");
+    let field_name = pool.utf8("LIBRARY_NAME");
+    let field_descriptor = pool.utf8("Ljava/lang/String;");
+    let constant_value = pool.utf8("ConstantValue");
+    let method_name = pool.utf8("load");
+    let method_descriptor = pool.utf8("()V");
+    let code = pool.utf8("Code");
+
+    let mut class = Vec::new();
+    class.extend_from_slice(&0xcafe_babeu32.to_be_bytes());
+    class.extend_from_slice(&0u16.to_be_bytes());
+    class.extend_from_slice(&CLASS_FILE_MAJOR_VERSION.to_be_bytes());
+    class.extend_from_slice(&pool.count().to_be_bytes());
+    class.extend_from_slice(&pool.bytes);
+    // ACC_PUBLIC | ACC_FINAL | ACC_SUPER
+    push_u16s(&mut class, &[0x0031, this_class, super_class, 0]);
+    // One `public static final` field with a ConstantValue attribute.
+    push_u16s(&mut class, &[1, 0x0019, field_name, field_descriptor, 1]);
+    push_u16s(&mut class, &[constant_value]);
+    class.extend_from_slice(&2u32.to_be_bytes());
+    push_u16s(&mut class, &[library]);
+    // One `public static` method: ldc, invokestatic, return.
+    let [high, low] = load_library.to_be_bytes();
+    let bytecode = [0x12, library as u8, 0xb8, high, low, 0xb1];
+    push_u16s(&mut class, &[1, 0x0009, method_name, method_descriptor, 1]);
+    push_u16s(&mut class, &[code]);
+    class.extend_from_slice(&(12 + bytecode.len() as u32).to_be_bytes());
+    push_u16s(&mut class, &[1, 0]);
+    class.extend_from_slice(&(bytecode.len() as u32).to_be_bytes());
+    class.extend_from_slice(&bytecode);
+    push_u16s(&mut class, &[0, 0]);
+    // No class attributes.
+    push_u16s(&mut class, &[0]);
+    class
 }
 
-fn android_package_name(
-    artifact: &xforge_core::build_plan::BuiltArtifact,
-) -> Result<String, PackError> {
-    let derived = derive_package_name(artifact);
-    if is_valid_android_package(&derived) {
-        return Ok(derived);
-    }
-    Err(PackError::InvalidRequest {
-        message: format!(
-            "invalid android package name '{}' derived from artifact_name",
-            derived
-        ),
-    })
+#[derive(Default)]
+struct ConstantPool {
+    bytes: Vec<u8>,
+    entries: u16,
+}
+
+impl ConstantPool {
+    fn count(&self) -> u16 {
+        self.entries + 1
+    }
+
+    fn push(&mut self, tag: u8, body: &[u8]) -> u16 {
+        self.bytes.push(tag);
+        self.bytes.extend_from_slice(body);
+        self.entries += 1;
+        self.entries
+    }
+
+    /// Names here are ASCII, where modified UTF-8 equals UTF-8.
+    fn utf8(&mut self, value: &str) -> u16 {
+        let mut body = (value.len() as u16).to_be_bytes().to_vec();
+        body.extend_from_slice(value.as_bytes());
+        self.push(1, &body)
+    }
+
+    fn class(&mut self, name: &str) -> u16 {
+        let name = self.utf8(name);
+        self.push(7, &name.to_be_bytes())
+    }
+
+    fn string(&mut self, value: &str) -> u16 {
+        let value = self.utf8(value);
+        self.push(8, &value.to_be_bytes())
+    }
+
+    fn method_ref(&mut self, class: &str, name: &str, descriptor: &str) -> u16 {
+        let class = self.class(class);
+        let name = self.utf8(name);
+        let descriptor = self.utf8(descriptor);
+        let mut name_and_type = name.to_be_bytes().to_vec();
+        name_and_type.extend_from_slice(&descriptor.to_be_bytes());
+        let name_and_type = self.push(12, &name_and_type);
+        let mut body = class.to_be_bytes().to_vec();
+        body.extend_from_slice(&name_and_type.to_be_bytes());
+        self.push(10, &body)
+    }
+}
+
+fn push_u16s(bytes: &mut Vec<u8>, values: &[u16]) {
+    for value in values {
+        bytes.extend_from_slice(&value.to_be_bytes());
+    }
 }
 
 fn is_valid_android_package(name: &str) -> bool {
@@ -158,26 +369,3 @@ fn is_valid_java_identifier(segment: &str) -> bool {
     }
     chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
 }
-
-fn create_classes_jar(root: &Path) -> Result<(), PackError> {
-    let jar_path = root.join("classes.jar");
-    let file = fs::File::create(&jar_path).map_err(|err| PackError::Io {
-        message: err.to_string(),
-    })?;
-    let writer = zip::ZipWriter::new(file);
-    writer.finish().map_err(|err| PackError::Io {
-        message: err.to_string(),
-    })?;
-    Ok(())
-}
-
-fn android_abi(platform: PlatformKey) -> Result<&'static str, PackError> {
-    match platform {
-        PlatformKey::AndroidArm64 => Ok("arm64-v8a"),
-        PlatformKey::AndroidArmv7 => Ok("armeabi-v7a"),
-        PlatformKey::AndroidX86_64 => Ok("x86_64"),
-        _ => Err(PackError::InvalidRequest {
-            message: format!("non-android platform '{}'", platform),
-        }),
-    }
-}
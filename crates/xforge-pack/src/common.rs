@@ -2,6 +2,7 @@ use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
+use xforge_core::artifact::ArchiveKind;
 use xforge_core::build_plan::BuiltArtifact;
 use walkdir::WalkDir;
 
@@ -29,7 +30,14 @@ pub fn build_archive_entries(input: &PackInput) -> Result<Vec<ArchiveEntry>, Pac
     let mut entries = Vec::new();
     entries.push(file_entry(&artifact.manifest_path, &layout.manifest_path)?);
     entries.push(file_entry(&artifact.build_id_path, &layout.build_id_path)?);
-    entries.push(file_entry(&artifact.library_path, &layout.library_path)?);
+    for library in &layout.libraries {
+        let source = artifact
+            .library(library.kind)
+            .ok_or_else(|| PackError::InvalidRequest {
+                message: format!("artifact has no {} library for '{}'", library.kind, library.path),
+            })?;
+        entries.push(file_entry(source, &library.path)?);
+    }
     if let (Some(include_dir), Some(include_path)) =
         (artifact.include_dir.as_ref(), layout.include_path.as_ref())
     {
@@ -102,56 +110,85 @@ pub fn write_zip(path: &Path, entries: &[ArchiveEntry]) -> Result<(), PackError>
     Ok(())
 }
 
+/// zstd level used for `.tar.zst`; fixed so archives are reproducible.
+pub const ZSTD_LEVEL: i32 = 19;
+/// xz preset used for `.tar.xz`; fixed so archives are reproducible.
+pub const XZ_PRESET: u32 = 6;
+
 pub fn write_tar_gz(path: &Path, entries: &[ArchiveEntry]) -> Result<(), PackError> {
-    let file = fs::File::create(path).map_err(|err| PackError::Io {
-        message: err.to_string(),
-    })?;
+    let file = create_file(path)?;
     let encoder = flate2::GzBuilder::new()
         .mtime(0)
         .write(file, flate2::Compression::default());
-    let mut builder = tar::Builder::new(encoder);
+    write_tar(encoder, entries)?.finish().map_err(pack_io)?;
+    Ok(())
+}
+
+/// Write a zstd-compressed tar. The encoder runs single-threaded at
+/// [`ZSTD_LEVEL`], so the same entries always produce the same bytes.
+pub fn write_tar_zst(path: &Path, entries: &[ArchiveEntry]) -> Result<(), PackError> {
+    let file = create_file(path)?;
+    let mut encoder = zstd::Encoder::new(file, ZSTD_LEVEL).map_err(pack_io)?;
+    encoder.include_checksum(true).map_err(pack_io)?;
+    write_tar(encoder, entries)?.finish().map_err(pack_io)?;
+    Ok(())
+}
+
+/// Write an xz-compressed tar as a single block with the single-threaded
+/// encoder at [`XZ_PRESET`].
+pub fn write_tar_xz(path: &Path, entries: &[ArchiveEntry]) -> Result<(), PackError> {
+    let file = create_file(path)?;
+    let encoder = lzma_rust2::XzWriter::new(file, lzma_rust2::XzOptions::with_preset(XZ_PRESET))
+        .map_err(pack_io)?;
+    write_tar(encoder, entries)?.finish().map_err(pack_io)?;
+    Ok(())
+}
+
+/// Append `entries` to a tar stream with zeroed ownership and timestamps and
+/// return the underlying writer for the caller to finish.
+fn write_tar<W: io::Write>(writer: W, entries: &[ArchiveEntry]) -> Result<W, PackError> {
+    let mut builder = tar::Builder::new(writer);
     for entry in entries {
         let mut header = tar::Header::new_gnu();
         match &entry.source {
             EntrySource::File(path) => {
-                let metadata = fs::metadata(path).map_err(|err| PackError::Io {
-                    message: err.to_string(),
-                })?;
+                let metadata = fs::metadata(path).map_err(pack_io)?;
                 header.set_size(metadata.len());
                 header.set_mode(0o644);
                 header.set_uid(0);
                 header.set_gid(0);
                 header.set_mtime(0);
-                header
-                    .set_path(&entry.archive_path)
-                    .map_err(|err| PackError::Io {
-                        message: err.to_string(),
-                    })?;
+                header.set_path(&entry.archive_path).map_err(pack_io)?;
                 header.set_cksum();
-                let mut input = fs::File::open(path).map_err(|err| PackError::Io {
-                    message: err.to_string(),
-                })?;
+                let mut input = fs::File::open(path).map_err(pack_io)?;
                 builder
                     .append_data(&mut header, &entry.archive_path, &mut input)
-                    .map_err(|err| PackError::Io {
-                        message: err.to_string(),
-                    })?;
+                    .map_err(pack_io)?;
             }
         }
     }
-    builder.finish().map_err(|err| PackError::Io {
+    builder.finish().map_err(pack_io)?;
+    builder.into_inner().map_err(pack_io)
+}
+
+fn create_file(path: &Path) -> Result<fs::File, PackError> {
+    fs::File::create(path).map_err(pack_io)
+}
+
+pub fn pack_io(err: impl std::fmt::Display) -> PackError {
+    PackError::Io {
         message: err.to_string(),
-    })?;
-    builder
-        .into_inner()
-        .map_err(|err| PackError::Io {
-            message: err.to_string(),
-        })?
-        .finish()
-        .map_err(|err| PackError::Io {
-            message: err.to_string(),
-        })?;
-    Ok(())
+    }
+}
+
+/// Escape text for an XML element or an attribute value in either quote.
+pub fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
 }
 
 pub fn derive_package_name(artifact: &BuiltArtifact) -> String {
@@ -163,13 +200,7 @@ pub fn derive_package_name(artifact: &BuiltArtifact) -> String {
 }
 
 pub fn replace_extension(name: &str, new_extension: &str) -> String {
-    if let Some(stripped) = name.strip_suffix(".tar.gz") {
-        return format!("{}.{}", stripped, new_extension);
-    }
-    if let Some(stripped) = name.strip_suffix(".zip") {
-        return format!("{}.{}", stripped, new_extension);
-    }
-    format!("{}.{}", name, new_extension)
+    format!("{}.{}", strip_known_extension(name), new_extension)
 }
 
 fn file_entry(source: &str, archive_path: &str) -> Result<ArchiveEntry, PackError> {
@@ -185,7 +216,7 @@ fn file_entry(source: &str, archive_path: &str) -> Result<ArchiveEntry, PackErro
     })
 }
 
-fn include_dir_entries(
+pub fn include_dir_entries(
     include_dir: &str,
     include_path: &str,
 ) -> Result<Vec<ArchiveEntry>, PackError> {
@@ -237,11 +268,9 @@ fn join_archive_path(prefix: &str, suffix: &str) -> String {
 }
 
 fn strip_known_extension(name: &str) -> String {
-    if let Some(stripped) = name.strip_suffix(".tar.gz") {
-        return stripped.to_string();
-    }
-    if let Some(stripped) = name.strip_suffix(".zip") {
-        return stripped.to_string();
-    }
-    name.to_string()
+    ArchiveKind::ALL
+        .into_iter()
+        .find_map(|kind| name.strip_suffix(&format!(".{}", kind.extension())))
+        .unwrap_or(name)
+        .to_string()
 }
@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use xforge_core::artifact::DEBUG_DIR_NAME;
+
+use crate::common::{
+    include_dir_entries, write_tar_gz, write_tar_xz, write_tar_zst, write_zip, ArchiveEntry,
+    EntrySource,
+};
+use crate::{PackError, PackFormat};
+
+/// Pack split debug-info side files (`.debug` files, `.dSYM` bundles, `.pdb`
+/// files) under `debug/` into their own archive at `output_path`.
+pub fn pack_debug_info(
+    format: PackFormat,
+    sources: &[PathBuf],
+    output_path: &Path,
+) -> Result<PathBuf, PackError> {
+    if sources.is_empty() {
+        return Err(PackError::InvalidRequest {
+            message: "no debug info files to pack".to_string(),
+        });
+    }
+    let mut entries = Vec::new();
+    for source in sources {
+        let name = source
+            .file_name()
+            .ok_or_else(|| PackError::InvalidRequest {
+                message: format!("invalid debug info path '{}'", source.display()),
+            })?
+            .to_string_lossy();
+        let archive_path = format!("{}/{}", DEBUG_DIR_NAME, name);
+        if source.is_dir() {
+            entries.extend(include_dir_entries(&source.to_string_lossy(), &archive_path)?);
+        } else if source.is_file() {
+            entries.push(ArchiveEntry {
+                archive_path,
+                source: EntrySource::File(source.clone()),
+            });
+        } else {
+            return Err(PackError::InvalidRequest {
+                message: format!("missing debug info '{}'", source.display()),
+            });
+        }
+    }
+    entries.sort_by(|left, right| left.archive_path.cmp(&right.archive_path));
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent).map_err(|err| PackError::Io {
+            message: err.to_string(),
+        })?;
+    }
+    match format {
+        PackFormat::TarGz => write_tar_gz(output_path, &entries)?,
+        PackFormat::TarZst => write_tar_zst(output_path, &entries)?,
+        PackFormat::TarXz => write_tar_xz(output_path, &entries)?,
+        PackFormat::Zip => write_zip(output_path, &entries)?,
+        other => {
+            return Err(PackError::InvalidRequest {
+                message: format!("debug info cannot be packed as {:?}", other),
+            })
+        }
+    }
+    Ok(output_path.to_path_buf())
+}
@@ -1,14 +1,25 @@
 use xforge_core::artifact::layout::ArchiveLayout;
+use xforge_core::artifact::ArchiveKind;
 use xforge_core::build_plan::BuiltArtifact;
 
 mod common;
 pub mod android;
+pub mod debug;
+pub mod macho;
+pub mod maven;
+pub mod nuget;
 pub mod tar;
+pub mod wheel;
 pub mod xcframework;
 pub mod zip;
 
 pub use android::AarPacker;
-pub use tar::TarGzPacker;
+pub use debug::pack_debug_info;
+pub use macho::{universal_binary, write_universal};
+pub use maven::{write_maven_repository, MavenPublication};
+pub use nuget::NugetPacker;
+pub use tar::{TarGzPacker, TarXzPacker, TarZstPacker};
+pub use wheel::WheelPacker;
 pub use xcframework::XcframeworkPacker;
 pub use zip::ZipPacker;
 
@@ -16,8 +27,23 @@ pub use zip::ZipPacker;
 pub enum PackFormat {
     Zip,
     TarGz,
+    TarZst,
+    TarXz,
     XCFramework,
     AAR,
+    Wheel,
+    Nupkg,
+}
+
+impl From<ArchiveKind> for PackFormat {
+    fn from(kind: ArchiveKind) -> Self {
+        match kind {
+            ArchiveKind::TarGz => PackFormat::TarGz,
+            ArchiveKind::Zip => PackFormat::Zip,
+            ArchiveKind::TarZst => PackFormat::TarZst,
+            ArchiveKind::TarXz => PackFormat::TarXz,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
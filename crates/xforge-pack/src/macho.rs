@@ -0,0 +1,177 @@
+use std::fs;
+use std::path::Path;
+
+use xforge_core::binary::{inspect, Architecture, BinaryFormat, BinarySlice};
+
+use crate::PackError;
+
+const FAT_MAGIC: u32 = 0xcafe_babe;
+const MH_MAGIC: u32 = 0xfeed_face;
+const MH_MAGIC_64: u32 = 0xfeed_facf;
+const FAT_HEADER_SIZE: usize = 8;
+const FAT_ARCH_SIZE: usize = 20;
+/// arm64 slices sit on 16 KiB boundaries, everything else on 4 KiB pages,
+/// matching what `lipo` writes.
+const ARM64_ALIGN: u32 = 14;
+const DEFAULT_ALIGN: u32 = 12;
+
+/// One thin Mach-O ready to go into a fat file.
+struct ThinSlice<'a> {
+    bytes: &'a [u8],
+    header: BinarySlice,
+    cputype: u32,
+    cpusubtype: u32,
+    align: u32,
+}
+
+/// Merge thin Mach-O files into one fat (universal) Mach-O at `output`, the
+/// way `lipo -create` does, without needing Apple tools on the host.
+pub fn write_universal(slices: &[&Path], output: &Path) -> Result<(), PackError> {
+    let mut contents = Vec::with_capacity(slices.len());
+    for path in slices {
+        contents.push(fs::read(path).map_err(|err| PackError::Io {
+            message: format!("failed to read '{}': {}", path.display(), err),
+        })?);
+    }
+    let borrowed: Vec<&[u8]> = contents.iter().map(Vec::as_slice).collect();
+    let merged = universal_binary(&borrowed)?;
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).map_err(|err| PackError::Io {
+            message: err.to_string(),
+        })?;
+    }
+    fs::write(output, merged).map_err(|err| PackError::Io {
+        message: format!("failed to write '{}': {}", output.display(), err),
+    })
+}
+
+/// Build the bytes of a fat Mach-O from thin slices. Every slice must be a
+/// thin Mach-O of the same file type and OS, and no architecture may appear
+/// twice. Slices are ordered by alignment, then CPU type, so the output does
+/// not depend on the order of `slices`.
+pub fn universal_binary(slices: &[&[u8]]) -> Result<Vec<u8>, PackError> {
+    if slices.len() < 2 {
+        return Err(invalid("a universal binary needs at least two slices"));
+    }
+    let mut thin = Vec::with_capacity(slices.len());
+    for (index, bytes) in slices.iter().enumerate() {
+        thin.push(thin_slice(index, bytes)?);
+    }
+    let first = &thin[0].header;
+    for slice in &thin[1..] {
+        if slice.header.file_type != first.file_type {
+            return Err(invalid(format!(
+                "slices disagree on file type: {} vs {}",
+                first, slice.header
+            )));
+        }
+        if slice.header.os != first.os {
+            return Err(invalid(format!(
+                "slices disagree on OS: {} vs {}",
+                first, slice.header
+            )));
+        }
+    }
+    thin.sort_by_key(|slice| (slice.align, slice.cputype, slice.cpusubtype));
+    for pair in thin.windows(2) {
+        if pair[0].cputype == pair[1].cputype && pair[0].cpusubtype == pair[1].cpusubtype {
+            return Err(invalid(format!(
+                "more than one slice for architecture {}",
+                pair[0].header.architecture.as_str()
+            )));
+        }
+    }
+
+    let mut header = Vec::with_capacity(FAT_HEADER_SIZE + thin.len() * FAT_ARCH_SIZE);
+    header.extend_from_slice(&FAT_MAGIC.to_be_bytes());
+    header.extend_from_slice(&(thin.len() as u32).to_be_bytes());
+    let mut offset = (FAT_HEADER_SIZE + thin.len() * FAT_ARCH_SIZE) as u64;
+    let mut offsets = Vec::with_capacity(thin.len());
+    for slice in &thin {
+        offset = align_up(offset, slice.align);
+        let size = slice.bytes.len() as u64;
+        if offset + size > u64::from(u32::MAX) {
+            return Err(invalid(
+                "universal binary exceeds the 4 GiB limit of 32-bit fat headers",
+            ));
+        }
+        header.extend_from_slice(&slice.cputype.to_be_bytes());
+        header.extend_from_slice(&slice.cpusubtype.to_be_bytes());
+        header.extend_from_slice(&(offset as u32).to_be_bytes());
+        header.extend_from_slice(&(size as u32).to_be_bytes());
+        header.extend_from_slice(&slice.align.to_be_bytes());
+        offsets.push(offset as usize);
+        offset += size;
+    }
+
+    let mut merged = header;
+    for (slice, offset) in thin.iter().zip(offsets) {
+        merged.resize(offset, 0);
+        merged.extend_from_slice(slice.bytes);
+    }
+    Ok(merged)
+}
+
+fn thin_slice<'a>(index: usize, bytes: &'a [u8]) -> Result<ThinSlice<'a>, PackError> {
+    let info = inspect(bytes).map_err(|err| invalid(format!("slice {}: {}", index, err)))?;
+    if info.format != BinaryFormat::MachO {
+        return Err(invalid(format!(
+            "slice {} is {}, not mach-o",
+            index,
+            info.format.as_str()
+        )));
+    }
+    if info.universal {
+        return Err(invalid(format!(
+            "slice {} is already a universal binary",
+            index
+        )));
+    }
+    let header = info
+        .slices
+        .into_iter()
+        .next()
+        .ok_or_else(|| invalid(format!("slice {} has no architecture", index)))?;
+    if header.architecture == Architecture::Unknown {
+        return Err(invalid(format!("slice {} has an unknown CPU type", index)));
+    }
+    let magic = read_u32_le(bytes, 0);
+    let big_endian = magic != MH_MAGIC && magic != MH_MAGIC_64;
+    let read = |offset| {
+        let value = read_u32_le(bytes, offset);
+        if big_endian {
+            value.swap_bytes()
+        } else {
+            value
+        }
+    };
+    let align = match header.architecture {
+        Architecture::Aarch64 => ARM64_ALIGN,
+        _ => DEFAULT_ALIGN,
+    };
+    Ok(ThinSlice {
+        bytes,
+        cputype: read(4),
+        cpusubtype: read(8),
+        header,
+        align,
+    })
+}
+
+/// `inspect` has already checked the Mach-O header is present.
+fn read_u32_le(bytes: &[u8], offset: usize) -> u32 {
+    let mut word = [0u8; 4];
+    word.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(word)
+}
+
+fn align_up(offset: u64, align: u32) -> u64 {
+    let step = 1u64 << align;
+    offset.div_ceil(step) * step
+}
+
+fn invalid(message: impl Into<String>) -> PackError {
+    PackError::InvalidRequest {
+        message: message.into(),
+    }
+}
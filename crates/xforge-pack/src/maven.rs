@@ -0,0 +1,161 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::common::{pack_io, xml_escape};
+use crate::PackError;
+
+/// Coordinates and descriptive POM fields of one published AAR.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MavenPublication {
+    pub group_id: String,
+    pub artifact_id: String,
+    pub version: String,
+    /// `<name>` of the POM, normally the crate name.
+    pub name: String,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub license: Option<String>,
+}
+
+impl MavenPublication {
+    /// `<group path>/<artifactId>/<version>`, relative to the repository root.
+    pub fn version_dir(&self) -> PathBuf {
+        let mut dir: PathBuf = self.group_id.split('.').collect();
+        dir.push(&self.artifact_id);
+        dir.push(&self.version);
+        dir
+    }
+
+    fn file_name(&self, extension: &str) -> String {
+        format!("{}-{}.{}", self.artifact_id, self.version, extension)
+    }
+}
+
+/// Checksum files Maven and Gradle look for next to every published file.
+const CHECKSUMS: [&str; 4] = ["md5", "sha1", "sha256", "sha512"];
+
+/// Write `aar` into the Maven 2 repository at `repository` as
+/// `publication`, with its POM, its Gradle module metadata and a checksum
+/// file per algorithm for each of the three, and return every path written.
+/// Files of other versions already in the repository are left alone.
+pub fn write_maven_repository(
+    aar: &Path,
+    publication: &MavenPublication,
+    repository: &Path,
+) -> Result<Vec<PathBuf>, PackError> {
+    let aar_bytes = fs::read(aar).map_err(pack_io)?;
+    let dir = repository.join(publication.version_dir());
+    fs::create_dir_all(&dir).map_err(pack_io)?;
+    let aar_name = publication.file_name("aar");
+    let files = [
+        (aar_name.clone(), aar_bytes.clone()),
+        (publication.file_name("pom"), pom(publication).into_bytes()),
+        (
+            publication.file_name("module"),
+            module_metadata(publication, &aar_name, &aar_bytes).into_bytes(),
+        ),
+    ];
+    let mut paths = Vec::new();
+    for (name, bytes) in files {
+        let path = dir.join(&name);
+        fs::write(&path, &bytes).map_err(pack_io)?;
+        paths.push(path);
+        for algorithm in CHECKSUMS {
+            let path = dir.join(format!("{}.{}", name, algorithm));
+            fs::write(&path, checksum(algorithm, &bytes)).map_err(pack_io)?;
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+fn checksum(algorithm: &str, bytes: &[u8]) -> String {
+    match algorithm {
+        "md5" => hex::encode(Md5::digest(bytes)),
+        "sha1" => hex::encode(Sha1::digest(bytes)),
+        "sha256" => hex::encode(Sha256::digest(bytes)),
+        _ => hex::encode(Sha512::digest(bytes)),
+    }
+}
+
+/// POM of the AAR. The marker comment tells Gradle to read the `.module`
+/// file instead, as Gradle's own publications do.
+fn pom(publication: &MavenPublication) -> String {
+    let mut pom = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <project xmlns=\"http://maven.apache.org/POM/4.0.0\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://maven.apache.org/POM/4.0.0 https://maven.apache.org/xsd/maven-4.0.0.xsd\">\n  \
+         <!-- do_not_remove: published-with-gradle-metadata -->\n  \
+         <modelVersion>4.0.0</modelVersion>\n",
+    );
+    for (tag, value) in [
+        ("groupId", Some(publication.group_id.as_str())),
+        ("artifactId", Some(publication.artifact_id.as_str())),
+        ("version", Some(publication.version.as_str())),
+        ("packaging", Some("aar")),
+        ("name", Some(publication.name.as_str())),
+        ("description", publication.description.as_deref()),
+        ("url", publication.url.as_deref()),
+    ] {
+        if let Some(value) = value {
+            pom.push_str(&format!("  <{}>{}</{}>\n", tag, xml_escape(value), tag));
+        }
+    }
+    if let Some(license) = &publication.license {
+        pom.push_str(&format!(
+            "  <licenses>\n    <license>\n      <name>{}</name>\n    </license>\n  </licenses>\n",
+            xml_escape(license)
+        ));
+    }
+    pom.push_str("</project>\n");
+    pom
+}
+
+/// Gradle module metadata (format 1.1) with an API and a runtime variant,
+/// both resolving to the AAR.
+fn module_metadata(publication: &MavenPublication, aar_name: &str, aar_bytes: &[u8]) -> String {
+    let file = serde_json::json!({
+        "name": aar_name,
+        "url": aar_name,
+        "size": aar_bytes.len(),
+        "sha512": checksum("sha512", aar_bytes),
+        "sha256": checksum("sha256", aar_bytes),
+        "sha1": checksum("sha1", aar_bytes),
+        "md5": checksum("md5", aar_bytes),
+    });
+    let variant = |name: &str, usage: &str| {
+        serde_json::json!({
+            "name": name,
+            "attributes": {
+                "org.gradle.category": "library",
+                "org.gradle.dependency.bundling": "external",
+                "org.gradle.libraryelements": "aar",
+                "org.gradle.usage": usage,
+            },
+            "files": [file.clone()],
+        })
+    };
+    let module = serde_json::json!({
+        "formatVersion": "1.1",
+        "component": {
+            "group": publication.group_id,
+            "module": publication.artifact_id,
+            "version": publication.version,
+            "attributes": {
+                "org.gradle.status": "release",
+            },
+        },
+        "variants": [
+            variant("releaseApiElements", "java-api"),
+            variant("releaseRuntimeElements", "java-runtime"),
+        ],
+    });
+    let mut json = serde_json::to_string_pretty(&module).expect("module metadata");
+    json.push('\n');
+    json
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use xforge_core::artifact::LibraryKind;
+use xforge_core::bindings::dotnet::{nupkg_file_name, runtime_identifier};
+use xforge_core::bindings::DotnetBinding;
+use xforge_core::manifest::Package;
+
+use crate::common::{pack_io, write_zip, xml_escape, ArchiveEntry, EntrySource};
+use crate::{PackError, PackExecutor, PackFormat, PackRequest, PackResult};
+
+/// Relationship type of the `.nuspec` in `_rels/.rels`.
+const MANIFEST_RELATIONSHIP: &str = "http://schemas.microsoft.com/packaging/2010/07/manifest";
+
+/// Builds one `.nupkg` holding the dynamic library of every platform under
+/// `runtimes/<rid>/native/`, for the runtime identifiers of a
+/// [`DotnetBinding`].
+pub struct NugetPacker {
+    id: String,
+    version: String,
+    binding: DotnetBinding,
+    description: Option<String>,
+    authors: Vec<String>,
+    license: Option<String>,
+    project_url: Option<String>,
+}
+
+impl NugetPacker {
+    pub fn new(id: impl Into<String>, version: impl Into<String>, binding: DotnetBinding) -> Self {
+        Self {
+            id: id.into(),
+            version: version.into(),
+            binding,
+            description: None,
+            authors: Vec::new(),
+            license: None,
+            project_url: None,
+        }
+    }
+
+    /// Fill the descriptive `.nuspec` fields from the manifest `package`
+    /// block.
+    pub fn with_package_info(mut self, package: &Package) -> Self {
+        self.description = package.description.clone();
+        self.authors = package.authors.clone();
+        self.license = package.license.clone();
+        self.project_url = package.repository.clone();
+        self
+    }
+
+    fn nuspec(&self) -> String {
+        // `description` and `authors` are required by NuGet.
+        let description = self
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("Native libraries of {}.", self.id));
+        let authors = if self.authors.is_empty() {
+            self.id.clone()
+        } else {
+            self.authors.join(", ")
+        };
+        let mut nuspec = format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <package xmlns=\"http://schemas.microsoft.com/packaging/2013/05/nuspec.xsd\">\n  \
+             <metadata>\n    \
+             <id>{}</id>\n    \
+             <version>{}</version>\n    \
+             <authors>{}</authors>\n    \
+             <description>{}</description>\n",
+            xml_escape(&self.id),
+            xml_escape(&self.version),
+            xml_escape(&authors),
+            xml_escape(&description)
+        );
+        if let Some(license) = &self.license {
+            // Cargo still accepts the deprecated `MIT/Apache-2.0` form; NuGet
+            // wants an SPDX expression.
+            nuspec.push_str(&format!(
+                "    <license type=\"expression\">{}</license>\n",
+                xml_escape(&license.replace('/', " OR "))
+            ));
+        }
+        if let Some(url) = &self.project_url {
+            nuspec.push_str(&format!(
+                "    <projectUrl>{}</projectUrl>\n",
+                xml_escape(url)
+            ));
+        }
+        nuspec.push_str("  </metadata>\n</package>\n");
+        nuspec
+    }
+
+    fn relationships(&self) -> String {
+        let target = format!("/{}.nuspec", self.id);
+        let id = format!("R{}", &hex::encode(Sha256::digest(target.as_bytes()))[..16]);
+        format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n  \
+             <Relationship Type=\"{}\" Target=\"{}\" Id=\"{}\" />\n\
+             </Relationships>\n",
+            MANIFEST_RELATIONSHIP,
+            xml_escape(&target),
+            id
+        )
+    }
+}
+
+impl PackExecutor for NugetPacker {
+    fn pack(&self, request: &PackRequest) -> Result<PackResult, PackError> {
+        if request.format != PackFormat::Nupkg {
+            return Err(PackError::InvalidRequest {
+                message: "nuget packer only supports PackFormat::Nupkg".to_string(),
+            });
+        }
+        if request.inputs.is_empty() {
+            return Err(PackError::InvalidRequest {
+                message: "nuget packer requires at least one input".to_string(),
+            });
+        }
+        self.binding
+            .validate()
+            .map_err(|err| PackError::InvalidRequest {
+                message: err.to_string(),
+            })?;
+
+        let mut files = Vec::new();
+        let mut rids = BTreeSet::new();
+        let mut extensions = BTreeSet::from(["nuspec".to_string(), "rels".to_string()]);
+        for input in &request.inputs {
+            let platform = input.artifact.platform;
+            let rid = runtime_identifier(platform).ok_or_else(|| PackError::InvalidRequest {
+                message: format!("platform '{}' has no .NET runtime identifier", platform),
+            })?;
+            if !rids.insert(rid) {
+                return Err(PackError::InvalidRequest {
+                    message: format!("rid '{}' is packed twice", rid),
+                });
+            }
+            let library = input
+                .artifact
+                .library(LibraryKind::Dynamic)
+                .map(PathBuf::from)
+                .ok_or_else(|| PackError::InvalidRequest {
+                    message: format!("nuget slice '{}' has no dynamic library", platform),
+                })?;
+            let library_name = library
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .ok_or_else(|| PackError::InvalidRequest {
+                    message: format!("library path '{}' has no file name", library.display()),
+                })?;
+            if let Some(extension) = Path::new(&library_name).extension() {
+                extensions.insert(extension.to_string_lossy().to_lowercase());
+            }
+            files.push((format!("runtimes/{}/native/{}", rid, library_name), library));
+        }
+        files.sort();
+
+        let temp = tempfile::tempdir().map_err(pack_io)?;
+        let root = temp.path();
+        let nuspec = root.join("package.nuspec");
+        fs::write(&nuspec, self.nuspec()).map_err(pack_io)?;
+        files.insert(0, (format!("{}.nuspec", self.id), nuspec));
+        let rels = root.join(".rels");
+        fs::write(&rels, self.relationships()).map_err(pack_io)?;
+        files.push(("_rels/.rels".to_string(), rels));
+        let content_types = root.join("content_types.xml");
+        fs::write(&content_types, content_types_xml(&extensions)).map_err(pack_io)?;
+        files.push(("[Content_Types].xml".to_string(), content_types));
+
+        let entries: Vec<ArchiveEntry> = files
+            .into_iter()
+            .map(|(archive_path, source)| ArchiveEntry {
+                archive_path,
+                source: EntrySource::File(source),
+            })
+            .collect();
+        let mut output_path = PathBuf::from(&request.output_dir);
+        fs::create_dir_all(&output_path).map_err(pack_io)?;
+        output_path.push(nupkg_file_name(&self.id, &self.version));
+        write_zip(&output_path, &entries)?;
+        Ok(PackResult {
+            format: PackFormat::Nupkg,
+            output_paths: vec![output_path.to_string_lossy().into_owned()],
+        })
+    }
+}
+
+/// `[Content_Types].xml` of the package's Open Packaging Conventions zip,
+/// with a default content type for every file extension in it.
+fn content_types_xml(extensions: &BTreeSet<String>) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+         <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\n",
+    );
+    for extension in extensions {
+        let content_type = if extension == "rels" {
+            "application/vnd.openxmlformats-package.relationships+xml"
+        } else {
+            "application/octet"
+        };
+        xml.push_str(&format!(
+            "  <Default Extension=\"{}\" ContentType=\"{}\" />\n",
+            xml_escape(extension),
+            content_type
+        ));
+    }
+    xml.push_str("</Types>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packer() -> NugetPacker {
+        NugetPacker::new(
+            "Demo.Native",
+            "1.2.0",
+            DotnetBinding {
+                runtime_identifiers: vec!["linux-x64".to_string()],
+            },
+        )
+    }
+
+    #[test]
+    fn nuspec_falls_back_to_the_id_for_required_fields() {
+        let nuspec = packer().nuspec();
+        assert!(nuspec.contains("<id>Demo.Native</id>"));
+        assert!(nuspec.contains("<version>1.2.0</version>"));
+        assert!(nuspec.contains("<authors>Demo.Native</authors>"));
+        assert!(nuspec.contains("<description>Native libraries of Demo.Native.</description>"));
+        assert!(!nuspec.contains("<license"));
+        assert!(!nuspec.contains("<projectUrl>"));
+    }
+
+    #[test]
+    fn nuspec_takes_package_info_and_rewrites_cargo_licenses() {
+        let nuspec = packer()
+            .with_package_info(&Package {
+                name: "demo".to_string(),
+                version: "1.2.0".to_string(),
+                description: Some("Fast & small".to_string()),
+                license: Some("MIT/Apache-2.0".to_string()),
+                authors: vec!["Ada".to_string(), "Grace <grace@example.com>".to_string()],
+                repository: Some("https://example.com/demo".to_string()),
+            })
+            .nuspec();
+        assert!(nuspec.contains("<authors>Ada, Grace &lt;grace@example.com&gt;</authors>"));
+        assert!(nuspec.contains("<description>Fast &amp; small</description>"));
+        assert!(nuspec.contains("<license type=\"expression\">MIT OR Apache-2.0</license>"));
+        assert!(nuspec.contains("<projectUrl>https://example.com/demo</projectUrl>"));
+    }
+
+    #[test]
+    fn content_types_cover_every_extension() {
+        let extensions =
+            BTreeSet::from(["nuspec".to_string(), "rels".to_string(), "so".to_string()]);
+        assert_eq!(
+            content_types_xml(&extensions),
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\n  \
+             <Default Extension=\"nuspec\" ContentType=\"application/octet\" />\n  \
+             <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\" />\n  \
+             <Default Extension=\"so\" ContentType=\"application/octet\" />\n\
+             </Types>\n"
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::fs;
+use std::path::PathBuf;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use xforge_core::artifact::LibraryKind;
+use xforge_core::bindings::python::{
+    normalize_distribution, pep440_version, wheel_file_name, WHEEL_PYTHON_TAG,
+};
+use xforge_core::bindings::PythonBinding;
+
+use crate::common::{pack_io, write_zip, ArchiveEntry, EntrySource};
+use crate::{PackError, PackExecutor, PackFormat, PackRequest, PackResult};
+
+const GENERATOR: &str = concat!("xforge ", env!("CARGO_PKG_VERSION"));
+
+/// Builds a PEP 427 wheel for one platform: an import package holding the
+/// native library (and, optionally, an `__init__.py` that loads it) plus the
+/// `.dist-info` metadata, with the tags of a [`PythonBinding`].
+pub struct WheelPacker {
+    distribution: String,
+    version: String,
+    binding: PythonBinding,
+    loader: bool,
+}
+
+impl WheelPacker {
+    pub fn new(
+        distribution: impl Into<String>,
+        version: impl Into<String>,
+        binding: PythonBinding,
+    ) -> Self {
+        Self {
+            distribution: distribution.into(),
+            version: version.into(),
+            binding,
+            loader: false,
+        }
+    }
+
+    /// Generate `<package>/__init__.py`, which opens the library with
+    /// `ctypes.CDLL` and exposes it as `lib`.
+    pub fn with_loader(mut self, loader: bool) -> Self {
+        self.loader = loader;
+        self
+    }
+
+    /// Every `Tag:` line of `WHEEL`, one per platform tag in the binding's
+    /// compressed tag set.
+    fn tags(&self) -> Vec<String> {
+        self.binding
+            .platform_tag
+            .split('.')
+            .map(|platform| format!("{}-{}-{}", WHEEL_PYTHON_TAG, self.binding.abi_tag, platform))
+            .collect()
+    }
+}
+
+impl PackExecutor for WheelPacker {
+    fn pack(&self, request: &PackRequest) -> Result<PackResult, PackError> {
+        if request.format != PackFormat::Wheel {
+            return Err(PackError::InvalidRequest {
+                message: "wheel packer only supports PackFormat::Wheel".to_string(),
+            });
+        }
+        let [input] = request.inputs.as_slice() else {
+            return Err(PackError::InvalidRequest {
+                message: "wheel packer expects exactly one input".to_string(),
+            });
+        };
+        self.binding
+            .validate()
+            .map_err(|err| PackError::InvalidRequest {
+                message: err.to_string(),
+            })?;
+        let version = pep440_version(&self.version).ok_or_else(|| PackError::InvalidRequest {
+            message: format!("version '{}' has no PEP 440 form", self.version),
+        })?;
+        let distribution = normalize_distribution(&self.distribution);
+        if !is_python_identifier(&distribution) {
+            return Err(PackError::InvalidRequest {
+                message: format!(
+                    "distribution '{}' does not give an importable package name",
+                    self.distribution
+                ),
+            });
+        }
+        let library = input
+            .artifact
+            .library(LibraryKind::Dynamic)
+            .map(PathBuf::from)
+            .ok_or_else(|| PackError::InvalidRequest {
+                message: format!(
+                    "wheel for '{}' needs a dynamic library",
+                    input.artifact.platform
+                ),
+            })?;
+        let library_name = library
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .ok_or_else(|| PackError::InvalidRequest {
+                message: format!("library path '{}' has no file name", library.display()),
+            })?;
+
+        let temp = tempfile::tempdir().map_err(pack_io)?;
+        let root = temp.path();
+        let dist_info = format!("{}-{}.dist-info", distribution, version);
+        let mut files = vec![(format!("{}/{}", distribution, library_name), library)];
+        if self.loader {
+            let path = root.join("__init__.py");
+            fs::write(&path, loader_module(&library_name)).map_err(pack_io)?;
+            files.push((format!("{}/__init__.py", distribution), path));
+        }
+        files.sort();
+
+        let metadata = root.join("METADATA");
+        fs::write(
+            &metadata,
+            format!(
+                "Metadata-Version: 2.1\nName: {}\nVersion: {}\n",
+                self.distribution, version
+            ),
+        )
+        .map_err(pack_io)?;
+        files.push((format!("{}/METADATA", dist_info), metadata));
+        let wheel = root.join("WHEEL");
+        let mut contents = format!(
+            "Wheel-Version: 1.0\nGenerator: {}\nRoot-Is-Purelib: false\n",
+            GENERATOR
+        );
+        for tag in self.tags() {
+            contents.push_str(&format!("Tag: {}\n", tag));
+        }
+        fs::write(&wheel, contents).map_err(pack_io)?;
+        files.push((format!("{}/WHEEL", dist_info), wheel));
+
+        // RECORD lists every other file with its hash and size, and itself
+        // without either; it goes last so installers see it after the rest.
+        let record_path = format!("{}/RECORD", dist_info);
+        let mut record = String::new();
+        for (archive_path, source) in &files {
+            let bytes = fs::read(source).map_err(pack_io)?;
+            record.push_str(&format!(
+                "{},sha256={},{}\n",
+                csv_field(archive_path),
+                URL_SAFE_NO_PAD.encode(Sha256::digest(&bytes)),
+                bytes.len()
+            ));
+        }
+        record.push_str(&format!("{},,\n", csv_field(&record_path)));
+        let record_file = root.join("RECORD");
+        fs::write(&record_file, record).map_err(pack_io)?;
+        files.push((record_path, record_file));
+
+        let entries: Vec<ArchiveEntry> = files
+            .into_iter()
+            .map(|(archive_path, source)| ArchiveEntry {
+                archive_path,
+                source: EntrySource::File(source),
+            })
+            .collect();
+        let mut output_path = PathBuf::from(&request.output_dir);
+        fs::create_dir_all(&output_path).map_err(pack_io)?;
+        output_path.push(wheel_file_name(
+            &self.distribution,
+            &version,
+            WHEEL_PYTHON_TAG,
+            &self.binding.abi_tag,
+            &self.binding.platform_tag,
+        ));
+        write_zip(&output_path, &entries)?;
+        Ok(PackResult {
+            format: PackFormat::Wheel,
+            output_paths: vec![output_path.to_string_lossy().into_owned()],
+        })
+    }
+}
+
+fn loader_module(library_name: &str) -> String {
+    format!(
+        "\"\"\"Load the native library shipped in this wheel.\"\"\"\n\
+         \n\
+         import ctypes\n\
+         import pathlib\n\
+         \n\
+         LIBRARY_PATH = pathlib.Path(__file__).with_name(\"{}\")\n\
+         lib = ctypes.CDLL(str(LIBRARY_PATH))\n",
+        library_name
+    )
+}
+
+fn is_python_identifier(value: &str) -> bool {
+    let mut chars = value.chars();
+    matches!(chars.next(), Some(ch) if ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// RECORD is CSV; quote paths that would otherwise split the row.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -1,47 +1,157 @@
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use crate::common::{derive_package_name, entries_from_dir};
-use crate::{PackError, PackExecutor, PackFormat, PackRequest, PackResult};
+use walkdir::WalkDir;
+use xforge_core::artifact::naming::xcframework_artifact_name;
+use xforge_core::artifact::LibraryKind;
+use xforge_core::platform::PlatformKey;
 
-pub struct XcframeworkPacker;
+use crate::common::{derive_package_name, entries_from_dir, pack_io, write_zip, xml_escape};
+use crate::macho::universal_binary;
+use crate::{PackError, PackExecutor, PackFormat, PackInput, PackRequest, PackResult};
 
-impl PackExecutor for XcframeworkPacker {
-    fn pack(&self, request: &PackRequest) -> Result<PackResult, PackError> {
-        if request.format != PackFormat::XCFramework {
-            return Err(PackError::InvalidRequest {
-                message: "xcframework packer only supports PackFormat::XCFramework".to_string(),
-            });
+const HEADERS_DIR_NAME: &str = "Headers";
+const MODULE_MAP_FILE_NAME: &str = "module.modulemap";
+
+/// Assembles a zipped `<module>.xcframework` from the dynamic libraries of
+/// thin Apple slices. Slices of the same platform (both iOS simulator
+/// architectures, or both macOS ones) are merged into one fat Mach-O, so
+/// the whole framework can be built on any host. `with_xcodebuild` hands the
+/// merged slices to `xcodebuild -create-xcframework` instead.
+pub struct XcframeworkPacker {
+    module: String,
+    xcodebuild: bool,
+}
+
+/// One `<LibraryIdentifier>/` directory of the framework.
+struct SliceGroup<'a> {
+    platform: &'static str,
+    variant: Option<&'static str>,
+    architectures: Vec<&'static str>,
+    libraries: Vec<&'a str>,
+    include_dir: Option<&'a str>,
+}
+
+impl SliceGroup<'_> {
+    fn identifier(&self) -> String {
+        let mut identifier = format!("{}-{}", self.platform, self.architectures.join("_"));
+        if let Some(variant) = self.variant {
+            identifier.push('-');
+            identifier.push_str(variant);
         }
-        if request.inputs.is_empty() {
-            return Err(PackError::InvalidRequest {
-                message: "xcframework packer expects at least one input".to_string(),
-            });
+        identifier
+    }
+}
+
+impl XcframeworkPacker {
+    pub fn new(module: impl Into<String>) -> Self {
+        Self {
+            module: module.into(),
+            xcodebuild: false,
         }
-        let first = &request.inputs[0];
-        let output_name = format!("{}.xcframework", derive_package_name(&first.artifact));
-        let mut output_dir = PathBuf::from(&request.output_dir);
-        fs::create_dir_all(&output_dir).map_err(|err| PackError::Io {
-            message: err.to_string(),
-        })?;
-        output_dir.push(output_name);
-        if output_dir.exists() {
-            fs::remove_dir_all(&output_dir).map_err(|err| PackError::Io {
-                message: err.to_string(),
-            })?;
+    }
+
+    pub fn with_xcodebuild(mut self, xcodebuild: bool) -> Self {
+        self.xcodebuild = xcodebuild;
+        self
+    }
+
+    fn framework_name(&self) -> String {
+        format!("{}.xcframework", self.module)
+    }
+
+    /// Write each slice group's library (merged when it has several
+    /// architectures) and headers under `<staging>/<LibraryIdentifier>/`.
+    fn stage_slices(
+        &self,
+        staging: &Path,
+        groups: &BTreeMap<String, SliceGroup<'_>>,
+    ) -> Result<(), PackError> {
+        for (identifier, group) in groups {
+            let slice_dir = staging.join(identifier);
+            fs::create_dir_all(&slice_dir).map_err(pack_io)?;
+            let library = slice_dir.join(library_file_name(group.libraries[0])?);
+            if group.libraries.len() == 1 {
+                fs::copy(group.libraries[0], &library).map_err(pack_io)?;
+            } else {
+                let mut contents = Vec::with_capacity(group.libraries.len());
+                for path in &group.libraries {
+                    contents.push(fs::read(path).map_err(pack_io)?);
+                }
+                let slices: Vec<&[u8]> = contents.iter().map(Vec::as_slice).collect();
+                let merged = universal_binary(&slices).map_err(|err| match err {
+                    PackError::InvalidRequest { message } => PackError::InvalidRequest {
+                        message: format!("{}: {}", identifier, message),
+                    },
+                    other => other,
+                })?;
+                fs::write(&library, merged).map_err(pack_io)?;
+            }
+            if let Some(include_dir) = group.include_dir {
+                self.stage_headers(Path::new(include_dir), &slice_dir.join(HEADERS_DIR_NAME))?;
+            }
         }
+        Ok(())
+    }
+
+    /// Copy the generated headers and add a `module.modulemap` listing them,
+    /// unless the include directory already ships one.
+    fn stage_headers(&self, include_dir: &Path, headers_dir: &Path) -> Result<(), PackError> {
+        let mut headers = Vec::new();
+        for entry in WalkDir::new(include_dir).sort_by_file_name() {
+            let entry = entry.map_err(pack_io)?;
+            if entry.file_type().is_dir() {
+                continue;
+            }
+            let relative = entry.path().strip_prefix(include_dir).map_err(pack_io)?;
+            let destination = headers_dir.join(relative);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).map_err(pack_io)?;
+            }
+            fs::copy(entry.path(), &destination).map_err(pack_io)?;
+            let is_header = matches!(
+                relative.extension().and_then(|value| value.to_str()),
+                Some("h" | "hpp")
+            );
+            if is_header {
+                let components: Vec<String> = relative
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                    .collect();
+                headers.push(components.join("/"));
+            }
+        }
+        let module_map = headers_dir.join(MODULE_MAP_FILE_NAME);
+        if !module_map.exists() {
+            fs::write(&module_map, module_map_contents(&self.module, &headers)).map_err(pack_io)?;
+        }
+        Ok(())
+    }
+
+    fn run_xcodebuild(
+        &self,
+        staging: &Path,
+        groups: &BTreeMap<String, SliceGroup<'_>>,
+        output: &Path,
+    ) -> Result<(), PackError> {
         let mut command = Command::new("xcodebuild");
         command.arg("-create-xcframework");
-        for input in &request.inputs {
-            command.arg("-library").arg(&input.artifact.library_path);
-            if let Some(headers) = &input.artifact.include_dir {
-                command.arg("-headers").arg(headers);
+        for (identifier, group) in groups {
+            let slice_dir = staging.join(identifier);
+            command
+                .arg("-library")
+                .arg(slice_dir.join(library_file_name(group.libraries[0])?));
+            if group.include_dir.is_some() {
+                command
+                    .arg("-headers")
+                    .arg(slice_dir.join(HEADERS_DIR_NAME));
             }
         }
-        command.arg("-output").arg(&output_dir);
+        command.arg("-output").arg(output);
         let output = command.output().map_err(|err| PackError::Io {
-            message: err.to_string(),
+            message: format!("failed to run xcodebuild: {}", err),
         })?;
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -49,22 +159,199 @@ impl PackExecutor for XcframeworkPacker {
                 message: format!("xcodebuild failed: {}", stderr.trim()),
             });
         }
-        write_metadata(&output_dir, &first.layout, &first.artifact)?;
-        let entries = entries_from_dir(&output_dir)?;
-        if entries.is_empty() {
+        Ok(())
+    }
+}
+
+impl PackExecutor for XcframeworkPacker {
+    fn pack(&self, request: &PackRequest) -> Result<PackResult, PackError> {
+        if request.format != PackFormat::XCFramework {
             return Err(PackError::InvalidRequest {
-                message: "xcframework output is empty".to_string(),
+                message: "xcframework packer only supports PackFormat::XCFramework".to_string(),
             });
         }
+        if request.inputs.is_empty() {
+            return Err(PackError::InvalidRequest {
+                message: "xcframework packer expects at least one input".to_string(),
+            });
+        }
+        let first = &request.inputs[0];
+        let groups = group_slices(&request.inputs)?;
+        let temp = tempfile::tempdir().map_err(pack_io)?;
+        let root = temp.path().join(self.framework_name());
+        if self.xcodebuild {
+            let staging = temp.path().join("slices");
+            self.stage_slices(&staging, &groups)?;
+            self.run_xcodebuild(&staging, &groups, &root)?;
+        } else {
+            self.stage_slices(&root, &groups)?;
+            fs::write(root.join("Info.plist"), info_plist(&groups)?).map_err(pack_io)?;
+        }
+        write_metadata(&root, &first.layout, &first.artifact)?;
+
+        // Zip the framework directory itself so it unpacks as
+        // `<module>.xcframework/`, as SwiftPM binary targets expect.
+        let entries = entries_from_dir(&root)?
+            .into_iter()
+            .map(|mut entry| {
+                entry.archive_path = format!("{}/{}", self.framework_name(), entry.archive_path);
+                entry
+            })
+            .collect::<Vec<_>>();
+        let output_name = xcframework_artifact_name(
+            &derive_package_name(&first.artifact),
+            &first.artifact.build_id,
+        )
+        .map_err(|err| PackError::InvalidRequest {
+            message: err.to_string(),
+        })?;
+        let mut output_path = PathBuf::from(&request.output_dir);
+        fs::create_dir_all(&output_path).map_err(pack_io)?;
+        output_path.push(output_name);
+        write_zip(&output_path, &entries)?;
         Ok(PackResult {
             format: PackFormat::XCFramework,
-            output_paths: vec![output_dir.to_string_lossy().into_owned()],
+            output_paths: vec![output_path.to_string_lossy().into_owned()],
         })
     }
 }
 
+/// Group inputs by XCFramework platform and variant, keyed by library
+/// identifier so the framework layout is sorted and stable.
+fn group_slices(inputs: &[PackInput]) -> Result<BTreeMap<String, SliceGroup<'_>>, PackError> {
+    let mut grouped: BTreeMap<(&str, Option<&str>), SliceGroup<'_>> = BTreeMap::new();
+    for input in inputs {
+        let platform = input.artifact.platform;
+        let (name, variant, architecture) = apple_slice(platform)?;
+        let library = input
+            .artifact
+            .library(LibraryKind::Dynamic)
+            .ok_or_else(|| PackError::InvalidRequest {
+                message: format!("xcframework slice '{}' has no dynamic library", platform),
+            })?;
+        let group = grouped
+            .entry((name, variant))
+            .or_insert_with(|| SliceGroup {
+                platform: name,
+                variant,
+                architectures: Vec::new(),
+                libraries: Vec::new(),
+                include_dir: input.artifact.include_dir.as_deref(),
+            });
+        if group.architectures.contains(&architecture) {
+            return Err(PackError::InvalidRequest {
+                message: format!("duplicate xcframework slice '{}'", platform),
+            });
+        }
+        group.architectures.push(architecture);
+        group.libraries.push(library);
+    }
+    Ok(grouped
+        .into_values()
+        .map(|mut group| {
+            let mut slices: Vec<_> = group
+                .architectures
+                .iter()
+                .copied()
+                .zip(group.libraries.iter().copied())
+                .collect();
+            slices.sort();
+            group.architectures = slices
+                .iter()
+                .map(|(architecture, _)| *architecture)
+                .collect();
+            group.libraries = slices.iter().map(|(_, library)| *library).collect();
+            (group.identifier(), group)
+        })
+        .collect())
+}
+
+/// `SupportedPlatform`, `SupportedPlatformVariant` and architecture of a thin
+/// Apple platform.
+fn apple_slice(
+    platform: PlatformKey,
+) -> Result<(&'static str, Option<&'static str>, &'static str), PackError> {
+    match platform {
+        PlatformKey::MacosArm64 => Ok(("macos", None, "arm64")),
+        PlatformKey::MacosX86_64 => Ok(("macos", None, "x86_64")),
+        PlatformKey::IosArm64 => Ok(("ios", None, "arm64")),
+        PlatformKey::IosSimulatorArm64 => Ok(("ios", Some("simulator"), "arm64")),
+        PlatformKey::IosSimulatorX86_64 => Ok(("ios", Some("simulator"), "x86_64")),
+        _ => Err(PackError::InvalidRequest {
+            message: format!("'{}' is not a thin apple platform", platform),
+        }),
+    }
+}
+
+fn info_plist(groups: &BTreeMap<String, SliceGroup<'_>>) -> Result<String, PackError> {
+    let mut plist = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \t<key>AvailableLibraries</key>\n\
+         \t<array>\n",
+    );
+    for (identifier, group) in groups {
+        let library = library_file_name(group.libraries[0])?;
+        plist.push_str("\t\t<dict>\n");
+        plist_entry(&mut plist, "BinaryPath", &library);
+        if group.include_dir.is_some() {
+            plist_entry(&mut plist, "HeadersPath", HEADERS_DIR_NAME);
+        }
+        plist_entry(&mut plist, "LibraryIdentifier", identifier);
+        plist_entry(&mut plist, "LibraryPath", &library);
+        plist.push_str("\t\t\t<key>SupportedArchitectures</key>\n\t\t\t<array>\n");
+        for architecture in &group.architectures {
+            plist.push_str(&format!("\t\t\t\t<string>{}</string>\n", architecture));
+        }
+        plist.push_str("\t\t\t</array>\n");
+        plist_entry(&mut plist, "SupportedPlatform", group.platform);
+        if let Some(variant) = group.variant {
+            plist_entry(&mut plist, "SupportedPlatformVariant", variant);
+        }
+        plist.push_str("\t\t</dict>\n");
+    }
+    plist.push_str(
+        "\t</array>\n\
+         \t<key>CFBundlePackageType</key>\n\
+         \t<string>XFWK</string>\n\
+         \t<key>XCFrameworkFormatVersion</key>\n\
+         \t<string>1.0</string>\n\
+         </dict>\n\
+         </plist>\n",
+    );
+    Ok(plist)
+}
+
+fn plist_entry(plist: &mut String, key: &str, value: &str) {
+    plist.push_str(&format!(
+        "\t\t\t<key>{}</key>\n\t\t\t<string>{}</string>\n",
+        key,
+        xml_escape(value)
+    ));
+}
+
+fn module_map_contents(module: &str, headers: &[String]) -> String {
+    let mut contents = format!("module {} {{\n", module);
+    for header in headers {
+        contents.push_str(&format!("    header \"{}\"\n", header));
+    }
+    contents.push_str("    export *\n}\n");
+    contents
+}
+
+fn library_file_name(path: &str) -> Result<String, PackError> {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| PackError::InvalidRequest {
+            message: format!("library path '{}' has no file name", path),
+        })
+}
+
 fn write_metadata(
-    root: &PathBuf,
+    root: &Path,
     layout: &xforge_core::artifact::layout::ArchiveLayout,
     artifact: &xforge_core::build_plan::BuiltArtifact,
 ) -> Result<(), PackError> {
@@ -124,10 +124,12 @@ fn content_type_for_path(path: &Path) -> String {
         "application/zip".to_string()
     } else if name.ends_with(".tar.gz") {
         "application/gzip".to_string()
+    } else if name.ends_with(".tar.zst") {
+        "application/zstd".to_string()
+    } else if name.ends_with(".tar.xz") {
+        "application/x-xz".to_string()
     } else if name.ends_with(".json") {
         "application/json".to_string()
-    } else if name.ends_with(".sig") {
-        "application/octet-stream".to_string()
     } else {
         "application/octet-stream".to_string()
     }